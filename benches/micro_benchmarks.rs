@@ -7,8 +7,8 @@ fn with_python<F, R>(f: F) -> R
 where
     F: FnOnce(Python) -> R,
 {
-    pyo3::prepare_freethreaded_python();
-    Python::with_gil(|py| f(py))
+    Python::initialize();
+    Python::attach(f)
 }
 
 fn bench_type_checking(c: &mut Criterion) {
@@ -168,7 +168,7 @@ fn bench_number_extraction(c: &mut Criterion) {
     
     with_python(|py| {
         let int_obj = 42i64.into_pyobject(py).unwrap();
-        let float_obj = 3.14f64.into_pyobject(py).unwrap();
+        let float_obj = 2.5f64.into_pyobject(py).unwrap();
         
         group.bench_function("extract_int", |b| {
             b.iter(|| {
@@ -0,0 +1,37 @@
+//! Records build metadata for `version_info()`: the resolved version and
+//! source of the `toon` and `pyo3` dependencies, and the cargo profile.
+
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+/// `(version, source)` of `name` in a Cargo.lock, scanning `[[package]]` blocks.
+fn locked(lock: &str, name: &str) -> Option<(String, String)> {
+    lock.split("[[package]]").find_map(|block| {
+        let field = |key: &str| {
+            block.lines().find_map(|line| {
+                let value = line.strip_prefix(key)?.trim().strip_prefix('=')?.trim();
+                Some(value.trim_matches('"').to_string())
+            })
+        };
+        (field("name").as_deref() == Some(name)).then(|| (field("version").unwrap_or_default(), field("source").unwrap_or_default()))
+    })
+}
+
+fn main() {
+    // The lock file sits in the manifest directory, or in a workspace above it
+    let manifest_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap_or_default());
+    let lock_path = manifest_dir.ancestors().map(|dir| dir.join("Cargo.lock")).find(|p| p.is_file());
+    let lock = lock_path.as_ref().and_then(|p| fs::read_to_string(p).ok()).unwrap_or_default();
+    if let Some(path) = &lock_path {
+        println!("cargo:rerun-if-changed={}", path.display());
+    }
+    println!("cargo:rerun-if-changed=build.rs");
+
+    for (name, prefix) in [("toon", "TOON_CRATE"), ("pyo3", "PYO3_CRATE")] {
+        let (version, source) = locked(&lock, name).unwrap_or_else(|| ("unknown".into(), String::new()));
+        println!("cargo:rustc-env={}_VERSION={}", prefix, version);
+        println!("cargo:rustc-env={}_SOURCE={}", prefix, source);
+    }
+    println!("cargo:rustc-env=TOON_BUILD_PROFILE={}", env::var("PROFILE").unwrap_or_else(|_| "unknown".into()));
+}
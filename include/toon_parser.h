@@ -0,0 +1,38 @@
+/* C interface to the toon_parser core. Build with `cargo build --release --features capi`. */
+
+#ifndef TOON_PARSER_H
+#define TOON_PARSER_H
+
+#include <stdbool.h>
+#include <stddef.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+#define TOON_OK 0
+#define TOON_ERR_SYNTAX 1
+#define TOON_ERR_INVALID_ARGUMENT 2
+#define TOON_ERR_INVALID_JSON 3
+#define TOON_ERR_OTHER 4
+
+typedef struct ToonError {
+    int code;
+    size_t line;   /* 1-based line of a syntax error, 0 when unknown */
+    char *message; /* owned; release with toon_error_clear */
+} ToonError;
+
+/* JSON -> TOON. delimiter is NULL or "comma", "tab", "pipe". Returns NULL on failure. */
+char *toon_encode(const char *json, const char *delimiter, bool strict, ToonError *err);
+
+/* TOON -> JSON. Returns NULL on failure. */
+char *toon_decode(const char *toon, bool pretty, bool strict, ToonError *err);
+
+void toon_string_free(char *s);
+void toon_error_clear(ToonError *err);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif /* TOON_PARSER_H */
@@ -1,7 +1,8 @@
 """Async wrappers for toon_parser using asyncio.to_thread()"""
 
 import asyncio
-from typing import Any, Optional
+import threading
+from typing import Any, AsyncIterator, Optional, Union
 import toon_parser
 
 
@@ -37,4 +38,61 @@ async def decode_batch(toon_strs: list, **kwargs) -> list:
     return await asyncio.gather(*tasks)
 
 
-__all__ = ['encode', 'decode', 'dumps', 'loads', 'encode_batch', 'decode_batch']
+class _Stopped(Exception):
+    """Raised into the encoder when the consumer stops iterating."""
+
+
+async def aencode_stream(
+    data: Any,
+    chunk_size: int = 65536,
+    binary: bool = False,
+    options: Optional[toon_parser.Options] = None,
+    max_buffered: int = 4,
+) -> AsyncIterator[Union[str, bytes]]:
+    """Encode Python data to TOON as an async iterator of chunks.
+
+    The encoder runs in a worker thread and queues at most `max_buffered`
+    chunks ahead of the consumer; while a slow client drains the response it waits,
+    so the full encoding is never buffered. Leaving the iteration early stops
+    the encoder. Chunks are as for ``toon_parser.encode_to``.
+    """
+    loop = asyncio.get_running_loop()
+    queue: asyncio.Queue = asyncio.Queue(maxsize=max(1, max_buffered))
+    stopped = threading.Event()
+
+    def sink(chunk):
+        if stopped.is_set():
+            raise _Stopped()
+        # Blocks the worker while the queue is full
+        asyncio.run_coroutine_threadsafe(queue.put(chunk), loop).result()
+
+    task = asyncio.ensure_future(
+        asyncio.to_thread(toon_parser.encode_to, data, sink, chunk_size, binary, options)
+    )
+    try:
+        while True:
+            get = asyncio.ensure_future(queue.get())
+            await asyncio.wait({get, task}, return_when=asyncio.FIRST_COMPLETED)
+            if get.done():
+                yield get.result()
+                continue
+            get.cancel()
+            # The encoder is done: hand over what it left, then its error if any
+            while not queue.empty():
+                yield queue.get_nowait()
+            task.result()
+            return
+    finally:
+        if not task.done():
+            stopped.set()
+            # Unblock a pending put so the encoder reaches the check in sink
+            while not queue.empty():
+                queue.get_nowait()
+            try:
+                await task
+            except Exception:
+                # The consumer has left; there is no one to report to
+                pass
+
+
+__all__ = ['encode', 'decode', 'dumps', 'loads', 'encode_batch', 'decode_batch', 'aencode_stream']
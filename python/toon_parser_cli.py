@@ -0,0 +1,233 @@
+"""Command line interface for toon_parser (installed as ``toon``)."""
+
+import argparse
+import gzip
+import json
+import sys
+from typing import List, Optional
+
+import toon_parser
+
+
+def _read_text(path: str) -> str:
+    """Read a file ('-' for stdin), transparently decompressing gzip input."""
+    if path == "-":
+        data = sys.stdin.buffer.read()
+    else:
+        with open(path, "rb") as f:
+            data = f.read()
+    if data[:2] == b"\x1f\x8b":
+        data = gzip.decompress(data)
+    return data.decode("utf-8")
+
+
+def _write_text(path: Optional[str], text: str) -> None:
+    if path is None or path == "-":
+        sys.stdout.write(text)
+        if text and not text.endswith("\n"):
+            sys.stdout.write("\n")
+    else:
+        with open(path, "w", encoding="utf-8", newline="") as f:
+            f.write(text)
+
+
+def _format_of(path: Optional[str]) -> Optional[str]:
+    """Guess 'json' or 'toon' from a file name, ignoring a trailing .gz."""
+    if path is None or path == "-":
+        return None
+    name = path.lower()
+    if name.endswith(".gz"):
+        name = name[:-3]
+    if name.endswith(".json"):
+        return "json"
+    if name.endswith(".toon"):
+        return "toon"
+    return None
+
+
+def _cmd_convert(args: argparse.Namespace) -> int:
+    text = _read_text(args.input)
+    source = args.source or _format_of(args.input)
+    if source is None:
+        source = "json" if text.lstrip()[:1] in ("{", "[") else "toon"
+
+    if source == "json":
+        result = toon_parser.json_to_toon(text, delimiter=args.delimiter, strict=args.strict)
+    else:
+        result = toon_parser.toon_to_json(text, pretty=args.pretty, strict=args.strict)
+    _write_text(args.output, result)
+    return 0
+
+
+def _cmd_fmt(args: argparse.Namespace) -> int:
+    status = 0
+    for path in args.files:
+        original = _read_text(path)
+        # Files end with a newline; normalize() returns the document without one
+        formatted = toon_parser.normalize(original, strict=args.strict) + "\n"
+        if path == "-":
+            _write_text(None, formatted)
+        elif formatted != original:
+            if args.check:
+                print("would reformat {}".format(path), file=sys.stderr)
+                status = 1
+            else:
+                _write_text(path, formatted)
+                print("reformatted {}".format(path), file=sys.stderr)
+    return status
+
+
+def _cmd_validate(args: argparse.Namespace) -> int:
+    status = 0
+    for path in args.files:
+        name = "<stdin>" if path == "-" else path
+        for d in toon_parser.lint(_read_text(path), strict=args.strict):
+            location = ":".join(str(part) for part in (name, d["line"], d["column"]) if part is not None)
+            print("{}: {}: {}".format(location, d["severity"], d["message"]))
+            if d["severity"] == "error":
+                status = 1
+    return status
+
+
+def _print_value(value, output_format: str) -> None:
+    if output_format == "json":
+        _write_text(None, json.dumps(value, indent=2, ensure_ascii=False))
+    else:
+        _write_text(None, toon_parser.encode(value))
+
+
+def _cmd_get(args: argparse.Namespace) -> int:
+    try:
+        value = toon_parser.get(_read_text(args.file), args.path)
+    except KeyError as e:
+        print("toon: {}".format(e.args[0]), file=sys.stderr)
+        return 1
+    _print_value(value, args.format)
+    return 0
+
+
+def _cmd_query(args: argparse.Namespace) -> int:
+    _print_value(toon_parser.query(_read_text(args.file), args.expr), args.format)
+    return 0
+
+
+_COLORS = {"added": "\033[32m", "removed": "\033[31m", "changed": "\033[33m"}
+_RESET = "\033[0m"
+
+
+def _show(value) -> str:
+    return json.dumps(value, ensure_ascii=False)
+
+
+def _cmd_diff(args: argparse.Namespace) -> int:
+    if args.old == "-" and args.new == "-":
+        raise ValueError("only one of OLD and NEW can be '-'")
+    changes = toon_parser.diff(_read_text(args.old), _read_text(args.new))
+    if args.format == "json":
+        _write_text(None, json.dumps(changes, indent=2, ensure_ascii=False))
+        return 1 if changes else 0
+
+    color = args.color == "always" or (args.color == "auto" and sys.stdout.isatty())
+    for c in changes:
+        if c["op"] == "added":
+            line = "+ {}: {}".format(c["path"], _show(c["new"]))
+        elif c["op"] == "removed":
+            line = "- {}: {}".format(c["path"], _show(c["old"]))
+        else:
+            line = "~ {}: {} -> {}".format(c["path"], _show(c["old"]), _show(c["new"]))
+        print(_COLORS[c["op"]] + line + _RESET if color else line)
+    return 1 if changes else 0
+
+
+def _add_delimiter_strict(parser: argparse.ArgumentParser) -> None:
+    parser.add_argument("--delimiter", choices=["comma", "tab", "pipe"], help="TOON delimiter")
+    parser.add_argument("--strict", action="store_true", default=None, help="enable strict mode")
+
+
+def build_parser() -> argparse.ArgumentParser:
+    parser = argparse.ArgumentParser(prog="toon", description="Work with TOON documents.")
+    commands = parser.add_subparsers(dest="command", metavar="COMMAND")
+    commands.required = True
+
+    convert = commands.add_parser(
+        "convert",
+        help="convert between JSON and TOON",
+        description="Convert JSON to TOON or back. The direction follows the input file "
+        "extension (.json / .toon, optionally .gz), --from, or the content itself.",
+    )
+    convert.add_argument("input", nargs="?", default="-", help="input file, '-' for stdin (default)")
+    convert.add_argument("-o", "--output", help="output file (default: stdout)")
+    convert.add_argument("--from", dest="source", choices=["json", "toon"], help="input format")
+    convert.add_argument("--pretty", action="store_true", help="indent JSON output")
+    _add_delimiter_strict(convert)
+    convert.set_defaults(func=_cmd_convert)
+
+    fmt = commands.add_parser(
+        "fmt",
+        help="reformat TOON files in canonical form",
+        description="Rewrite TOON files in place in canonical form (sorted keys, canonical "
+        "numbers, default delimiter and indentation). '-' formats stdin to stdout.",
+    )
+    fmt.add_argument("files", nargs="+", help="files to format")
+    fmt.add_argument("--check", action="store_true", help="only report files that would change; exit 1 if any")
+    fmt.add_argument("--strict", action="store_true", default=None, help="enable strict mode")
+    fmt.set_defaults(func=_cmd_fmt)
+
+    validate = commands.add_parser(
+        "validate",
+        aliases=["lint"],
+        help="report problems in TOON files",
+        description="Print every diagnostic as FILE:LINE:COLUMN: SEVERITY: MESSAGE and exit 1 "
+        "if any error was found. --strict turns style warnings into errors.",
+    )
+    validate.add_argument("files", nargs="+", help="files to check, '-' for stdin")
+    validate.add_argument("--strict", action="store_true", help="enable strict mode")
+    validate.set_defaults(func=_cmd_validate)
+
+    get = commands.add_parser(
+        "get",
+        help="print the value at a path",
+        description="Print one value addressed by a path such as users[0].email.",
+    )
+    get.add_argument("file", help="TOON file, '-' for stdin")
+    get.add_argument("path", help="path, e.g. users[0].email")
+    get.add_argument("--format", choices=["toon", "json"], default="toon", help="output format (default: toon)")
+    get.set_defaults(func=_cmd_get)
+
+    query = commands.add_parser(
+        "query",
+        help="print values matching a JSONPath expression",
+        description="Print the list of values matching a JSONPath expression, e.g. "
+        "'$.rows[?(@.status==\"failed\")]'.",
+    )
+    query.add_argument("file", help="TOON file, '-' for stdin")
+    query.add_argument("expr", help="JSONPath expression")
+    query.add_argument("--format", choices=["toon", "json"], default="toon", help="output format (default: toon)")
+    query.set_defaults(func=_cmd_query)
+
+    diff = commands.add_parser(
+        "diff",
+        help="show value-level differences between two documents",
+        description="Print one line per difference (+ added, - removed, ~ changed) and exit 1 "
+        "if the documents differ, like diff(1).",
+    )
+    diff.add_argument("old", help="old document, '-' for stdin")
+    diff.add_argument("new", help="new document, '-' for stdin")
+    diff.add_argument("--format", choices=["text", "json"], default="text", help="output format (default: text)")
+    diff.add_argument("--color", choices=["auto", "always", "never"], default="auto", help="colorize text output")
+    diff.set_defaults(func=_cmd_diff)
+
+    return parser
+
+
+def main(argv: Optional[List[str]] = None) -> int:
+    args = build_parser().parse_args(argv)
+    try:
+        return args.func(args)
+    except (toon_parser.ToonError, ValueError, OSError) as e:
+        print("toon: {}".format(e), file=sys.stderr)
+        return 1
+
+
+if __name__ == "__main__":
+    sys.exit(main())
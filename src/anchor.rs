@@ -0,0 +1,165 @@
+//! Anchors for repeated sub-objects.
+//!
+//! The first occurrence of a repeated object gains an `"&": "<id>"` entry; later
+//! occurrences are replaced by the string `"*<id>"`. Resolution removes the
+//! anchor entries and substitutes the referenced objects back in; strings that
+//! start with `*` but name no anchor are left alone.
+//!
+//! So that data cannot pose as either, the encoder doubles the first character
+//! of strings starting with `*` and of keys starting with `&`, and resolution
+//! undoes it.
+
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+const ANCHOR_KEY: &str = "&";
+
+/// Objects whose compact JSON is shorter than this are cheaper to repeat than
+/// to reference.
+const MIN_ANCHOR_LEN: usize = 24;
+
+/// Write repeated sub-objects once and reference them thereafter.
+pub(crate) fn compress(value: &mut Value) {
+    escape(value);
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    count(value, true, &mut counts);
+    counts.retain(|json, n| *n > 1 && json.len() >= MIN_ANCHOR_LEN);
+    if counts.is_empty() {
+        return;
+    }
+    let mut ids: HashMap<String, String> = HashMap::new();
+    replace(value, true, &counts, &mut ids);
+}
+
+fn count(value: &Value, root: bool, counts: &mut HashMap<String, usize>) {
+    match value {
+        Value::Object(map) => {
+            if !root {
+                if let Ok(json) = serde_json::to_string(value) {
+                    *counts.entry(json).or_default() += 1;
+                }
+            }
+            map.values().for_each(|v| count(v, false, counts));
+        }
+        Value::Array(items) => items.iter().for_each(|v| count(v, false, counts)),
+        _ => {}
+    }
+}
+
+fn replace(value: &mut Value, root: bool, repeated: &HashMap<String, usize>, ids: &mut HashMap<String, String>) {
+    if let (false, Value::Object(_)) = (root, &*value) {
+        if let Some(json) = serde_json::to_string(value).ok().filter(|j| repeated.contains_key(j)) {
+            if let Some(id) = ids.get(&json) {
+                *value = Value::String(format!("*{}", id));
+                return;
+            }
+            let id = format!("a{}", ids.len() + 1);
+            ids.insert(json, id.clone());
+            if let Value::Object(map) = value {
+                let mut anchored = Map::with_capacity(map.len() + 1);
+                anchored.insert(ANCHOR_KEY.to_string(), Value::String(id));
+                anchored.extend(std::mem::take(map));
+                *map = anchored;
+            }
+        }
+    }
+    match value {
+        Value::Object(map) => map
+            .iter_mut()
+            .filter(|(k, _)| k.as_str() != ANCHOR_KEY)
+            .for_each(|(_, v)| replace(v, false, repeated, ids)),
+        Value::Array(items) => items.iter_mut().for_each(|v| replace(v, false, repeated, ids)),
+        _ => {}
+    }
+}
+
+/// Remove anchors and expand `*id` references.
+pub(crate) fn resolve(value: &mut Value) -> Result<(), String> {
+    let mut anchors: HashMap<String, Value> = HashMap::new();
+    collect(value, &mut anchors);
+    if !anchors.is_empty() {
+        expand(value, &anchors, 0)?;
+    }
+    unescape(value);
+    Ok(())
+}
+
+/// Double the leading `*` of strings and `&` of keys throughout `value`.
+fn escape(value: &mut Value) {
+    match value {
+        Value::String(s) if s.starts_with('*') => s.insert(0, '*'),
+        Value::Object(map) => {
+            *map = std::mem::take(map)
+                .into_iter()
+                .map(|(key, mut v)| {
+                    escape(&mut v);
+                    if key.starts_with('&') {
+                        (format!("&{}", key), v)
+                    } else {
+                        (key, v)
+                    }
+                })
+                .collect();
+        }
+        Value::Array(items) => items.iter_mut().for_each(escape),
+        _ => {}
+    }
+}
+
+/// Undo [`escape`].
+fn unescape(value: &mut Value) {
+    match value {
+        Value::String(s) if s.starts_with("**") => {
+            s.remove(0);
+        }
+        Value::Object(map) => {
+            *map = std::mem::take(map)
+                .into_iter()
+                .map(|(key, mut v)| {
+                    unescape(&mut v);
+                    match key.strip_prefix('&').filter(|rest| rest.starts_with('&')) {
+                        Some(rest) => (rest.to_string(), v),
+                        None => (key, v),
+                    }
+                })
+                .collect();
+        }
+        Value::Array(items) => items.iter_mut().for_each(unescape),
+        _ => {}
+    }
+}
+
+fn collect(value: &mut Value, anchors: &mut HashMap<String, Value>) {
+    match value {
+        Value::Object(map) => {
+            map.values_mut().for_each(|v| collect(v, anchors));
+            if let Some(Value::String(id)) = map.get(ANCHOR_KEY).cloned() {
+                map.shift_remove(ANCHOR_KEY);
+                anchors.insert(id, Value::Object(map.clone()));
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(|v| collect(v, anchors)),
+        _ => {}
+    }
+}
+
+fn expand(value: &mut Value, anchors: &HashMap<String, Value>, depth: usize) -> Result<(), String> {
+    // Anchored objects are tree copies, so nesting this deep means a cycle
+    if depth > 256 {
+        return Err("Anchor references nest too deeply; an anchor probably refers to itself".to_string());
+    }
+    match value {
+        Value::String(s) => {
+            let Some(target) = s.strip_prefix('*').and_then(|id| anchors.get(id)) else {
+                return Ok(());
+            };
+            let mut target = target.clone();
+            expand(&mut target, anchors, depth + 1)?;
+            *value = target;
+            Ok(())
+        }
+        Value::Object(map) => map.values_mut().try_for_each(|v| expand(v, anchors, depth)),
+        Value::Array(items) => items.iter_mut().try_for_each(|v| expand(v, anchors, depth)),
+        _ => Ok(()),
+    }
+}
@@ -0,0 +1,254 @@
+//! Appending rows to the table at the end of a TOON file (`append_rows`).
+//!
+//! Log and export files grow one batch at a time. Instead of decoding and
+//! re-encoding the whole file, [`append_rows`] scans back from the end over
+//! the rows of the table that ends the file to its header, encodes only the
+//! new rows with its delimiter and columns, and raises the length in its
+//! header. When the new length has as many digits as the old one the digits
+//! are overwritten in place; otherwise the file is rewritten from the length
+//! on.
+
+use serde_json::{Map, Value};
+use std::fs::{File, OpenOptions};
+use std::io::{self, Read, Seek, SeekFrom, Write};
+use std::ops::Range;
+use std::path::Path;
+
+use crate::{diagnostics, dump, footer, scan};
+
+pub(crate) enum Error {
+    Io(io::Error),
+    /// The file or the rows do not allow appending
+    Invalid(String),
+}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Error::Io(err)
+    }
+}
+
+/// The table rows are appended to.
+struct Target {
+    /// File offsets of the length digits in the header
+    length: Range<u64>,
+    declared: usize,
+    delimiter: char,
+    fields: Vec<String>,
+    row_indent: usize,
+    /// File offset just past the last row, before any trailing blank lines
+    end: u64,
+    newline: &'static str,
+}
+
+fn unquote(field: &str) -> &str {
+    let field = field.trim();
+    field.strip_prefix('"').and_then(|f| f.strip_suffix('"')).unwrap_or(field)
+}
+
+/// Bytes read at a time when scanning back from the end of the file.
+const BLOCK: u64 = 64 * 1024;
+
+/// The lines of a file from the last one back, read a block at a time.
+struct Backward<'a> {
+    file: &'a mut File,
+    /// File offset of `pending`
+    start: u64,
+    /// The bytes before the lines returned so far
+    pending: Vec<u8>,
+}
+
+impl<'a> Backward<'a> {
+    fn new(file: &'a mut File) -> io::Result<Self> {
+        let start = file.seek(SeekFrom::End(0))?;
+        Ok(Backward { file, start, pending: Vec::new() })
+    }
+
+    /// The previous line, with its line ending, and its offset in the file.
+    fn prev(&mut self) -> Result<Option<(u64, String)>, Error> {
+        loop {
+            let body = self.pending.strip_suffix(b"\n").unwrap_or(&self.pending);
+            let at = match body.iter().rposition(|&b| b == b'\n') {
+                Some(i) => i + 1,
+                None if self.start > 0 => {
+                    self.read_block()?;
+                    continue;
+                }
+                None if self.pending.is_empty() => return Ok(None),
+                None => 0,
+            };
+            let line = String::from_utf8(self.pending.split_off(at))
+                .map_err(|_| Error::Io(io::Error::new(io::ErrorKind::InvalidData, "stream did not contain valid UTF-8")))?;
+            return Ok(Some((self.start + at as u64, line)));
+        }
+    }
+
+    fn read_block(&mut self) -> io::Result<()> {
+        let from = self.start.saturating_sub(BLOCK);
+        let mut block = vec![0; (self.start - from) as usize];
+        self.file.seek(SeekFrom::Start(from))?;
+        self.file.read_exact(&mut block)?;
+        block.extend_from_slice(&self.pending);
+        self.pending = block;
+        self.start = from;
+        Ok(())
+    }
+}
+
+fn indent_of(line: &str) -> usize {
+    line.len() - line.trim_start_matches(' ').len()
+}
+
+/// Find the table that ends the file by scanning back from the end over its
+/// rows to the header, so only that table is read however large the file is.
+fn locate(file: &mut File) -> Result<Target, Error> {
+    let no_table = || Error::Invalid("the file does not end with a table".into());
+    let mut lines = Backward::new(file)?;
+    // The last line with content: a row, or the header of a table without any
+    let (last_at, last) = loop {
+        match lines.prev()? {
+            Some((_, line)) if line.trim().is_empty() => {}
+            Some(found) => break found,
+            None => return Err(no_table()),
+        }
+    };
+    if footer::split(&last).1.is_some() {
+        return Err(Error::Invalid("cannot append to a file with a checksum footer".into()));
+    }
+    let last_body = last.trim_end_matches(['\n', '\r']);
+    let end = last_at + last_body.len() as u64;
+    let row_indent = indent_of(last_body);
+    let (header_at, header, row_indent) = if diagnostics::table_header(last_body.trim()).is_some() {
+        (last_at, last, row_indent + 2)
+    } else {
+        loop {
+            match lines.prev()? {
+                Some((_, line)) if line.trim().is_empty() || indent_of(&line) == row_indent => {}
+                Some((at, line)) if indent_of(&line) < row_indent => break (at, line, row_indent),
+                _ => return Err(no_table()),
+            }
+        }
+    };
+    let newline = if header.ends_with("\r\n") { "\r\n" } else { "\n" };
+    let header = header.trim_end();
+    let (_, delimiter, fields) = diagnostics::table_header(header.trim_start_matches(' ')).ok_or_else(no_table)?;
+    // `key[N<delimiter>]{fields}:`, scanning back from the brace
+    let fields_start = header.len() - 2 - fields.len();
+    let close = fields_start - 2;
+    let digits_end = if header[..close].ends_with(['|', '\t']) { close - 1 } else { close };
+    let digits_start = header[..digits_end].trim_end_matches(|c: char| c.is_ascii_digit()).len();
+    let declared = header[digits_start..digits_end]
+        .parse()
+        .map_err(|_| Error::Invalid("the table header has no valid length".into()))?;
+    let fields = scan::split_cells(fields, delimiter)
+        .into_iter()
+        .map(|f| unquote(f).to_string())
+        .collect();
+    Ok(Target {
+        length: header_at + digits_start as u64..header_at + digits_end as u64,
+        declared,
+        delimiter,
+        fields,
+        row_indent,
+        end,
+        newline,
+    })
+}
+
+/// `rows` as table row lines without indentation, with the columns of `target`.
+fn encode_rows(rows: Vec<Value>, target: &Target, opts: &toon::Options) -> Result<Vec<String>, Error> {
+    let count = rows.len();
+    if count == 0 {
+        return Ok(Vec::new());
+    }
+    let mut ordered = Vec::with_capacity(count);
+    for (i, row) in rows.into_iter().enumerate() {
+        let Value::Object(mut map) = row else {
+            return Err(Error::Invalid(format!("row {} is not a dict", i)));
+        };
+        if map.len() != target.fields.len() || target.fields.iter().any(|f| !map.contains_key(f)) {
+            let keys: Vec<&str> = map.keys().map(String::as_str).collect();
+            return Err(Error::Invalid(format!(
+                "row {} has keys {:?} but the table has fields {:?}", i, keys, target.fields
+            )));
+        }
+        let mut row = Map::with_capacity(target.fields.len());
+        for field in &target.fields {
+            row.insert(field.clone(), map.shift_remove(field).unwrap_or(Value::Null));
+        }
+        ordered.push(Value::Object(row));
+    }
+    let mut opts = opts.clone();
+    opts.delimiter = match target.delimiter {
+        '|' => toon::Delimiter::Pipe,
+        '\t' => toon::Delimiter::Tab,
+        _ => toon::Delimiter::Comma,
+    };
+    let mut document = Map::new();
+    document.insert("rows".to_string(), Value::Array(ordered));
+    let text = toon::encode_to_string(&Value::Object(document), &opts).map_err(|e| Error::Invalid(e.to_string()))?;
+    let mut lines = text.lines();
+    let header = lines.next().unwrap_or_default();
+    let encoded_fields: Option<Vec<&str>> = header
+        .find('{')
+        .zip(header.rfind('}'))
+        .map(|(open, close)| scan::split_cells(&header[open + 1..close], target.delimiter).into_iter().map(unquote).collect());
+    if encoded_fields.is_none_or(|fields| fields != target.fields) {
+        return Err(Error::Invalid("rows must hold only scalar values for the table's fields".into()));
+    }
+    let rows: Vec<String> = lines.map(|line| line.trim_start_matches(' ').to_string()).collect();
+    if rows.len() != count {
+        return Err(Error::Invalid("rows must hold only scalar values for the table's fields".into()));
+    }
+    Ok(rows)
+}
+
+/// Append `rows` to the table that ends the file at `path`, returning the
+/// table's new length. With `exclusive`, an advisory lock on the file is held
+/// throughout, so concurrent appenders that also lock take turns instead of
+/// interleaving partial rows.
+pub(crate) fn append_rows(path: &Path, rows: Vec<Value>, opts: &toon::Options, exclusive: bool) -> Result<usize, Error> {
+    let mut options = OpenOptions::new();
+    options.read(true).write(true);
+    let mut file = if exclusive { dump::open_locked(path, &options)? } else { options.open(path)? };
+    append_to(&mut file, rows, opts)
+}
+
+fn append_to(file: &mut File, rows: Vec<Value>, opts: &toon::Options) -> Result<usize, Error> {
+    let target = locate(file)?;
+    let lines = encode_rows(rows, &target, opts)?;
+    if lines.is_empty() {
+        return Ok(target.declared);
+    }
+    // Rows go right after the last one, replacing any trailing blank lines
+    let indent = " ".repeat(target.row_indent);
+    let mut tail = String::new();
+    for line in &lines {
+        tail.push_str(target.newline);
+        tail.push_str(&indent);
+        tail.push_str(line);
+    }
+    tail.push_str(target.newline);
+    let total = target.declared + lines.len();
+    let digits = total.to_string();
+    if digits.len() as u64 == target.length.end - target.length.start {
+        // Rows before the length, so the header never counts rows that are not there
+        file.seek(SeekFrom::Start(target.end))?;
+        file.write_all(tail.as_bytes())?;
+        file.set_len(target.end + tail.len() as u64)?;
+        file.seek(SeekFrom::Start(target.length.start))?;
+        file.write_all(digits.as_bytes())?;
+    } else {
+        // The rows move, so the table is read back and rewritten after the new length
+        let mut rest = Vec::with_capacity((target.end - target.length.start) as usize + tail.len() + 1);
+        rest.extend_from_slice(digits.as_bytes());
+        file.seek(SeekFrom::Start(target.length.end))?;
+        Read::by_ref(file).take(target.end - target.length.end).read_to_end(&mut rest)?;
+        rest.extend_from_slice(tail.as_bytes());
+        file.seek(SeekFrom::Start(target.length.start))?;
+        file.write_all(&rest)?;
+        file.set_len(target.length.start + rest.len() as u64)?;
+    }
+    file.flush()?;
+    Ok(total)
+}
@@ -0,0 +1,84 @@
+//! ASCII-only output for `encode(..., ensure_ascii=True)`.
+//!
+//! Before encoding, [`escape`] replaces every non-ASCII character of strings
+//! and keys with `\<MARK>xxxx`, its UTF-16 code unit in hex. The backslash
+//! makes the encoder quote the string and write it as `\\`, and [`finish`]
+//! turns each `\\<MARK>` of the output into `\u`, leaving the escapes
+//! `json.dumps` writes: `"café"`, with surrogate pairs above U+FFFF.
+//! `MARK` in the data is escaped like any other character, so every one left
+//! in the output is a mark.
+
+use serde_json::{Map, Value};
+use std::borrow::Cow;
+use std::fmt::Write;
+
+use crate::scan::Token;
+
+/// Stands for `u` until the encoder has quoted the string. A Unicode
+/// noncharacter.
+const MARK: char = '\u{FDD5}';
+
+fn escape_str(s: &str, keep: &[&Token]) -> Option<String> {
+    if s.is_ascii() {
+        return None;
+    }
+    let mut out = String::with_capacity(s.len() + 8);
+    let mut rest = s;
+    while let Some(c) = rest.chars().next() {
+        // Markers of placeholders and hex integers are removed after encoding
+        if let Some(token) = keep.iter().find(|token| rest.starts_with(token.as_str())) {
+            out.push_str(token.as_str());
+            rest = &rest[token.as_str().len()..];
+            continue;
+        }
+        rest = &rest[c.len_utf8()..];
+        if c.is_ascii() {
+            out.push(c);
+            continue;
+        }
+        for unit in c.encode_utf16(&mut [0; 2]) {
+            let _ = write!(out, "\\{}{:04x}", MARK, unit);
+        }
+    }
+    Some(out)
+}
+
+/// Mark the non-ASCII characters of every string and key in `value`, except
+/// those of the tokens in `keep`.
+pub(crate) fn escape(value: &mut Value, keep: &[&Token]) {
+    match value {
+        Value::String(s) => {
+            if let Some(escaped) = escape_str(s, keep) {
+                *s = escaped;
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(|item| escape(item, keep)),
+        Value::Object(map) => {
+            if map.keys().any(|k| !k.is_ascii()) {
+                // Rebuilt to keep the key order
+                *map = std::mem::take(map)
+                    .into_iter()
+                    .map(|(k, v)| (escape_str(&k, keep).unwrap_or(k), v))
+                    .collect::<Map<String, Value>>();
+            }
+            map.values_mut().for_each(|item| escape(item, keep));
+        }
+        _ => {}
+    }
+}
+
+/// Encoded `text` with the marks [`escape`] added written as `\u` escapes.
+pub(crate) fn finish(text: &str) -> Cow<'_, str> {
+    if text.contains(MARK) {
+        Cow::Owned(text.replace(&format!("\\\\{}", MARK), "\\u"))
+    } else {
+        Cow::Borrowed(text)
+    }
+}
+
+/// The most bytes the encoder writes for output that [`finish`] shortens to
+/// `len` bytes: each marked UTF-16 unit is encoded as `\\`, the mark and four
+/// hex digits (9 bytes) and finished as `\uxxxx` (6 bytes).
+pub(crate) fn marked_len(len: usize) -> usize {
+    len.saturating_add(len / 2)
+}
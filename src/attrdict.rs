@@ -0,0 +1,195 @@
+//! `AttrDict`, the mapping returned by `decode(..., attrdict=True)`.
+//!
+//! Keys are reachable both as items and as attributes (`cfg.db.host`). The
+//! abi3 build cannot subclass `dict`, so the class wraps one and is registered
+//! as a `collections.abc.MutableMapping`. Methods such as `keys` or `items`
+//! take precedence over keys of the same name, which stay reachable with
+//! `cfg["items"]`.
+
+use pyo3::exceptions::{PyAttributeError, PyKeyError};
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList, PyString, PyTuple};
+
+/// Mapping whose keys can also be read and written as attributes.
+///
+/// Example:
+///     >>> cfg = toonpy.decode("db:\n  host: localhost", attrdict=True)
+///     >>> cfg.db.host
+///     'localhost'
+#[pyclass(module = "toon_parser", mapping)]
+pub(crate) struct AttrDict {
+    data: Py<PyDict>,
+}
+
+impl AttrDict {
+    pub(crate) fn wrap<'py>(dict: Bound<'py, PyDict>) -> PyResult<Bound<'py, PyAny>> {
+        let py = dict.py();
+        Ok(Bound::new(py, AttrDict { data: dict.unbind() })?.into_any())
+    }
+
+    pub(crate) fn dict<'py>(&self, py: Python<'py>) -> &Bound<'py, PyDict> {
+        self.data.bind(py)
+    }
+}
+
+/// Plain dicts and lists in place of every `AttrDict`, recursively.
+fn unwrap<'py>(value: &Bound<'py, PyAny>) -> PyResult<Bound<'py, PyAny>> {
+    let py = value.py();
+    if let Ok(attr) = value.cast::<AttrDict>() {
+        let out = PyDict::new(py);
+        for (k, v) in attr.borrow().dict(py).iter() {
+            out.set_item(k, unwrap(&v)?)?;
+        }
+        Ok(out.into_any())
+    } else if let Ok(list) = value.cast::<PyList>() {
+        let items = list.iter().map(|item| unwrap(&item)).collect::<PyResult<Vec<_>>>()?;
+        Ok(PyList::new(py, items)?.into_any())
+    } else if let Ok(tuple) = value.cast::<PyTuple>() {
+        let items = tuple.iter().map(|item| unwrap(&item)).collect::<PyResult<Vec<_>>>()?;
+        Ok(PyTuple::new(py, items)?.into_any())
+    } else {
+        Ok(value.clone())
+    }
+}
+
+fn no_attribute(name: &str) -> PyErr {
+    PyAttributeError::new_err(format!("'AttrDict' object has no attribute '{}'", name))
+}
+
+#[pymethods]
+impl AttrDict {
+    #[new]
+    #[pyo3(signature = (mapping=None, **kwargs))]
+    fn new(py: Python<'_>, mapping: Option<&Bound<'_, PyAny>>, kwargs: Option<&Bound<'_, PyDict>>) -> PyResult<Self> {
+        let data = PyDict::new(py);
+        if let Some(mapping) = mapping {
+            // Same rules as dict(): a mapping or an iterable of pairs
+            data.call_method1("update", (mapping,))?;
+        }
+        if let Some(kwargs) = kwargs {
+            data.update(kwargs.as_mapping())?;
+        }
+        Ok(AttrDict { data: data.unbind() })
+    }
+
+    fn __getattr__<'py>(&self, py: Python<'py>, name: &str) -> PyResult<Bound<'py, PyAny>> {
+        self.dict(py).get_item(name)?.ok_or_else(|| no_attribute(name))
+    }
+
+    fn __setattr__(&self, py: Python<'_>, name: &str, value: &Bound<'_, PyAny>) -> PyResult<()> {
+        self.dict(py).set_item(name, value)
+    }
+
+    fn __delattr__(&self, py: Python<'_>, name: &str) -> PyResult<()> {
+        self.dict(py).del_item(name).map_err(|_| no_attribute(name))
+    }
+
+    fn __getitem__<'py>(&self, py: Python<'py>, key: &Bound<'py, PyAny>) -> PyResult<Bound<'py, PyAny>> {
+        self.dict(py)
+            .get_item(key)?
+            .ok_or_else(|| PyKeyError::new_err(key.clone().unbind()))
+    }
+
+    fn __setitem__(&self, py: Python<'_>, key: &Bound<'_, PyAny>, value: &Bound<'_, PyAny>) -> PyResult<()> {
+        self.dict(py).set_item(key, value)
+    }
+
+    fn __delitem__(&self, py: Python<'_>, key: &Bound<'_, PyAny>) -> PyResult<()> {
+        self.dict(py).del_item(key)
+    }
+
+    fn __len__(&self, py: Python<'_>) -> usize {
+        self.dict(py).len()
+    }
+
+    fn __contains__(&self, py: Python<'_>, key: &Bound<'_, PyAny>) -> PyResult<bool> {
+        self.dict(py).contains(key)
+    }
+
+    fn __iter__<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        Ok(self.dict(py).as_any().try_iter()?.into_any())
+    }
+
+    fn __eq__(&self, py: Python<'_>, other: &Bound<'_, PyAny>) -> PyResult<bool> {
+        match other.cast::<AttrDict>() {
+            Ok(other) => self.dict(py).eq(other.borrow().dict(py)),
+            Err(_) => self.dict(py).eq(other),
+        }
+    }
+
+    fn __repr__(&self, py: Python<'_>) -> PyResult<String> {
+        Ok(format!("AttrDict({})", self.dict(py).repr()?))
+    }
+
+    /// Keys that are strings, then the methods, for tab completion.
+    fn __dir__<'py>(slf: &Bound<'py, Self>) -> PyResult<Bound<'py, PyList>> {
+        let py = slf.py();
+        let names = PyList::empty(py);
+        for key in slf.borrow().dict(py).keys() {
+            if key.is_instance_of::<PyString>() {
+                names.append(key)?;
+            }
+        }
+        for name in slf.get_type().dir()? {
+            names.append(name)?;
+        }
+        Ok(names)
+    }
+
+    fn __reduce__<'py>(slf: &Bound<'py, Self>) -> PyResult<(Bound<'py, PyAny>, (Bound<'py, PyDict>,))> {
+        let py = slf.py();
+        Ok((slf.get_type().into_any(), (slf.borrow().dict(py).clone(),)))
+    }
+
+    #[pyo3(signature = (key, default=None))]
+    fn get<'py>(&self, py: Python<'py>, key: &Bound<'py, PyAny>, default: Option<Bound<'py, PyAny>>) -> PyResult<Bound<'py, PyAny>> {
+        Ok(match self.dict(py).get_item(key)? {
+            Some(value) => value,
+            None => default.unwrap_or_else(|| py.None().into_bound(py)),
+        })
+    }
+
+    fn keys<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        self.dict(py).call_method0("keys")
+    }
+
+    fn values<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        self.dict(py).call_method0("values")
+    }
+
+    fn items<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        self.dict(py).call_method0("items")
+    }
+
+    // The `MutableMapping` methods, which `__getattr__` would otherwise shadow
+
+    #[pyo3(signature = (key, *default))]
+    fn pop<'py>(&self, py: Python<'py>, key: &Bound<'py, PyAny>, default: &Bound<'py, PyTuple>) -> PyResult<Bound<'py, PyAny>> {
+        let args = PyTuple::new(py, std::iter::once(key.clone()).chain(default.iter()).collect::<Vec<_>>())?;
+        self.dict(py).call_method1("pop", args)
+    }
+
+    fn popitem<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        self.dict(py).call_method0("popitem")
+    }
+
+    fn clear(&self, py: Python<'_>) {
+        self.dict(py).clear()
+    }
+
+    #[pyo3(signature = (*args, **kwargs))]
+    fn update(&self, py: Python<'_>, args: &Bound<'_, PyTuple>, kwargs: Option<&Bound<'_, PyDict>>) -> PyResult<()> {
+        self.dict(py).call_method("update", args, kwargs).map(drop)
+    }
+
+    #[pyo3(signature = (key, default=None))]
+    fn setdefault<'py>(&self, py: Python<'py>, key: &Bound<'py, PyAny>, default: Option<Bound<'py, PyAny>>) -> PyResult<Bound<'py, PyAny>> {
+        let default = default.unwrap_or_else(|| py.None().into_bound(py));
+        self.dict(py).call_method1("setdefault", (key, default))
+    }
+
+    /// Convert to plain dicts and lists, recursively.
+    fn to_dict<'py>(slf: &Bound<'py, Self>) -> PyResult<Bound<'py, PyAny>> {
+        unwrap(slf.as_any())
+    }
+}
@@ -0,0 +1,203 @@
+//! Batch helpers: per-item error handling for `decode_batch` and
+//! `encode_batch`, and `DecodeIter`, the iterator returned by
+//! `decode_batch_iter()`.
+//!
+//! For the iterator, a background thread parses the strings in order and
+//! hands each value over a bounded channel, so the consumer can work on the
+//! first results while later ones are still being parsed, and at most `AHEAD`
+//! parsed values wait in memory. Dropping the iterator stops the thread at
+//! its next send.
+
+use pyo3::exceptions::{PyBaseException, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::{PyList, PyTuple};
+use serde_json::Value;
+use std::sync::mpsc::{self, Receiver};
+use std::sync::Mutex;
+use std::thread;
+
+use crate::python::{convert_toon_error, json_to_python, ToonLimitError};
+use crate::{front, guard, newline};
+
+/// What a batch call does when one item fails.
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+pub(crate) enum OnError {
+    /// Raise the first error, as a single call would
+    #[default]
+    Raise,
+    /// Leave failed items out of the result
+    Skip,
+    /// Put `None` in place of failed items
+    Null,
+    /// `None` in place, plus a list of `(index, exception)` pairs
+    Collect,
+}
+
+impl OnError {
+    pub(crate) fn parse(name: &str) -> PyResult<Self> {
+        match name {
+            "raise" => Ok(OnError::Raise),
+            "skip" => Ok(OnError::Skip),
+            "none" => Ok(OnError::Null),
+            "collect" => Ok(OnError::Collect),
+            _ => Err(PyValueError::new_err(format!(
+                "Invalid on_error '{}'. Must be 'raise', 'skip', 'none', or 'collect'", name
+            ))),
+        }
+    }
+}
+
+/// Failed items paired with their index in the batch.
+pub(crate) type Errors = Vec<(usize, Py<PyBaseException>)>;
+
+/// Handle the failures among `items` as `on_error` says, using `placeholder`
+/// in place of a failed item for `'none'` and `'collect'`.
+pub(crate) fn settle<T>(
+    py: Python<'_>,
+    items: impl IntoIterator<Item = PyResult<T>>,
+    on_error: OnError,
+    placeholder: impl Fn() -> T,
+) -> PyResult<(Vec<T>, Errors)> {
+    let mut values = Vec::new();
+    let mut errors = Vec::new();
+    for (i, item) in items.into_iter().enumerate() {
+        match (item, on_error) {
+            (Ok(value), _) => values.push(value),
+            (Err(err), OnError::Raise) => return Err(err),
+            (Err(_), OnError::Skip) => {}
+            (Err(_), OnError::Null) => values.push(placeholder()),
+            (Err(err), OnError::Collect) => {
+                values.push(placeholder());
+                errors.push((i, err.into_value(py)));
+            }
+        }
+    }
+    Ok((values, errors))
+}
+
+/// `result`, or `(result, errors)` when collecting.
+pub(crate) fn finish<'py>(result: Bound<'py, PyAny>, errors: Errors, on_error: OnError) -> PyResult<Bound<'py, PyAny>> {
+    if on_error != OnError::Collect {
+        return Ok(result);
+    }
+    let py = result.py();
+    Ok(PyTuple::new(py, [result, PyList::new(py, errors)?.into_any()])?.into_any())
+}
+
+/// The list of results for a batch, or `(results, errors)` when collecting.
+pub(crate) fn gather<'py>(
+    py: Python<'py>,
+    items: impl IntoIterator<Item = PyResult<Bound<'py, PyAny>>>,
+    on_error: OnError,
+) -> PyResult<Bound<'py, PyAny>> {
+    let (values, errors) = settle(py, items, on_error, || py.None().into_bound(py))?;
+    finish(PyList::new(py, values)?.into_any(), errors, on_error)
+}
+
+/// How `encode_batch(..., join=...)` combines the encoded objects.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Join {
+    /// One top-level array, written as a table when the objects allow
+    Array,
+    /// One document per object, separated by `---` lines
+    Documents,
+}
+
+impl Join {
+    /// `None` for the default of one string per object.
+    pub(crate) fn parse(join: &Bound<'_, PyAny>) -> PyResult<Option<Self>> {
+        if let Ok(join) = join.extract::<bool>() {
+            return Ok(join.then_some(Join::Array));
+        }
+        match join.extract::<String>().as_deref() {
+            Ok("array") => Ok(Some(Join::Array)),
+            Ok("documents") => Ok(Some(Join::Documents)),
+            _ => Err(PyValueError::new_err(format!(
+                "Invalid join {}. Must be a bool, 'array', or 'documents'", join.repr()?
+            ))),
+        }
+    }
+}
+
+/// Line between documents joined with [`Join::Documents`]. The encoder
+/// quotes strings starting with `-`, so it never writes this line itself.
+pub(crate) const SEPARATOR: &str = "\n---\n";
+
+/// The documents of a stream joined with [`Join::Documents`], with the number
+/// of lines before each. Every `---` line separates documents here, including
+/// a first one, which `decode` would take for a front matter fence.
+pub(crate) fn split_documents(source: &str) -> Vec<(&str, usize)> {
+    let mut docs = Vec::new();
+    let (mut start, mut offset, mut lines) = (0, 0, 0);
+    let mut first_line = 0;
+    for line in source.split_inclusive('\n') {
+        if line.trim_end() == SEPARATOR.trim() {
+            docs.push((&source[start..offset], first_line));
+            start = offset + line.len();
+            first_line = lines + 1;
+        }
+        offset += line.len();
+        lines += 1;
+    }
+    docs.push((&source[start..], first_line));
+    docs
+}
+
+/// Decode every document of a stream joined with [`Join::Documents`], with
+/// syntax error line numbers counted in the whole stream. Line endings are
+/// normalized before splitting, so `---` lines end in `\r` too. Fails as a
+/// whole only when `reading` rejects a carriage return. Runs without the GIL.
+pub(crate) fn decode_documents(source: &str, opts: &toon::Options, reading: &newline::Reading) -> PyResult<Vec<PyResult<Value>>> {
+    let source = newline::normalize(source, reading.newlines).map_err(convert_toon_error)?;
+    Ok(split_documents(&source)
+        .into_iter()
+        .map(|(doc, offset)| newline::decode_str(doc, opts, reading).map_err(|e| convert_toon_error(front::shift(e, offset))))
+        .collect())
+}
+
+/// Decode one string of a batch after checking it against `limits`. Runs
+/// without the GIL.
+pub(crate) fn decode_one(toon_str: &str, opts: &toon::Options, limits: &guard::Limits, reading: &newline::Reading) -> PyResult<Value> {
+    limits.check(toon_str).map_err(ToonLimitError::new_err)?;
+    newline::decode_str(toon_str, opts, reading).map_err(convert_toon_error)
+}
+
+/// Parsed values the background thread may hold before the consumer catches up.
+const AHEAD: usize = 64;
+
+/// Iterator over values decoded in a background thread.
+#[pyclass(module = "toon_parser")]
+pub(crate) struct DecodeIter {
+    results: Mutex<Receiver<PyResult<Value>>>,
+}
+
+impl DecodeIter {
+    pub(crate) fn spawn(toon_strings: Vec<String>, opts: toon::Options, limits: guard::Limits, reading: newline::Reading) -> Self {
+        let (tx, rx) = mpsc::sync_channel(AHEAD);
+        thread::spawn(move || {
+            for toon_str in toon_strings {
+                if tx.send(decode_one(&toon_str, &opts, &limits, &reading)).is_err() {
+                    // The iterator was dropped
+                    break;
+                }
+            }
+        });
+        DecodeIter { results: Mutex::new(rx) }
+    }
+}
+
+#[pymethods]
+impl DecodeIter {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__<'py>(&self, py: Python<'py>) -> PyResult<Option<Bound<'py, PyAny>>> {
+        // Wait without the GIL so the caller's other threads keep running
+        let next = py.detach(|| self.results.lock().ok().and_then(|rx| rx.recv().ok()));
+        match next {
+            Some(result) => json_to_python(py, &result?).map(Some),
+            None => Ok(None),
+        }
+    }
+}
@@ -0,0 +1,153 @@
+//! Canonical form of documents, used for hashing and normalization.
+
+use serde_json::{Map, Number, Value};
+use sha2::{Digest, Sha224, Sha256, Sha384, Sha512};
+
+/// Rewrite `value` into canonical form: object keys sorted recursively and
+/// numbers normalized so that `1`, `1.0` and `-0.0`/`0` compare equal.
+pub(crate) fn canonicalize(value: &mut Value) {
+    match value {
+        Value::Object(map) => {
+            let mut entries: Vec<(String, Value)> = std::mem::take(map).into_iter().collect();
+            entries.sort_by(|a, b| a.0.cmp(&b.0));
+            let mut sorted = Map::with_capacity(entries.len());
+            for (k, mut v) in entries {
+                canonicalize(&mut v);
+                sorted.insert(k, v);
+            }
+            *map = sorted;
+        }
+        Value::Array(items) => items.iter_mut().for_each(canonicalize),
+        Value::Number(n) => {
+            if let Some(normalized) = normalize_number(n) {
+                *n = normalized;
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Integral floats become integers; everything else is left untouched.
+fn normalize_number(n: &Number) -> Option<Number> {
+    let f = n.as_f64().filter(|_| n.is_f64())?;
+    if f.fract() != 0.0 || f.abs() >= 9.007_199_254_740_992e15 {
+        return None;
+    }
+    Some(Number::from(f as i64))
+}
+
+/// Structural equality with an absolute tolerance for numbers.
+///
+/// Integers and floats compare by numeric value, so `1 == 1.0`. Object keys
+/// are matched by name unless `ordered` is set, in which case they must also
+/// appear in the same order.
+pub(crate) fn deep_equal(a: &Value, b: &Value, tolerance: f64, ordered: bool) -> bool {
+    match (a, b) {
+        (Value::Number(x), Value::Number(y)) => numbers_equal(x, y, tolerance),
+        (Value::Array(x), Value::Array(y)) => {
+            x.len() == y.len() && x.iter().zip(y).all(|(x, y)| deep_equal(x, y, tolerance, ordered))
+        }
+        (Value::Object(x), Value::Object(y)) => {
+            if x.len() != y.len() {
+                return false;
+            }
+            if ordered {
+                x.iter()
+                    .zip(y)
+                    .all(|((kx, vx), (ky, vy))| kx == ky && deep_equal(vx, vy, tolerance, ordered))
+            } else {
+                x.iter()
+                    .all(|(k, vx)| y.get(k).is_some_and(|vy| deep_equal(vx, vy, tolerance, ordered)))
+            }
+        }
+        _ => a == b,
+    }
+}
+
+fn numbers_equal(x: &Number, y: &Number, tolerance: f64) -> bool {
+    if let (Some(x), Some(y)) = (x.as_i64(), y.as_i64()) {
+        return x == y;
+    }
+    if let (Some(x), Some(y)) = (x.as_u64(), y.as_u64()) {
+        return x == y;
+    }
+    match (x.as_f64(), y.as_f64()) {
+        (Some(x), Some(y)) => x == y || (x - y).abs() <= tolerance,
+        _ => false,
+    }
+}
+
+/// Hex digest of `bytes` with the named algorithm.
+pub(crate) fn hex_digest(algorithm: &str, bytes: &[u8]) -> Result<String, String> {
+    let mut hasher = Hasher::new(algorithm)?;
+    hasher.update(bytes);
+    Ok(hasher.hex())
+}
+
+/// A running digest with one of the supported algorithms.
+pub(crate) enum Hasher {
+    Sha224(Sha224),
+    Sha256(Sha256),
+    Sha384(Sha384),
+    Sha512(Sha512),
+}
+
+impl Hasher {
+    pub(crate) fn new(algorithm: &str) -> Result<Self, String> {
+        match algorithm {
+            "sha224" => Ok(Hasher::Sha224(Sha224::new())),
+            "sha256" => Ok(Hasher::Sha256(Sha256::new())),
+            "sha384" => Ok(Hasher::Sha384(Sha384::new())),
+            "sha512" => Ok(Hasher::Sha512(Sha512::new())),
+            _ => Err(format!(
+                "Unsupported hash algorithm '{}'. Must be 'sha224', 'sha256', 'sha384' or 'sha512'", algorithm
+            )),
+        }
+    }
+
+    pub(crate) fn update(&mut self, bytes: &[u8]) {
+        match self {
+            Hasher::Sha224(h) => h.update(bytes),
+            Hasher::Sha256(h) => h.update(bytes),
+            Hasher::Sha384(h) => h.update(bytes),
+            Hasher::Sha512(h) => h.update(bytes),
+        }
+    }
+
+    pub(crate) fn hex(self) -> String {
+        match self {
+            Hasher::Sha224(h) => to_hex(&h.finalize()),
+            Hasher::Sha256(h) => to_hex(&h.finalize()),
+            Hasher::Sha384(h) => to_hex(&h.finalize()),
+            Hasher::Sha512(h) => to_hex(&h.finalize()),
+        }
+    }
+}
+
+/// Collects written bytes and hashes them on the way in, so the output is
+/// digested without a second pass over it.
+pub(crate) struct Tee {
+    pub(crate) buf: Vec<u8>,
+    pub(crate) hasher: Hasher,
+}
+
+impl std::io::Write for Tee {
+    fn write(&mut self, data: &[u8]) -> std::io::Result<usize> {
+        self.hasher.update(data);
+        self.buf.extend_from_slice(data);
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+pub(crate) fn to_hex(bytes: &[u8]) -> String {
+    use std::fmt::Write;
+    let mut out = String::with_capacity(bytes.len() * 2);
+    for b in bytes {
+        let _ = write!(out, "{:02x}", b);
+    }
+    out
+}
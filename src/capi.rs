@@ -0,0 +1,205 @@
+//! C ABI over the core API, for bindings in other languages (`capi` feature).
+//!
+//! Every returned string is owned by the caller and must be released with
+//! `toon_string_free`; error messages with `toon_error_clear`. The matching
+//! header is `include/toon_parser.h`.
+
+use std::ffi::{c_char, c_int, CStr, CString};
+use std::ptr;
+
+use crate::core;
+
+pub const TOON_OK: c_int = 0;
+pub const TOON_ERR_SYNTAX: c_int = 1;
+pub const TOON_ERR_INVALID_ARGUMENT: c_int = 2;
+pub const TOON_ERR_INVALID_JSON: c_int = 3;
+pub const TOON_ERR_OTHER: c_int = 4;
+
+/// Error details filled in by failing calls. `line` is 0 when unknown.
+#[repr(C)]
+pub struct ToonError {
+    pub code: c_int,
+    pub line: usize,
+    pub message: *mut c_char,
+}
+
+unsafe fn read_str<'a>(ptr: *const c_char, what: &str) -> core::Result<&'a str> {
+    if ptr.is_null() {
+        return Err(core::Error::InvalidArgument(format!("{} must not be NULL", what)));
+    }
+    CStr::from_ptr(ptr)
+        .to_str()
+        .map_err(|_| core::Error::InvalidArgument(format!("{} is not valid UTF-8", what)))
+}
+
+unsafe fn read_options(delimiter: *const c_char, strict: bool) -> core::Result<core::Options> {
+    let delimiter = if delimiter.is_null() { None } else { Some(read_str(delimiter, "delimiter")?) };
+    core::options(delimiter, Some(strict))
+}
+
+unsafe fn finish(result: core::Result<String>, err: *mut ToonError) -> *mut c_char {
+    let result = result.and_then(|text| {
+        CString::new(text).map_err(|_| core::Error::InvalidArgument("output contains a NUL byte".to_string()))
+    });
+    match result {
+        Ok(text) => {
+            if !err.is_null() {
+                *err = ToonError { code: TOON_OK, line: 0, message: ptr::null_mut() };
+            }
+            text.into_raw()
+        }
+        Err(e) => {
+            if !err.is_null() {
+                let (code, line) = match &e {
+                    core::Error::Toon(toon::Error::Syntax { line, .. }) => (TOON_ERR_SYNTAX, *line),
+                    core::Error::Toon(_) => (TOON_ERR_OTHER, 0),
+                    core::Error::InvalidJson(_) => (TOON_ERR_INVALID_JSON, 0),
+                    core::Error::InvalidArgument(_) => (TOON_ERR_INVALID_ARGUMENT, 0),
+                };
+                let message = CString::new(e.to_string().replace('\0', " ")).unwrap_or_default();
+                *err = ToonError { code, line, message: message.into_raw() };
+            }
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Encode a JSON document as TOON. Returns NULL on failure.
+///
+/// # Safety
+///
+/// `json` must be a NUL-terminated string; `delimiter` is NULL or a
+/// NUL-terminated "comma", "tab" or "pipe"; `err` is NULL or writable.
+#[no_mangle]
+pub unsafe extern "C" fn toon_encode(
+    json: *const c_char,
+    delimiter: *const c_char,
+    strict: bool,
+    err: *mut ToonError,
+) -> *mut c_char {
+    let result = read_str(json, "json")
+        .and_then(|json| Ok((json, read_options(delimiter, strict)?)))
+        .and_then(|(json, opts)| core::json_to_toon(json, &opts));
+    finish(result, err)
+}
+
+/// Decode a TOON document to JSON. Returns NULL on failure.
+///
+/// # Safety
+///
+/// `toon` must be a NUL-terminated string; `err` is NULL or writable.
+#[no_mangle]
+pub unsafe extern "C" fn toon_decode(toon: *const c_char, pretty: bool, strict: bool, err: *mut ToonError) -> *mut c_char {
+    let result = read_str(toon, "toon")
+        .and_then(|toon| Ok((toon, read_options(ptr::null(), strict)?)))
+        .and_then(|(toon, opts)| core::toon_to_json(toon, pretty, &opts));
+    finish(result, err)
+}
+
+/// Release a string returned by this library. NULL is ignored.
+///
+/// # Safety
+///
+/// `s` must come from `toon_encode`/`toon_decode` and not be freed twice.
+#[no_mangle]
+pub unsafe extern "C" fn toon_string_free(s: *mut c_char) {
+    if !s.is_null() {
+        drop(CString::from_raw(s));
+    }
+}
+
+/// Release the message held by `err` and reset it.
+///
+/// # Safety
+///
+/// `err` is NULL or was filled in by this library.
+#[no_mangle]
+pub unsafe extern "C" fn toon_error_clear(err: *mut ToonError) {
+    if let Some(err) = err.as_mut() {
+        toon_string_free(err.message);
+        *err = ToonError { code: TOON_OK, line: 0, message: ptr::null_mut() };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn blank() -> ToonError {
+        ToonError { code: -1, line: 0, message: ptr::null_mut() }
+    }
+
+    /// Take ownership of a returned string.
+    unsafe fn take(s: *mut c_char) -> Option<String> {
+        if s.is_null() {
+            return None;
+        }
+        let text = CStr::from_ptr(s).to_str().unwrap().to_string();
+        toon_string_free(s);
+        Some(text)
+    }
+
+    #[test]
+    fn encode_and_decode_round_trip() {
+        let json = CString::new(r#"{"xs":[1,2]}"#).unwrap();
+        let pipe = CString::new("pipe").unwrap();
+        let mut err = blank();
+        unsafe {
+            let toon = take(toon_encode(json.as_ptr(), pipe.as_ptr(), false, &mut err)).unwrap();
+            assert_eq!(toon, "xs[2|]: 1|2");
+            assert_eq!((err.code, err.message.is_null()), (TOON_OK, true));
+
+            let toon = CString::new(toon).unwrap();
+            let back = take(toon_decode(toon.as_ptr(), false, false, &mut err)).unwrap();
+            assert_eq!(back, r#"{"xs":[1,2]}"#);
+            assert_eq!(err.code, TOON_OK);
+        }
+    }
+
+    #[test]
+    fn failures_fill_in_the_error() {
+        let cases: [(&str, Option<&str>, c_int); 2] =
+            [("{", None, TOON_ERR_INVALID_JSON), ("{}", Some("semicolon"), TOON_ERR_INVALID_ARGUMENT)];
+        for (json, delimiter, code) in cases {
+            let json = CString::new(json).unwrap();
+            let delimiter = delimiter.map(|d| CString::new(d).unwrap());
+            let delimiter = delimiter.as_ref().map_or(ptr::null(), |d| d.as_ptr());
+            let mut err = blank();
+            unsafe {
+                let out = toon_encode(json.as_ptr(), delimiter, false, &mut err);
+                assert!(out.is_null());
+                assert_eq!(err.code, code);
+                assert!(!CStr::from_ptr(err.message).to_bytes().is_empty());
+                toon_error_clear(&mut err);
+                assert_eq!((err.code, err.message.is_null()), (TOON_OK, true));
+            }
+        }
+    }
+
+    #[test]
+    fn syntax_errors_carry_their_line() {
+        let toon = CString::new("a: 1\nxs[3]: 1,2\n").unwrap();
+        let mut err = blank();
+        unsafe {
+            assert!(toon_decode(toon.as_ptr(), false, true, &mut err).is_null());
+            assert_eq!((err.code, err.line), (TOON_ERR_SYNTAX, 2));
+            assert!(CStr::from_ptr(err.message).to_str().unwrap().starts_with("Line 2: "));
+            toon_error_clear(&mut err);
+        }
+    }
+
+    #[test]
+    fn null_arguments_are_rejected() {
+        let mut err = blank();
+        unsafe {
+            assert!(toon_decode(ptr::null(), false, false, &mut err).is_null());
+            assert_eq!(err.code, TOON_ERR_INVALID_ARGUMENT);
+            assert_eq!(CStr::from_ptr(err.message).to_str().unwrap(), "toon must not be NULL");
+            toon_error_clear(&mut err);
+            // A NULL error pointer is allowed, and so is freeing NULL
+            assert!(toon_encode(ptr::null(), ptr::null(), false, ptr::null_mut()).is_null());
+            toon_string_free(ptr::null_mut());
+            toon_error_clear(ptr::null_mut());
+        }
+    }
+}
@@ -0,0 +1,97 @@
+//! Key case conversion between Python and foreign naming conventions.
+
+use serde_json::{Map, Value};
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum KeyCase {
+    Snake,
+    Camel,
+    Kebab,
+}
+
+impl KeyCase {
+    pub(crate) fn parse(name: &str) -> Option<Self> {
+        match name {
+            "snake" => Some(KeyCase::Snake),
+            "camel" => Some(KeyCase::Camel),
+            "kebab" => Some(KeyCase::Kebab),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            KeyCase::Snake => "snake",
+            KeyCase::Camel => "camel",
+            KeyCase::Kebab => "kebab",
+        }
+    }
+
+    /// Rewrite `key` in this case, e.g. `user_id` -> `userId` for camel.
+    pub(crate) fn apply(self, key: &str) -> String {
+        let words = split_words(key);
+        match self {
+            KeyCase::Snake => words.join("_"),
+            KeyCase::Kebab => words.join("-"),
+            KeyCase::Camel => {
+                let mut out = String::with_capacity(key.len());
+                for (i, word) in words.iter().enumerate() {
+                    if i == 0 {
+                        out.push_str(word);
+                    } else {
+                        let mut chars = word.chars();
+                        if let Some(first) = chars.next() {
+                            out.extend(first.to_uppercase());
+                            out.push_str(chars.as_str());
+                        }
+                    }
+                }
+                out
+            }
+        }
+    }
+}
+
+/// Lowercase words of `key`, split on `_`, `-`, spaces and case changes.
+/// Acronyms stay together: `HTTPServer` gives `http`, `server`.
+fn split_words(key: &str) -> Vec<String> {
+    let chars: Vec<char> = key.chars().collect();
+    let mut words = Vec::new();
+    let mut current = String::new();
+
+    for (i, &c) in chars.iter().enumerate() {
+        if c == '_' || c == '-' || c == ' ' {
+            if !current.is_empty() {
+                words.push(std::mem::take(&mut current));
+            }
+            continue;
+        }
+        if c.is_uppercase() && !current.is_empty() {
+            let prev = chars[i - 1];
+            let next_lower = chars.get(i + 1).is_some_and(|n| n.is_lowercase());
+            if prev.is_lowercase() || prev.is_ascii_digit() || (prev.is_uppercase() && next_lower) {
+                words.push(std::mem::take(&mut current));
+            }
+        }
+        current.extend(c.to_lowercase());
+    }
+    if !current.is_empty() {
+        words.push(current);
+    }
+    words
+}
+
+/// Rename every object key in `value`, recursively.
+pub(crate) fn transform_keys(value: Value, case: KeyCase) -> Value {
+    match value {
+        Value::Object(map) => {
+            let mut renamed = Map::with_capacity(map.len());
+            for (k, v) in map {
+                renamed.insert(case.apply(&k), transform_keys(v, case));
+            }
+            Value::Object(renamed)
+        }
+        Value::Array(items) => Value::Array(items.into_iter().map(|v| transform_keys(v, case)).collect()),
+        other => other,
+    }
+}
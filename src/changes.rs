@@ -0,0 +1,58 @@
+//! Value-level differences between two documents.
+
+use serde_json::Value;
+
+use crate::{canonical, path};
+
+pub enum Change<'a> {
+    Added(String, &'a Value),
+    Removed(String, &'a Value),
+    Changed(String, &'a Value, &'a Value),
+}
+
+/// Every difference between `old` and `new`, in document order. Objects are
+/// compared by key and arrays by position; numbers compare by value.
+pub(crate) fn diff<'a>(old: &'a Value, new: &'a Value) -> Vec<Change<'a>> {
+    let mut out = Vec::new();
+    walk(old, new, "$".to_string(), &mut out);
+    out
+}
+
+fn walk<'a>(old: &'a Value, new: &'a Value, path: String, out: &mut Vec<Change<'a>>) {
+    match (old, new) {
+        (Value::Object(a), Value::Object(b)) => {
+            for (k, va) in a {
+                let mut child = path.clone();
+                path::push_key(&mut child, k);
+                match b.get(k) {
+                    Some(vb) => walk(va, vb, child, out),
+                    None => out.push(Change::Removed(child, va)),
+                }
+            }
+            for (k, vb) in b {
+                if !a.contains_key(k) {
+                    let mut child = path.clone();
+                    path::push_key(&mut child, k);
+                    out.push(Change::Added(child, vb));
+                }
+            }
+        }
+        (Value::Array(a), Value::Array(b)) => {
+            for (i, va) in a.iter().enumerate() {
+                let child = format!("{}[{}]", path, i);
+                match b.get(i) {
+                    Some(vb) => walk(va, vb, child, out),
+                    None => out.push(Change::Removed(child, va)),
+                }
+            }
+            for (i, vb) in b.iter().enumerate().skip(a.len()) {
+                out.push(Change::Added(format!("{}[{}]", path, i), vb));
+            }
+        }
+        _ => {
+            if !canonical::deep_equal(old, new, 0.0, false) {
+                out.push(Change::Changed(path, old, new));
+            }
+        }
+    }
+}
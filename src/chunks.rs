@@ -0,0 +1,202 @@
+//! `TableChunks`, the iterator returned by `iter_table_chunks()`.
+//!
+//! The file is read line by line, whatever its line endings: the iterator skips to the table's header,
+//! then parses the rows `chunk_rows` at a time as a table of their own and
+//! turns each batch into columns. Only one chunk of rows is held at a time,
+//! so memory stays flat however long the table is. The rows are counted as
+//! they go by and checked against the header's `[N]` once the table ends.
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyList};
+use serde_json::Value;
+use std::fs::File;
+use std::io::{self, BufReader};
+use std::path::Path;
+
+use crate::{diagnostics, header_types, newline, scan};
+use crate::python::{convert_toon_error, json_to_python, ToonIOError};
+
+fn unquote(key: &str) -> &str {
+    let key = key.trim();
+    key.strip_prefix('"').and_then(|k| k.strip_suffix('"')).unwrap_or(key)
+}
+
+fn io_error(path: &Path, err: io::Error) -> PyErr {
+    ToonIOError::new_err(format!("{}: {}", path.display(), err))
+}
+
+/// Column-oriented chunks of one table in a TOON file.
+///
+/// Each item is a dict of lists (``{"id": [...], "score": [...]}``) or, with
+/// ``format="arrow"``, a ``pyarrow.RecordBatch``.
+#[pyclass(module = "toon_parser")]
+pub(crate) struct TableChunks {
+    reader: BufReader<File>,
+    /// Delimiter and field list of the table's header, repeated for each chunk
+    delimiter: char,
+    fields: String,
+    columns: Vec<String>,
+    indent: usize,
+    chunk_rows: usize,
+    arrow: bool,
+    opts: toon::Options,
+    /// Line of the header and the row count it declares
+    header_line: usize,
+    declared: usize,
+    /// Rows read so far
+    seen: usize,
+    /// Number of the last line read
+    line_no: usize,
+    done: bool,
+}
+
+impl TableChunks {
+    /// Open `path` and read up to the header of the table at `table` (dotted
+    /// keys), or of the first table.
+    pub(crate) fn open(path: &Path, table: Option<&str>, chunk_rows: usize, arrow: bool, opts: toon::Options) -> PyResult<Self> {
+        let mut reader = BufReader::new(File::open(path).map_err(|e| io_error(path, e))?);
+        let mut line = String::new();
+        let mut line_no = 0;
+        // Keys of the objects enclosing the current line, with their indentation
+        let mut parents: Vec<(usize, String)> = Vec::new();
+        loop {
+            line.clear();
+            if newline::read_line(&mut reader, &mut line).map_err(|e| io_error(path, e))? == 0 {
+                return Err(PyValueError::new_err(match table {
+                    Some(table) => format!("no table '{}' in {}", table, path.display()),
+                    None => format!("no table in {}", path.display()),
+                }));
+            }
+            line_no += 1;
+            let raw = line.as_str();
+            let content = raw.trim_start_matches(' ').trim_end();
+            if content.is_empty() {
+                continue;
+            }
+            let indent = raw.len() - raw.trim_start_matches(' ').len();
+            while parents.last().is_some_and(|&(at, _)| at >= indent) {
+                parents.pop();
+            }
+            if let Some((key, delimiter, fields)) = diagnostics::table_header(content) {
+                let mut keys: Vec<&str> = parents.iter().map(|(_, key)| key.as_str()).collect();
+                keys.push(unquote(key));
+                if table.is_none_or(|table| table == keys.join(".")) {
+                    return Ok(TableChunks {
+                        reader,
+                        delimiter,
+                        fields: fields.to_string(),
+                        columns: scan::split_cells(fields, delimiter).into_iter().map(|field| diagnostics::field_name(field).to_string()).collect(),
+                        indent,
+                        chunk_rows,
+                        arrow,
+                        opts,
+                        header_line: line_no,
+                        declared: diagnostics::declared_length(content).unwrap_or(0),
+                        seen: 0,
+                        line_no,
+                        done: false,
+                    });
+                }
+            } else if let Some(key) = content.strip_suffix(':').filter(|_| diagnostics::array_header(content).is_none()) {
+                parents.push((indent, unquote(key).to_string()));
+            }
+        }
+    }
+
+    /// The next rows of the table, trimmed, with their line numbers.
+    ///
+    /// Rows are trimmed of spaces only: with a tab delimiter, a trailing tab
+    /// is an empty last cell.
+    fn read_rows(&mut self) -> io::Result<Vec<(usize, String)>> {
+        let mut rows = Vec::new();
+        let mut line = String::new();
+        while !self.done && rows.len() < self.chunk_rows {
+            line.clear();
+            if newline::read_line(&mut self.reader, &mut line)? == 0 {
+                self.done = true;
+                break;
+            }
+            self.line_no += 1;
+            let raw = line.as_str();
+            let content = raw.trim_start_matches(' ').trim_end_matches(' ');
+            if content.is_empty() {
+                continue;
+            }
+            if raw.len() - raw.trim_start_matches(' ').len() <= self.indent {
+                // Past the last row
+                self.done = true;
+                break;
+            }
+            rows.push((self.line_no, content.to_string()));
+        }
+        self.seen += rows.len();
+        Ok(rows)
+    }
+
+    /// An error when the table has ended with another number of rows than
+    /// its header declares.
+    fn check_length(&self) -> Result<(), toon::Error> {
+        if !self.done || self.seen == self.declared {
+            return Ok(());
+        }
+        Err(toon::Error::Syntax {
+            line: self.header_line,
+            message: format!("table declares {} rows but has {}", self.declared, self.seen),
+        })
+    }
+
+    /// `rows` parsed as a table of their own.
+    fn parse(&self, rows: &[(usize, String)]) -> Result<Vec<Value>, toon::Error> {
+        let suffix = if self.delimiter == ',' { String::new() } else { self.delimiter.to_string() };
+        let mut doc = format!("[{}{}]{{{}}}:\n", rows.len(), suffix, self.fields);
+        for (_, row) in rows {
+            doc.push_str("  ");
+            doc.push_str(row);
+            doc.push('\n');
+        }
+        let doc = header_types::apply(&doc)?;
+        match toon::decode_from_str::<Value>(&doc, &self.opts) {
+            Ok(Value::Array(items)) => Ok(items),
+            Ok(_) => Err(toon::Error::Message("table rows did not decode to a list".into())),
+            // Report the line in the file, not in the chunk
+            Err(toon::Error::Syntax { line, message }) => Err(toon::Error::Syntax {
+                line: line.checked_sub(2).and_then(|i| rows.get(i)).map_or(line, |&(no, _)| no),
+                message,
+            }),
+            Err(err) => Err(err),
+        }
+    }
+}
+
+#[pymethods]
+impl TableChunks {
+    fn __iter__(slf: PyRef<'_, Self>) -> PyRef<'_, Self> {
+        slf
+    }
+
+    fn __next__<'py>(&mut self, py: Python<'py>) -> PyResult<Option<Bound<'py, PyAny>>> {
+        let rows = py.detach(|| self.read_rows()).map_err(|e| ToonIOError::new_err(e.to_string()))?;
+        self.check_length().map_err(convert_toon_error)?;
+        if rows.is_empty() {
+            return Ok(None);
+        }
+        let items = py.detach(|| self.parse(&rows)).map_err(convert_toon_error)?;
+        let chunk = PyDict::new(py);
+        for name in &self.columns {
+            let mut cells = Vec::with_capacity(items.len());
+            for item in &items {
+                cells.push(match item.get(name.as_str()) {
+                    Some(value) => json_to_python(py, value)?,
+                    None => py.None().into_bound(py),
+                });
+            }
+            chunk.set_item(name, PyList::new(py, cells)?)?;
+        }
+        if self.arrow {
+            let batch = py.import("pyarrow")?.getattr("RecordBatch")?.call_method1("from_pydict", (chunk,))?;
+            return Ok(Some(batch));
+        }
+        Ok(Some(chunk.into_any()))
+    }
+}
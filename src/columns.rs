@@ -0,0 +1,62 @@
+//! Columnar views of tables: a list of row objects as one list per column,
+//! and back.
+
+use serde_json::{Map, Value};
+
+/// The table `decode_columns` reads when no path is given: the root itself
+/// when it is a list, or the only list field of a root object.
+pub(crate) fn default_table(root: &Value) -> Result<&Vec<Value>, String> {
+    match root {
+        Value::Array(rows) => Ok(rows),
+        Value::Object(map) => {
+            let mut tables = map.values().filter_map(Value::as_array);
+            match (tables.next(), tables.next()) {
+                (Some(rows), None) => Ok(rows),
+                (None, _) => Err("document has no table".to_string()),
+                (Some(_), Some(_)) => Err("document has several tables; pass path= to pick one".to_string()),
+            }
+        }
+        _ => Err("document has no table".to_string()),
+    }
+}
+
+/// A column name and its cells, `None` where a row lacks the column.
+pub(crate) type Column<'a> = (&'a str, Vec<Option<&'a Value>>);
+
+/// One column per key, in first-seen order.
+pub(crate) fn split(rows: &[Value]) -> Result<Vec<Column<'_>>, String> {
+    let mut names: Vec<&str> = Vec::new();
+    for (i, row) in rows.iter().enumerate() {
+        let row = row.as_object().ok_or_else(|| format!("row {} is not an object", i))?;
+        for key in row.keys() {
+            if !names.contains(&key.as_str()) {
+                names.push(key);
+            }
+        }
+    }
+    Ok(names
+        .into_iter()
+        .map(|name| {
+            let cells = rows.iter().map(|row| row.get(name)).collect();
+            (name, cells)
+        })
+        .collect())
+}
+
+/// Rows built from equally long columns, the inverse of [`split`].
+pub(crate) fn join(columns: Vec<(String, Vec<Value>)>) -> Result<Vec<Value>, String> {
+    let len = columns.first().map_or(0, |(_, cells)| cells.len());
+    if let Some((name, cells)) = columns.iter().find(|(_, cells)| cells.len() != len) {
+        return Err(format!(
+            "column '{}' has {} values, but '{}' has {}",
+            name, cells.len(), columns[0].0, len
+        ));
+    }
+    let mut rows: Vec<Map<String, Value>> = (0..len).map(|_| Map::with_capacity(columns.len())).collect();
+    for (name, cells) in columns {
+        for (row, cell) in rows.iter_mut().zip(cells) {
+            row.insert(name.clone(), cell);
+        }
+    }
+    Ok(rows.into_iter().map(Value::Object).collect())
+}
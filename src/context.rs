@@ -0,0 +1,341 @@
+//! Syntactic context at a position, for editor tooling (`context_at`).
+//!
+//! A language server needs to know what the cursor is in rather than what
+//! the document decodes to. [`at`] walks the lines before the position,
+//! tracking the enclosing objects, lists and tables by indentation, then
+//! classifies the position within its own line. It never fails: documents
+//! being edited are usually invalid.
+
+use crate::scan::{self, closing_quote, Quotes};
+use crate::{diagnostics, path};
+
+pub(crate) struct Context {
+    /// 1-based
+    pub(crate) line: usize,
+    /// 1-based, in characters
+    pub(crate) column: usize,
+    /// 'blank', 'indent', 'key', 'header', 'value', 'list_item' or 'table_row'
+    pub(crate) kind: &'static str,
+    /// Path of the value the position belongs to, e.g. `$.users[1].name`
+    pub(crate) path: String,
+    pub(crate) key: Option<String>,
+    /// 0-based row within a table
+    pub(crate) row: Option<usize>,
+    /// 0-based cell within a table row or inline array
+    pub(crate) cell: Option<usize>,
+    /// Table field of `cell`
+    pub(crate) field: Option<String>,
+    pub(crate) in_string: bool,
+    /// What may come next: 'key', 'value', ':', '[', '-', 'row', 'length',
+    /// 'field', a delimiter, 'newline', or '"' inside a string
+    pub(crate) expected: Vec<&'static str>,
+}
+
+enum Frame {
+    Object,
+    List { items: usize },
+    Table { fields: Vec<String>, delimiter: char, rows: usize },
+}
+
+struct Open {
+    /// Indent of the lines inside
+    indent: usize,
+    path: String,
+    frame: Frame,
+}
+
+/// Parts of an entry line: the key, the header (`[N]{fields}`) if any, and
+/// the byte offset just past the colon.
+struct Entry<'a> {
+    key: &'a str,
+    key_end: usize,
+    header: Option<(usize, usize)>,
+    colon: Option<usize>,
+}
+
+fn entry(content: &str) -> Entry<'_> {
+    let key_end = if content.starts_with('"') {
+        closing_quote(content).unwrap_or(content.len())
+    } else {
+        content.find([':', '[']).unwrap_or(content.len())
+    };
+    let raw_key = &content[..key_end];
+    let key = raw_key.strip_prefix('"').map_or(raw_key, |k| k.strip_suffix('"').unwrap_or(k));
+    let mut header = None;
+    let mut after = key_end;
+    if content[key_end..].starts_with('[') {
+        if let Some(close) = content[key_end..].find(']') {
+            after = key_end + close + 1;
+            if content[after..].starts_with('{') {
+                after = content[after..].find('}').map_or(content.len(), |end| after + end + 1);
+            }
+        } else {
+            after = content.len();
+        }
+        header = Some((key_end, after));
+    }
+    let colon = content[after..].starts_with(':').then_some(after + 1);
+    Entry { key, key_end, header, colon }
+}
+
+/// Whether `pos` in `text` falls inside a quoted string, and how many
+/// unquoted `delimiter`s come before it.
+fn scan(text: &str, pos: usize, delimiter: char) -> (bool, usize) {
+    let mut quotes = Quotes::default();
+    let mut cells = 0;
+    for (_, c) in text.char_indices().take_while(|&(i, _)| i < pos) {
+        if quotes.step(c) && c == delimiter {
+            cells += 1;
+        }
+    }
+    (quotes.inside(), cells)
+}
+
+fn fields(header: &str, delimiter: char) -> Vec<String> {
+    let Some(open) = header.find('{') else {
+        return Vec::new();
+    };
+    let body = &header[open + 1..header.rfind('}').unwrap_or(header.len())];
+    scan::split_cells(body, delimiter)
+        .into_iter()
+        .map(|f| {
+            let f = f.trim();
+            f.strip_prefix('"').and_then(|f| f.strip_suffix('"')).unwrap_or(f).to_string()
+        })
+        .collect()
+}
+
+fn child(path: &str, key: &str) -> String {
+    let mut child = path.to_string();
+    if !key.is_empty() {
+        path::push_key(&mut child, key);
+    }
+    child
+}
+
+/// Open the block an entry line starts, if any.
+fn open_entry(stack: &mut Vec<Open>, content: &str, indent: usize, path: &str) {
+    let parts = entry(content);
+    let Some(colon) = parts.colon else {
+        return;
+    };
+    if !content[colon..].trim().is_empty() {
+        return;
+    }
+    let path = child(path, parts.key);
+    let frame = match parts.header {
+        Some((start, end)) => match diagnostics::array_header(content) {
+            Some((delimiter, _)) => match fields(&content[start..end], delimiter) {
+                fields if fields.is_empty() => Frame::List { items: 0 },
+                fields => Frame::Table { fields, delimiter, rows: 0 },
+            },
+            None => return,
+        },
+        None => Frame::Object,
+    };
+    stack.push(Open { indent: indent + 2, path, frame });
+}
+
+/// The context at byte offset `offset` of `source`. Lines may end in `\n`,
+/// `\r\n` or a lone `\r`.
+pub(crate) fn at(source: &str, mut offset: usize) -> Context {
+    // Between the two bytes of `\r\n` is still the end of the line
+    if source[..offset].ends_with('\r') && source[offset..].starts_with('\n') {
+        offset -= 1;
+    }
+    let line_start = source[..offset].rfind(['\n', '\r']).map_or(0, |i| i + 1);
+    let line_no = source[..line_start].matches('\n').count() + source[..line_start].matches('\r').count()
+        - source[..line_start].matches("\r\n").count()
+        + 1;
+    let mut stack = vec![Open { indent: 0, path: "$".to_string(), frame: Frame::Object }];
+
+    // `\r\n` splits into an extra blank line, which changes nothing
+    for raw in source[..line_start].split(['\n', '\r']) {
+        let content = raw.trim_start_matches(' ');
+        if content.trim().is_empty() {
+            continue;
+        }
+        let indent = raw.len() - content.len();
+        let content = content.trim_end();
+        while stack.len() > 1 && stack.last().is_some_and(|top| top.indent > indent) {
+            stack.pop();
+        }
+        let Some(top) = stack.last_mut() else {
+            break;
+        };
+        match &mut top.frame {
+            Frame::Table { rows, .. } => *rows += 1,
+            Frame::List { items } => {
+                let Some(rest) = content.strip_prefix("- ").or((content == "-").then_some("")) else {
+                    continue;
+                };
+                let path = format!("{}[{}]", top.path, items);
+                *items += 1;
+                if rest.is_empty() {
+                    stack.push(Open { indent: indent + 2, path, frame: Frame::Object });
+                } else if entry(rest).colon.is_some() {
+                    let key_path = path.clone();
+                    // `- key: value` starts an object whose other keys align with `key`
+                    if !entry(rest).key.is_empty() {
+                        stack.push(Open { indent: indent + 2, path, frame: Frame::Object });
+                    }
+                    open_entry(&mut stack, rest, indent + 2, &key_path);
+                }
+            }
+            Frame::Object => {
+                let path = top.path.clone();
+                open_entry(&mut stack, content, indent, &path);
+            }
+        }
+    }
+
+    let line_end = source[line_start..].find(['\n', '\r']).map_or(source.len(), |i| line_start + i);
+    let raw = &source[line_start..line_end];
+    let col = (offset - line_start).min(raw.len());
+    let content = raw.trim_start_matches(' ');
+    let indent = raw.len() - content.len();
+    let blank = content.trim().is_empty();
+    let mut context = Context {
+        line: line_no,
+        column: raw[..col].chars().count() + 1,
+        kind: "indent",
+        path: String::new(),
+        key: None,
+        row: None,
+        cell: None,
+        field: None,
+        in_string: false,
+        expected: Vec::new(),
+    };
+    // On a blank line the cursor decides which block it continues
+    let level = if blank { col } else { indent };
+    while stack.len() > 1 && stack.last().is_some_and(|top| top.indent > level) {
+        stack.pop();
+    }
+    let Some(top) = stack.last() else {
+        return context;
+    };
+    context.path = top.path.clone();
+    if blank || col <= indent {
+        context.kind = if blank { "blank" } else { "indent" };
+        context.expected = match top.frame {
+            Frame::Table { .. } => vec!["row"],
+            Frame::List { .. } => vec!["-"],
+            Frame::Object => vec!["key"],
+        };
+        return context;
+    }
+    let content = content.trim_end();
+    let pos = (col - indent).min(content.len());
+    match &top.frame {
+        Frame::Table { fields, delimiter, rows } => {
+            let (in_string, cell) = scan(content, pos, *delimiter);
+            context.kind = "table_row";
+            context.row = Some(*rows);
+            context.cell = Some(cell);
+            context.field = fields.get(cell).cloned();
+            context.in_string = in_string;
+            context.path = format!("{}[{}]", top.path, rows);
+            if let Some(field) = &context.field {
+                path::push_key(&mut context.path, field);
+            }
+            context.expected = if in_string {
+                vec!["\""]
+            } else if cell + 1 < fields.len() {
+                vec!["value", delimiter_name(*delimiter)]
+            } else {
+                vec!["value", "newline"]
+            };
+        }
+        Frame::List { items } => {
+            let path = format!("{}[{}]", top.path, items);
+            match content.strip_prefix("- ").or((content == "-").then_some("")) {
+                Some(rest) if pos >= 2 && entry(rest).colon.is_none() => {
+                    context.kind = "value";
+                    context.in_string = scan(rest, pos - 2, '\0').0;
+                    context.path = path;
+                    context.expected = if context.in_string { vec!["\""] } else { vec!["value"] };
+                }
+                Some(rest) if pos >= 2 => classify(&mut context, rest, pos - 2, &path),
+                _ => {
+                    context.kind = "list_item";
+                    context.path = path;
+                    context.expected = vec!["value", "key"];
+                }
+            }
+        }
+        Frame::Object => {
+            let path = top.path.clone();
+            classify(&mut context, content, pos, &path);
+        }
+    }
+    context
+}
+
+fn delimiter_name(delimiter: char) -> &'static str {
+    match delimiter {
+        '|' => "|",
+        '\t' => "\t",
+        _ => ",",
+    }
+}
+
+/// Fill `context` for position `pos` within entry text `content`.
+fn classify(context: &mut Context, content: &str, pos: usize, path: &str) {
+    let parts = entry(content);
+    let (in_string, _) = scan(content, pos, '\0');
+    context.in_string = in_string;
+    context.key = Some(parts.key.to_string());
+    context.path = child(path, parts.key);
+    if pos <= parts.key_end {
+        context.kind = "key";
+        context.expected = if in_string { vec!["\""] } else { vec![":", "["] };
+        return;
+    }
+    match (parts.header, parts.colon) {
+        (Some((start, end)), colon) if pos < colon.unwrap_or(usize::MAX) => {
+            context.kind = "header";
+            let header = &content[start..end];
+            let at = pos - start;
+            let bracket_end = header.find(']').map_or(header.len(), |i| i + 1);
+            let fields_close = header[bracket_end..]
+                .starts_with('{')
+                .then(|| header.rfind('}').filter(|&i| i > bracket_end).unwrap_or(header.len()));
+            context.expected = if at < bracket_end {
+                vec!["length", "]"]
+            } else if fields_close.is_some_and(|close| at > bracket_end && at <= close) {
+                vec!["field", "}"]
+            } else {
+                vec![":"]
+            };
+        }
+        (header, Some(colon)) => {
+            context.kind = "value";
+            let rest = &content[colon..];
+            if in_string {
+                context.expected = vec!["\""];
+                return;
+            }
+            match header.and_then(|_| diagnostics::array_header(content)) {
+                Some((delimiter, values)) if !values.is_empty() || pos > colon => {
+                    let start = colon + (rest.len() - rest.trim_start().len());
+                    let (_, cell) = scan(&content[start..], pos.saturating_sub(start), delimiter);
+                    let declared = diagnostics::declared_length(content).unwrap_or(0);
+                    context.cell = Some(cell);
+                    context.path = format!("{}[{}]", context.path, cell);
+                    context.expected = if cell + 1 < declared {
+                        vec!["value", delimiter_name(delimiter)]
+                    } else {
+                        vec!["value", "newline"]
+                    };
+                }
+                _ if rest.trim().is_empty() => context.expected = vec!["value", "newline"],
+                _ => context.expected = vec!["value"],
+            }
+        }
+        _ => {
+            context.kind = "key";
+            context.expected = vec![":", "["];
+        }
+    }
+}
@@ -0,0 +1,278 @@
+//! Pure-Rust API shared by every binding.
+//!
+//! Nothing in this module depends on PyO3. Rust applications can use it by
+//! depending on the crate with `default-features = false`:
+//!
+//! ```toml
+//! toonpy = { git = "https://github.com/magi8101/toonpy", default-features = false }
+//! ```
+//!
+//! ```ignore
+//! use toon_parser::core;
+//!
+//! let opts = core::options(Some("tab"), None)?;
+//! let toon = core::json_to_toon(r#"{"id": 1}"#, &opts)?;
+//! ```
+
+use std::fmt;
+
+pub use crate::changes::Change;
+pub use crate::diagnostics::Diagnostic;
+pub use serde_json::Value;
+pub use toon::{Delimiter, Options};
+
+use crate::{canonical, changes, diagnostics, front, path, trace};
+
+/// Version of this crate.
+pub const VERSION: &str = env!("CARGO_PKG_VERSION");
+/// Version of the linked `toon` crate as resolved in Cargo.lock, or "unknown".
+pub const TOON_VERSION: &str = env!("TOON_CRATE_VERSION");
+/// Where the `toon` crate came from, e.g. `git+https://...#<commit>`.
+pub const TOON_SOURCE: &str = env!("TOON_CRATE_SOURCE");
+/// Features enabled on the `toon` dependency.
+pub const TOON_FEATURES: &[&str] = &["json", "de_direct", "perf_memchr", "perf_smallvec", "perf_lexical"];
+/// Cargo profile of this build ("debug" or "release").
+pub const PROFILE: &str = env!("TOON_BUILD_PROFILE");
+
+/// Cargo features this build was compiled with.
+pub fn features() -> Vec<&'static str> {
+    [
+        ("python", cfg!(feature = "python")),
+        ("wasm", cfg!(feature = "wasm")),
+        ("capi", cfg!(feature = "capi")),
+        ("tracing", cfg!(feature = "tracing")),
+    ]
+    .into_iter()
+    .filter_map(|(name, enabled)| enabled.then_some(name))
+    .collect()
+}
+
+#[derive(Debug)]
+pub enum Error {
+    /// The TOON parser or encoder failed.
+    Toon(toon::Error),
+    /// The input was not valid JSON.
+    InvalidJson(serde_json::Error),
+    /// An argument such as a delimiter name or path expression was invalid.
+    InvalidArgument(String),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Toon(toon::Error::Syntax { line, message }) => write!(f, "Line {}: {}", line, message),
+            Error::Toon(toon::Error::Message(msg)) => f.write_str(msg),
+            Error::Toon(toon::Error::Io(err)) => write!(f, "{}", err),
+            Error::Toon(toon::Error::SerdeJson(err)) => write!(f, "JSON error: {}", err),
+            Error::InvalidJson(err) => write!(f, "Invalid JSON: {}", err),
+            Error::InvalidArgument(msg) => f.write_str(msg),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<toon::Error> for Error {
+    fn from(err: toon::Error) -> Self {
+        Error::Toon(err)
+    }
+}
+
+/// Parse a delimiter name: 'comma', 'tab' or 'pipe'.
+pub fn parse_delimiter(name: &str) -> Result<Delimiter> {
+    match name {
+        "comma" => Ok(Delimiter::Comma),
+        "tab" => Ok(Delimiter::Tab),
+        "pipe" => Ok(Delimiter::Pipe),
+        _ => Err(Error::InvalidArgument(format!(
+            "Invalid delimiter '{}'. Must be 'comma', 'tab', or 'pipe'", name
+        ))),
+    }
+}
+
+pub fn delimiter_name(delimiter: Delimiter) -> &'static str {
+    match delimiter {
+        Delimiter::Comma => "comma",
+        Delimiter::Tab => "tab",
+        Delimiter::Pipe => "pipe",
+    }
+}
+
+/// Build options from optional settings; unset ones keep their defaults.
+pub fn options(delimiter: Option<&str>, strict: Option<bool>) -> Result<Options> {
+    let mut opts = Options::default();
+    if let Some(d) = delimiter {
+        opts.delimiter = parse_delimiter(d)?;
+    }
+    if let Some(s) = strict {
+        opts.strict = s;
+    }
+    Ok(opts)
+}
+
+pub fn encode(value: &Value, opts: &Options) -> Result<String> {
+    trace::span!("encode", bytes = tracing::field::Empty);
+    let text = toon::encode_to_string(value, opts)?;
+    trace::record!("bytes", text.len());
+    Ok(text)
+}
+
+/// Decode a document, skipping front matter and a checksum footer.
+pub fn decode(source: &str, opts: &Options) -> Result<Value> {
+    Ok(decode_with_meta(source, opts)?.0)
+}
+
+/// Decode a document and return its front matter separately.
+pub fn decode_with_meta(source: &str, opts: &Options) -> Result<(Value, Option<Value>)> {
+    trace::span!("parse", bytes = source.len());
+    Ok(front::decode(source, opts, &Default::default())?)
+}
+
+pub fn json_to_toon(json: &str, opts: &Options) -> Result<String> {
+    let value: Value = {
+        trace::span!("convert", direction = "json_to_value", bytes = json.len());
+        serde_json::from_str(json).map_err(Error::InvalidJson)?
+    };
+    encode(&value, opts)
+}
+
+pub fn toon_to_json(source: &str, pretty: bool, opts: &Options) -> Result<String> {
+    let value = decode(source, opts)?;
+    trace::span!("convert", direction = "value_to_json");
+    let json = if pretty { serde_json::to_string_pretty(&value) } else { serde_json::to_string(&value) };
+    json.map_err(|e| Error::Toon(toon::Error::SerdeJson(e)))
+}
+
+/// Re-emit a document in canonical form: sorted keys, canonical numbers,
+/// default delimiter and indentation.
+pub fn normalize(source: &str, opts: &Options) -> Result<String> {
+    let mut value = decode(source, opts)?;
+    canonical::canonicalize(&mut value);
+    encode(&value, &Options::default())
+}
+
+/// Hex digest of the canonical encoding of `value`.
+pub fn content_hash(value: &Value, algorithm: &str) -> Result<String> {
+    let mut value = value.clone();
+    canonical::canonicalize(&mut value);
+    let encoded = encode(&value, &Options::default())?;
+    canonical::hex_digest(algorithm, encoded.as_bytes()).map_err(Error::InvalidArgument)
+}
+
+/// Value-level differences between two documents.
+pub fn diff<'a>(old: &'a Value, new: &'a Value) -> Vec<Change<'a>> {
+    changes::diff(old, new)
+}
+
+/// Every diagnostic for a document, including the parser's syntax error
+/// unless the scan already reported an error on its line.
+pub fn lint(source: &str, strict: bool) -> Vec<Diagnostic> {
+    let mut found = diagnostics::check(source, strict);
+    let opts = options(None, Some(strict)).unwrap_or_default();
+    if let Err(err) = front::decode(source, &opts, &Default::default()) {
+        let (line, message) = match err {
+            toon::Error::Syntax { line, message } => (Some(line), message),
+            other => (None, Error::Toon(other).to_string()),
+        };
+        if !found.iter().any(|d| d.severity == "error" && line.is_some() && d.line == line) {
+            found.push(Diagnostic { line, column: None, severity: "error", message });
+            diagnostics::sort(&mut found);
+        }
+    }
+    found
+}
+
+/// Values matching a JSONPath expression.
+pub fn query<'a>(value: &'a Value, expr: &str) -> Result<Vec<&'a Value>> {
+    let path = path::Path::parse(expr).map_err(Error::InvalidArgument)?;
+    Ok(path.select(value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn options_keep_defaults_for_unset_settings() {
+        let opts = options(Some("pipe"), None).unwrap();
+        assert_eq!(delimiter_name(opts.delimiter), "pipe");
+        assert_eq!(opts.strict, Options::default().strict);
+        assert!(options(None, Some(true)).unwrap().strict);
+        match options(Some("semicolon"), None) {
+            Err(Error::InvalidArgument(msg)) => assert!(msg.contains("'semicolon'")),
+            other => panic!("expected an invalid argument, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn json_and_toon_convert_both_ways() {
+        let opts = Options::default();
+        let toon = json_to_toon(r#"{"users":[{"id":1,"name":"Ann"}]}"#, &opts).unwrap();
+        assert_eq!(toon, "users[1]{id,name}:\n  1,Ann");
+        assert_eq!(toon_to_json(&toon, false, &opts).unwrap(), r#"{"users":[{"id":1,"name":"Ann"}]}"#);
+        assert!(toon_to_json(&toon, true, &opts).unwrap().contains("\n  \"users\": [\n"));
+        assert!(matches!(json_to_toon("{", &opts), Err(Error::InvalidJson(_))));
+    }
+
+    #[test]
+    fn decode_reports_syntax_errors_with_their_line() {
+        let err = decode("a: 1\nxs[3]: 1,2\n", &options(None, Some(true)).unwrap()).unwrap_err();
+        assert!(err.to_string().starts_with("Line 2: "), "{}", err);
+    }
+
+    #[test]
+    fn decode_with_meta_splits_off_front_matter() {
+        let (value, meta) = decode_with_meta("---\nversion: 2\n---\na: 1\n", &Options::default()).unwrap();
+        assert_eq!(value, json!({"a": 1}));
+        assert_eq!(meta, Some(json!({"version": 2})));
+    }
+
+    #[test]
+    fn normalize_and_content_hash_ignore_key_order() {
+        let opts = Options::default();
+        assert_eq!(normalize("b: 2\na: 1.50\n", &opts).unwrap(), "a: 1.5\nb: 2");
+        let a = content_hash(&json!({"a": 1, "b": [1, 2]}), "sha256").unwrap();
+        let b = content_hash(&json!({"b": [1, 2], "a": 1}), "sha256").unwrap();
+        assert_eq!(a, b);
+        assert_eq!(a.len(), 64);
+        assert!(matches!(content_hash(&json!(1), "md4"), Err(Error::InvalidArgument(_))));
+    }
+
+    #[test]
+    fn diff_lists_changes_by_path() {
+        let (old, new) = (json!({"a": 1, "b": [1, 2]}), json!({"a": 2, "b": [1], "c": true}));
+        let paths: Vec<_> = diff(&old, &new)
+            .into_iter()
+            .map(|c| match c {
+                Change::Added(p, _) => format!("+{}", p),
+                Change::Removed(p, _) => format!("-{}", p),
+                Change::Changed(p, _, _) => format!("~{}", p),
+            })
+            .collect();
+        assert_eq!(paths, ["~$.a", "-$.b[1]", "+$.c"]);
+    }
+
+    #[test]
+    fn lint_includes_the_parser_error() {
+        let found = lint("a: 1\nb: \"open\n", false);
+        assert_eq!(found.len(), 1);
+        assert_eq!((found[0].line, found[0].severity), (Some(2), "error"));
+        assert!(lint("a: 1\n", false).is_empty());
+    }
+
+    #[test]
+    fn query_selects_in_document_order() {
+        let value = json!({"rows": [{"id": 1, "ok": true}, {"id": 2, "ok": false}, {"id": 3, "ok": true}]});
+        assert_eq!(query(&value, "$.rows[?(@.ok == true)].id").unwrap(), [&json!(1), &json!(3)]);
+        assert!(matches!(query(&value, "$.rows["), Err(Error::InvalidArgument(_))));
+    }
+
+    #[test]
+    fn features_match_the_build() {
+        assert_eq!(features().contains(&"python"), cfg!(feature = "python"));
+        assert_eq!(features().contains(&"capi"), cfg!(feature = "capi"));
+    }
+}
@@ -0,0 +1,475 @@
+//! Text-level diagnostics for TOON documents.
+//!
+//! The upstream parser stops at the first syntax error. These checks scan the
+//! text directly so that one run can report every problem that can be found
+//! without decoding: indentation, trailing whitespace, and array/table lengths
+//! or row widths that disagree with their headers.
+//!
+//! [`check_reader`] runs the same checks over a stream, one line at a time,
+//! for files too large to hold in memory.
+
+use std::collections::HashSet;
+use std::io::{self, BufRead};
+use std::ops::Range;
+
+use crate::guard::Limits;
+use crate::newline;
+use crate::scan::{closing_quote, split_cells};
+
+pub struct Diagnostic {
+    pub line: Option<usize>,
+    /// 1-based; None for problems only known by line, such as parser errors.
+    pub column: Option<usize>,
+    /// 'error' or 'warning'.
+    pub severity: &'static str,
+    pub message: String,
+}
+
+struct Line<'a> {
+    number: usize,
+    indent: usize,
+    content: &'a str,
+}
+
+/// `key[N<delim>]{fields}: rest`
+struct Header<'a> {
+    declared: usize,
+    bracket_column: usize,
+    delimiter: char,
+    fields: Option<usize>,
+    fields_range: Option<Range<usize>>,
+    rest: &'a str,
+}
+
+pub(crate) fn check(source: &str, strict: bool) -> Vec<Diagnostic> {
+    let mut out = Vec::new();
+    let style = if strict { "error" } else { "warning" };
+    let mut lines = Vec::new();
+
+    for (idx, raw) in source.lines().enumerate() {
+        let raw = raw.strip_suffix('\r').unwrap_or(raw);
+        if let Some(line) = style_checks(idx + 1, raw, style, &mut out) {
+            lines.push(line);
+        }
+    }
+
+    let mut rows = HashSet::new();
+    for (i, line) in lines.iter().enumerate() {
+        if rows.contains(&line.number) {
+            continue;
+        }
+        let (content, offset) = match line.content.strip_prefix("- ") {
+            Some(rest) => (rest, 2),
+            None => (line.content, 0),
+        };
+        let Some(header) = parse_header(content) else {
+            continue;
+        };
+        let column = line.indent + offset + header.bracket_column;
+        let rest = header.rest.trim();
+
+        if !rest.is_empty() {
+            let found = split_cells(rest, header.delimiter).len();
+            if found != header.declared {
+                out.push(length_mismatch(line.number, column, header.declared, found, "values"));
+            }
+            continue;
+        }
+
+        let children = children(&lines[i + 1..], line.indent);
+        match header.fields {
+            Some(width) => {
+                rows.extend(children.iter().map(|row| row.number));
+                for row in &children {
+                    let cells = split_cells(row.content, header.delimiter).len();
+                    if cells != width {
+                        out.push(Diagnostic {
+                            line: Some(row.number),
+                            column: Some(row.indent + 1),
+                            severity: "error",
+                            message: format!("row has {} values but the table declares {} fields", cells, width),
+                        });
+                    }
+                }
+                if children.len() != header.declared {
+                    out.push(length_mismatch(line.number, column, header.declared, children.len(), "rows"));
+                }
+            }
+            None => {
+                let items = children.iter().filter(|c| c.content == "-" || c.content.starts_with("- ")).count();
+                if items != header.declared {
+                    out.push(length_mismatch(line.number, column, header.declared, items, "items"));
+                }
+            }
+        }
+    }
+
+    sort(&mut out);
+    out
+}
+
+/// Report indentation and trailing whitespace problems on one line; None
+/// for blank lines.
+fn style_checks<'a>(number: usize, raw: &'a str, style: &'static str, out: &mut Vec<Diagnostic>) -> Option<Line<'a>> {
+    if raw.trim().is_empty() {
+        return None;
+    }
+    let trimmed = raw.trim_start_matches([' ', '\t']);
+    let lead = &raw[..raw.len() - trimmed.len()];
+    if let Some(pos) = lead.find('\t') {
+        out.push(Diagnostic { line: Some(number), column: Some(pos + 1), severity: "error", message: "tab used for indentation".into() });
+    } else if !lead.len().is_multiple_of(2) {
+        out.push(Diagnostic {
+            line: Some(number),
+            column: Some(1),
+            severity: style,
+            message: format!("indentation of {} spaces is not a multiple of 2", lead.len()),
+        });
+    }
+    let content = trimmed.trim_end();
+    if content.len() < trimmed.len() {
+        out.push(Diagnostic {
+            line: Some(number),
+            column: Some(raw.trim_end().chars().count() + 1),
+            severity: style,
+            message: "trailing whitespace".into(),
+        });
+    }
+    Some(Line { number, indent: lead.len(), content })
+}
+
+/// An array or table header whose items [`check_reader`] is still counting.
+struct Open {
+    number: usize,
+    indent: usize,
+    column: usize,
+    declared: usize,
+    delimiter: char,
+    /// Field count of a table; None for lists
+    width: Option<usize>,
+    /// Indent of the first item, once seen
+    items_at: Option<usize>,
+    found: usize,
+}
+
+impl Open {
+    fn finish(self, out: &mut Vec<Diagnostic>) {
+        if self.found != self.declared {
+            let what = if self.width.is_some() { "rows" } else { "items" };
+            out.push(length_mismatch(self.number, self.column, self.declared, self.found, what));
+        }
+    }
+}
+
+/// [`check`] over a document read line by line, holding only the headers
+/// still open. It also reports what `check` leaves to the parser: invalid
+/// UTF-8, unterminated strings and unexpected indentation, plus `\r` line
+/// endings and empty table cells in strict mode. Exceeded `limits` are
+/// reported as errors. Reading stops once `max_diagnostics` problems have
+/// been found, and at most that many are returned.
+pub(crate) fn check_reader<R: BufRead>(
+    mut reader: R,
+    strict: bool,
+    limits: &Limits,
+    max_diagnostics: usize,
+) -> io::Result<Vec<Diagnostic>> {
+    let mut out = Vec::new();
+    let style = if strict { "error" } else { "warning" };
+    let mut open: Vec<Open> = Vec::new();
+    let mut buf = Vec::new();
+    let mut number = 0;
+    let mut total = 0usize;
+    // Indent of the previous line and whether lines may nest under it
+    let mut previous: Option<(usize, bool)> = None;
+    let mut complete = true;
+    loop {
+        if out.len() >= max_diagnostics {
+            complete = false;
+            break;
+        }
+        buf.clear();
+        let (read, carriage_return) = newline::read_raw_line(&mut reader, &mut buf)?;
+        if read == 0 {
+            break;
+        }
+        number += 1;
+        total = total.saturating_add(read);
+        if carriage_return && strict {
+            let column = String::from_utf8_lossy(&buf).chars().count() + 1;
+            out.push(error(number, column, "carriage return line ending"));
+        }
+        let raw = match std::str::from_utf8(&buf) {
+            Ok(raw) => raw,
+            Err(err) => {
+                let column = String::from_utf8_lossy(&buf[..err.valid_up_to()]).chars().count() + 1;
+                out.push(error(number, column, "invalid UTF-8"));
+                previous = None;
+                continue;
+            }
+        };
+        let Some(line) = style_checks(number, raw, style, &mut out) else {
+            continue;
+        };
+        if let Err(message) = limits.check_line(line.indent, line.content) {
+            out.push(error(number, line.indent + 1, message));
+        }
+        if let Some(column) = unterminated(line.content) {
+            out.push(error(number, line.indent + column, "unterminated string"));
+        }
+        if let Some((indent, _)) = previous.filter(|&(indent, nests)| line.indent > indent && !nests) {
+            out.push(error(number, 1, format!("unexpected indentation of {} spaces under a line at {}", line.indent, indent)));
+        }
+
+        while open.last().is_some_and(|o| o.indent >= line.indent) {
+            if let Some(done) = open.pop() {
+                done.finish(&mut out);
+            }
+        }
+        let mut row = false;
+        if let Some(parent) = open.last_mut() {
+            let items_at = *parent.items_at.get_or_insert(line.indent);
+            if line.indent == items_at {
+                match parent.width {
+                    Some(width) => {
+                        row = true;
+                        parent.found += 1;
+                        let cells = split_cells(line.content, parent.delimiter);
+                        if cells.len() != width {
+                            out.push(error(
+                                number,
+                                line.indent + 1,
+                                format!("row has {} values but the table declares {} fields", cells.len(), width),
+                            ));
+                        } else if strict && cells.iter().any(|cell| cell.trim().is_empty()) {
+                            out.push(error(number, line.indent + 1, "empty table cell"));
+                        }
+                    }
+                    None if line.content == "-" || line.content.starts_with("- ") => parent.found += 1,
+                    None => {}
+                }
+            } else if line.indent < items_at {
+                out.push(error(
+                    number,
+                    1,
+                    format!("indentation of {} spaces does not match the items above at {}", line.indent, items_at),
+                ));
+            }
+        }
+        let nests = !row && (line.content.ends_with(':') || line.content == "-" || line.content.starts_with("- "));
+        previous = Some((line.indent, nests));
+        if row {
+            continue;
+        }
+
+        let (content, offset) = match line.content.strip_prefix("- ") {
+            Some(rest) => (rest, 2),
+            None => (line.content, 0),
+        };
+        let Some(header) = parse_header(content) else {
+            continue;
+        };
+        let column = line.indent + offset + header.bracket_column;
+        let rest = header.rest.trim();
+        if rest.is_empty() {
+            open.push(Open {
+                number,
+                indent: line.indent,
+                column,
+                declared: header.declared,
+                delimiter: header.delimiter,
+                width: header.fields,
+                items_at: None,
+                found: 0,
+            });
+        } else {
+            let found = split_cells(rest, header.delimiter).len();
+            if found != header.declared {
+                out.push(length_mismatch(number, column, header.declared, found, "values"));
+            }
+        }
+    }
+    // Counts and the size are only known when the whole file was read
+    if complete {
+        while let Some(done) = open.pop() {
+            done.finish(&mut out);
+        }
+        if let Err(message) = limits.check_size(total) {
+            out.push(Diagnostic { line: None, column: None, severity: "error", message });
+        }
+    }
+    sort(&mut out);
+    out.truncate(max_diagnostics);
+    Ok(out)
+}
+
+fn error(line: usize, column: usize, message: impl Into<String>) -> Diagnostic {
+    Diagnostic { line: Some(line), column: Some(column), severity: "error", message: message.into() }
+}
+
+/// 1-based column of a quote that is never closed on its line.
+fn unterminated(content: &str) -> Option<usize> {
+    let mut opened = None;
+    let mut escaped = false;
+    for (i, c) in content.chars().enumerate() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if opened.is_some() => escaped = true,
+            '"' => opened = if opened.is_some() { None } else { Some(i + 1) },
+            _ => {}
+        }
+    }
+    opened
+}
+
+/// A table found in the text, with 1-based line numbers.
+pub(crate) struct Table {
+    pub(crate) header: usize,
+    /// Byte range of the field list (between the braces) within the header line
+    pub(crate) fields: Range<usize>,
+    pub(crate) delimiter: char,
+    pub(crate) rows: Vec<usize>,
+}
+
+/// Every table in `source` whose rows follow its header line.
+pub(crate) fn tables(source: &str) -> Vec<Table> {
+    let lines: Vec<Line<'_>> = source
+        .lines()
+        .enumerate()
+        .filter(|(_, raw)| !raw.trim().is_empty())
+        .map(|(idx, raw)| {
+            let trimmed = raw.trim_start_matches(' ');
+            Line { number: idx + 1, indent: raw.len() - trimmed.len(), content: trimmed.trim_end() }
+        })
+        .collect();
+    let mut tables = Vec::new();
+    for (i, line) in lines.iter().enumerate() {
+        let (content, offset) = match line.content.strip_prefix("- ") {
+            Some(rest) => (rest, 2),
+            None => (line.content, 0),
+        };
+        let Some(header) = parse_header(content) else {
+            continue;
+        };
+        if let (Some(fields), "") = (header.fields_range, header.rest.trim()) {
+            let start = line.indent + offset;
+            tables.push(Table {
+                header: line.number,
+                fields: start + fields.start..start + fields.end,
+                delimiter: header.delimiter,
+                rows: children(&lines[i + 1..], line.indent).iter().map(|row| row.number).collect(),
+            });
+        }
+    }
+    tables
+}
+
+/// The delimiter and inline values of an array header line such as
+/// `tags[3|]: a|b|c`; the values are empty for tables and lists.
+pub(crate) fn array_header(content: &str) -> Option<(char, &str)> {
+    let content = content.strip_prefix("- ").unwrap_or(content);
+    parse_header(content).map(|header| (header.delimiter, header.rest.trim()))
+}
+
+/// The key, delimiter and field list of a table header line such as
+/// `users[2]{id,name}:`, whose rows follow on the next lines.
+pub(crate) fn table_header(content: &str) -> Option<(&str, char, &str)> {
+    let content = content.strip_prefix("- ").unwrap_or(content);
+    let header = parse_header(content)?;
+    let fields = header.fields_range.filter(|_| header.rest.trim().is_empty())?;
+    Some((&content[..header.bracket_column - 1], header.delimiter, &content[fields]))
+}
+
+/// The length an array header line such as `users[3]{id,name}:` declares.
+pub(crate) fn declared_length(content: &str) -> Option<usize> {
+    let content = content.strip_prefix("- ").unwrap_or(content);
+    parse_header(content).map(|header| header.declared)
+}
+
+/// Every row of every table in `source`, as its 1-based line number and the
+/// table's delimiter.
+pub(crate) fn table_rows(source: &str) -> Vec<(usize, char)> {
+    tables(source)
+        .into_iter()
+        .flat_map(|table| table.rows.into_iter().map(move |row| (row, table.delimiter)))
+        .collect()
+}
+
+pub(crate) fn sort(diagnostics: &mut [Diagnostic]) {
+    diagnostics.sort_by_key(|d| (d.line.unwrap_or(0), d.column.unwrap_or(0)));
+}
+
+fn length_mismatch(line: usize, column: usize, declared: usize, found: usize, what: &str) -> Diagnostic {
+    Diagnostic {
+        line: Some(line),
+        column: Some(column),
+        severity: "error",
+        message: format!("header declares {} {} but {} found", declared, what, found),
+    }
+}
+
+/// Lines directly nested under the entry at `indent`.
+fn children<'a, 'b>(rest: &'b [Line<'a>], indent: usize) -> Vec<&'b Line<'a>> {
+    let Some(first) = rest.first().filter(|l| l.indent > indent) else {
+        return Vec::new();
+    };
+    rest.iter()
+        .take_while(|l| l.indent > indent)
+        .filter(|l| l.indent == first.indent)
+        .collect()
+}
+
+fn parse_header(content: &str) -> Option<Header<'_>> {
+    let open = if content.starts_with('"') {
+        let close = closing_quote(content)?;
+        close + content[close..].find('[')?
+    } else {
+        content.find('[')?
+    };
+    let key = &content[..open];
+    if !key.starts_with('"') && key.contains([':', ',', '"', '|', '\t']) {
+        return None;
+    }
+    let close = open + content[open..].find(']')?;
+    let inside = &content[open + 1..close];
+    let digits = inside.trim_start_matches('#');
+    let count_len = digits.find(|c: char| !c.is_ascii_digit()).unwrap_or(digits.len());
+    if count_len == 0 {
+        return None;
+    }
+    // Lengths past usize are still lengths, just impossible ones
+    let declared = digits[..count_len].parse().unwrap_or(usize::MAX);
+    let delimiter = match &digits[count_len..] {
+        "" => ',',
+        "|" => '|',
+        "\t" => '\t',
+        _ => return None,
+    };
+
+    let mut after = &content[close + 1..];
+    let mut fields = None;
+    let mut fields_range = None;
+    if let Some(body) = after.strip_prefix('{') {
+        let end = body.find('}')?;
+        fields = Some(split_cells(&body[..end], delimiter).len());
+        let start = close + 2;
+        fields_range = Some(start..start + end);
+        after = &body[end + 1..];
+    }
+    let rest = after.strip_prefix(':')?;
+    Some(Header { declared, bracket_column: open + 1, delimiter, fields, fields_range, rest })
+}
+
+/// The name in a table header field, without quotes or a type annotation
+/// such as `:int`.
+pub(crate) fn field_name(field: &str) -> &str {
+    let field = field.trim();
+    if let Some(name) = field.strip_prefix('"').and_then(|f| f.strip_suffix('"')) {
+        return name;
+    }
+    if field.starts_with('"') {
+        // Quoted and annotated: `"a:b":int`
+        return closing_quote(field).map_or(field, |end| &field[1..end - 1]);
+    }
+    // Unquoted names cannot contain colons
+    field.split(':').next().unwrap_or(field)
+}
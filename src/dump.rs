@@ -0,0 +1,122 @@
+//! Writing encoded documents to files (`dump_path`).
+//!
+//! An atomic write goes to a temporary file in the same directory, which is
+//! then renamed over the target, so readers and crashes only ever see the old
+//! or the new document. The temporary file takes the permissions of the file
+//! it replaces. With fsync the data (and on Unix the directory entry)
+//! reaches the disk before the call returns.
+
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+#[derive(Clone, Copy, Default)]
+pub(crate) struct Mode {
+    /// Hold an advisory lock on the file while writing
+    pub(crate) exclusive: bool,
+    /// Write a temporary file and rename it over the target
+    pub(crate) atomic: bool,
+    /// Flush the data to disk before returning
+    pub(crate) fsync: bool,
+}
+
+/// Open `path` with `options` and lock it. An atomic write may replace the
+/// file while we wait for the lock; then the lock is on a file no longer at
+/// `path`, so open the new one and try again.
+pub(crate) fn open_locked(path: &Path, options: &OpenOptions) -> io::Result<File> {
+    loop {
+        let file = options.open(path)?;
+        // Released when the file is closed
+        file.lock()?;
+        if is_current(&file, path)? {
+            return Ok(file);
+        }
+    }
+}
+
+#[cfg(unix)]
+fn is_current(file: &File, path: &Path) -> io::Result<bool> {
+    use std::os::unix::fs::MetadataExt;
+    let locked = file.metadata()?;
+    match fs::metadata(path) {
+        Ok(current) => Ok(locked.dev() == current.dev() && locked.ino() == current.ino()),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(false),
+        Err(err) => Err(err),
+    }
+}
+
+#[cfg(not(unix))]
+fn is_current(_file: &File, _path: &Path) -> io::Result<bool> {
+    Ok(true)
+}
+
+/// A name next to `path` that no other write uses.
+fn temp_path(path: &Path) -> PathBuf {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+    let name = path.file_name().map_or_else(|| "toon".into(), |n| n.to_string_lossy());
+    let n = COUNTER.fetch_add(1, Ordering::Relaxed);
+    path.with_file_name(format!(".{}.{}.{}.tmp", name, std::process::id(), n))
+}
+
+#[cfg(unix)]
+fn sync_dir(path: &Path) -> io::Result<()> {
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    File::open(dir)?.sync_all()
+}
+
+#[cfg(not(unix))]
+fn sync_dir(_path: &Path) -> io::Result<()> {
+    Ok(())
+}
+
+fn replace(path: &Path, text: &str, fsync: bool) -> io::Result<()> {
+    let temp = temp_path(path);
+    let result = (|| {
+        let mut file = File::create_new(&temp)?;
+        // Keep the mode of the file being replaced, not the default of a new one
+        match fs::metadata(path) {
+            Ok(existing) => file.set_permissions(existing.permissions())?,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {}
+            Err(err) => return Err(err),
+        }
+        file.write_all(text.as_bytes())?;
+        if fsync {
+            file.sync_all()?;
+        }
+        fs::rename(&temp, path)?;
+        if fsync {
+            sync_dir(path)?;
+        }
+        Ok(())
+    })();
+    if result.is_err() {
+        let _ = fs::remove_file(&temp);
+    }
+    result
+}
+
+/// Replace the contents of `path` with `text`, creating the file if needed.
+pub(crate) fn write(path: &Path, text: &str, mode: Mode) -> io::Result<()> {
+    let mut options = OpenOptions::new();
+    options.write(true).create(true).truncate(false);
+    let locked = if mode.exclusive { Some(open_locked(path, &options)?) } else { None };
+    if mode.atomic {
+        // The lock stays on the old file until the new one is in place
+        return replace(path, text, mode.fsync);
+    }
+    let mut file = match locked {
+        Some(file) => file,
+        None => options.open(path)?,
+    };
+    // Truncate only now, so a lock holder never has the file emptied under it
+    file.set_len(0)?;
+    file.write_all(text.as_bytes())?;
+    if mode.fsync {
+        file.sync_all()?;
+    }
+    file.flush()
+}
@@ -0,0 +1,146 @@
+//! Duplicate keys, kept for `decode(..., multidict=True)` and
+//! `decode(..., merge_tables=True)`.
+//!
+//! The parser keeps one value per key. Before parsing, [`mark`] renames the
+//! second and later occurrences of a key within one object to
+//! `key<token><n>`, so every value survives; converting to Python then turns
+//! objects holding marked keys into lists of `(key, value)` pairs.
+//! [`merge_arrays`] instead appends repeated table sections, as written by
+//! append-style loggers, to the first.
+
+use serde_json::Value;
+use std::borrow::Cow;
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+
+use crate::diagnostics;
+use crate::scan::{self, Token};
+
+/// Opens and closes the [`Token`] separating a key from its occurrence
+/// number.
+pub(crate) const MARK: char = '\u{FDD0}';
+
+/// Whether [`mark`] renamed `key` with `token`.
+pub(crate) fn is_marked(key: &str, token: &Token) -> bool {
+    key.contains(token.as_str())
+}
+
+/// The key and its occurrence number, 1 for keys without a mark.
+pub(crate) fn split<'a>(key: &'a str, token: &Token) -> (&'a str, usize) {
+    match key.split_once(token.as_str()) {
+        Some((name, n)) => (name, n.parse().unwrap_or(1)),
+        None => (key, 1),
+    }
+}
+
+/// Byte range of the key of an entry line (`key: v`, `key:` or an array
+/// header), quotes included, and its name without quotes.
+fn key_span(entry: &str) -> Option<(Range<usize>, &str)> {
+    let end = if entry.starts_with('"') {
+        scan::closing_quote(entry)?
+    } else {
+        entry.find([':', '['])?
+    };
+    let rest = &entry[end..];
+    let is_entry = if rest.starts_with('[') {
+        diagnostics::array_header(entry).is_some()
+    } else {
+        rest.strip_prefix(':').is_some_and(|value| value.is_empty() || value.starts_with(' '))
+    };
+    let key = &entry[..end];
+    let name = key.strip_prefix('"').and_then(|k| k.strip_suffix('"')).unwrap_or(key);
+    (is_entry && !name.is_empty()).then_some((0..end, name))
+}
+
+/// `source` with repeated keys renamed with `token`, or borrowed when no
+/// object repeats a key.
+pub(crate) fn mark<'a>(source: &'a str, token: &Token) -> Cow<'a, str> {
+    let rows: HashSet<usize> = diagnostics::table_rows(source).into_iter().map(|(row, _)| row).collect();
+    // Keys seen so far in each open object, innermost last
+    let mut levels: Vec<(usize, HashMap<&str, usize>)> = Vec::new();
+    let mut renames: Vec<(Range<usize>, String)> = Vec::new();
+    let mut offset = 0;
+    for (idx, line) in source.split_inclusive('\n').enumerate() {
+        let start = offset;
+        offset += line.len();
+        let body = line.trim_end_matches(['\n', '\r']);
+        if body.trim().is_empty() || rows.contains(&(idx + 1)) {
+            continue;
+        }
+        let content = body.trim_start_matches(' ');
+        let indent = body.len() - content.len();
+        while levels.last().is_some_and(|(level, _)| *level > indent) {
+            levels.pop();
+        }
+        let (entry, entry_indent) = match content.strip_prefix("- ") {
+            Some(entry) => {
+                // A list item opens a new object
+                levels.retain(|(level, _)| *level <= indent);
+                levels.push((indent + 2, HashMap::new()));
+                (entry, indent + 2)
+            }
+            None if content == "-" => continue,
+            None => (content, indent),
+        };
+        let Some((span, name)) = key_span(entry) else {
+            continue;
+        };
+        if levels.last().is_none_or(|(level, _)| *level != entry_indent) {
+            levels.push((entry_indent, HashMap::new()));
+        }
+        let Some((_, seen)) = levels.last_mut() else {
+            continue;
+        };
+        let count = seen.entry(name).or_insert(0);
+        *count += 1;
+        if *count > 1 {
+            let at = start + body.len() - entry.len();
+            renames.push((at + span.start..at + span.end, format!("\"{}{}{}\"", name, token, count)));
+        }
+    }
+    if renames.is_empty() {
+        return Cow::Borrowed(source);
+    }
+    let mut out = String::with_capacity(source.len() + renames.len() * 8);
+    let mut copied = 0;
+    for (range, key) in renames {
+        out.push_str(&source[copied..range.start]);
+        out.push_str(&key);
+        copied = range.end;
+    }
+    out.push_str(&source[copied..]);
+    Cow::Owned(out)
+}
+
+/// Append the items of every repeated array key (`logs[2]{..}:` followed
+/// later by `logs[1]{..}:`) to its first occurrence, throughout `value`, for
+/// keys [`mark`] renamed with `token`. Other repeated keys keep their marks
+/// when `keep_others` is set (for `multidict`), and otherwise the last value,
+/// as without marking.
+pub(crate) fn merge_arrays(value: &mut Value, token: &Token, keep_others: bool) {
+    match value {
+        Value::Object(map) => {
+            map.values_mut().for_each(|v| merge_arrays(v, token, keep_others));
+            let mut marked: Vec<String> = map.keys().filter(|k| is_marked(k, token)).cloned().collect();
+            marked.sort_by_key(|k| split(k, token).1);
+            for key in marked {
+                let name = split(&key, token).0.to_string();
+                let Some(repeat) = map.shift_remove(&key) else {
+                    continue;
+                };
+                let unmerged = match (map.get_mut(&name), repeat) {
+                    (Some(Value::Array(first)), Value::Array(items)) => {
+                        first.extend(items);
+                        None
+                    }
+                    (_, repeat) => Some(repeat),
+                };
+                if let Some(repeat) = unmerged {
+                    map.insert(if keep_others { key } else { name }, repeat);
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(|v| merge_arrays(v, token, keep_others)),
+        _ => {}
+    }
+}
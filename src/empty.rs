@@ -0,0 +1,161 @@
+//! Empty dicts and lists.
+//!
+//! TOON writes an empty dict as a bare `key:` and an empty list as `key[0]:`,
+//! which readers easily take for a missing value. `empty_collections="explicit"`
+//! writes `key: {}` and `key: []` instead, and `- {}` / `- []` for list items.
+//! Decoding always accepts the explicit markers and turns them back into
+//! empty containers. Unquoted braces and brackets never stand for strings,
+//! since strings holding them are quoted.
+
+use std::borrow::Cow;
+
+use crate::{diagnostics, scan};
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub(crate) enum Style {
+    /// `key:` and `key[0]:`, as the format defines them
+    #[default]
+    Bare,
+    /// `key: {}` and `key: []`
+    Explicit,
+}
+
+impl Style {
+    pub(crate) fn parse(name: &str) -> Option<Self> {
+        match name {
+            "bare" => Some(Style::Bare),
+            "explicit" => Some(Style::Explicit),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            Style::Bare => "bare",
+            Style::Explicit => "explicit",
+        }
+    }
+}
+
+/// A bare or quoted key with nothing else in it.
+fn is_key(key: &str) -> bool {
+    if key.starts_with('"') {
+        return scan::closing_quote(key) == Some(key.len());
+    }
+    !key.is_empty() && !key.contains([' ', ':', '"', '[', ']', '{', '}', ',', '|', '\t'])
+}
+
+/// `content` with its `[0]:` suffix replaced by `: []`, when it is the header
+/// of an empty array.
+fn empty_array(content: &str) -> Option<String> {
+    let body = content.strip_prefix("- ").unwrap_or(content);
+    let (_, values) = diagnostics::array_header(body)?;
+    if !values.is_empty() {
+        return None;
+    }
+    let open = body.rfind('[')?;
+    if !matches!(&body[open..], "[0]:" | "[0|]:" | "[0\t]:" | "[#0]:" | "[#0|]:" | "[#0\t]:") {
+        return None;
+    }
+    let key = &body[..open];
+    let prefix = &content[..content.len() - body.len()];
+    match (key, prefix) {
+        ("", "") => None,
+        ("", _) => Some("- []".to_string()),
+        (key, _) if is_key(key) => Some(format!("{}{}: []", prefix, key)),
+        _ => None,
+    }
+}
+
+/// Rewrite encoded `text` so empty dicts and lists carry explicit markers.
+pub(crate) fn explicit(text: &str) -> String {
+    let lines: Vec<&str> = text.split_inclusive('\n').collect();
+    let mut out = String::with_capacity(text.len());
+    for (i, line) in lines.iter().enumerate() {
+        let body = line.trim_end_matches('\n');
+        let content = body.trim_start_matches(' ');
+        let lead = &body[..body.len() - content.len()];
+        let rewritten = empty_array(content).or_else(|| {
+            // A dict is empty when no line is nested under it
+            let own = scan::indent(body) + if content.starts_with("- ") || content == "-" { 2 } else { 0 };
+            let nested = lines[i + 1..]
+                .iter()
+                .find(|next| !next.trim().is_empty())
+                .is_some_and(|next| scan::indent(next) > own);
+            if nested {
+                return None;
+            }
+            if content == "-" {
+                return Some("- {}".to_string());
+            }
+            let entry = content.strip_prefix("- ").unwrap_or(content);
+            let key = entry.strip_suffix(':')?;
+            is_key(key).then(|| format!("{}: {{}}", content.trim_end_matches(':')))
+        });
+        match rewritten {
+            Some(rewritten) => {
+                out.push_str(lead);
+                out.push_str(&rewritten);
+                out.push_str(&line[body.len()..]);
+            }
+            None => out.push_str(line),
+        }
+    }
+    out
+}
+
+/// True when `bytes` may hold explicit markers, for paths that skip [`read`]
+/// otherwise.
+pub(crate) fn maybe_explicit(bytes: &[u8]) -> bool {
+    bytes.windows(2).any(|w| w == b"{}" || w == b"[]")
+}
+
+/// The part of `content` before a trailing `{}` or `[]` marker, and whether
+/// the marker is `[]`. Spaces around the marker are ignored, as they are
+/// around other values, but at least one must separate it from the key.
+fn marker(content: &str) -> Option<(&str, bool)> {
+    let trimmed = content.trim_end_matches(' ');
+    let (rest, list) = match trimmed.strip_suffix("{}") {
+        Some(rest) => (rest, false),
+        None => (trimmed.strip_suffix("[]")?, true),
+    };
+    rest.ends_with(' ').then(|| (rest.trim_end_matches(' '), list))
+}
+
+/// `source` with explicit markers turned back into the forms the parser
+/// reads as empty containers.
+pub(crate) fn read(source: &str) -> Cow<'_, str> {
+    if !maybe_explicit(source.as_bytes()) {
+        return Cow::Borrowed(source);
+    }
+    let mut out = String::with_capacity(source.len());
+    let mut changed = false;
+    for line in source.split_inclusive('\n') {
+        let body = line.trim_end_matches('\n');
+        let content = body.trim_start_matches(' ');
+        let lead = &body[..body.len() - content.len()];
+        let rewritten = marker(content).and_then(|(rest, list)| {
+            if rest == "-" {
+                return Some(if list { "- [0]:" } else { "-" }.to_string());
+            }
+            let entry = rest.strip_prefix("- ").unwrap_or(rest);
+            let prefix = &rest[..rest.len() - entry.len()];
+            let key = entry.strip_suffix(':').filter(|key| is_key(key))?;
+            Some(format!("{}{}{}", prefix, key, if list { "[0]:" } else { ":" }))
+        });
+        match rewritten {
+            Some(rewritten) => {
+                changed = true;
+                out.push_str(lead);
+                out.push_str(&rewritten);
+                out.push_str(&line[body.len()..]);
+            }
+            None => out.push_str(line),
+        }
+    }
+    if changed {
+        Cow::Owned(out)
+    } else {
+        Cow::Borrowed(source)
+    }
+}
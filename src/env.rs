@@ -0,0 +1,53 @@
+//! `${VAR}` interpolation in decoded strings (`decode(..., env_substitution=True)`).
+//!
+//! Variables come from a mapping the caller passes, never implicitly from the
+//! process environment. `${VAR:-default}` falls back to `default` when `VAR`
+//! is missing, and `$${` writes a literal `${`. Keys are left alone.
+
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// Expand every `${VAR}` in the string values of `value`.
+pub(crate) fn substitute(value: &mut Value, vars: &HashMap<String, String>) -> Result<(), String> {
+    match value {
+        Value::String(s) if s.contains('$') => *s = expand(s, vars)?,
+        Value::Array(items) => items.iter_mut().try_for_each(|item| substitute(item, vars))?,
+        Value::Object(map) => map.values_mut().try_for_each(|item| substitute(item, vars))?,
+        _ => {}
+    }
+    Ok(())
+}
+
+fn expand(text: &str, vars: &HashMap<String, String>) -> Result<String, String> {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(at) = rest.find('$') {
+        out.push_str(&rest[..at]);
+        let after = &rest[at..];
+        if let Some(escaped) = after.strip_prefix("$${") {
+            out.push_str("${");
+            rest = escaped;
+            continue;
+        }
+        let Some(body) = after.strip_prefix("${") else {
+            out.push('$');
+            rest = &after[1..];
+            continue;
+        };
+        let end = body.find('}').ok_or_else(|| format!("unterminated '${{' in {:?}", text))?;
+        let (name, default) = match body[..end].split_once(":-") {
+            Some((name, default)) => (name, Some(default)),
+            None => (&body[..end], None),
+        };
+        if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            return Err(format!("invalid variable name {:?} in {:?}", name, text));
+        }
+        match vars.get(name).map(String::as_str).or(default) {
+            Some(found) => out.push_str(found),
+            None => return Err(format!("undefined variable '{}' in {:?}", name, text)),
+        }
+        rest = &body[end + 1..];
+    }
+    out.push_str(rest);
+    Ok(out)
+}
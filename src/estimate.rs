@@ -0,0 +1,211 @@
+//! Predicted Python memory footprint of a decode (`estimate_decoded_size`).
+//!
+//! One pass over the text classifies every key and value the way the parser
+//! would, without building anything, and prices the objects with CPython's
+//! sizes on 64-bit builds. Short strings that repeat, such as table keys, are
+//! shared by the converter and counted once; small integers, `None` and
+//! booleans are cached by CPython and cost nothing. The result is an
+//! estimate for a plain `decode`: options that add or rewrite values are not
+//! taken into account.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::{diagnostics, scan};
+
+/// Strings up to this many bytes are shared between repeats when decoding.
+const SHARED_LEN: usize = 16;
+
+#[derive(Default)]
+pub(crate) struct Estimate {
+    pub(crate) dicts: usize,
+    pub(crate) lists: usize,
+    pub(crate) strings: usize,
+    pub(crate) ints: usize,
+    pub(crate) floats: usize,
+    pub(crate) bytes: usize,
+}
+
+struct Scan<'a> {
+    estimate: Estimate,
+    shared: HashSet<&'a str>,
+}
+
+impl<'a> Scan<'a> {
+    fn dict(&mut self, keys: usize) {
+        self.estimate.dicts += 1;
+        // Dict object, key table and slack of a dict built by insertion
+        self.add_bytes(24usize.saturating_mul(keys).saturating_add(104));
+    }
+
+    fn list(&mut self, items: usize) {
+        self.estimate.lists += 1;
+        // The length may come from a header, which the document can declare
+        // as anything up to `usize::MAX`
+        self.add_bytes(8usize.saturating_mul(items).saturating_add(56));
+    }
+
+    fn add_bytes(&mut self, bytes: usize) {
+        self.estimate.bytes = self.estimate.bytes.saturating_add(bytes);
+    }
+
+    fn string(&mut self, s: &'a str) {
+        if s.len() <= SHARED_LEN && !self.shared.insert(s) {
+            return;
+        }
+        self.estimate.strings += 1;
+        let chars = s.chars().count();
+        self.add_bytes(match s.chars().map(u32::from).max().unwrap_or(0) {
+            0..=0x7F => 49 + chars,
+            0x80..=0xFF => 73 + chars,
+            0x100..=0xFFFF => 74 + 2 * chars,
+            _ => 76 + 4 * chars,
+        });
+    }
+
+    fn key(&mut self, key: &'a str) {
+        self.string(unquote(key));
+    }
+
+    fn value(&mut self, cell: &'a str) {
+        let cell = cell.trim();
+        if cell.starts_with('"') {
+            return self.string(unquote(cell));
+        }
+        if matches!(cell, "" | "null" | "true" | "false") {
+            return;
+        }
+        if let Ok(i) = cell.parse::<i64>() {
+            self.estimate.ints += 1;
+            self.add_bytes(match i {
+                -5..=256 => 0,
+                _ if i.unsigned_abs() < 1 << 30 => 28,
+                _ => 32,
+            });
+        } else if cell.parse::<f64>().is_ok_and(f64::is_finite) {
+            self.estimate.floats += 1;
+            self.add_bytes(24);
+        } else {
+            self.string(cell);
+        }
+    }
+}
+
+fn unquote(s: &str) -> &str {
+    s.strip_prefix('"').and_then(|s| s.strip_suffix('"')).unwrap_or(s)
+}
+
+/// `entry` split at the colon after its key, which may be quoted.
+fn split_key(entry: &str) -> Option<(&str, &str)> {
+    let end = if entry.starts_with('"') {
+        let mut escaped = false;
+        entry.char_indices().skip(1).find_map(|(i, c)| match c {
+            _ if escaped => {
+                escaped = false;
+                None
+            }
+            '\\' => {
+                escaped = true;
+                None
+            }
+            '"' => Some(i + 1),
+            _ => None,
+        })?
+    } else {
+        entry.find(':')?
+    };
+    let rest = entry[end..].strip_prefix(':')?;
+    (rest.is_empty() || rest.starts_with(' ')).then_some((&entry[..end], rest))
+}
+
+/// Estimate the objects decoding `source` creates.
+pub(crate) fn estimate(source: &str) -> Estimate {
+    let lines: Vec<&str> = source.lines().collect();
+    // Row line -> (delimiter, fields per row)
+    let rows: HashMap<usize, (char, usize)> = diagnostics::tables(source)
+        .into_iter()
+        .flat_map(|table| {
+            let line = lines[table.header - 1];
+            let width = scan::split_cells(&line[table.fields.clone()], table.delimiter).len();
+            table.rows.into_iter().map(move |row| (row, (table.delimiter, width)))
+        })
+        .collect();
+    let mut scan = Scan { estimate: Estimate::default(), shared: HashSet::new() };
+    // Keys of the objects whose size is not known from a header
+    let mut loose_keys = 0;
+    let mut loose_dicts = 0;
+    let mut first = true;
+    for (idx, &line) in lines.iter().enumerate() {
+        let content = line.trim();
+        if content.is_empty() {
+            continue;
+        }
+        if let Some(&(delimiter, width)) = rows.get(&(idx + 1)) {
+            scan.dict(width);
+            for cell in scan::split_cells(content, delimiter) {
+                scan.value(cell);
+            }
+            continue;
+        }
+        let (item, entry) = match content.strip_prefix("- ") {
+            Some(rest) => (true, rest),
+            None => (false, content.strip_prefix('-').filter(|rest| rest.is_empty()).unwrap_or(content)),
+        };
+        if first {
+            first = false;
+            // A document that is not a root array is a dict
+            if !entry.starts_with('[') {
+                loose_dicts += 1;
+            }
+        }
+        if let Some((delimiter, inline)) = diagnostics::array_header(entry) {
+            let open = entry.find('[').unwrap_or(0);
+            if open > 0 {
+                scan.key(&entry[..open]);
+                loose_keys += 1;
+            }
+            if item && open > 0 {
+                // `- key[N]: ...` opens an object in a list
+                loose_dicts += 1;
+            }
+            if let (Some(open), Some(close)) = (entry.find('{'), entry.rfind('}')) {
+                // Every row of a table shares the header's key objects
+                for field in scan::split_cells(&entry[open + 1..close], delimiter) {
+                    scan.key(field.trim());
+                }
+            }
+            let cells = if inline.is_empty() { Vec::new() } else { scan::split_cells(inline, delimiter) };
+            scan.list(diagnostics::declared_length(entry).unwrap_or(cells.len()));
+            for cell in cells {
+                scan.value(cell);
+            }
+            continue;
+        }
+        match split_key(entry) {
+            Some((key, rest)) => {
+                if item {
+                    loose_dicts += 1;
+                }
+                scan.key(key);
+                loose_keys += 1;
+                if rest.trim().is_empty() {
+                    // A nested object
+                    loose_dicts += 1;
+                } else {
+                    scan.value(rest);
+                }
+            }
+            None if item => scan.value(entry),
+            // `-` alone is an empty object in a list
+            None if entry.is_empty() => loose_dicts += 1,
+            None => {}
+        }
+    }
+    if first {
+        // An empty document decodes to an empty dict
+        loose_dicts += 1;
+    }
+    scan.add_bytes(104usize.saturating_mul(loose_dicts).saturating_add(24usize.saturating_mul(loose_keys)));
+    let mut estimate = scan.estimate;
+    estimate.dicts = estimate.dicts.saturating_add(loose_dicts);
+    estimate
+}
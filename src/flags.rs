@@ -0,0 +1,54 @@
+//! orjson-style bit flags for `encode(data, option=...)`.
+//!
+//! Flags are plain integers, so hot paths can pass a precomputed
+//! `OPT_STRICT | OPT_DELIMITER_TAB` instead of building an `Options` object.
+
+pub(crate) const OPT_DELIMITER_TAB: u32 = 1 << 0;
+pub(crate) const OPT_DELIMITER_PIPE: u32 = 1 << 1;
+pub(crate) const OPT_STRICT: u32 = 1 << 2;
+pub(crate) const OPT_SORT_KEYS: u32 = 1 << 3;
+pub(crate) const OPT_APPEND_NEWLINE: u32 = 1 << 4;
+pub(crate) const OPT_ANCHORS: u32 = 1 << 5;
+pub(crate) const OPT_CHECKSUM: u32 = 1 << 6;
+
+/// Every flag with its Python name, in bit order.
+pub(crate) const ALL: &[(&str, u32)] = &[
+    ("OPT_DELIMITER_TAB", OPT_DELIMITER_TAB),
+    ("OPT_DELIMITER_PIPE", OPT_DELIMITER_PIPE),
+    ("OPT_STRICT", OPT_STRICT),
+    ("OPT_SORT_KEYS", OPT_SORT_KEYS),
+    ("OPT_APPEND_NEWLINE", OPT_APPEND_NEWLINE),
+    ("OPT_ANCHORS", OPT_ANCHORS),
+    ("OPT_CHECKSUM", OPT_CHECKSUM),
+];
+
+#[derive(Clone, Copy, Default)]
+pub(crate) struct Flags(u32);
+
+impl Flags {
+    pub(crate) fn parse(bits: u32) -> Result<Self, String> {
+        let known = ALL.iter().fold(0, |acc, (_, bit)| acc | bit);
+        if bits & !known != 0 {
+            return Err(format!("unknown option bits 0x{:x}", bits & !known));
+        }
+        if bits & OPT_DELIMITER_TAB != 0 && bits & OPT_DELIMITER_PIPE != 0 {
+            return Err("OPT_DELIMITER_TAB and OPT_DELIMITER_PIPE are mutually exclusive".to_string());
+        }
+        Ok(Flags(bits))
+    }
+
+    pub(crate) fn has(self, flag: u32) -> bool {
+        self.0 & flag != 0
+    }
+
+    /// Delimiter name selected by the flags, if any.
+    pub(crate) fn delimiter(self) -> Option<&'static str> {
+        if self.has(OPT_DELIMITER_TAB) {
+            Some("tab")
+        } else if self.has(OPT_DELIMITER_PIPE) {
+            Some("pipe")
+        } else {
+            None
+        }
+    }
+}
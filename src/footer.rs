@@ -0,0 +1,47 @@
+//! Checksum footer: a trailing `# sha256:<hex>` line covering everything above it.
+
+use sha2::{Digest, Sha256};
+
+const PREFIX: &str = "# sha256:";
+
+/// Append a checksum line for `text`.
+pub(crate) fn append(mut text: String) -> String {
+    if !text.is_empty() && !text.ends_with('\n') {
+        text.push('\n');
+    }
+    let digest = digest(&text);
+    text.push_str(PREFIX);
+    text.push_str(&digest);
+    text.push('\n');
+    text
+}
+
+/// Split off a trailing checksum line, returning the covered content and the
+/// recorded digest.
+pub(crate) fn split(source: &str) -> (&str, Option<&str>) {
+    let trimmed = source.trim_end_matches(['\n', '\r']);
+    let start = trimmed.rfind('\n').map_or(0, |i| i + 1);
+    match trimmed[start..].strip_prefix(PREFIX) {
+        Some(recorded) => (&source[..start], Some(recorded.trim())),
+        None => (source, None),
+    }
+}
+
+/// Check the footer of `source`. Errors explain what is wrong.
+pub(crate) fn verify(source: &str) -> Result<(), String> {
+    match split(source) {
+        (_, None) => Err("Document has no checksum footer".to_string()),
+        (content, Some(recorded)) => {
+            let actual = digest(content);
+            if actual.eq_ignore_ascii_case(recorded) {
+                Ok(())
+            } else {
+                Err(format!("Checksum mismatch: footer records {}, content hashes to {}", recorded, actual))
+            }
+        }
+    }
+}
+
+fn digest(text: &str) -> String {
+    crate::canonical::to_hex(&Sha256::digest(text.as_bytes()))
+}
@@ -0,0 +1,74 @@
+//! Front matter: an optional metadata block at the top of a document.
+//!
+//! ```text
+//! ---
+//! generator: toonpy
+//! version: 1
+//! ---
+//! users[2]{id,name}:
+//!   ...
+//! ```
+//!
+//! The block holds TOON itself and is kept apart from the data it precedes.
+
+use serde_json::Value;
+
+const FENCE: &str = "---";
+
+/// Split `source` into its metadata text (if any), the body, and the number of
+/// lines that precede the body.
+pub(crate) fn split(source: &str) -> (Option<&str>, &str, usize) {
+    let Some(rest) = source.strip_prefix(FENCE).and_then(|r| r.strip_prefix('\n').or_else(|| r.strip_prefix("\r\n"))) else {
+        return (None, source, 0);
+    };
+    let mut offset = 0;
+    let mut lines = 1;
+    for line in rest.split_inclusive('\n') {
+        lines += 1;
+        if line.trim_end() == FENCE {
+            let meta = &rest[..offset];
+            let body = &rest[offset + line.len()..];
+            return (Some(meta), body, lines);
+        }
+        offset += line.len();
+    }
+    // Unterminated fence: not front matter, let the parser report on it
+    (None, source, 0)
+}
+
+/// Prefix `body` with a metadata block.
+pub(crate) fn join(meta: &str, body: &str) -> String {
+    let mut out = String::with_capacity(meta.len() + body.len() + 8);
+    out.push_str(FENCE);
+    out.push('\n');
+    out.push_str(meta);
+    if !meta.is_empty() && !meta.ends_with('\n') {
+        out.push('\n');
+    }
+    out.push_str(FENCE);
+    out.push('\n');
+    out.push_str(body);
+    out
+}
+
+/// Decode a document and its front matter, keeping syntax error line numbers
+/// relative to the whole source. A checksum footer is ignored, and the text
+/// goes through [`crate::newline::prepare`] first.
+pub(crate) fn decode(source: &str, opts: &toon::Options, reading: &crate::newline::Reading) -> Result<(Value, Option<Value>), toon::Error> {
+    let source = crate::newline::prepare(source, opts, reading)?;
+    let (source, _) = crate::footer::split(&source);
+    let (meta, body, offset) = split(source);
+    let meta = meta
+        .map(|m| toon::decode_from_str(m, opts).map_err(|e| shift(e, 1)))
+        .transpose()?;
+    let data = toon::decode_from_str(body, opts).map_err(|e| shift(e, offset))?;
+    Ok((data, meta))
+}
+
+/// `err` with its line moved down by `offset` lines.
+pub(crate) fn shift(err: toon::Error, offset: usize) -> toon::Error {
+    match err {
+        toon::Error::Syntax { line, message } => toon::Error::Syntax { line: line + offset, message },
+        other => other,
+    }
+}
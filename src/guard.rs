@@ -0,0 +1,142 @@
+//! Resource limits for decoding untrusted input.
+//!
+//! The checks scan the text before it reaches the parser, so a hostile
+//! document is rejected before anything is built from it: the input size,
+//! how deeply it nests, and the lengths its array headers declare.
+//!
+//! [`check_lengths`] runs on every document regardless of limits: a header
+//! declaring more items than the rest of the input could possibly hold is an
+//! error, so an untrusted count such as `[2000000000]` never drives an
+//! allocation.
+
+use crate::diagnostics;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub(crate) struct Limits {
+    pub(crate) max_input_bytes: Option<usize>,
+    /// Nesting levels, counted by indentation
+    pub(crate) max_depth: Option<usize>,
+    /// Largest length an array or table header may declare
+    pub(crate) max_array_length: Option<usize>,
+}
+
+impl Limits {
+    /// The preset behind `Options.untrusted()`.
+    pub(crate) const UNTRUSTED: Limits = Limits {
+        max_input_bytes: Some(16 * 1024 * 1024),
+        max_depth: Some(64),
+        max_array_length: Some(1_000_000),
+    };
+
+    pub(crate) fn is_unlimited(&self) -> bool {
+        *self == Limits::default()
+    }
+
+    /// Reject `source` if it exceeds a limit, naming the line where it can.
+    pub(crate) fn check(&self, source: &str) -> Result<(), String> {
+        if self.is_unlimited() {
+            return Ok(());
+        }
+        self.check_size(source.len())?;
+        if self.max_depth.is_none() && self.max_array_length.is_none() {
+            return Ok(());
+        }
+        for (idx, line) in source.lines().enumerate() {
+            let content = line.trim_start_matches(' ');
+            if !content.trim().is_empty() {
+                self.check_line(line.len() - content.len(), content.trim_end())
+                    .map_err(|message| format!("Line {}: {}", idx + 1, message))?;
+            }
+        }
+        Ok(())
+    }
+
+    /// The depth and length limits for one non-blank line, given its indent
+    /// and its content without the indent.
+    pub(crate) fn check_line(&self, indent: usize, content: &str) -> Result<(), String> {
+        let depth = indent / 2 + 1;
+        if let Some(max) = self.max_depth.filter(|&max| depth > max) {
+            return Err(format!("nesting depth {} exceeds max_depth={}", depth, max));
+        }
+        let Some(max) = self.max_array_length else {
+            return Ok(());
+        };
+        match diagnostics::declared_length(content) {
+            Some(declared) if declared > max => Err(format!(
+                "array declares {} items, more than max_array_length={}", declared, max
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// [`check`](Self::check) for undecoded bytes; the size is checked before
+    /// anything else.
+    pub(crate) fn check_bytes(&self, bytes: &[u8]) -> Result<(), String> {
+        if self.is_unlimited() {
+            return Ok(());
+        }
+        self.check_size(bytes.len())?;
+        // Invalid UTF-8 is left for the decoder to report
+        match std::str::from_utf8(bytes) {
+            Ok(text) => self.check(text),
+            Err(_) => Ok(()),
+        }
+    }
+
+    pub(crate) fn check_size(&self, len: usize) -> Result<(), String> {
+        match self.max_input_bytes {
+            Some(max) if len > max => Err(format!("input is {} bytes, more than max_input_bytes={}", len, max)),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// True when some `[N` declares more than the bytes after it, so that only
+/// such documents pay for [`check_lengths`].
+fn maybe_oversized(bytes: &[u8]) -> bool {
+    bytes.iter().enumerate().filter(|(_, &b)| b == b'[').any(|(i, _)| {
+        let rest = &bytes[i + 1..];
+        let rest = rest.strip_prefix(b"#").unwrap_or(rest);
+        let digits = rest.iter().take_while(|b| b.is_ascii_digit());
+        let declared = digits.fold(0usize, |n, &d| n.saturating_mul(10).saturating_add(usize::from(d - b'0')));
+        declared > bytes.len() - i
+    })
+}
+
+/// Reject array headers that declare more items than the input left after
+/// them could hold. Every item after the first needs at least a delimiter or
+/// a line break, so the check never rejects a well-formed document.
+pub(crate) fn check_lengths(source: &str) -> Result<(), toon::Error> {
+    if !maybe_oversized(source.as_bytes()) {
+        return Ok(());
+    }
+    let mut start = 0;
+    for (idx, line) in source.split_inclusive('\n').enumerate() {
+        let available = source.len() - start;
+        start += line.len();
+        let Some(declared) = diagnostics::declared_length(line.trim()) else {
+            continue;
+        };
+        if declared > available.saturating_add(1) {
+            // Lengths past usize were saturated when parsed
+            let declared = if declared == usize::MAX { "an out-of-range number of".to_string() } else { declared.to_string() };
+            return Err(toon::Error::Syntax {
+                line: idx + 1,
+                message: format!("array declares {} items but only {} bytes of input follow", declared, available),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// [`check_lengths`] for undecoded bytes.
+pub(crate) fn check_lengths_bytes(bytes: &[u8]) -> Result<(), toon::Error> {
+    if !maybe_oversized(bytes) {
+        return Ok(());
+    }
+    // Invalid UTF-8 is left for the decoder to report
+    match std::str::from_utf8(bytes) {
+        Ok(text) => check_lengths(text),
+        Err(_) => Ok(()),
+    }
+}
@@ -0,0 +1,219 @@
+//! Typed table headers: `users[2]{id:int,name:str,score:float}:`.
+//!
+//! `encode(..., typed_headers=True)` annotates each column whose cells share
+//! one type. Decoding strips the annotations before parsing and coerces the
+//! cells to the declared type, so `"42"` in an `int` column reads as 42 and
+//! `7` in a `str` column as `"7"`. Cells that cannot be coerced are syntax
+//! errors. Columns without an annotation are read as usual.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+use crate::diagnostics::{self, Table};
+use crate::scan::split_cells;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Type {
+    Int,
+    Float,
+    Str,
+    Bool,
+}
+
+impl Type {
+    fn parse(name: &str) -> Option<Self> {
+        match name {
+            "int" => Some(Type::Int),
+            "float" => Some(Type::Float),
+            "str" => Some(Type::Str),
+            "bool" => Some(Type::Bool),
+            _ => None,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Type::Int => "int",
+            Type::Float => "float",
+            Type::Str => "str",
+            Type::Bool => "bool",
+        }
+    }
+}
+
+fn is_int(token: &str) -> bool {
+    let digits = token.strip_prefix('-').unwrap_or(token);
+    !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit())
+}
+
+fn is_float(token: &str) -> bool {
+    let digits = token.strip_prefix('-').unwrap_or(token);
+    digits.starts_with(|c: char| c.is_ascii_digit()) && token.parse::<f64>().is_ok()
+}
+
+/// The type of an encoded cell, `None` for null.
+fn kind(cell: &str) -> Option<Type> {
+    match cell {
+        "null" => None,
+        "true" | "false" => Some(Type::Bool),
+        _ if cell.starts_with('"') => Some(Type::Str),
+        _ if is_int(cell) => Some(Type::Int),
+        _ if is_float(cell) => Some(Type::Float),
+        _ => Some(Type::Str),
+    }
+}
+
+/// Replace whole lines of `source`, keeping their line endings.
+fn replace_lines<'a>(source: &'a str, mut lines: HashMap<usize, String>) -> Cow<'a, str> {
+    if lines.is_empty() {
+        return Cow::Borrowed(source);
+    }
+    let mut out = String::with_capacity(source.len() + 16 * lines.len());
+    for (idx, line) in source.split_inclusive('\n').enumerate() {
+        match lines.remove(&(idx + 1)) {
+            Some(replacement) => {
+                out.push_str(&replacement);
+                out.push_str(&line[line.trim_end_matches('\n').len()..]);
+            }
+            None => out.push_str(line),
+        }
+    }
+    Cow::Owned(out)
+}
+
+/// A row line rebuilt from its cells, keeping the indentation.
+fn rebuild(line: &str, cells: &[Cow<'_, str>], delimiter: char) -> String {
+    let indent = line.len() - line.trim_start_matches(' ').len();
+    let cells: Vec<&str> = cells.iter().map(|cell| cell.as_ref()).collect();
+    format!("{}{}", &line[..indent], cells.join(delimiter.encode_utf8(&mut [0; 4])))
+}
+
+/// Annotate every table header in encoded `text` with its column types.
+pub(crate) fn annotate(text: &str) -> String {
+    let lines: Vec<&str> = text.lines().collect();
+    let mut edits = HashMap::new();
+    for table in diagnostics::tables(text) {
+        let header = lines[table.header - 1];
+        let fields = split_cells(&header[table.fields.clone()], table.delimiter);
+        let mut types: Vec<Option<Option<Type>>> = vec![None; fields.len()];
+        for &row in &table.rows {
+            let cells = split_cells(lines[row - 1].trim(), table.delimiter);
+            for (seen, cell) in types.iter_mut().zip(cells) {
+                *seen = match (*seen, kind(cell.trim())) {
+                    (seen, None) => seen,
+                    (None, kind) => Some(kind),
+                    (Some(Some(Type::Int)), Some(Type::Float)) | (Some(Some(Type::Float)), Some(Type::Int)) => {
+                        Some(Some(Type::Float))
+                    }
+                    (Some(Some(seen)), Some(kind)) if seen == kind => Some(Some(seen)),
+                    // Mixed column: leave it unannotated
+                    _ => Some(None),
+                };
+            }
+        }
+        let annotated: Vec<String> = fields
+            .iter()
+            .zip(types)
+            .map(|(field, ty)| match ty.flatten() {
+                Some(ty) => format!("{}:{}", field, ty.name()),
+                None => field.to_string(),
+            })
+            .collect();
+        let annotated = annotated.join(table.delimiter.encode_utf8(&mut [0; 4]));
+        edits.insert(
+            table.header,
+            format!("{}{}{}", &header[..table.fields.start], annotated, &header[table.fields.end..]),
+        );
+    }
+    replace_lines(text, edits).into_owned()
+}
+
+/// Split `name:type` at the last colon outside quotes.
+fn split_annotation(field: &str) -> (&str, Option<Type>) {
+    let quoted_end = field.strip_prefix('"').and_then(|rest| rest.find('"')).map_or(0, |at| at + 2);
+    match field[quoted_end..].rfind(':') {
+        Some(at) => {
+            let at = quoted_end + at;
+            match Type::parse(field[at + 1..].trim()) {
+                Some(ty) => (&field[..at], Some(ty)),
+                None => (field, None),
+            }
+        }
+        None => (field, None),
+    }
+}
+
+/// `cell` rewritten so it decodes as `ty`, or `None` when it cannot.
+fn coerce(cell: &str, ty: Type) -> Option<Cow<'_, str>> {
+    if cell == "null" {
+        return Some(Cow::Borrowed(cell));
+    }
+    let inner = cell.strip_prefix('"').and_then(|c| c.strip_suffix('"')).unwrap_or(cell);
+    match ty {
+        Type::Str if kind(cell) == Some(Type::Str) => Some(Cow::Borrowed(cell)),
+        Type::Str => Some(Cow::Owned(format!("\"{}\"", cell))),
+        Type::Int if is_int(inner) => Some(Cow::Borrowed(inner)),
+        Type::Int => {
+            // Integral floats such as 3.0
+            let value = inner.parse::<f64>().ok().filter(|v| is_float(inner) && v.fract() == 0.0 && v.abs() < 9.0e15)?;
+            Some(Cow::Owned(format!("{}", value as i64)))
+        }
+        Type::Float if is_int(inner) => Some(Cow::Owned(format!("{}.0", inner))),
+        Type::Float if is_float(inner) => Some(Cow::Borrowed(inner)),
+        Type::Bool if matches!(inner, "true" | "false") => Some(Cow::Borrowed(inner)),
+        _ => None,
+    }
+}
+
+fn apply_table(lines: &[&str], table: &Table, edits: &mut HashMap<usize, String>) -> Result<(), toon::Error> {
+    let header = lines[table.header - 1];
+    let fields: Vec<(&str, Option<Type>)> =
+        split_cells(&header[table.fields.clone()], table.delimiter).into_iter().map(split_annotation).collect();
+    if fields.iter().all(|(_, ty)| ty.is_none()) {
+        return Ok(());
+    }
+    let delimiter = table.delimiter.encode_utf8(&mut [0; 4]).to_string();
+    let names: Vec<&str> = fields.iter().map(|(name, _)| *name).collect();
+    edits.insert(
+        table.header,
+        format!("{}{}{}", &header[..table.fields.start], names.join(&delimiter), &header[table.fields.end..]),
+    );
+    for &row in &table.rows {
+        let line = lines[row - 1];
+        let mut cells: Vec<Cow<'_, str>> = Vec::new();
+        for (i, cell) in split_cells(line.trim(), table.delimiter).into_iter().enumerate() {
+            let cell = cell.trim();
+            // Extra cells are left for the parser to report
+            match fields.get(i) {
+                Some((name, Some(ty))) => cells.push(coerce(cell, *ty).ok_or_else(|| toon::Error::Syntax {
+                    line: row,
+                    message: format!("cell {} in column {} is not a valid {}", cell, name, ty.name()),
+                })?),
+                _ => cells.push(Cow::Borrowed(cell)),
+            }
+        }
+        edits.insert(row, rebuild(line, &cells, table.delimiter));
+    }
+    Ok(())
+}
+
+/// Strip type annotations from table headers and coerce the cells of typed
+/// columns.
+pub(crate) fn apply(source: &str) -> Result<Cow<'_, str>, toon::Error> {
+    // Fast path: no annotation anywhere
+    if !maybe_typed(source.as_bytes()) {
+        return Ok(Cow::Borrowed(source));
+    }
+    let lines: Vec<&str> = source.lines().collect();
+    let mut edits = HashMap::new();
+    for table in diagnostics::tables(source) {
+        apply_table(&lines, &table, &mut edits)?;
+    }
+    Ok(replace_lines(source, edits))
+}
+
+/// True when `bytes` may hold typed headers, for paths that skip [`apply`]
+/// otherwise.
+pub(crate) fn maybe_typed(bytes: &[u8]) -> bool {
+    [&b":int"[..], b":float", b":str", b":bool"].iter().any(|ty| bytes.windows(ty.len()).any(|w| w == *ty))
+}
@@ -0,0 +1,143 @@
+//! `!include` directives for composing a document from several files.
+//!
+//! ```text
+//! service: api
+//! database: !include db.toon
+//! workers[2]:
+//!   - !include workers/a.toon
+//!   - !include workers/b.toon
+//! ```
+//!
+//! Before parsing, [`mark`] turns each unquoted `!include path` value into a
+//! marker string; after parsing, [`resolve`] replaces the markers with the
+//! decoded files. Markers start with a [`Token`] drawn for each file, so a
+//! string in the document never passes for one, whatever it contains. Paths are relative to the including file (the base directory
+//! for the root document) and must stay inside the base directory, so a
+//! document cannot read arbitrary files. A file that includes itself, directly
+//! or through others, is an error. The resource limits of the decode apply to
+//! every file, and `max_input_bytes` to all of them together.
+
+use serde_json::Value;
+use std::borrow::Cow;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::scan::Token;
+use crate::{front, guard, newline, scalars};
+
+/// Opens and closes the [`Token`] prefixing the path of an include directive.
+pub(crate) const MARK: char = '\u{FDD4}';
+
+const DIRECTIVE: &str = "!include ";
+
+/// `source` with every unquoted `!include path` value marked for [`resolve`].
+pub(crate) fn mark<'a>(source: &'a str, token: &Token) -> Cow<'a, str> {
+    scalars::rewrite(source, |cell| {
+        let path = cell.strip_prefix(DIRECTIVE)?.trim();
+        let path = path.strip_prefix('"').and_then(|p| p.strip_suffix('"')).unwrap_or(path);
+        Some(format!("{}{}", token, path))
+    })
+}
+
+pub(crate) enum Error {
+    Toon(toon::Error),
+    /// An included file, or the document and its includes together, exceed
+    /// a resource limit
+    Limit(String),
+}
+
+impl From<toon::Error> for Error {
+    fn from(err: toon::Error) -> Self {
+        Error::Toon(err)
+    }
+}
+
+/// Replace every marker `token` starts in `value` with the decoded file, reading
+/// paths relative to and only from within `base`. Each file is checked
+/// against `limits` before it is parsed, and `max_input_bytes` also caps the
+/// total of `source_len`, the including document's size, and every file.
+pub(crate) fn resolve(
+    value: &mut Value,
+    token: &Token,
+    base: &Path,
+    opts: &toon::Options,
+    reading: &newline::Reading,
+    limits: &guard::Limits,
+    source_len: usize,
+) -> Result<(), Error> {
+    let base = base.canonicalize().map_err(|e| in_file(base, e))?;
+    let mut includes = Includes { base: &base, opts, reading, limits, stack: Vec::new(), total: source_len };
+    includes.walk(value, token, &base)
+}
+
+/// State of one [`resolve`].
+struct Includes<'a> {
+    base: &'a Path,
+    opts: &'a toon::Options,
+    reading: &'a newline::Reading,
+    limits: &'a guard::Limits,
+    /// Files being decoded, outermost first
+    stack: Vec<PathBuf>,
+    /// Bytes read so far, the including document's among them
+    total: usize,
+}
+
+impl Includes<'_> {
+    fn walk(&mut self, value: &mut Value, token: &Token, dir: &Path) -> Result<(), Error> {
+        match value {
+            Value::String(s) => {
+                if let Some(target) = token.strip(s) {
+                    let target = target.to_string();
+                    *value = self.load(&target, dir)?;
+                }
+            }
+            Value::Array(items) => {
+                for item in items {
+                    self.walk(item, token, dir)?;
+                }
+            }
+            Value::Object(map) => {
+                for item in map.values_mut() {
+                    self.walk(item, token, dir)?;
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn load(&mut self, target: &str, dir: &Path) -> Result<Value, Error> {
+        let joined = dir.join(target);
+        let path = joined.canonicalize().map_err(|e| in_file(&joined, e))?;
+        if !path.starts_with(self.base) {
+            return Err(toon::Error::Message(format!(
+                "cannot include '{}': outside the include base {}", target, self.base.display()
+            )).into());
+        }
+        if let Some(start) = self.stack.iter().position(|seen| *seen == path) {
+            let chain: Vec<String> = self.stack[start..].iter().chain([&path]).map(|p| p.display().to_string()).collect();
+            return Err(toon::Error::Message(format!("include cycle: {}", chain.join(" -> "))).into());
+        }
+        let text = std::fs::read_to_string(&path).map_err(|e| in_file(&path, e))?;
+        self.total = self.total.saturating_add(text.len());
+        self.limits
+            .check(&text)
+            .and_then(|()| self.limits.check_size(self.total).map_err(|message| format!("with includes, {}", message)))
+            .map_err(|message| Error::Limit(format!("{}: {}", path.display(), message)))?;
+        let token = Token::new(MARK);
+        let (mut value, _) = front::decode(&mark(&text, &token), self.opts, self.reading).map_err(|err| match err {
+            toon::Error::Syntax { line, message } => {
+                toon::Error::Message(format!("{}: Line {}: {}", path.display(), line, message))
+            }
+            other => other,
+        })?;
+        self.stack.push(path.clone());
+        self.walk(&mut value, &token, path.parent().unwrap_or(self.base))?;
+        self.stack.pop();
+        Ok(value)
+    }
+}
+
+fn in_file(path: &Path, err: io::Error) -> toon::Error {
+    toon::Error::Io(io::Error::new(err.kind(), format!("{}: {}", path.display(), err)))
+}
@@ -0,0 +1,80 @@
+//! Indentation other than the encoder's two spaces (`indent=`).
+//!
+//! TOON strings never span lines, so the leading spaces of every encoded line
+//! are indentation and changing the unit only rewrites line starts. [`apply`]
+//! re-indents encoder output; [`restore`] turns a document indented that way
+//! back into the two-space form the parser reads.
+
+use std::borrow::Cow;
+
+use crate::scan;
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub(crate) enum Indent {
+    Spaces(usize),
+    Tab,
+}
+
+impl Indent {
+    /// What the encoder writes and the parser reads
+    pub(crate) const DEFAULT: Indent = Indent::Spaces(2);
+
+    /// Widest space indentation accepted
+    pub(crate) const MAX_SPACES: usize = 16;
+
+    fn unit(self) -> String {
+        match self {
+            Indent::Spaces(width) => " ".repeat(width),
+            Indent::Tab => "\t".to_string(),
+        }
+    }
+}
+
+/// Rewrite the indentation of every line, given its depth in levels and the
+/// bytes of indentation it had.
+fn reindent<'a>(text: &'a str, level: impl Fn(&str) -> (usize, usize), unit: &str) -> Cow<'a, str> {
+    let mut out = String::with_capacity(text.len() + text.len() / 8);
+    for line in text.split_inclusive('\n') {
+        let (depth, lead) = level(line);
+        for _ in 0..depth {
+            out.push_str(unit);
+        }
+        out.push_str(&line[lead..]);
+    }
+    Cow::Owned(out)
+}
+
+/// Encoder output re-indented with `indent`.
+pub(crate) fn apply(text: &str, indent: Indent) -> Cow<'_, str> {
+    if indent == Indent::DEFAULT {
+        return Cow::Borrowed(text);
+    }
+    reindent(
+        text,
+        |line| {
+            let lead = scan::indent(line);
+            (lead / 2, lead - lead % 2)
+        },
+        &indent.unit(),
+    )
+}
+
+/// A document indented with `indent`, re-indented for the parser. Lines
+/// indented otherwise are left for the parser to report.
+pub(crate) fn restore(source: &str, indent: Indent) -> Cow<'_, str> {
+    if indent == Indent::DEFAULT {
+        return Cow::Borrowed(source);
+    }
+    let (unit, width) = match indent {
+        Indent::Spaces(width) => (' ', width),
+        Indent::Tab => ('\t', 1),
+    };
+    reindent(
+        source,
+        |line| {
+            let lead = line.len() - line.trim_start_matches(unit).len();
+            (lead / width, lead - lead % width)
+        },
+        "  ",
+    )
+}
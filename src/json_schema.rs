@@ -0,0 +1,45 @@
+//! JSON Schema validation of decoded documents.
+
+use pyo3::prelude::*;
+use serde_json::Value;
+
+use crate::locate;
+use crate::python::ToonSchemaError;
+
+/// Compile a JSON Schema, surfacing invalid schemas as `ValueError`.
+pub(crate) fn compile(schema: &Value) -> PyResult<jsonschema::Validator> {
+    jsonschema::validator_for(schema)
+        .map_err(|e| pyo3::exceptions::PyValueError::new_err(format!("Invalid JSON Schema: {}", e)))
+}
+
+/// Validate `instance` and raise `ToonSchemaError` for the first violation.
+///
+/// When the TOON source is available the error also carries the line that
+/// produced the offending value.
+pub(crate) fn check(
+    py: Python<'_>,
+    validator: &jsonschema::Validator,
+    instance: &Value,
+    source: Option<&str>,
+) -> PyResult<()> {
+    let Some(error) = validator.iter_errors(instance).next() else {
+        return Ok(());
+    };
+
+    let instance_path = error.instance_path.as_str().to_string();
+    let schema_path = error.schema_path.as_str().to_string();
+    let line = source.and_then(|s| locate::line_for_pointer(s, &instance_path));
+
+    let location = if instance_path.is_empty() { "/" } else { instance_path.as_str() };
+    let message = match line {
+        Some(line) => format!("Line {}: schema violation at '{}': {}", line, location, error),
+        None => format!("Schema violation at '{}': {}", location, error),
+    };
+
+    let err = ToonSchemaError::new_err(message);
+    let value = err.value(py);
+    value.setattr("instance_path", instance_path)?;
+    value.setattr("schema_path", schema_path)?;
+    value.setattr("line", line)?;
+    Err(err)
+}
@@ -0,0 +1,164 @@
+//! `ToonValue`, the proxy returned by `decode_lazy()`.
+//!
+//! The parsed document stays a Rust value shared by every proxy into it.
+//! Indexing a container yields another proxy; only scalars and explicit
+//! `to_python()` calls build Python objects, so reading three fields of a
+//! 100k-row table converts three values.
+
+use pyo3::exceptions::{PyIndexError, PyKeyError, PyTypeError};
+use pyo3::prelude::*;
+use pyo3::types::{PyList, PyString};
+use serde_json::Value;
+use std::sync::Arc;
+
+use crate::python::json_to_python;
+use crate::shape;
+
+#[derive(Clone)]
+enum Step {
+    Key(String),
+    Index(usize),
+}
+
+/// Read-only view of a dict or list inside a lazily decoded document.
+///
+/// Supports ``len()``, iteration (keys for dicts, items for lists), ``in``,
+/// ``[key]`` / ``[index]``, ``get()``, ``keys()`` and ``to_python()``.
+#[pyclass(module = "toon_parser", frozen)]
+pub(crate) struct ToonValue {
+    root: Arc<Value>,
+    path: Vec<Step>,
+}
+
+impl ToonValue {
+    /// Proxy for containers, plain Python objects for scalars.
+    fn wrap(py: Python<'_>, root: Arc<Value>, path: Vec<Step>) -> PyResult<Bound<'_, PyAny>> {
+        let proxy = ToonValue { root, path };
+        match proxy.value() {
+            Value::Array(_) | Value::Object(_) => Ok(Bound::new(py, proxy)?.into_any()),
+            scalar => json_to_python(py, scalar),
+        }
+    }
+
+    pub(crate) fn root(py: Python<'_>, value: Value) -> PyResult<Bound<'_, PyAny>> {
+        Self::wrap(py, Arc::new(value), Vec::new())
+    }
+
+    fn value(&self) -> &Value {
+        let mut value = &*self.root;
+        for step in &self.path {
+            // Paths are only built from keys and indices that exist
+            value = match step {
+                Step::Key(key) => &value[key.as_str()],
+                Step::Index(i) => &value[*i],
+            };
+        }
+        value
+    }
+
+    pub(crate) fn to_value(&self) -> Value {
+        self.value().clone()
+    }
+
+    fn child<'py>(&self, py: Python<'py>, step: Step) -> PyResult<Bound<'py, PyAny>> {
+        let mut path = self.path.clone();
+        path.push(step);
+        Self::wrap(py, self.root.clone(), path)
+    }
+
+    /// Resolve a Python key or index against this container.
+    fn step(&self, key: &Bound<'_, PyAny>) -> PyResult<Option<Step>> {
+        match self.value() {
+            Value::Object(map) => {
+                let key = key.cast::<PyString>().map_err(|_| PyTypeError::new_err("dict keys are str"))?;
+                let key = key.to_cow()?;
+                Ok(map.contains_key(key.as_ref()).then(|| Step::Key(key.into_owned())))
+            }
+            Value::Array(items) => {
+                let i: i64 = key.extract().map_err(|_| PyTypeError::new_err("list indices must be integers"))?;
+                let len = items.len() as i64;
+                let i = if i < 0 { i + len } else { i };
+                Ok((0..len).contains(&i).then_some(Step::Index(i as usize)))
+            }
+            _ => Err(PyTypeError::new_err("value is not subscriptable")),
+        }
+    }
+}
+
+#[pymethods]
+impl ToonValue {
+    fn __getitem__<'py>(&self, py: Python<'py>, key: &Bound<'py, PyAny>) -> PyResult<Bound<'py, PyAny>> {
+        match self.step(key)? {
+            Some(step) => self.child(py, step),
+            None if self.value().is_array() => Err(PyIndexError::new_err("list index out of range")),
+            None => Err(PyKeyError::new_err(key.clone().unbind())),
+        }
+    }
+
+    #[pyo3(signature = (key, default=None))]
+    fn get<'py>(&self, py: Python<'py>, key: &Bound<'py, PyAny>, default: Option<Bound<'py, PyAny>>) -> PyResult<Bound<'py, PyAny>> {
+        match self.step(key)? {
+            Some(step) => self.child(py, step),
+            None => Ok(default.unwrap_or_else(|| py.None().into_bound(py))),
+        }
+    }
+
+    fn __contains__(&self, key: &Bound<'_, PyAny>) -> PyResult<bool> {
+        match self.value() {
+            Value::Object(_) => Ok(self.step(key).unwrap_or(None).is_some()),
+            // Membership in a list compares values, as for a real list
+            Value::Array(_) => self.to_python(key.py())?.contains(key),
+            _ => Ok(false),
+        }
+    }
+
+    fn __len__(&self) -> usize {
+        match self.value() {
+            Value::Object(map) => map.len(),
+            Value::Array(items) => items.len(),
+            _ => 0,
+        }
+    }
+
+    fn __iter__<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        let items = match self.value() {
+            Value::Object(map) => map.keys().map(|k| Ok(PyString::new(py, k).into_any())).collect::<PyResult<Vec<_>>>()?,
+            Value::Array(items) => (0..items.len()).map(|i| self.child(py, Step::Index(i))).collect::<PyResult<Vec<_>>>()?,
+            _ => Vec::new(),
+        };
+        Ok(PyList::new(py, items)?.as_any().try_iter()?.into_any())
+    }
+
+    fn keys<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyList>> {
+        match self.value() {
+            Value::Object(map) => PyList::new(py, map.keys()),
+            _ => Err(PyTypeError::new_err("only dict values have keys")),
+        }
+    }
+
+    /// ``'dict'`` or ``'list'``.
+    #[getter]
+    fn kind(&self) -> &'static str {
+        shape::kind(self.value())
+    }
+
+    /// Convert this value, and everything below it, to Python objects.
+    fn to_python<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyAny>> {
+        json_to_python(py, self.value())
+    }
+
+    fn __eq__(&self, py: Python<'_>, other: &Bound<'_, PyAny>) -> PyResult<bool> {
+        match other.cast::<ToonValue>() {
+            Ok(other) => Ok(self.value() == other.get().value()),
+            Err(_) => self.to_python(py)?.eq(other),
+        }
+    }
+
+    fn __repr__(&self) -> String {
+        match self.value() {
+            Value::Object(map) => format!("<ToonValue dict with {} keys>", map.len()),
+            Value::Array(items) => format!("<ToonValue list with {} items>", items.len()),
+            other => format!("<ToonValue {}>", other),
+        }
+    }
+}
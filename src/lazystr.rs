@@ -0,0 +1,126 @@
+//! `LazyString`, returned by `decode(..., lazy_strings=N)` for long strings.
+//!
+//! The UTF-8 text stays in Rust until the first use that needs a Python
+//! `str`, so a document carrying a few 100 MB blobs decodes without building
+//! them. Once built, the `str` is cached and the Rust copy released.
+
+use pyo3::prelude::*;
+use pyo3::pyclass::CompareOp;
+use pyo3::sync::PyOnceLock;
+use pyo3::types::{PyBytes, PyString};
+use std::sync::Mutex;
+
+/// A decoded string that becomes a ``str`` on first use.
+///
+/// ``str()``, comparison, hashing, ``+``, ``in``, indexing and every ``str``
+/// method materialize it; ``len()`` and ``bytes()`` do not. ``encode()`` and
+/// ``dumps()`` accept it like a ``str``.
+///
+/// Example:
+///     >>> doc = toonpy.decode(text, lazy_strings=1 << 20)
+///     >>> doc["blob"]
+///     <LazyString of 104857600 chars>
+#[pyclass(module = "toon_parser", frozen)]
+pub(crate) struct LazyString {
+    /// Released once `string` is built
+    text: Mutex<Option<Box<str>>>,
+    string: PyOnceLock<Py<PyString>>,
+    chars: usize,
+}
+
+impl LazyString {
+    pub(crate) fn wrap<'py>(py: Python<'py>, text: &str) -> PyResult<Bound<'py, PyAny>> {
+        let lazy = LazyString {
+            text: Mutex::new(Some(text.into())),
+            string: PyOnceLock::new(),
+            chars: text.chars().count(),
+        };
+        Ok(Bound::new(py, lazy)?.into_any())
+    }
+
+    /// The Python string, built on first call.
+    pub(crate) fn materialize<'py>(&self, py: Python<'py>) -> &Bound<'py, PyString> {
+        self.string
+            .get_or_init(py, || {
+                let text = self.text.lock().unwrap_or_else(|e| e.into_inner()).take().unwrap_or_default();
+                PyString::new(py, &text).unbind()
+            })
+            .bind(py)
+    }
+
+    /// The text as Rust data, without building the Python string.
+    pub(crate) fn to_string(&self, py: Python<'_>) -> PyResult<String> {
+        let text = self.text.lock().unwrap_or_else(|e| e.into_inner());
+        match text.as_deref() {
+            Some(text) => Ok(text.to_string()),
+            None => {
+                drop(text);
+                Ok(self.materialize(py).to_cow()?.into_owned())
+            }
+        }
+    }
+}
+
+#[pymethods]
+impl LazyString {
+    fn __str__<'py>(&self, py: Python<'py>) -> Bound<'py, PyString> {
+        self.materialize(py).clone()
+    }
+
+    fn __repr__(&self) -> String {
+        format!("<LazyString of {} chars>", self.chars)
+    }
+
+    fn __len__(&self) -> usize {
+        self.chars
+    }
+
+    /// The UTF-8 encoding, without building the ``str`` first.
+    fn __bytes__<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyBytes>> {
+        let text = self.text.lock().unwrap_or_else(|e| e.into_inner());
+        match text.as_deref() {
+            Some(text) => Ok(PyBytes::new(py, text.as_bytes())),
+            None => {
+                drop(text);
+                Ok(self.materialize(py).call_method1("encode", ("utf-8",))?.cast_into::<PyBytes>()?)
+            }
+        }
+    }
+
+    fn __richcmp__<'py>(&self, py: Python<'py>, other: &Bound<'py, PyAny>, op: CompareOp) -> PyResult<Bound<'py, PyAny>> {
+        self.materialize(py).rich_compare(as_str(py, other), op)
+    }
+
+    fn __add__<'py>(&self, py: Python<'py>, other: &Bound<'py, PyAny>) -> PyResult<Bound<'py, PyAny>> {
+        self.materialize(py).add(as_str(py, other))
+    }
+
+    fn __radd__<'py>(&self, py: Python<'py>, other: &Bound<'py, PyAny>) -> PyResult<Bound<'py, PyAny>> {
+        other.add(self.materialize(py))
+    }
+
+    fn __hash__(&self, py: Python<'_>) -> PyResult<isize> {
+        self.materialize(py).hash()
+    }
+
+    fn __contains__(&self, py: Python<'_>, part: &Bound<'_, PyAny>) -> PyResult<bool> {
+        self.materialize(py).contains(part)
+    }
+
+    fn __getitem__<'py>(&self, py: Python<'py>, key: &Bound<'py, PyAny>) -> PyResult<Bound<'py, PyAny>> {
+        self.materialize(py).get_item(key)
+    }
+
+    /// Every other attribute is the ``str``'s own, e.g. ``startswith``.
+    fn __getattr__<'py>(&self, py: Python<'py>, name: &Bound<'py, PyString>) -> PyResult<Bound<'py, PyAny>> {
+        self.materialize(py).getattr(name)
+    }
+}
+
+/// `other` with a `LazyString` replaced by its `str`.
+fn as_str<'py>(py: Python<'py>, other: &Bound<'py, PyAny>) -> Bound<'py, PyAny> {
+    match other.cast::<LazyString>() {
+        Ok(other) => other.get().materialize(py).clone().into_any(),
+        Err(_) => other.clone(),
+    }
+}
@@ -0,0 +1,46 @@
+//! Output size cap for `encode(..., max_output_bytes=n)`.
+//!
+//! The encoder writes into a buffer that refuses to grow past the cap, so a
+//! runaway object is abandoned as soon as it crosses the limit instead of
+//! after its whole encoding has been built.
+
+use serde_json::Value;
+use std::io::{self, Write};
+
+pub(crate) enum Error {
+    /// The output would have been longer than the limit
+    Exceeded,
+    Toon(toon::Error),
+}
+
+struct Capped {
+    buf: Vec<u8>,
+    limit: usize,
+    exceeded: bool,
+}
+
+impl Write for Capped {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        if self.buf.len() + data.len() > self.limit {
+            self.exceeded = true;
+            return Err(io::Error::other("output limit exceeded"));
+        }
+        self.buf.extend_from_slice(data);
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// `toon::encode_to_string`, giving up once the output passes `limit` bytes.
+pub(crate) fn encode(value: &Value, opts: &toon::Options, limit: usize) -> Result<String, Error> {
+    let mut out = Capped { buf: Vec::new(), limit, exceeded: false };
+    match toon::encode_to_writer(&mut out, value, opts) {
+        Err(_) if out.exceeded => Err(Error::Exceeded),
+        Err(err) => Err(Error::Toon(err)),
+        // The encoder only writes UTF-8
+        Ok(()) => String::from_utf8(out.buf).map_err(|e| Error::Toon(toon::Error::Message(e.to_string()))),
+    }
+}
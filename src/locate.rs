@@ -0,0 +1,162 @@
+//! Best-effort mapping from decoded value paths back to TOON source lines.
+//!
+//! The upstream parser only reports positions for syntax errors, so errors
+//! raised after decoding (schema violations, type mismatches) use this module
+//! to point the user at the line that produced the offending value.
+
+/// One logical entry of a TOON document. List items (`- a: 1`) are split into
+/// a marker entry and a content entry indented past the dash, so nested
+/// objects inside list items line up like ordinary blocks.
+struct Entry<'a> {
+    line: usize,
+    indent: usize,
+    content: &'a str,
+    marker: bool,
+}
+
+enum Node {
+    Block { start: usize, indent: usize },
+    Table { first_row: usize, indent: usize },
+    Inline,
+}
+
+fn entries(source: &str) -> Vec<Entry<'_>> {
+    let mut out = Vec::new();
+    let (_, body, offset) = crate::front::split(source);
+    for (idx, raw) in body.lines().enumerate() {
+        let idx = idx + offset;
+        let trimmed = raw.trim_start_matches(' ');
+        if trimmed.trim().is_empty() {
+            continue;
+        }
+        let indent = raw.len() - trimmed.len();
+        if let Some(rest) = trimmed.strip_prefix("- ").or_else(|| (trimmed == "-").then_some("")) {
+            out.push(Entry { line: idx + 1, indent, content: "-", marker: true });
+            if !rest.is_empty() {
+                out.push(Entry { line: idx + 1, indent: indent + 2, content: rest, marker: false });
+            }
+        } else {
+            out.push(Entry { line: idx + 1, indent, content: trimmed, marker: false });
+        }
+    }
+    out
+}
+
+/// Extract the key of a `key: value` / `key[N]...:` entry.
+pub(crate) fn entry_key(content: &str) -> Option<String> {
+    if let Some(rest) = content.strip_prefix('"') {
+        let mut key = String::new();
+        let mut chars = rest.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => key.push(chars.next()?),
+                '"' => return Some(key),
+                _ => key.push(c),
+            }
+        }
+        None
+    } else {
+        let end = content.find([':', '['])?;
+        Some(content[..end].trim_end().to_string())
+    }
+}
+
+/// Text following the key, e.g. `[2]{id,name}:` or `: value`.
+fn after_key(content: &str) -> &str {
+    if content.starts_with('"') {
+        let bytes = content.as_bytes();
+        let mut i = 1;
+        while i < bytes.len() {
+            match bytes[i] {
+                b'\\' => i += 2,
+                b'"' => return &content[i + 1..],
+                _ => i += 1,
+            }
+        }
+        ""
+    } else {
+        content.find([':', '[']).map(|i| &content[i..]).unwrap_or("")
+    }
+}
+
+/// Children of a block are the entries at exactly `indent` until the block ends.
+fn children<'e, 'a>(entries: &'e [Entry<'a>], start: usize, indent: usize) -> impl Iterator<Item = usize> + 'e {
+    entries[start.min(entries.len())..]
+        .iter()
+        .enumerate()
+        .take_while(move |(_, e)| e.indent >= indent)
+        .filter(move |(_, e)| e.indent == indent)
+        .map(move |(i, _)| start + i)
+}
+
+fn child_node(entries: &[Entry<'_>], idx: usize) -> Node {
+    let next_indent = |i: usize| entries.get(i).map(|e| e.indent).filter(|&n| n > entries[idx].indent);
+    if entries[idx].marker {
+        return match next_indent(idx + 1) {
+            Some(indent) => Node::Block { start: idx + 1, indent },
+            None => Node::Inline,
+        };
+    }
+    let rest = after_key(entries[idx].content);
+    let opens_block = rest.trim_end().ends_with(':');
+    match next_indent(idx + 1) {
+        Some(indent) if opens_block && rest.contains('{') => Node::Table { first_row: idx + 1, indent },
+        Some(indent) if opens_block => Node::Block { start: idx + 1, indent },
+        _ => Node::Inline,
+    }
+}
+
+/// Decode one RFC 6901 pointer token.
+fn unescape(token: &str) -> String {
+    token.replace("~1", "/").replace("~0", "~")
+}
+
+/// Return the 1-based line of the value addressed by a JSON pointer such as
+/// `/users/3/email`, or the closest enclosing line that could be resolved.
+pub(crate) fn line_for_pointer(source: &str, pointer: &str) -> Option<usize> {
+    let tokens: Vec<String> = pointer.split('/').skip(1).map(unescape).collect();
+    line_for_path(source, &tokens)
+}
+
+/// Same as [`line_for_pointer`] for an already-split path.
+pub(crate) fn line_for_path<S: AsRef<str>>(source: &str, path: &[S]) -> Option<usize> {
+    let entries = entries(source);
+    let first = entries.first()?;
+    // A root array (`[2]{id,name}:`) has no key to look up
+    let mut node = if first.content.starts_with('[') && !first.marker {
+        child_node(&entries, 0)
+    } else {
+        Node::Block { start: 0, indent: first.indent }
+    };
+    let mut line = None;
+
+    for token in path {
+        let token = token.as_ref();
+        let found = match node {
+            Node::Block { start, indent } => match token.parse::<usize>() {
+                Ok(n) if entries.get(start).is_some_and(|e| e.marker) => {
+                    children(&entries, start, indent).filter(|&i| entries[i].marker).nth(n)
+                }
+                _ => children(&entries, start, indent)
+                    .find(|&i| !entries[i].marker && entry_key(entries[i].content).as_deref() == Some(token)),
+            },
+            Node::Table { first_row, indent } => token
+                .parse::<usize>()
+                .ok()
+                .and_then(|n| children(&entries, first_row, indent).nth(n)),
+            Node::Inline => break,
+        };
+        match found {
+            Some(idx) => {
+                line = Some(entries[idx].line);
+                node = match node {
+                    Node::Table { .. } => Node::Inline,
+                    _ => child_node(&entries, idx),
+                };
+            }
+            None => break,
+        }
+    }
+
+    line.or(Some(first.line))
+}
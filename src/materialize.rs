@@ -0,0 +1,250 @@
+//! Building Python objects from decoded values when the caller asks for
+//! something other than plain lists and dicts.
+//!
+//! `decode` keeps using the inlined `json_to_python` fast path for the
+//! default output; this module handles the alternatives.
+
+use pyo3::exceptions::{PyTypeError, PyValueError};
+use pyo3::intern;
+use pyo3::prelude::*;
+use pyo3::sync::PyOnceLock;
+use pyo3::types::{PyBytes, PyDict, PyList, PyString, PyTuple, PyType};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+
+use crate::attrdict::AttrDict;
+use crate::lazystr::LazyString;
+use crate::python::json_to_python;
+use crate::registry::Snapshot;
+use crate::scan::Token;
+use crate::{duplicates, surrogate};
+
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+pub(crate) enum Arrays {
+    #[default]
+    List,
+    Tuple,
+}
+
+impl Arrays {
+    pub(crate) fn parse(name: &str) -> PyResult<Self> {
+        match name {
+            "list" => Ok(Arrays::List),
+            "tuple" => Ok(Arrays::Tuple),
+            _ => Err(PyValueError::new_err(format!(
+                "Invalid arrays '{}'. Must be 'list' or 'tuple'", name
+            ))),
+        }
+    }
+}
+
+static MAPPING_PROXY: PyOnceLock<Py<PyType>> = PyOnceLock::new();
+
+/// How decoded containers are represented in Python.
+#[derive(Default)]
+pub(crate) struct Output<'py> {
+    pub(crate) arrays: Arrays,
+    /// Read-only mappings and tuples throughout
+    pub(crate) frozen: bool,
+    /// `AttrDict` instead of `dict`
+    pub(crate) attrdict: bool,
+    /// Called with each decoded dict, e.g. `OrderedDict`
+    pub(crate) dict_factory: Option<Bound<'py, PyAny>>,
+    /// Called with each decoded list, e.g. `deque`
+    pub(crate) list_factory: Option<Bound<'py, PyAny>>,
+    /// Strings may hold parked lone surrogates to restore
+    pub(crate) surrogates: bool,
+    /// Objects whose keys [`duplicates::mark`] renamed with this token become
+    /// lists of `(key, value)` pairs
+    pub(crate) multidict: Option<Token>,
+    /// String values longer than this many bytes become `LazyString`s
+    pub(crate) lazy_strings: Option<usize>,
+    /// Strings marked by [`Snapshot::mark`] are built by their constructor
+    pub(crate) scalars: Snapshot<'py>,
+}
+
+impl Output<'_> {
+    /// Reject options that each decide the same container type, and factories
+    /// that cannot be called.
+    pub(crate) fn check(&self) -> PyResult<()> {
+        for (name, factory) in [("dict_factory", &self.dict_factory), ("list_factory", &self.list_factory)] {
+            if factory.as_ref().is_some_and(|f| !f.is_callable()) {
+                return Err(PyTypeError::new_err(format!("{} must be callable", name)));
+            }
+        }
+        let conflict = if self.frozen && self.attrdict {
+            Some("frozen and attrdict")
+        } else if self.dict_factory.is_some() && (self.frozen || self.attrdict) {
+            Some("dict_factory and frozen/attrdict")
+        } else if self.list_factory.is_some() && (self.frozen || self.arrays == Arrays::Tuple) {
+            Some("list_factory and frozen/arrays='tuple'")
+        } else {
+            None
+        };
+        match conflict {
+            Some(options) => Err(PyValueError::new_err(format!("{} cannot be combined", options))),
+            None => Ok(()),
+        }
+    }
+
+    /// True when the fast `json_to_python` path produces the same result.
+    pub(crate) fn is_default(&self) -> bool {
+        self.arrays == Arrays::List
+            && !self.frozen
+            && !self.attrdict
+            && self.dict_factory.is_none()
+            && self.list_factory.is_none()
+            && !self.surrogates
+            && self.multidict.is_none()
+            && self.lazy_strings.is_none()
+            && self.scalars.is_empty()
+    }
+}
+
+pub(crate) fn to_python<'py>(py: Python<'py>, value: &Value, output: &Output<'py>) -> PyResult<Bound<'py, PyAny>> {
+    if output.is_default() {
+        return json_to_python(py, value);
+    }
+    match value {
+        Value::Array(items) => {
+            let items = items
+                .iter()
+                .map(|item| to_python(py, item, output))
+                .collect::<PyResult<Vec<_>>>()?;
+            sequence(py, items, output)
+        }
+        Value::Object(map) => {
+            if let Some(token) = output.multidict.as_ref().filter(|token| map.keys().any(|k| duplicates::is_marked(k, token))) {
+                return pairs(py, map, token, output);
+            }
+            let dict = PyDict::new(py);
+            for (k, v) in map {
+                dict.set_item(string(py, k, output)?, to_python(py, v, output)?)?;
+            }
+            if output.frozen {
+                // The proxy holds the only reference, so nothing can mutate the dict
+                MAPPING_PROXY.import(py, "types", "MappingProxyType")?.call1((dict,))
+            } else if output.attrdict {
+                AttrDict::wrap(dict)
+            } else if let Some(factory) = &output.dict_factory {
+                factory.call1((dict,))
+            } else {
+                Ok(dict.into_any())
+            }
+        }
+        Value::String(s) if output.scalars.is_marked(s) => output.scalars.construct(s),
+        Value::String(s) if output.lazy_strings.is_some_and(|min| s.len() > min) && !surrogate::has_parked(s) => {
+            LazyString::wrap(py, s)
+        }
+        Value::String(s) => string(py, s, output),
+        scalar => json_to_python(py, scalar),
+    }
+}
+
+/// `items` as the sequence type `output` asks for: a tuple when frozen or
+/// with `arrays='tuple'`, else a list passed through `list_factory`.
+fn sequence<'py>(py: Python<'py>, items: Vec<Bound<'py, PyAny>>, output: &Output<'py>) -> PyResult<Bound<'py, PyAny>> {
+    if output.frozen || output.arrays == Arrays::Tuple {
+        return Ok(PyTuple::new(py, items)?.into_any());
+    }
+    let list = PyList::new(py, items)?;
+    match &output.list_factory {
+        Some(factory) => factory.call1((list,)),
+        None => Ok(list.into_any()),
+    }
+}
+
+/// An object holding keys [`duplicates::mark`] renamed with `token`, as a
+/// sequence of `(key, value)` pairs grouped by key, keys in the order they
+/// first appear.
+fn pairs<'py>(py: Python<'py>, map: &Map<String, Value>, token: &Token, output: &Output<'py>) -> PyResult<Bound<'py, PyAny>> {
+    let mut first: HashMap<&str, usize> = HashMap::new();
+    let mut pairs: Vec<(usize, usize, &str, &Value)> = map
+        .iter()
+        .map(|(k, v)| {
+            let (name, n) = duplicates::split(k, token);
+            let next = first.len();
+            (*first.entry(name).or_insert(next), n, name, v)
+        })
+        .collect();
+    pairs.sort_by_key(|&(group, n, _, _)| (group, n));
+    let pairs = pairs
+        .into_iter()
+        .map(|(_, _, name, v)| Ok(PyTuple::new(py, [string(py, name, output)?, to_python(py, v, output)?])?.into_any()))
+        .collect::<PyResult<Vec<_>>>()?;
+    sequence(py, pairs, output)
+}
+
+fn string<'py>(py: Python<'py>, s: &str, output: &Output<'py>) -> PyResult<Bound<'py, PyAny>> {
+    if output.surrogates && surrogate::has_parked(s) {
+        let bytes: Vec<u8> = surrogate::restore(s).into_iter().flat_map(u16::to_le_bytes).collect();
+        PyBytes::new(py, &bytes).call_method1(intern!(py, "decode"), ("utf-16-le", "surrogatepass"))
+    } else {
+        Ok(PyString::new(py, s).into_any())
+    }
+}
+
+/// Update `target` in place from a decoded object: dicts get their keys
+/// merged, other objects their attributes set. Nested dicts and objects are
+/// updated recursively, so references held elsewhere stay valid. The whole
+/// tree is checked first, so a bad key anywhere leaves `target` untouched.
+pub(crate) fn fill<'py>(target: &Bound<'py, PyAny>, map: &Map<String, Value>, output: &Output<'py>) -> PyResult<()> {
+    check_fields(target, map)?;
+    assign(target, map, output)
+}
+
+/// Dataclasses only accept their declared fields; check every one that
+/// [`assign`] will reach before changing anything.
+fn check_fields(target: &Bound<'_, PyAny>, map: &Map<String, Value>) -> PyResult<()> {
+    let py = target.py();
+    let dict = target.cast::<PyDict>().ok();
+    let fields = match dict {
+        Some(_) => None,
+        None => target.getattr(intern!(py, "__dataclass_fields__")).ok(),
+    };
+    for (k, v) in map {
+        if let Some(fields) = &fields {
+            if !fields.contains(k)? {
+                return Err(PyValueError::new_err(format!(
+                    "'{}' is not a field of {}", k, target.get_type().name()?
+                )));
+            }
+        }
+        let Value::Object(inner) = v else {
+            continue;
+        };
+        let existing = match &dict {
+            Some(dict) => dict.get_item(k)?,
+            None => target.getattr(k.as_str()).ok(),
+        };
+        if let Some(existing) = existing.filter(is_fillable) {
+            check_fields(&existing, inner)?;
+        }
+    }
+    Ok(())
+}
+
+fn assign<'py>(target: &Bound<'py, PyAny>, map: &Map<String, Value>, output: &Output<'py>) -> PyResult<()> {
+    let py = target.py();
+    if let Ok(dict) = target.cast::<PyDict>() {
+        for (k, v) in map {
+            match (dict.get_item(k)?, v) {
+                (Some(existing), Value::Object(inner)) if is_fillable(&existing) => assign(&existing, inner, output)?,
+                _ => dict.set_item(k, to_python(py, v, output)?)?,
+            }
+        }
+        return Ok(());
+    }
+    for (k, v) in map {
+        match (target.getattr(k.as_str()).ok(), v) {
+            (Some(existing), Value::Object(inner)) if is_fillable(&existing) => assign(&existing, inner, output)?,
+            _ => target.setattr(k.as_str(), to_python(py, v, output)?)?,
+        }
+    }
+    Ok(())
+}
+
+/// Dicts and instances with attributes, as opposed to scalars and lists.
+fn is_fillable(value: &Bound<'_, PyAny>) -> bool {
+    value.is_instance_of::<PyDict>() || (value.hasattr("__dict__").unwrap_or(false) && !value.is_instance_of::<PyType>())
+}
@@ -0,0 +1,62 @@
+//! Structural merging of several documents.
+
+use serde_json::Value;
+use std::collections::BTreeSet;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Strategy {
+    /// Concatenate arrays found at the same path; tables must share columns.
+    AppendTables,
+    /// Merge objects recursively; arrays and scalars from later documents win.
+    DeepMerge,
+}
+
+impl Strategy {
+    pub(crate) fn parse(name: &str) -> Option<Self> {
+        match name {
+            "append_tables" => Some(Strategy::AppendTables),
+            "deep_merge" => Some(Strategy::DeepMerge),
+            _ => None,
+        }
+    }
+}
+
+fn columns(rows: &[Value]) -> Option<BTreeSet<&String>> {
+    let first = rows.first()?.as_object()?;
+    rows.iter().all(Value::is_object).then(|| first.keys().collect())
+}
+
+/// Merge `incoming` into `target`. Errors carry the path of the conflict.
+pub(crate) fn merge_into(target: &mut Value, incoming: Value, strategy: Strategy, path: &str) -> Result<(), String> {
+    match (target, incoming) {
+        (Value::Object(dst), Value::Object(src)) => {
+            for (k, v) in src {
+                match dst.get_mut(&k) {
+                    Some(existing) => merge_into(existing, v, strategy, &format!("{}.{}", path, k))?,
+                    None => {
+                        dst.insert(k, v);
+                    }
+                }
+            }
+            Ok(())
+        }
+        (Value::Array(dst), Value::Array(src)) if strategy == Strategy::AppendTables => {
+            if let (Some(a), Some(b)) = (columns(dst), columns(&src)) {
+                if a != b {
+                    return Err(format!(
+                        "{}: cannot append tables with different columns ({} vs {})",
+                        path,
+                        a.into_iter().cloned().collect::<Vec<_>>().join(","),
+                        b.into_iter().cloned().collect::<Vec<_>>().join(",")
+                    ));
+                }
+            }
+            dst.extend(src);
+            Ok(())
+        }
+        (dst, src) => {
+            *dst = src;
+            Ok(())
+        }
+    }
+}
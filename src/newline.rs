@@ -0,0 +1,219 @@
+//! Line endings. Documents saved with `\r\n` (Windows) or a lone `\r` decode
+//! like `\n` ones; `Options(newlines="lf")` rejects them instead, naming the
+//! line of the first carriage return.
+//!
+//! [`prepare`] bundles this with the other text fix-ups made before parsing.
+
+use serde_json::Value;
+use std::borrow::Cow;
+use std::io::{self, BufRead};
+
+use crate::{empty, guard, header_types, sparse, surrogate};
+
+/// Line endings a decode accepts.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default, Debug)]
+pub(crate) enum Newlines {
+    /// `\n`, `\r\n` and lone `\r`
+    #[default]
+    Any,
+    /// Only `\n`
+    Lf,
+}
+
+impl Newlines {
+    pub(crate) fn parse(name: &str) -> Option<Self> {
+        match name {
+            "any" => Some(Newlines::Any),
+            "lf" => Some(Newlines::Lf),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            Newlines::Any => "any",
+            Newlines::Lf => "lf",
+        }
+    }
+}
+
+/// The settings of [`prepare`] that `toon::Options` has no room for, taken
+/// from an `Options` object.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default, Debug)]
+pub(crate) struct Reading {
+    pub(crate) newlines: Newlines,
+    /// Read empty table cells as null rather than leaving them to the parser
+    pub(crate) empty_cells_as_none: bool,
+}
+
+pub(crate) fn normalize(source: &str, newlines: Newlines) -> Result<Cow<'_, str>, toon::Error> {
+    let Some(at) = source.find('\r') else {
+        return Ok(Cow::Borrowed(source));
+    };
+    if newlines == Newlines::Lf {
+        let line = source[..at].matches('\n').count() + 1;
+        return Err(toon::Error::Syntax {
+            line,
+            message: "carriage return in line ending; newlines='lf' requires '\\n'".to_string(),
+        });
+    }
+    Ok(Cow::Owned(source.replace("\r\n", "\n").replace('\r', "\n")))
+}
+
+/// Read one line ending in `\n`, `\r\n` or a lone `\r` into `line`, without
+/// its ending. Returns the number of bytes consumed, 0 at the end of input.
+pub(crate) fn read_line(reader: &mut impl BufRead, line: &mut String) -> io::Result<usize> {
+    let mut bytes = Vec::new();
+    let (consumed, _) = read_raw_line(reader, &mut bytes)?;
+    let text = std::str::from_utf8(&bytes).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+    line.push_str(text);
+    Ok(consumed)
+}
+
+/// [`read_line`] for input that may not be UTF-8: appends the line's bytes
+/// to `bytes` and also returns whether its ending held a carriage return.
+pub(crate) fn read_raw_line(reader: &mut impl BufRead, bytes: &mut Vec<u8>) -> io::Result<(usize, bool)> {
+    let mut consumed = 0;
+    loop {
+        let buf = reader.fill_buf()?;
+        if buf.is_empty() {
+            return Ok((consumed, false));
+        }
+        match buf.iter().position(|&b| b == b'\n' || b == b'\r') {
+            Some(i) => {
+                let ending = buf[i];
+                bytes.extend_from_slice(&buf[..i]);
+                reader.consume(i + 1);
+                consumed += i + 1;
+                if ending == b'\r' && reader.fill_buf()?.first() == Some(&b'\n') {
+                    reader.consume(1);
+                    consumed += 1;
+                }
+                return Ok((consumed, ending == b'\r'));
+            }
+            None => {
+                let len = buf.len();
+                bytes.extend_from_slice(buf);
+                reader.consume(len);
+                consumed += len;
+            }
+        }
+    }
+}
+
+/// The text fix-ups every decoding path applies before parsing: line endings
+/// are normalized, lone surrogate escapes and impossible array lengths
+/// rejected, explicit empty containers read, empty table cells read as null
+/// when `reading` says so (rejected in strict mode) and typed table headers
+/// applied.
+pub(crate) fn prepare<'a>(source: &'a str, opts: &toon::Options, reading: &Reading) -> Result<Cow<'a, str>, toon::Error> {
+    let source = normalize(source, reading.newlines)?;
+    surrogate::apply(&source, surrogate::Policy::Error)?;
+    guard::check_lengths(&source)?;
+    let source = match empty::read(&source) {
+        Cow::Owned(read) => Cow::Owned(read),
+        Cow::Borrowed(_) => source,
+    };
+    let source = match (reading.empty_cells_as_none || opts.strict).then(|| sparse::fill_empty(&source, opts.strict)).transpose()? {
+        Some(Cow::Owned(filled)) => Cow::Owned(filled),
+        _ => source,
+    };
+    let source = match header_types::apply(&source)? {
+        Cow::Owned(typed) => Cow::Owned(typed),
+        Cow::Borrowed(_) => source,
+    };
+    Ok(source)
+}
+
+/// `toon::decode_from_str` after [`prepare`].
+pub(crate) fn decode_str(source: &str, opts: &toon::Options, reading: &Reading) -> Result<Value, toon::Error> {
+    toon::decode_from_str(&prepare(source, opts, reading)?, opts)
+}
+
+/// Decode UTF-8 bytes, streaming them straight to the parser when
+/// [`prepare`] has nothing to change.
+pub(crate) fn decode_bytes(bytes: &[u8], opts: &toon::Options, reading: &Reading) -> Result<Value, toon::Error> {
+    guard::check_lengths_bytes(bytes)?;
+    if !bytes.contains(&b'\r') && !bytes.windows(2).any(|w| w == b"\\u") && !empty::maybe_explicit(bytes) && !((reading.empty_cells_as_none || opts.strict) && sparse::maybe_empty(bytes)) && !header_types::maybe_typed(bytes) {
+        return toon::decode_from_reader(bytes, opts);
+    }
+    let text = std::str::from_utf8(bytes).map_err(|e| toon::Error::Message(format!("invalid UTF-8: {}", e)))?;
+    decode_str(text, opts, reading)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reading(empty_cells_as_none: bool) -> Reading {
+        Reading { newlines: Newlines::Any, empty_cells_as_none }
+    }
+
+    #[test]
+    fn read_line_splits_on_every_line_ending() {
+        let mut reader = io::Cursor::new("a\r\nb\rc\n\r\nd");
+        let mut lines = Vec::new();
+        let mut line = String::new();
+        while read_line(&mut reader, &mut line).unwrap() > 0 {
+            lines.push(std::mem::take(&mut line));
+        }
+        assert_eq!(lines, ["a", "b", "c", "", "d"]);
+    }
+
+    #[test]
+    fn read_line_joins_an_ending_split_across_buffers() {
+        let mut reader = io::BufReader::with_capacity(2, "ab\r\ncd".as_bytes());
+        let mut line = String::new();
+        assert_eq!(read_line(&mut reader, &mut line).unwrap(), 4);
+        assert_eq!(line, "ab");
+        line.clear();
+        assert_eq!(read_line(&mut reader, &mut line).unwrap(), 2);
+        assert_eq!(line, "cd");
+    }
+
+    #[test]
+    fn carriage_returns_are_normalized_before_the_other_fixups() {
+        let source = "a: {}\r\nb: []\r\nrows[2]{x:int,y}:\r\n  1,\r\n  \"2\",b\r\n";
+        let prepared = prepare(source, &toon::Options::default(), &reading(true)).unwrap();
+        assert_eq!(prepared, "a:\nb[0]:\nrows[2]{x,y}:\n  1,null\n  2,b\n");
+    }
+
+    #[test]
+    fn empty_cells_are_filled_before_headers_are_typed() {
+        // An empty int cell would fail the coercion if the header came first
+        let source = "rows[2]{x:int,y:str}:\n  ,a\n  2,b\n";
+        let prepared = prepare(source, &toon::Options::default(), &reading(true)).unwrap();
+        assert_eq!(prepared, "rows[2]{x,y}:\n  null,a\n  2,b\n");
+    }
+
+    #[test]
+    fn errors_name_the_original_line() {
+        let strict = toon::Options { strict: true, ..Default::default() };
+        let source = "a: {}\r\nrows[1]{x,y}:\r\n  1,\r\n";
+        match prepare(source, &strict, &reading(false)) {
+            Err(toon::Error::Syntax { line, .. }) => assert_eq!(line, 3),
+            other => panic!("expected a syntax error, got {:?}", other.map(|s| s.into_owned())),
+        }
+        let source = "a: 1\r\nb: \"\\ud800\"\r\n";
+        match prepare(source, &toon::Options::default(), &reading(false)) {
+            Err(toon::Error::Syntax { line, .. }) => assert_eq!(line, 2),
+            other => panic!("expected a syntax error, got {:?}", other.map(|s| s.into_owned())),
+        }
+    }
+
+    #[test]
+    fn lf_mode_rejects_before_rewriting() {
+        let source = "a: {}\nb: 1\r\n";
+        let lf = Reading { newlines: Newlines::Lf, ..reading(false) };
+        match prepare(source, &toon::Options::default(), &lf) {
+            Err(toon::Error::Syntax { line, .. }) => assert_eq!(line, 2),
+            other => panic!("expected a syntax error, got {:?}", other.map(|s| s.into_owned())),
+        }
+    }
+
+    #[test]
+    fn untouched_documents_are_borrowed() {
+        let prepared = prepare("a: 1\nb[2]: x,y\n", &toon::Options::default(), &reading(true)).unwrap();
+        assert!(matches!(prepared, Cow::Borrowed(_)));
+    }
+}
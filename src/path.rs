@@ -0,0 +1,320 @@
+//! Path lookups and a JSONPath subset for selecting values.
+//!
+//! Supported: `$`, `.key`, `['key']`, `[n]` (negative counts from the end),
+//! `[start:end]`, `*` / `[*]`, recursive descent `..key` / `..*`, and filters
+//! `[?(@.field)]` or `[?(@.field <op> literal)]` with `==`, `!=`, `<`, `<=`,
+//! `>`, `>=`. The leading `$` is optional, so `users[0].email` also works.
+
+use serde_json::Value;
+use std::cmp::Ordering;
+
+enum Step {
+    Key(String),
+    Index(i64),
+    Slice(Option<i64>, Option<i64>),
+    Wildcard,
+    Descendant(Option<String>),
+    Filter(Filter),
+}
+
+struct Filter {
+    field: Vec<String>,
+    test: Option<(Op, Value)>,
+}
+
+#[derive(Clone, Copy)]
+enum Op {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+pub(crate) struct Path {
+    steps: Vec<Step>,
+}
+
+impl Path {
+    pub(crate) fn parse(expr: &str) -> Result<Self, String> {
+        let expr = expr.trim();
+        let mut rest = expr.strip_prefix('$').unwrap_or(expr);
+        let mut steps = Vec::new();
+
+        // A bare leading name, as in `users[0].email`
+        if !rest.is_empty() && !rest.starts_with(['.', '[']) {
+            let (name, tail) = take_name(rest);
+            steps.push(name_step(name));
+            rest = tail;
+        }
+
+        while !rest.is_empty() {
+            if let Some(tail) = rest.strip_prefix("..") {
+                let (name, tail) = take_name(tail);
+                if name.is_empty() {
+                    return Err(format!("expected a name after '..' in '{}'", expr));
+                }
+                steps.push(Step::Descendant((name != "*").then(|| name.to_string())));
+                rest = tail;
+            } else if let Some(tail) = rest.strip_prefix('.') {
+                let (name, tail) = take_name(tail);
+                if name.is_empty() {
+                    return Err(format!("expected a name after '.' in '{}'", expr));
+                }
+                steps.push(name_step(name));
+                rest = tail;
+            } else if let Some(tail) = rest.strip_prefix('[') {
+                let end = bracket_end(tail).ok_or_else(|| format!("unclosed '[' in '{}'", expr))?;
+                steps.push(parse_bracket(tail[..end].trim())?);
+                rest = &tail[end + 1..];
+            } else {
+                return Err(format!("unexpected '{}' in '{}'", rest, expr));
+            }
+        }
+        Ok(Path { steps })
+    }
+
+    /// True when the path addresses at most one value.
+    pub(crate) fn is_single(&self) -> bool {
+        self.steps.iter().all(|s| matches!(s, Step::Key(_) | Step::Index(_)))
+    }
+
+    /// Every value selected by the path, in document order.
+    pub(crate) fn select<'a>(&self, root: &'a Value) -> Vec<&'a Value> {
+        let mut current = vec![root];
+        for step in &self.steps {
+            let mut next = Vec::new();
+            for value in current {
+                apply(step, value, &mut next);
+            }
+            current = next;
+        }
+        current
+    }
+
+    /// Follow a single-value path, reporting the first segment that is missing.
+    pub(crate) fn get<'a>(&self, root: &'a Value) -> Result<&'a Value, String> {
+        let mut value = root;
+        let mut walked = String::from("$");
+        for step in &self.steps {
+            let next = match step {
+                Step::Key(key) => {
+                    push_key(&mut walked, key);
+                    value.get(key)
+                }
+                Step::Index(i) => {
+                    walked.push_str(&format!("[{}]", i));
+                    value.as_array().and_then(|items| resolve_index(*i, items.len()).map(|i| &items[i]))
+                }
+                _ => return Err("path selects several values; use query()".to_string()),
+            };
+            value = next.ok_or_else(|| format!("'{}' not found", walked))?;
+        }
+        Ok(value)
+    }
+}
+
+/// Append `.key` to a path, or `["key"]` when the key would not parse back
+/// as a name.
+pub(crate) fn push_key(path: &mut String, key: &str) {
+    let plain = !key.is_empty()
+        && key != "*"
+        && !key.contains(['.', '[', ']', '"', '\''])
+        && !key.contains(char::is_whitespace);
+    if plain {
+        path.push('.');
+        path.push_str(key);
+    } else {
+        path.push('[');
+        path.push_str(&Value::from(key).to_string());
+        path.push(']');
+    }
+}
+
+fn name_step(name: &str) -> Step {
+    if name == "*" {
+        Step::Wildcard
+    } else {
+        Step::Key(name.to_string())
+    }
+}
+
+fn take_name(s: &str) -> (&str, &str) {
+    let end = s.find(['.', '[']).unwrap_or(s.len());
+    (&s[..end], &s[end..])
+}
+
+/// Index of the `]` closing a bracket, skipping quotes and parentheses.
+fn bracket_end(s: &str) -> Option<usize> {
+    let mut quote = None;
+    let mut depth = 0;
+    for (i, c) in s.char_indices() {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '(') => depth += 1,
+            (None, ')') => depth -= 1,
+            (None, ']') if depth == 0 => return Some(i),
+            _ => {}
+        }
+    }
+    None
+}
+
+fn parse_bracket(inner: &str) -> Result<Step, String> {
+    if inner == "*" {
+        return Ok(Step::Wildcard);
+    }
+    if let Some(filter) = inner.strip_prefix('?') {
+        let filter = filter.trim();
+        let body = filter
+            .strip_prefix('(')
+            .and_then(|f| f.strip_suffix(')'))
+            .unwrap_or(filter);
+        return parse_filter(body.trim()).map(Step::Filter);
+    }
+    if inner.starts_with(['"', '\'']) {
+        return parse_string(inner).map(Step::Key);
+    }
+    if let Some((start, end)) = inner.split_once(':') {
+        let bound = |s: &str| -> Result<Option<i64>, String> {
+            let s = s.trim();
+            if s.is_empty() {
+                Ok(None)
+            } else {
+                s.parse().map(Some).map_err(|_| format!("invalid slice bound '{}'", s))
+            }
+        };
+        return Ok(Step::Slice(bound(start)?, bound(end)?));
+    }
+    inner.parse().map(Step::Index).map_err(|_| format!("invalid index '{}'", inner))
+}
+
+fn parse_string(s: &str) -> Result<String, String> {
+    if let Some(body) = s.strip_prefix('\'').and_then(|b| b.strip_suffix('\'')) {
+        return Ok(body.replace("\\'", "'"));
+    }
+    match serde_json::from_str::<Value>(s) {
+        Ok(Value::String(s)) => Ok(s),
+        _ => Err(format!("invalid string {}", s)),
+    }
+}
+
+fn parse_filter(body: &str) -> Result<Filter, String> {
+    let rest = body
+        .strip_prefix('@')
+        .ok_or_else(|| format!("filter must start with '@': '{}'", body))?;
+    let ops = [("==", Op::Eq), ("!=", Op::Ne), ("<=", Op::Le), (">=", Op::Ge), ("<", Op::Lt), (">", Op::Gt)];
+    let found = ops
+        .iter()
+        .filter_map(|&(token, op)| rest.find(token).map(|at| (at, token, op)))
+        .min_by_key(|&(at, token, _)| (at, std::cmp::Reverse(token.len())));
+
+    let (field, test) = match found {
+        Some((at, token, op)) => {
+            let literal = rest[at + token.len()..].trim();
+            let literal = if literal.starts_with('\'') {
+                Value::String(parse_string(literal)?)
+            } else {
+                serde_json::from_str(literal).map_err(|_| format!("invalid literal '{}'", literal))?
+            };
+            (&rest[..at], Some((op, literal)))
+        }
+        None => (rest, None),
+    };
+    let field = field
+        .trim()
+        .split('.')
+        .filter(|s| !s.is_empty())
+        .map(str::to_string)
+        .collect();
+    Ok(Filter { field, test })
+}
+
+fn resolve_index(i: i64, len: usize) -> Option<usize> {
+    let i = if i < 0 { len as i64 + i } else { i };
+    (0..len as i64).contains(&i).then_some(i as usize)
+}
+
+fn apply<'a>(step: &Step, value: &'a Value, out: &mut Vec<&'a Value>) {
+    match step {
+        Step::Key(key) => out.extend(value.get(key)),
+        Step::Index(i) => {
+            if let Some(items) = value.as_array() {
+                out.extend(resolve_index(*i, items.len()).map(|i| &items[i]));
+            }
+        }
+        Step::Slice(start, end) => {
+            if let Some(items) = value.as_array() {
+                let len = items.len() as i64;
+                let clamp = |b: i64| (if b < 0 { len + b } else { b }).clamp(0, len) as usize;
+                let start = start.map_or(0, clamp);
+                let end = end.map_or(items.len(), clamp);
+                if start < end {
+                    out.extend(&items[start..end]);
+                }
+            }
+        }
+        Step::Wildcard => children(value, out),
+        Step::Descendant(name) => {
+            let mut stack = vec![value];
+            while let Some(node) = stack.pop() {
+                match name {
+                    Some(name) => out.extend(node.get(name.as_str())),
+                    None => children(node, out),
+                }
+                let mut kids = Vec::new();
+                children(node, &mut kids);
+                stack.extend(kids.into_iter().rev());
+            }
+        }
+        Step::Filter(filter) => {
+            let mut candidates = Vec::new();
+            children(value, &mut candidates);
+            out.extend(candidates.into_iter().filter(|c| filter.matches(c)));
+        }
+    }
+}
+
+fn children<'a>(value: &'a Value, out: &mut Vec<&'a Value>) {
+    match value {
+        Value::Object(map) => out.extend(map.values()),
+        Value::Array(items) => out.extend(items),
+        _ => {}
+    }
+}
+
+impl Filter {
+    fn matches(&self, candidate: &Value) -> bool {
+        let mut value = candidate;
+        for key in &self.field {
+            match value.get(key) {
+                Some(v) => value = v,
+                None => return false,
+            }
+        }
+        let Some((op, literal)) = &self.test else {
+            return true;
+        };
+        let ordering = compare(value, literal);
+        match op {
+            Op::Eq => ordering == Some(Ordering::Equal),
+            Op::Ne => ordering != Some(Ordering::Equal),
+            Op::Lt => ordering == Some(Ordering::Less),
+            Op::Le => matches!(ordering, Some(Ordering::Less | Ordering::Equal)),
+            Op::Gt => ordering == Some(Ordering::Greater),
+            Op::Ge => matches!(ordering, Some(Ordering::Greater | Ordering::Equal)),
+        }
+    }
+}
+
+/// Numbers compare by value and strings lexically; other values only by equality.
+fn compare(a: &Value, b: &Value) -> Option<Ordering> {
+    match (a, b) {
+        (Value::Number(x), Value::Number(y)) => x.as_f64()?.partial_cmp(&y.as_f64()?),
+        (Value::String(x), Value::String(y)) => Some(x.cmp(y)),
+        _ => (a == b).then_some(Ordering::Equal),
+    }
+}
@@ -0,0 +1,127 @@
+//! Truncated rendering of large documents for REPL/notebook inspection.
+//!
+//! Works on the raw text line by line, so previewing a huge file costs one
+//! scan and never materializes the decoded value.
+
+use crate::scan::split_cells;
+
+struct Frame {
+    level: usize,
+    table: bool,
+    shown: usize,
+    hidden: usize,
+    hiding: bool,
+}
+
+fn is_header(trimmed: &str) -> bool {
+    let Some(open) = trimmed.find('[') else {
+        return false;
+    };
+    trimmed.ends_with(':') && trimmed[open..].contains(']')
+}
+
+/// Cut an inline array such as `tags[40]: a,b,c,...` after `rows` values.
+fn cut_inline(line: &str, rows: usize) -> Option<String> {
+    let open = line.find('[')?;
+    let close = open + line[open..].find("]: ")?;
+    let marker = &line[open + 1..close];
+    marker.trim_end_matches(['|', '\t']).parse::<usize>().ok()?;
+    let delimiter = match marker.chars().last()? {
+        c @ ('|' | '\t') => c,
+        _ => ',',
+    };
+    let values = split_cells(&line[close + 3..], delimiter);
+    (values.len() > rows).then(|| {
+        let shown = values[..rows].join(&delimiter.to_string());
+        format!("{}{} ... ({} more values)", &line[..close + 3], shown, values.len() - rows)
+    })
+}
+
+fn pad(level: usize, unit: usize) -> String {
+    " ".repeat(level * unit)
+}
+
+/// Render `source` keeping at most `rows` rows/items/values per array and
+/// eliding lines nested `depth` or more indentation levels deep.
+pub(crate) fn preview(source: &str, rows: usize, depth: usize) -> String {
+    let unit = source
+        .lines()
+        .map(|l| l.len() - l.trim_start_matches(' ').len())
+        .filter(|&n| n > 0)
+        .min()
+        .unwrap_or(2);
+
+    let mut out = String::new();
+    let mut stack: Vec<Frame> = Vec::new();
+    let mut elided = 0usize;
+
+    let flush_elided = |out: &mut String, elided: &mut usize| {
+        if *elided > 0 {
+            out.push_str(&format!("{}... ({} nested lines)\n", pad(depth, unit), elided));
+            *elided = 0;
+        }
+    };
+    let close = |out: &mut String, frame: Frame| {
+        if frame.hidden > 0 {
+            let noun = if frame.table { "rows" } else { "items" };
+            out.push_str(&format!("{}... ({} more {})\n", pad(frame.level, unit), frame.hidden, noun));
+        }
+    };
+
+    for line in source.lines() {
+        let trimmed = line.trim_start_matches(' ');
+        if trimmed.trim().is_empty() {
+            continue;
+        }
+        let level = (line.len() - trimmed.len()) / unit;
+
+        while stack.last().is_some_and(|f| level < f.level) {
+            flush_elided(&mut out, &mut elided);
+            if let Some(frame) = stack.pop() {
+                close(&mut out, frame);
+            }
+        }
+
+        if let Some(frame) = stack.last_mut() {
+            if level == frame.level && (frame.table || trimmed.starts_with('-')) {
+                frame.hiding = frame.shown >= rows;
+                if frame.hiding {
+                    frame.hidden += 1;
+                } else {
+                    frame.shown += 1;
+                }
+            }
+            if frame.hiding {
+                continue;
+            }
+        }
+
+        if level >= depth {
+            elided += 1;
+            continue;
+        }
+        flush_elided(&mut out, &mut elided);
+
+        match cut_inline(line, rows) {
+            Some(cut) => out.push_str(&cut),
+            None => out.push_str(line),
+        }
+        out.push('\n');
+        let body = trimmed.trim_start_matches("- ");
+        if is_header(body) {
+            stack.push(Frame {
+                level: level + 1,
+                table: body.contains('{'),
+                shown: 0,
+                hidden: 0,
+                hiding: false,
+            });
+        }
+    }
+
+    flush_elided(&mut out, &mut elided);
+    while let Some(frame) = stack.pop() {
+        close(&mut out, frame);
+    }
+    out
+}
@@ -0,0 +1,41 @@
+//! The thread pool behind the parallel batch APIs.
+//!
+//! Sized by `set_num_threads()`, or by the `TOON_NUM_THREADS` environment
+//! variable when the pool is first used. Zero, unset or unparsable values
+//! mean one thread per CPU. A dedicated pool keeps the global rayon pool of
+//! the host application untouched.
+
+use once_cell::sync::Lazy;
+use rayon::{ThreadPool, ThreadPoolBuilder};
+use std::sync::{Arc, RwLock};
+
+pub(crate) const ENV_VAR: &str = "TOON_NUM_THREADS";
+
+static POOL: Lazy<RwLock<Arc<ThreadPool>>> = Lazy::new(|| {
+    let n = std::env::var(ENV_VAR).ok().and_then(|v| v.trim().parse().ok()).unwrap_or(0);
+    RwLock::new(Arc::new(build(n).expect("failed to start the toon thread pool")))
+});
+
+fn build(num_threads: usize) -> Result<ThreadPool, String> {
+    ThreadPoolBuilder::new()
+        .num_threads(num_threads)
+        .thread_name(|i| format!("toon-{}", i))
+        .build()
+        .map_err(|e| e.to_string())
+}
+
+/// The current pool. Work already running keeps its pool after a resize.
+pub(crate) fn get() -> Arc<ThreadPool> {
+    POOL.read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// Replace the pool with one of `num_threads` threads (0 = one per CPU).
+pub(crate) fn resize(num_threads: usize) -> Result<(), String> {
+    let pool = Arc::new(build(num_threads)?);
+    *POOL.write().unwrap_or_else(|e| e.into_inner()) = pool;
+    Ok(())
+}
+
+pub(crate) fn num_threads() -> usize {
+    get().current_num_threads()
+}
@@ -0,0 +1,134 @@
+//! Numeric literals that lose precision when decoded.
+//!
+//! Integers outside the 64-bit range become floats, and decimals with more
+//! significant digits than a double holds are rounded. Both happen silently in
+//! the parser, so the source is scanned for such literals to report them.
+
+use std::borrow::Cow;
+
+use crate::newline;
+
+/// A literal that does not survive decoding unchanged.
+pub(crate) struct Lossy {
+    pub(crate) line: usize,
+    pub(crate) literal: String,
+    /// What the literal decodes to
+    pub(crate) value: String,
+}
+
+impl Lossy {
+    pub(crate) fn message(&self) -> String {
+        format!(
+            "Line {}: {} cannot be represented exactly; decoded as {}",
+            self.line, self.literal, self.value
+        )
+    }
+}
+
+/// Digits a double always reproduces exactly (`DBL_DIG`).
+const EXACT_DIGITS: usize = 15;
+
+/// Every lossy numeric literal in `source`, in order.
+pub(crate) fn scan(source: &str) -> Vec<Lossy> {
+    // Fast path: nothing long enough to lose precision
+    if !has_long_number(source) {
+        return Vec::new();
+    }
+    // Lone `\r` line endings count as lines, as they do for the parser
+    let source = newline::normalize(source, newline::Newlines::Any).unwrap_or(Cow::Borrowed(source));
+    let mut found = Vec::new();
+    for (line_no, line) in source.lines().enumerate() {
+        let content = line.trim_start();
+        let content = content.strip_prefix("- ").unwrap_or(content);
+        for (cell, is_key) in cells(content) {
+            if let Some(value) = (!is_key).then(|| check(cell)).flatten() {
+                found.push(Lossy { line: line_no + 1, literal: cell.to_string(), value });
+            }
+        }
+    }
+    found
+}
+
+/// The cells of a line split on unquoted delimiters and colons, trimmed, each
+/// with whether a colon ends it (a key). A number inside a longer unquoted
+/// string is part of its cell, so it is never mistaken for a literal.
+fn cells(content: &str) -> Vec<(&str, bool)> {
+    let mut cells = Vec::new();
+    let mut in_quotes = false;
+    let mut escaped = false;
+    let mut from = 0;
+    for (i, c) in content.char_indices() {
+        if in_quotes {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_quotes = false,
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            '"' => in_quotes = true,
+            ',' | '|' | '\t' | ':' => {
+                cells.push((content[from..i].trim_matches(' '), c == ':'));
+                from = i + 1;
+            }
+            _ => {}
+        }
+    }
+    cells.push((content[from..].trim_matches(' '), false));
+    cells
+}
+
+fn has_long_number(source: &str) -> bool {
+    let mut run = 0;
+    source.bytes().any(|b| {
+        run = if b.is_ascii_digit() || b == b'.' { run + 1 } else { 0 };
+        run > EXACT_DIGITS
+    })
+}
+
+/// The decoded value of `token` when it is a number literal that decodes
+/// lossily.
+fn check(token: &str) -> Option<String> {
+    let unsigned = token.strip_prefix('-').unwrap_or(token);
+    let (mantissa, exponent) = match unsigned.find(['e', 'E']) {
+        Some(at) => (&unsigned[..at], Some(&unsigned[at + 1..])),
+        None => (unsigned, None),
+    };
+    let (int_part, frac_part) = match mantissa.split_once('.') {
+        Some((int_part, frac_part)) => (int_part, Some(frac_part)),
+        None => (mantissa, None),
+    };
+    let digits = |s: &str| !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit());
+    let exponent_ok = exponent.is_none_or(|e| digits(e.strip_prefix(['-', '+']).unwrap_or(e)));
+    if !digits(int_part) || !frac_part.is_none_or(digits) || !exponent_ok {
+        return None;
+    }
+    if frac_part.is_none() && exponent.is_none() {
+        if token.parse::<i64>().is_ok() || token.parse::<u64>().is_ok() {
+            return None;
+        }
+        return token.parse::<f64>().ok().map(|f| f.to_string());
+    }
+    let significant = significant_digits(mantissa);
+    if significant.len() <= EXACT_DIGITS {
+        return None;
+    }
+    let value = token.parse::<f64>().ok()?;
+    if !value.is_finite() {
+        return None;
+    }
+    (significant_digits(&value.abs().to_string()) != significant).then(|| value.to_string())
+}
+
+/// The digits of a decimal without sign, point, leading or trailing zeros.
+fn significant_digits(decimal: &str) -> String {
+    decimal
+        .chars()
+        .filter(char::is_ascii_digit)
+        .collect::<String>()
+        .trim_start_matches('0')
+        .trim_end_matches('0')
+        .to_string()
+}
@@ -0,0 +1,77 @@
+//! Column projection: `decode(..., columns=["id", "score"])`.
+//!
+//! A text prefilter run before parsing: every table header keeps only the
+//! requested fields and every row only the matching cells, split at
+//! delimiters outside quotes, so the dropped columns are never converted into
+//! values. Line numbers are unchanged, so errors still point at the document.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+use crate::diagnostics::{self, field_name};
+use crate::scan::{self, split_cells};
+
+/// `source` with every table reduced to the fields named in `columns`.
+///
+/// A table with none of them decodes as a list of empty dicts, one per row.
+/// A table with a row whose cell count differs from the header is left whole
+/// for the parser to report.
+pub(crate) fn apply<'a>(source: &'a str, columns: &[String]) -> Cow<'a, str> {
+    let lines: Vec<&str> = source.lines().collect();
+    let mut edits: HashMap<usize, String> = HashMap::new();
+    for table in diagnostics::tables(source) {
+        let header = lines[table.header - 1];
+        let fields = split_cells(&header[table.fields.clone()], table.delimiter);
+        let keep: Vec<usize> = (0..fields.len())
+            .filter(|&i| columns.iter().any(|column| column == field_name(fields[i])))
+            .collect();
+        if keep.len() == fields.len() {
+            continue;
+        }
+        let rows: Vec<(usize, &str, Vec<&str>)> = table
+            .rows
+            .iter()
+            .map(|&row| {
+                let line = lines[row - 1];
+                let indent = &line[..scan::indent(line)];
+                // Only spaces: with a tab delimiter, a trailing tab is an empty last cell
+                (row, indent, split_cells(line.trim_start_matches(' ').trim_end_matches([' ', '\r']), table.delimiter))
+            })
+            .collect();
+        if rows.iter().any(|(_, _, cells)| cells.len() != fields.len()) {
+            continue;
+        }
+        let delimiter = table.delimiter.encode_utf8(&mut [0; 4]).to_string();
+        let pick = |cells: &[&str]| keep.iter().map(|&i| cells[i].trim_matches(' ')).collect::<Vec<_>>().join(&delimiter);
+        if keep.is_empty() {
+            // `users[2]{id,name}:` becomes `users[2]:` over bare `-` items
+            edits.insert(
+                table.header,
+                format!("{}{}", &header[..table.fields.start - 1], &header[table.fields.end + 1..]),
+            );
+        } else {
+            edits.insert(
+                table.header,
+                format!("{}{}{}", &header[..table.fields.start], pick(&fields), &header[table.fields.end..]),
+            );
+        }
+        for (row, indent, cells) in rows {
+            let projected = if keep.is_empty() { "-".to_string() } else { pick(&cells) };
+            edits.insert(row, format!("{}{}", indent, projected));
+        }
+    }
+    if edits.is_empty() {
+        return Cow::Borrowed(source);
+    }
+    let mut out = String::with_capacity(source.len());
+    for (idx, line) in source.split_inclusive('\n').enumerate() {
+        match edits.remove(&(idx + 1)) {
+            Some(replacement) => {
+                out.push_str(&replacement);
+                out.push_str(&line[line.trim_end_matches(['\r', '\n']).len()..]);
+            }
+            None => out.push_str(line),
+        }
+    }
+    Cow::Owned(out)
+}
@@ -0,0 +1,104 @@
+//! Arrays whose elements do not share one shape: dicts with different key
+//! sets, or dicts mixed with other values.
+//!
+//! By default the encoder writes such arrays as block lists instead of
+//! tables. `heterogeneous="union"` pads dict rows with nulls so they form a
+//! table, `heterogeneous="drop"` keeps only the columns every row has, and
+//! `heterogeneous="error"` rejects them.
+
+use serde_json::Value;
+
+use crate::{path, shape};
+
+#[derive(Clone, Copy, PartialEq, Eq, Default)]
+pub(crate) enum Policy {
+    /// Leave the array to the encoder, which falls back to a block list
+    #[default]
+    Expand,
+    /// One column per key seen in any row, null where a row lacks it
+    Union,
+    /// Only the keys every row has; the other fields are left out
+    Drop,
+    /// Reject the array
+    Error,
+}
+
+impl Policy {
+    pub(crate) fn parse(name: &str) -> Option<Self> {
+        match name {
+            "expand" => Some(Policy::Expand),
+            "union" => Some(Policy::Union),
+            "drop" => Some(Policy::Drop),
+            "error" => Some(Policy::Error),
+            _ => None,
+        }
+    }
+}
+
+/// Apply `policy` to every array in `value`.
+pub(crate) fn apply(value: &mut Value, policy: Policy) -> Result<(), String> {
+    if policy == Policy::Expand {
+        return Ok(());
+    }
+    visit(value, policy, "$")
+}
+
+fn visit(value: &mut Value, policy: Policy, path: &str) -> Result<(), String> {
+    match value {
+        Value::Object(map) => {
+            for (k, v) in map.iter_mut() {
+                let mut child = path.to_string();
+                path::push_key(&mut child, k);
+                visit(v, policy, &child)?;
+            }
+        }
+        Value::Array(items) => {
+            for (i, item) in items.iter_mut().enumerate() {
+                visit(item, policy, &format!("{}[{}]", path, i))?;
+            }
+            if !items.iter().any(Value::is_object) {
+                return Ok(());
+            }
+            if let Some(other) = items.iter().find(|item| !item.is_object()) {
+                return match policy {
+                    Policy::Error => Err(format!("{}: array mixes dict and {} elements", path, shape::kind(other))),
+                    _ => Ok(()),
+                };
+            }
+            let mut columns: Vec<String> = Vec::new();
+            for row in items.iter().filter_map(Value::as_object) {
+                for k in row.keys() {
+                    if !columns.contains(k) {
+                        columns.push(k.clone());
+                    }
+                }
+            }
+            if policy == Policy::Drop {
+                let shared: Vec<String> = columns
+                    .into_iter()
+                    .filter(|column| items.iter().all(|row| row.get(column).is_some()))
+                    .collect();
+                for map in items.iter_mut().filter_map(Value::as_object_mut) {
+                    map.retain(|k, _| shared.contains(k));
+                }
+                return Ok(());
+            }
+            for (i, row) in items.iter_mut().enumerate() {
+                let Value::Object(map) = row else { continue };
+                for column in &columns {
+                    if map.contains_key(column) {
+                        continue;
+                    }
+                    if policy == Policy::Error {
+                        return Err(format!(
+                            "{}: rows have different keys; row {} lacks '{}'", path, i, column
+                        ));
+                    }
+                    map.insert(column.clone(), Value::Null);
+                }
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
@@ -0,0 +1,94 @@
+//! Scrubbing of sensitive fields during encoding, and the field patterns
+//! other per-field encode options share.
+//!
+//! Patterns are dotted key paths where `*` matches within one key and `**`
+//! matches any number of keys. Array indices are not part of the path, so
+//! `users.ssn` covers the `ssn` column of every row. A pattern without a dot
+//! matches that key at any depth.
+
+use serde_json::Value;
+
+pub(crate) struct Pattern {
+    segments: Vec<String>,
+    anywhere: bool,
+}
+
+impl Pattern {
+    pub(crate) fn parse(pattern: &str) -> Self {
+        let pattern = pattern.strip_prefix("$.").unwrap_or(pattern);
+        let segments: Vec<String> = pattern.split('.').map(str::to_string).collect();
+        let anywhere = segments.len() == 1;
+        Pattern { segments, anywhere }
+    }
+
+    fn matches(&self, path: &[&str]) -> bool {
+        if self.anywhere {
+            return path.last().is_some_and(|key| glob(&self.segments[0], key));
+        }
+        matches_segments(&self.segments, path)
+    }
+}
+
+fn matches_segments(segments: &[String], path: &[&str]) -> bool {
+    match segments.split_first() {
+        None => path.is_empty(),
+        Some((first, rest)) if first == "**" => {
+            (0..=path.len()).any(|skip| matches_segments(rest, &path[skip..]))
+        }
+        Some((first, rest)) => match path.split_first() {
+            Some((key, path)) => glob(first, key) && matches_segments(rest, path),
+            None => false,
+        },
+    }
+}
+
+/// Match `text` against a pattern where `*` stands for any run of characters.
+fn glob(pattern: &str, text: &str) -> bool {
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or("");
+    let Some(mut rest) = text.strip_prefix(first) else {
+        return false;
+    };
+    let mut parts: Vec<&str> = parts.collect();
+    let Some(last) = parts.pop() else {
+        return rest.is_empty();
+    };
+    for part in parts {
+        match rest.find(part) {
+            Some(at) => rest = &rest[at + part.len()..],
+            None => return false,
+        }
+    }
+    rest.len() >= last.len() && rest.ends_with(last)
+}
+
+/// Replace the value of every field matching one of `patterns` with `replacement`.
+pub(crate) fn redact(value: &mut Value, patterns: &[Pattern], replacement: &str) {
+    visit(value, patterns, |v| *v = Value::String(replacement.to_string()));
+}
+
+/// Call `f` on the value of every field matching one of `patterns`, without
+/// descending into the values it is called on.
+pub(crate) fn visit(value: &mut Value, patterns: &[Pattern], mut f: impl FnMut(&mut Value)) {
+    fn walk(value: &mut Value, path: &mut Vec<String>, patterns: &[Pattern], f: &mut impl FnMut(&mut Value)) {
+        match value {
+            Value::Object(map) => {
+                for (k, v) in map.iter_mut() {
+                    path.push(k.clone());
+                    let keys: Vec<&str> = path.iter().map(String::as_str).collect();
+                    if patterns.iter().any(|p| p.matches(&keys)) {
+                        f(v);
+                    } else {
+                        walk(v, path, patterns, f);
+                    }
+                    path.pop();
+                }
+            }
+            Value::Array(items) => items.iter_mut().for_each(|v| walk(v, path, patterns, f)),
+            _ => {}
+        }
+    }
+    if !patterns.is_empty() {
+        walk(value, &mut Vec::new(), patterns, &mut f);
+    }
+}
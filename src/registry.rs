@@ -0,0 +1,124 @@
+//! Custom scalar literals registered with `register_scalar`.
+//!
+//! Each entry pairs a matcher (the `fullmatch` of a compiled pattern, or a
+//! user predicate) with a constructor. `decode` takes a [`snapshot`] of the
+//! registry, and [`Snapshot::mark`] replaces every unquoted value a matcher
+//! accepts with `<token>index<MARK>text`, which the parser reads as a plain
+//! string. Building Python objects then calls the constructor of each marked
+//! string with the original text instead of returning the string.
+
+use pyo3::exceptions::{PyTypeError, PyValueError};
+use pyo3::prelude::*;
+use pyo3::types::PyString;
+use std::sync::Mutex;
+
+use crate::scan::Token;
+
+/// Opens and closes the [`Token`] of a marked value, and ends its registry
+/// index.
+pub(crate) const MARK: char = '\u{FDD3}';
+
+struct Entry {
+    matcher: Py<PyAny>,
+    constructor: Py<PyAny>,
+}
+
+static SCALARS: Mutex<Vec<Entry>> = Mutex::new(Vec::new());
+
+/// A registry entry for the duration of one decode.
+struct Scalar<'py> {
+    matcher: Bound<'py, PyAny>,
+    constructor: Bound<'py, PyAny>,
+}
+
+/// The registry as of one decode, and the token marking the values its
+/// matchers accepted.
+pub(crate) struct Snapshot<'py> {
+    scalars: Vec<Scalar<'py>>,
+    token: Token,
+}
+
+impl Default for Snapshot<'_> {
+    fn default() -> Self {
+        Snapshot { scalars: Vec::new(), token: Token::new(MARK) }
+    }
+}
+
+/// Register `constructor` for unquoted values matching `pattern_or_fn`: a
+/// regular expression the whole value must match, or a predicate.
+pub(crate) fn register(py: Python<'_>, pattern_or_fn: &Bound<'_, PyAny>, constructor: &Bound<'_, PyAny>) -> PyResult<()> {
+    let matcher = if pattern_or_fn.is_instance_of::<PyString>() {
+        py.import("re")?.call_method1("compile", (pattern_or_fn,))?.getattr("fullmatch")?
+    } else if pattern_or_fn.hasattr("fullmatch")? {
+        // Already compiled
+        pattern_or_fn.getattr("fullmatch")?
+    } else if pattern_or_fn.is_callable() {
+        pattern_or_fn.clone()
+    } else {
+        return Err(PyTypeError::new_err("pattern_or_fn must be a str, a compiled pattern or a callable"));
+    };
+    if !constructor.is_callable() {
+        return Err(PyTypeError::new_err("constructor must be callable"));
+    }
+    let entry = Entry { matcher: matcher.unbind(), constructor: constructor.clone().unbind() };
+    SCALARS.lock().unwrap_or_else(|e| e.into_inner()).push(entry);
+    Ok(())
+}
+
+/// Remove every registered scalar.
+pub(crate) fn clear() {
+    SCALARS.lock().unwrap_or_else(|e| e.into_inner()).clear();
+}
+
+/// The registered scalars, in registration order.
+pub(crate) fn snapshot(py: Python<'_>) -> Snapshot<'_> {
+    let scalars = SCALARS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .iter()
+        .map(|entry| Scalar { matcher: entry.matcher.bind(py).clone(), constructor: entry.constructor.bind(py).clone() })
+        .collect();
+    Snapshot { scalars, ..Default::default() }
+}
+
+/// Values the parser reads as something other than a string.
+fn is_literal(cell: &str) -> bool {
+    matches!(cell, "true" | "false" | "null") || cell.parse::<f64>().is_ok_and(f64::is_finite)
+}
+
+impl<'py> Snapshot<'py> {
+    pub(crate) fn is_empty(&self) -> bool {
+        self.scalars.is_empty()
+    }
+
+    /// `cell` marked for the first scalar whose matcher accepts it. Only
+    /// values that would otherwise decode as strings are offered.
+    pub(crate) fn mark(&self, cell: &str) -> PyResult<Option<String>> {
+        if is_literal(cell) {
+            return Ok(None);
+        }
+        for (index, scalar) in self.scalars.iter().enumerate() {
+            if scalar.matcher.call1((cell,))?.is_truthy()? {
+                return Ok(Some(format!("{}{}{}{}", self.token, index, MARK, cell)));
+            }
+        }
+        Ok(None)
+    }
+
+    pub(crate) fn is_marked(&self, s: &str) -> bool {
+        !self.scalars.is_empty() && self.token.strip(s).is_some()
+    }
+
+    /// The object the constructor of a marked string builds from its text.
+    pub(crate) fn construct(&self, s: &str) -> PyResult<Bound<'py, PyAny>> {
+        let parsed = self
+            .token
+            .strip(s)
+            .and_then(|rest| rest.split_once(MARK))
+            .and_then(|(index, text)| Some((self.scalars.get(index.parse::<usize>().ok()?)?, text)));
+        match parsed {
+            Some((scalar, text)) => scalar.constructor.call1((text,)),
+            None => Err(PyValueError::new_err(format!("malformed scalar marker in {:?}", s))),
+        }
+    }
+}
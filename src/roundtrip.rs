@@ -0,0 +1,108 @@
+//! Fidelity checks for `roundtrip_check()`: compare Python data with what
+//! comes back after an encode/decode round trip and report every path where
+//! the two differ.
+
+use pyo3::prelude::*;
+use pyo3::types::{PyDict, PyFloat, PyList, PyString, PyTuple};
+use std::collections::HashSet;
+use crate::path;
+
+/// One place where the decoded data differs from the original.
+pub(crate) struct Issue<'py> {
+    pub(crate) path: String,
+    /// 'type', 'key', 'float', 'value', 'length', 'missing' or 'extra'
+    pub(crate) kind: &'static str,
+    pub(crate) original: Option<Bound<'py, PyAny>>,
+    pub(crate) decoded: Option<Bound<'py, PyAny>>,
+}
+
+fn issue<'py>(
+    issues: &mut Vec<Issue<'py>>,
+    path: &str,
+    kind: &'static str,
+    original: Option<&Bound<'py, PyAny>>,
+    decoded: Option<&Bound<'py, PyAny>>,
+) {
+    issues.push(Issue { path: path.to_string(), kind, original: original.cloned(), decoded: decoded.cloned() });
+}
+
+/// Compare `original` with `decoded`, descending into dicts and sequences.
+pub(crate) fn compare<'py>(
+    original: &Bound<'py, PyAny>,
+    decoded: &Bound<'py, PyAny>,
+    path: &str,
+    issues: &mut Vec<Issue<'py>>,
+) -> PyResult<()> {
+    if let Ok(dict) = original.cast::<PyDict>() {
+        let Ok(out) = decoded.cast::<PyDict>() else {
+            issue(issues, path, "type", Some(original), Some(decoded));
+            return Ok(());
+        };
+        if !original.get_type().is(decoded.get_type()) {
+            issue(issues, path, "type", Some(original), Some(decoded));
+        }
+        let mut matched = HashSet::new();
+        for (key, value) in dict.iter() {
+            // Non-string keys are written with str()
+            let name = match key.cast::<PyString>() {
+                Ok(name) => name.to_cow()?.into_owned(),
+                Err(_) => key.str()?.to_cow()?.into_owned(),
+            };
+            let mut child = path.to_string();
+            path::push_key(&mut child, &name);
+            let Some(found) = out.get_item(&name)? else {
+                issue(issues, &child, "missing", Some(&value), None);
+                continue;
+            };
+            if !key.is_instance_of::<PyString>() {
+                issue(issues, &child, "key", Some(&key), Some(PyString::new(key.py(), &name).as_any()));
+            }
+            matched.insert(name);
+            compare(&value, &found, &child, issues)?;
+        }
+        for (key, value) in out.iter() {
+            let name = key.str()?.to_cow()?.into_owned();
+            if !matched.contains(&name) {
+                let mut child = path.to_string();
+                path::push_key(&mut child, &name);
+                issue(issues, &child, "extra", None, Some(&value));
+            }
+        }
+        return Ok(());
+    }
+
+    let items = if let Ok(list) = original.cast::<PyList>() {
+        Some(list.iter().collect::<Vec<_>>())
+    } else {
+        original.cast::<PyTuple>().ok().map(|tuple| tuple.iter().collect())
+    };
+    if let Some(items) = items {
+        let Ok(out) = decoded.cast::<PyList>() else {
+            issue(issues, path, "type", Some(original), Some(decoded));
+            return Ok(());
+        };
+        if !original.get_type().is(decoded.get_type()) {
+            issue(issues, path, "type", Some(original), Some(decoded));
+        }
+        if items.len() != out.len() {
+            issue(issues, path, "length", Some(original), Some(decoded));
+        }
+        for (i, (item, found)) in items.iter().zip(out.iter()).enumerate() {
+            compare(item, &found, &format!("{}[{}]", path, i), issues)?;
+        }
+        return Ok(());
+    }
+
+    if original.is_exact_instance_of::<PyFloat>() {
+        let same = decoded.is_exact_instance_of::<PyFloat>()
+            && original.extract::<f64>()?.to_bits() == decoded.extract::<f64>()?.to_bits();
+        if !same {
+            issue(issues, path, "float", Some(original), Some(decoded));
+        }
+    } else if !original.get_type().is(decoded.get_type()) {
+        issue(issues, path, "type", Some(original), Some(decoded));
+    } else if !original.eq(decoded)? {
+        issue(issues, path, "value", Some(original), Some(decoded));
+    }
+    Ok(())
+}
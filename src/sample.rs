@@ -0,0 +1,142 @@
+//! Row sampling and elision used to fit documents into a size budget.
+
+use serde_json::Value;
+
+pub(crate) enum Strategy {
+    /// Keep the first rows.
+    Head,
+    /// Keep a seeded random sample, preserving row order.
+    Random(u64),
+    /// Keep rows spread evenly over the table, or proportionally per distinct
+    /// value of a column when one is given.
+    Stratified(Option<String>),
+}
+
+/// One array that lost rows.
+pub(crate) struct Elision {
+    pub(crate) path: String,
+    pub(crate) kept: usize,
+    pub(crate) total: usize,
+}
+
+/// Length of the longest array in `value`, i.e. the row cap that keeps everything.
+pub(crate) fn longest_array(value: &Value) -> usize {
+    match value {
+        Value::Array(items) => items.iter().map(longest_array).max().unwrap_or(0).max(items.len()),
+        Value::Object(map) => map.values().map(longest_array).max().unwrap_or(0),
+        _ => 0,
+    }
+}
+
+/// Copy of `value` with every array cut down to at most `cap` rows.
+pub(crate) fn truncate(value: &Value, cap: usize, strategy: &Strategy, path: &str, notes: &mut Vec<Elision>) -> Value {
+    match value {
+        Value::Object(map) => Value::Object(
+            map.iter()
+                .map(|(k, v)| (k.clone(), truncate(v, cap, strategy, &format!("{}.{}", path, k), notes)))
+                .collect(),
+        ),
+        Value::Array(items) => {
+            let item_path = format!("{}[]", path);
+            let keep = pick(items, cap, strategy);
+            if keep.len() < items.len() {
+                notes.push(Elision { path: path.to_string(), kept: keep.len(), total: items.len() });
+            }
+            Value::Array(
+                keep.into_iter()
+                    .map(|i| truncate(&items[i], cap, strategy, &item_path, notes))
+                    .collect(),
+            )
+        }
+        _ => value.clone(),
+    }
+}
+
+/// Replace containers nested more than `max_depth` levels below the root with
+/// placeholders such as `{...3 keys}` or `[...5 items]`.
+pub(crate) fn limit_depth(value: &mut Value, max_depth: usize) {
+    fn walk(value: &mut Value, depth: usize, max_depth: usize) {
+        if depth > max_depth {
+            let placeholder = match value {
+                Value::Object(map) => format!("{{...{} keys}}", map.len()),
+                Value::Array(items) => format!("[...{} items]", items.len()),
+                _ => return,
+            };
+            *value = Value::String(placeholder);
+            return;
+        }
+        match value {
+            Value::Object(map) => map.values_mut().for_each(|v| walk(v, depth + 1, max_depth)),
+            Value::Array(items) => items.iter_mut().for_each(|v| walk(v, depth + 1, max_depth)),
+            _ => {}
+        }
+    }
+    walk(value, 0, max_depth);
+}
+
+/// Indices of the rows to keep, in ascending order.
+fn pick(rows: &[Value], cap: usize, strategy: &Strategy) -> Vec<usize> {
+    let len = rows.len();
+    if len <= cap {
+        return (0..len).collect();
+    }
+    match strategy {
+        Strategy::Head => (0..cap).collect(),
+        Strategy::Random(seed) => {
+            let mut state = *seed;
+            let mut indices: Vec<usize> = (0..len).collect();
+            for i in 0..cap {
+                let j = i + (splitmix64(&mut state) % (len - i) as u64) as usize;
+                indices.swap(i, j);
+            }
+            indices.truncate(cap);
+            indices.sort_unstable();
+            indices
+        }
+        Strategy::Stratified(None) => (0..cap).map(|i| i * len / cap).collect(),
+        Strategy::Stratified(Some(column)) => stratified(rows, cap, column),
+    }
+}
+
+/// Allocate `cap` rows across the distinct values of `column` in proportion to
+/// their frequency (largest remainder), taking the first rows of each group.
+fn stratified(rows: &[Value], cap: usize, column: &str) -> Vec<usize> {
+    let mut groups: Vec<(String, Vec<usize>)> = Vec::new();
+    for (i, row) in rows.iter().enumerate() {
+        let key = row.get(column).map(|v| v.to_string()).unwrap_or_default();
+        match groups.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, members)) => members.push(i),
+            None => groups.push((key, vec![i])),
+        }
+    }
+
+    let len = rows.len();
+    let mut quotas: Vec<(usize, usize, usize)> = groups
+        .iter()
+        .enumerate()
+        .map(|(g, (_, members))| {
+            let exact = members.len() * cap;
+            (g, exact / len, exact % len)
+        })
+        .collect();
+    let assigned: usize = quotas.iter().map(|q| q.1).sum();
+    quotas.sort_by_key(|q| std::cmp::Reverse(q.2));
+    for q in quotas.iter_mut().take(cap - assigned) {
+        q.1 += 1;
+    }
+
+    let mut keep: Vec<usize> = quotas
+        .iter()
+        .flat_map(|&(g, quota, _)| groups[g].1.iter().copied().take(quota))
+        .collect();
+    keep.sort_unstable();
+    keep
+}
+
+fn splitmix64(state: &mut u64) -> u64 {
+    *state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    let mut z = *state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
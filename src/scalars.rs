@@ -0,0 +1,382 @@
+//! Scalar literals the parser does not read, rewritten before parsing.
+//!
+//! Human-edited files write numbers as `1_000_000` or `+5`, and configs write
+//! masks and permissions as `0x1F` or `0o755` and timeouts and quotas as `30s`
+//! or `2GiB`, all of which the parser takes for strings. [`rewrite`] visits
+//! every unquoted value in a document (after `key: `, in list items, inline
+//! arrays and table rows) and replaces the ones the enabled [`Recognizers`]
+//! understand. Keys, headers and quoted strings are
+//! left alone, so `"1_000"` stays a string.
+//!
+//! Encoding goes the other way for `encode(..., hex=[...])`: [`hex`] turns
+//! integers into strings prefixed with a [`Token`], which the encoder writes
+//! unquoted, and [`finish_hex`] drops the tokens from the encoded text.
+
+use serde_json::Value;
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::ops::Range;
+
+use crate::scan::{self, Token};
+use crate::diagnostics;
+
+/// Rewrites a cell into the literal it stands for, or None to leave it alone.
+type Recognizer = fn(&str) -> Option<String>;
+
+/// Recognizers enabled by name through `decode(..., recognize=[...])`.
+pub(crate) const NAMES: [(&str, Recognizer); 2] = [("duration", duration), ("byte_size", byte_size)];
+
+/// Which literals `decode` reads beyond the parser's own.
+#[derive(Clone, Default)]
+pub(crate) struct Recognizers {
+    pub(crate) lenient_numbers: bool,
+    pub(crate) radix_integers: bool,
+    /// From [`NAMES`], in the order given
+    pub(crate) named: Vec<Recognizer>,
+}
+
+impl Recognizers {
+    /// Enable the recognizer listed in [`NAMES`] as `name`; false if there is
+    /// none.
+    pub(crate) fn enable(&mut self, name: &str) -> bool {
+        match NAMES.iter().find(|(known, _)| *known == name) {
+            Some(&(_, recognizer)) => {
+                self.named.push(recognizer);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub(crate) fn any(&self) -> bool {
+        self.lenient_numbers || self.radix_integers || !self.named.is_empty()
+    }
+
+    /// The literal the parser reads for `cell`, if an enabled recognizer
+    /// understands it.
+    pub(crate) fn recognize(&self, cell: &str) -> Option<String> {
+        let lenient = self.lenient_numbers.then(|| lenient_number(cell)).flatten();
+        lenient
+            .or_else(|| self.radix_integers.then(|| radix_integer(cell)).flatten())
+            .or_else(|| self.named.iter().find_map(|recognizer| recognizer(cell)))
+    }
+}
+
+/// Splits nothing: the value after `key: ` or `- ` is a single cell.
+const WHOLE: char = '\0';
+
+/// The unquoted values of a line outside tables, and the delimiter between
+/// them.
+fn values(content: &str) -> Option<(char, &str)> {
+    let (item, entry) = match content.strip_prefix("- ") {
+        Some(rest) => (true, rest),
+        None => (false, content),
+    };
+    if let Some((delimiter, inline)) = diagnostics::array_header(entry) {
+        return (!inline.is_empty()).then_some((delimiter, inline));
+    }
+    let key_end = if entry.starts_with('"') {
+        scan::closing_quote(entry)
+    } else {
+        entry.find(':')
+    };
+    let value = key_end
+        .and_then(|end| entry[end..].strip_prefix(':'))
+        .filter(|value| value.is_empty() || value.starts_with(' '));
+    match value {
+        Some(value) => (!value.trim().is_empty()).then_some((WHOLE, value)),
+        None => item.then_some((WHOLE, entry)),
+    }
+}
+
+/// `source` with every unquoted value `recognize` has a replacement for
+/// swapped for it, or borrowed when nothing was replaced.
+pub(crate) fn rewrite<'a>(source: &'a str, mut recognize: impl FnMut(&str) -> Option<String>) -> Cow<'a, str> {
+    let rows: HashMap<usize, char> = diagnostics::table_rows(source).into_iter().collect();
+    let mut edits: Vec<(Range<usize>, String)> = Vec::new();
+    for (idx, line) in source.split_inclusive('\n').enumerate() {
+        let body = line.trim_end_matches(['\n', '\r']);
+        let (delimiter, cells) = match rows.get(&(idx + 1)) {
+            Some(&delimiter) => (delimiter, body),
+            None => match values(body.trim()) {
+                Some(found) => found,
+                None => continue,
+            },
+        };
+        for cell in scan::split_cells(cells, delimiter) {
+            let cell = cell.trim();
+            if cell.is_empty() || cell.starts_with('"') {
+                continue;
+            }
+            if let Some(replacement) = recognize(cell) {
+                // Every cell is a slice of `source`
+                let at = cell.as_ptr() as usize - source.as_ptr() as usize;
+                edits.push((at..at + cell.len(), replacement));
+            }
+        }
+    }
+    if edits.is_empty() {
+        return Cow::Borrowed(source);
+    }
+    let mut out = String::with_capacity(source.len());
+    let mut copied = 0;
+    for (range, replacement) in edits {
+        out.push_str(&source[copied..range.start]);
+        out.push_str(&replacement);
+        copied = range.end;
+    }
+    out.push_str(&source[copied..]);
+    Cow::Owned(out)
+}
+
+/// Digits, optionally grouped by single underscores between them.
+fn grouped_digits(s: &str) -> bool {
+    !s.is_empty()
+        && !s.starts_with('_')
+        && !s.ends_with('_')
+        && !s.contains("__")
+        && s.bytes().all(|b| b.is_ascii_digit() || b == b'_')
+}
+
+/// `1_000_000`, `+5` or `+1_000.5e3` as the plain literal the parser reads,
+/// for `decode(..., lenient_numbers=True)`. Only ASCII digits count, and `.`
+/// is the only decimal separator, so the result never depends on locale.
+pub(crate) fn lenient_number(cell: &str) -> Option<String> {
+    let (sign, unsigned) = match cell.as_bytes().first()? {
+        b'+' => ("", &cell[1..]),
+        b'-' => ("-", &cell[1..]),
+        _ => ("", cell),
+    };
+    if !cell.starts_with('+') && !cell.contains('_') {
+        return None;
+    }
+    let (mantissa, exponent) = match unsigned.find(['e', 'E']) {
+        Some(at) => (&unsigned[..at], Some(&unsigned[at + 1..])),
+        None => (unsigned, None),
+    };
+    let (int_part, frac_part) = match mantissa.split_once('.') {
+        Some((int_part, frac_part)) => (int_part, Some(frac_part)),
+        None => (mantissa, None),
+    };
+    let exponent_ok = exponent.is_none_or(|e| grouped_digits(e.strip_prefix(['-', '+']).unwrap_or(e)));
+    if !grouped_digits(int_part) || !frac_part.is_none_or(grouped_digits) || !exponent_ok {
+        return None;
+    }
+    Some(format!("{}{}", sign, unsigned.replace('_', "")))
+}
+
+/// `0x1F`, `0b1010` or `0o755` (optionally signed, digits optionally grouped
+/// by underscores) as a decimal literal, for `decode(..., radix_integers=True)`.
+/// Literals past the 64-bit range are left as strings.
+pub(crate) fn radix_integer(cell: &str) -> Option<String> {
+    let (negative, unsigned) = match cell.as_bytes().first()? {
+        b'+' => (false, &cell[1..]),
+        b'-' => (true, &cell[1..]),
+        _ => (false, cell),
+    };
+    let radix = match unsigned.get(..2)? {
+        "0x" | "0X" => 16,
+        "0b" | "0B" => 2,
+        "0o" | "0O" => 8,
+        _ => return None,
+    };
+    let digits = &unsigned[2..];
+    if digits.is_empty() || digits.starts_with('_') || digits.ends_with('_') || digits.contains("__") {
+        return None;
+    }
+    let magnitude = u64::from_str_radix(&digits.replace('_', ""), radix).ok()?;
+    if !negative {
+        return Some(magnitude.to_string());
+    }
+    let value = 0i64.checked_sub_unsigned(magnitude)?;
+    Some(value.to_string())
+}
+
+/// A number followed by a unit, such as `1.5` and `GiB`.
+fn quantity(cell: &str) -> Option<(f64, &str)> {
+    let split = cell.find(|c: char| !(c.is_ascii_digit() || c == '.'))?;
+    let (number, unit) = cell.split_at(split);
+    let valid = !number.is_empty() && !number.starts_with('.') && !number.ends_with('.');
+    valid.then(|| number.parse().ok().map(|n| (n, unit))).flatten()
+}
+
+/// Whole numbers as integers, others as the shortest float literal.
+fn number_literal(value: f64) -> Option<String> {
+    if !value.is_finite() {
+        return None;
+    }
+    if value.fract() == 0.0 && value.abs() < 9.0e15 {
+        return Some((value as i64).to_string());
+    }
+    Some(value.to_string())
+}
+
+/// Nanoseconds in one of each duration unit. Amounts are summed in
+/// nanoseconds and divided once, so `500ns` reads as `5e-07` exactly.
+const DURATION_UNITS: [(&str, f64); 9] = [
+    ("ns", 1.0),
+    ("us", 1e3),
+    ("µs", 1e3),
+    ("ms", 1e6),
+    ("s", 1e9),
+    ("m", 6e10),
+    ("h", 3.6e12),
+    ("d", 8.64e13),
+    ("w", 6.048e14),
+];
+
+/// `30s`, `5m`, `1.5h` or compound `1h30m` as a number of seconds, for the
+/// `duration` recognizer. A leading `-` negates the whole duration.
+pub(crate) fn duration(cell: &str) -> Option<String> {
+    let (sign, mut rest) = match cell.strip_prefix('-') {
+        Some(rest) => (-1.0, rest),
+        None => (1.0, cell),
+    };
+    if rest.is_empty() {
+        return None;
+    }
+    let mut nanos = 0.0;
+    while !rest.is_empty() {
+        let (amount, after) = quantity(rest)?;
+        let unit_len = after.find(|c: char| c.is_ascii_digit()).unwrap_or(after.len());
+        let (_, scale) = DURATION_UNITS.iter().find(|(unit, _)| *unit == &after[..unit_len])?;
+        nanos += amount * scale;
+        rest = &after[unit_len..];
+    }
+    number_literal(sign * nanos / 1e9)
+}
+
+/// Bytes in one of each size unit: decimal `kB`/`MB`/... and binary
+/// `KiB`/`MiB`/...
+const BYTE_UNITS: [(&str, f64); 13] = [
+    ("B", 1.0),
+    ("kB", 1e3),
+    ("KB", 1e3),
+    ("MB", 1e6),
+    ("GB", 1e9),
+    ("TB", 1e12),
+    ("PB", 1e15),
+    ("KiB", 1024.0),
+    ("MiB", 1048576.0),
+    ("GiB", 1073741824.0),
+    ("TiB", 1099511627776.0),
+    ("PiB", 1125899906842624.0),
+    ("EiB", 1152921504606846976.0),
+];
+
+/// `512MB` or `2GiB` as a whole number of bytes, for the `byte_size`
+/// recognizer. Fractions of a byte are rounded.
+pub(crate) fn byte_size(cell: &str) -> Option<String> {
+    let (amount, unit) = quantity(cell)?;
+    let (_, scale) = BYTE_UNITS.iter().find(|(known, _)| *known == unit)?;
+    let bytes = (amount * scale).round();
+    (bytes < u64::MAX as f64).then(|| (bytes as u64).to_string())
+}
+
+/// Opens and closes the [`Token`] prefixing integers that are written in
+/// hex. No quoting rule applies to it.
+pub(crate) const HEX: char = '\u{FDD2}';
+
+/// Integers in `value`, itself or as items of a list, replaced by strings
+/// the encoder writes as `<token>0x1F`. Floats and other values are kept.
+pub(crate) fn hex(value: &mut Value, token: &Token) {
+    match value {
+        Value::Number(n) => {
+            let text = match (n.as_u64(), n.as_i64()) {
+                (Some(u), _) => format!("{}{:#X}", token, u),
+                (None, Some(i)) => format!("{}-{:#X}", token, i.unsigned_abs()),
+                _ => return,
+            };
+            *value = Value::String(text);
+        }
+        Value::Array(items) => items.iter_mut().for_each(|item| hex(item, token)),
+        _ => {}
+    }
+}
+
+/// Encoded `text` with the tokens [`hex`] added removed. String values that
+/// look like radix literals are quoted first, so `decode(...,
+/// radix_integers=True)` reads them back as strings.
+pub(crate) fn finish_hex<'a>(text: &'a str, token: &Token) -> Cow<'a, str> {
+    // Cells written by `hex` start with the token, which `radix_integer` rejects
+    let text = rewrite(text, |cell| radix_integer(cell).map(|_| format!("\"{}\"", cell)));
+    match text {
+        Cow::Borrowed(text) if !text.contains(token.as_str()) => Cow::Borrowed(text),
+        text => Cow::Owned(text.replace(token.as_str(), "")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    /// An independent statement of what `lenient_number` accepts: the
+    /// underscore rule of PEP 515 (each underscore between two digits), on
+    /// top of a float Rust parses whose `.` has digits on both sides.
+    fn reference(cell: &str) -> Option<String> {
+        if !cell.starts_with('+') && !cell.contains('_') {
+            return None;
+        }
+        let bytes = cell.as_bytes();
+        let between_digits = |i: usize| i > 0 && bytes[i - 1].is_ascii_digit() && bytes.get(i + 1).is_some_and(u8::is_ascii_digit);
+        if !(0..bytes.len()).all(|i| !matches!(bytes[i], b'_' | b'.') || between_digits(i)) {
+            return None;
+        }
+        let plain = cell.replace('_', "");
+        let unsigned = plain.strip_prefix(['+', '-']).unwrap_or(&plain);
+        if !unsigned.starts_with(|c: char| c.is_ascii_digit()) || !unsigned.bytes().all(|b| b"0123456789.eE+-".contains(&b)) {
+            return None;
+        }
+        unsigned.parse::<f64>().ok()?;
+        Some(plain.strip_prefix('+').unwrap_or(&plain).to_string())
+    }
+
+    #[test]
+    fn lenient_number_accepts_grouping_and_plus() {
+        assert_eq!(lenient_number("1_000_000").as_deref(), Some("1000000"));
+        assert_eq!(lenient_number("+5").as_deref(), Some("5"));
+        assert_eq!(lenient_number("-1_0").as_deref(), Some("-10"));
+        assert_eq!(lenient_number("+1_000.5e3").as_deref(), Some("1000.5e3"));
+        assert_eq!(lenient_number("1e+1_0").as_deref(), Some("1e+10"));
+    }
+
+    #[test]
+    fn lenient_number_rejects_malformed_grouping() {
+        for cell in ["+", "-", "_1", "1_", "1__0", "1_e5", "1e_5", "1e+_5", "1e-5_", "+-1", "-+1", "++1", "1_.5", "1._5", "+.5", "+1.", "+inf", "+nan", "1_0x"] {
+            assert_eq!(lenient_number(cell), None, "{}", cell);
+        }
+    }
+
+    #[test]
+    fn lenient_number_leaves_plain_numbers_to_the_parser() {
+        for cell in ["1", "-1", "1.5", "1e5"] {
+            assert_eq!(lenient_number(cell), None, "{}", cell);
+        }
+    }
+
+    #[test]
+    fn rewrite_touches_only_unquoted_values() {
+        let source = "a: +5\nb: \"+5\"\nrows[2]{x,y}:\n  1_0,\"2_0\"\n  +3,x\n";
+        assert_eq!(rewrite(source, lenient_number), "a: 5\nb: \"+5\"\nrows[2]{x,y}:\n  10,\"2_0\"\n  3,x\n");
+    }
+
+    #[test]
+    fn rewrite_borrows_when_nothing_changes() {
+        assert!(matches!(rewrite("a: 1\nb: x\n", lenient_number), Cow::Borrowed(_)));
+    }
+
+    proptest! {
+        #[test]
+        fn lenient_number_matches_reference(cell in "[-+_.eE0-9]{1,10}") {
+            prop_assert_eq!(lenient_number(&cell), reference(&cell));
+        }
+
+        #[test]
+        fn lenient_number_keeps_the_value(int in "[0-9](_?[0-9]){0,6}", frac in proptest::option::of("[0-9](_?[0-9]){0,4}"), exp in proptest::option::of("[-+]?[0-9](_?[0-9]){0,2}")) {
+            let cell = format!("+{}{}{}", int, frac.as_ref().map(|f| format!(".{}", f)).unwrap_or_default(), exp.as_ref().map(|e| format!("e{}", e)).unwrap_or_default());
+            let plain = lenient_number(&cell).expect("well-formed");
+            prop_assert_eq!(plain.parse::<f64>().unwrap(), cell.replace('_', "").parse::<f64>().unwrap());
+        }
+    }
+}
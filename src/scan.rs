@@ -0,0 +1,174 @@
+//! Scanning TOON text, shared by the rewrites made around the parser and the
+//! encoder.
+//!
+//! TOON strings never span lines, and inside quotes only `\` escapes, so
+//! [`Quotes`] can tell quoted text from structure one character at a time.
+//! [`closing_quote`] and [`split_cells`] are built on it. A [`Token`] marks
+//! strings that carry values through the encoder or parser, such as hex
+//! integers or repeated keys; it holds a random number, so data cannot
+//! contain one by accident or design.
+
+use std::collections::hash_map::RandomState;
+use std::fmt;
+use std::hash::{BuildHasher, Hasher};
+
+/// Whether a left-to-right scan is inside a quoted string.
+#[derive(Clone, Copy, Default, Debug)]
+pub(crate) struct Quotes {
+    quoted: bool,
+    escaped: bool,
+}
+
+impl Quotes {
+    /// Advance over `c`; true when `c` is structure, that is neither quoted
+    /// nor a quote. A newline always ends a string.
+    pub(crate) fn step(&mut self, c: char) -> bool {
+        match c {
+            _ if self.escaped => {
+                self.escaped = false;
+                false
+            }
+            '\\' if self.quoted => {
+                self.escaped = true;
+                false
+            }
+            '"' => {
+                self.quoted = !self.quoted;
+                false
+            }
+            '\n' => {
+                self.quoted = false;
+                true
+            }
+            _ => !self.quoted,
+        }
+    }
+
+    pub(crate) fn inside(&self) -> bool {
+        self.quoted
+    }
+}
+
+/// Byte offset just past the quote closing the quoted string `s` starts with.
+pub(crate) fn closing_quote(s: &str) -> Option<usize> {
+    let mut quotes = Quotes::default();
+    s.char_indices().find_map(|(i, c)| {
+        quotes.step(c);
+        (i > 0 && c == '"' && !quotes.inside()).then_some(i + 1)
+    })
+}
+
+/// Split a row on `delimiter`, ignoring delimiters inside quoted values.
+pub(crate) fn split_cells(row: &str, delimiter: char) -> Vec<&str> {
+    let mut cells = Vec::new();
+    let mut start = 0;
+    let mut quotes = Quotes::default();
+    for (i, c) in row.char_indices() {
+        if quotes.step(c) && c == delimiter {
+            cells.push(&row[start..i]);
+            start = i + c.len_utf8();
+        }
+    }
+    cells.push(&row[start..]);
+    cells
+}
+
+/// Width of the leading spaces of `line`.
+pub(crate) fn indent(line: &str) -> usize {
+    line.len() - line.trim_start_matches(' ').len()
+}
+
+/// A marker drawn for one encode or decode: `mark`, 16 random hex digits and
+/// `mark` again, with `mark` a Unicode noncharacter.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct Token(String);
+
+impl Token {
+    pub(crate) fn new(mark: char) -> Self {
+        let random = RandomState::new().build_hasher().finish();
+        Token(format!("{}{:016x}{}", mark, random, mark))
+    }
+
+    pub(crate) fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// What follows the token, when `s` starts with it.
+    pub(crate) fn strip<'a>(&self, s: &'a str) -> Option<&'a str> {
+        s.strip_prefix(self.0.as_str())
+    }
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn structure(text: &str) -> String {
+        let mut quotes = Quotes::default();
+        text.chars().filter(|&c| quotes.step(c)).collect()
+    }
+
+    #[test]
+    fn quotes_hide_their_contents() {
+        assert_eq!(structure(r#"a: "b, c", d"#), "a: , d");
+        assert_eq!(structure(r#"a: "say \"hi\", ok",x"#), "a: ,x");
+        assert_eq!(structure(r#""a\\",b"#), ",b");
+    }
+
+    #[test]
+    fn backslashes_outside_quotes_are_structure() {
+        assert_eq!(structure(r#"a\,"b""#), r#"a\,"#);
+    }
+
+    #[test]
+    fn newline_ends_an_unterminated_string() {
+        assert_eq!(structure("a: \"open\nb: 1"), "a: \nb: 1");
+    }
+
+    #[test]
+    fn closing_quote_skips_escapes() {
+        assert_eq!(closing_quote(r#""a": 1"#), Some(3));
+        assert_eq!(closing_quote(r#""a\"b": 1"#), Some(6));
+        assert_eq!(closing_quote(r#""a\\": 1"#), Some(5));
+        assert_eq!(closing_quote(r#""""#), Some(2));
+        assert_eq!(closing_quote(r#""open"#), None);
+        assert_eq!(closing_quote("\"open\n\"x"), None);
+    }
+
+    #[test]
+    fn split_cells_respects_quotes() {
+        assert_eq!(split_cells(r#"1,"a, b",x"#, ','), vec!["1", r#""a, b""#, "x"]);
+        assert_eq!(split_cells(r#"1|"a|b"|"c\"|d""#, '|'), vec!["1", r#""a|b""#, r#""c\"|d""#]);
+        assert_eq!(split_cells("1\t\tx", '\t'), vec!["1", "", "x"]);
+        assert_eq!(split_cells("", ','), vec![""]);
+        assert_eq!(split_cells("a,b", '|'), vec!["a,b"]);
+    }
+
+    #[test]
+    fn split_cells_handles_multibyte_text() {
+        assert_eq!(split_cells("café,\u{FDD0},\"é,è\"", ','), vec!["café", "\u{FDD0}", "\"é,è\""]);
+    }
+
+    #[test]
+    fn indent_counts_spaces_only() {
+        assert_eq!(indent("    a: 1"), 4);
+        assert_eq!(indent("\ta: 1"), 0);
+        assert_eq!(indent(""), 0);
+    }
+
+    #[test]
+    fn tokens_differ_and_strip_only_themselves() {
+        let (a, b) = (Token::new('\u{FDD0}'), Token::new('\u{FDD0}'));
+        assert_ne!(a, b);
+        assert!(a.as_str().starts_with('\u{FDD0}') && a.as_str().ends_with('\u{FDD0}'));
+        assert_eq!(a.strip(&format!("{}rest", a)), Some("rest"));
+        assert_eq!(a.strip(&format!("{}rest", b)), None);
+        assert_eq!(a.strip("\u{FDD0}rest"), None);
+    }
+}
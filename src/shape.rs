@@ -0,0 +1,213 @@
+//! Structural shape of a document: which fields exist, what kinds of values
+//! they hold, and the column layout of every table.
+//!
+//! Array indices are collapsed, so `$.users[].id` describes the `id` field of
+//! every row of the `users` table.
+
+use serde_json::Value;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+
+#[derive(Default)]
+pub(crate) struct Shape {
+    pub(crate) fields: BTreeMap<String, BTreeSet<&'static str>>,
+    pub(crate) tables: BTreeMap<String, Vec<String>>,
+}
+
+pub(crate) fn kind(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "null",
+        Value::Bool(_) => "bool",
+        Value::Number(n) if n.is_f64() => "float",
+        Value::Number(_) => "int",
+        Value::String(_) => "str",
+        Value::Array(_) => "list",
+        Value::Object(_) => "dict",
+    }
+}
+
+/// Render a set of kinds as e.g. `int|null`.
+pub(crate) fn kinds_label(kinds: &BTreeSet<&'static str>) -> String {
+    kinds.iter().copied().collect::<Vec<_>>().join("|")
+}
+
+impl Shape {
+    pub(crate) fn of(value: &Value) -> Self {
+        let mut shape = Shape::default();
+        shape.visit(value, "$".to_string());
+        shape
+    }
+
+    fn visit(&mut self, value: &Value, path: String) {
+        self.fields.entry(path.clone()).or_default().insert(kind(value));
+        match value {
+            Value::Object(map) => {
+                for (k, v) in map {
+                    self.visit(v, format!("{}.{}", path, k));
+                }
+            }
+            Value::Array(items) => {
+                if !items.is_empty() && items.iter().all(Value::is_object) {
+                    let columns = self.tables.entry(path.clone()).or_default();
+                    for row in items.iter().filter_map(Value::as_object) {
+                        for k in row.keys() {
+                            if !columns.contains(k) {
+                                columns.push(k.clone());
+                            }
+                        }
+                    }
+                }
+                let item_path = format!("{}[]", path);
+                for item in items {
+                    self.visit(item, item_path.clone());
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Encoded size attributed to one field path, including everything below it.
+#[derive(Default, Clone, Copy)]
+pub(crate) struct FieldSize {
+    pub(crate) bytes: usize,
+    pub(crate) tokens: usize,
+    pub(crate) count: usize,
+}
+
+/// Approximate per-field contribution to the encoded document.
+///
+/// Scalars count their literal text; keys count once per occurrence, except
+/// in tables where the column name is written once in the header. Sizes
+/// roll up, so `$.users` includes every column of the table.
+pub(crate) fn field_sizes(value: &Value) -> Vec<(String, FieldSize)> {
+    fn add(sizes: &mut BTreeMap<String, FieldSize>, ancestors: &[String], text: &str) {
+        let bytes = text.len();
+        let tokens = crate::tokens::estimate(text);
+        for path in ancestors {
+            let entry = sizes.entry(path.clone()).or_default();
+            entry.bytes += bytes;
+            entry.tokens += tokens;
+        }
+    }
+
+    fn walk(value: &Value, path: String, ancestors: &mut Vec<String>, sizes: &mut BTreeMap<String, FieldSize>) {
+        ancestors.push(path.clone());
+        sizes.entry(path.clone()).or_default().count += 1;
+        match value {
+            Value::Object(map) => {
+                for (k, v) in map {
+                    let child = format!("{}.{}", path, k);
+                    // Key text belongs to the field it names
+                    ancestors.push(child.clone());
+                    add(sizes, ancestors, &format!("{}: ", k));
+                    ancestors.pop();
+                    walk(v, child, ancestors, sizes);
+                }
+            }
+            Value::Array(items) => {
+                let item_path = format!("{}[]", path);
+                let tabular = !items.is_empty() && items.iter().all(Value::is_object);
+                for item in items {
+                    match (tabular, item) {
+                        (true, Value::Object(row)) => {
+                            ancestors.push(item_path.clone());
+                            sizes.entry(item_path.clone()).or_default().count += 1;
+                            for (k, v) in row {
+                                walk(v, format!("{}.{}", item_path, k), ancestors, sizes);
+                            }
+                            ancestors.pop();
+                        }
+                        _ => walk(item, item_path.clone(), ancestors, sizes),
+                    }
+                }
+                if tabular {
+                    // Column names appear once, in the table header
+                    let mut seen: Vec<&String> = Vec::new();
+                    for row in items.iter().filter_map(Value::as_object) {
+                        for k in row.keys() {
+                            if !seen.contains(&k) {
+                                seen.push(k);
+                                ancestors.push(item_path.clone());
+                                ancestors.push(format!("{}.{}", item_path, k));
+                                add(sizes, ancestors, k);
+                                ancestors.truncate(ancestors.len() - 2);
+                            }
+                        }
+                    }
+                }
+            }
+            Value::String(s) => add(sizes, ancestors, s),
+            other => add(sizes, ancestors, &other.to_string()),
+        }
+        ancestors.pop();
+    }
+
+    let mut sizes = BTreeMap::new();
+    walk(value, "$".to_string(), &mut Vec::new(), &mut sizes);
+    let mut out: Vec<(String, FieldSize)> = sizes.into_iter().collect();
+    out.sort_by(|a, b| b.1.bytes.cmp(&a.1.bytes).then_with(|| a.0.cmp(&b.0)));
+    out
+}
+
+/// Node counts for a decoded value, as reported by `decode(..., stats=True)`.
+#[derive(Default)]
+pub(crate) struct NodeCounts {
+    pub(crate) nodes: usize,
+    pub(crate) dicts: usize,
+    pub(crate) lists: usize,
+    pub(crate) strings: usize,
+    pub(crate) numbers: usize,
+    /// Arrays whose items are all objects, i.e. TOON tables
+    pub(crate) tables: usize,
+    pub(crate) table_rows: usize,
+    pub(crate) max_depth: usize,
+    /// Object keys, counted once per object they appear in
+    pub(crate) keys: usize,
+    /// Key names, counted once across the whole value
+    pub(crate) distinct_keys: usize,
+}
+
+impl NodeCounts {
+    pub(crate) fn of(value: &Value) -> Self {
+        let mut counts = NodeCounts::default();
+        let mut names = HashSet::new();
+        counts.visit(value, 1, &mut names);
+        counts.distinct_keys = names.len();
+        counts
+    }
+
+    /// Python objects a conversion allocates; `None` and booleans are singletons.
+    pub(crate) fn allocations(&self) -> usize {
+        self.dicts + self.lists + self.strings + self.numbers
+    }
+
+    fn visit<'a>(&mut self, value: &'a Value, depth: usize, names: &mut HashSet<&'a str>) {
+        self.nodes += 1;
+        self.max_depth = self.max_depth.max(depth);
+        match value {
+            Value::Object(map) => {
+                self.dicts += 1;
+                // Keys become str objects too
+                self.strings += map.len();
+                self.keys += map.len();
+                for (k, v) in map {
+                    names.insert(k);
+                    self.visit(v, depth + 1, names);
+                }
+            }
+            Value::Array(items) => {
+                self.lists += 1;
+                if !items.is_empty() && items.iter().all(Value::is_object) {
+                    self.tables += 1;
+                    self.table_rows += items.len();
+                }
+                for item in items {
+                    self.visit(item, depth + 1, names);
+                }
+            }
+            Value::String(_) => self.strings += 1,
+            Value::Number(_) => self.numbers += 1,
+            Value::Null | Value::Bool(_) => {}
+        }
+    }
+}
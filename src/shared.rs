@@ -0,0 +1,140 @@
+//! `SharedBuffer`, returned by `encode_shared` for passing documents between
+//! processes.
+//!
+//! The encoded text is written once into a `multiprocessing.shared_memory`
+//! segment. Pickling the buffer sends only the segment's name and the text's
+//! length, and unpickling in a worker maps the same memory, so a large
+//! payload crosses the process boundary without being serialized again.
+//! `decode_shared` copies the text out of the segment once before parsing,
+//! since other processes may still write to it.
+
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyDict, PySlice};
+
+/// Encoded TOON text in shared memory, picklable by reference.
+///
+/// Unpickling attaches to the segment instead of copying it, which makes the
+/// buffer cheap to pass to ``multiprocessing`` workers. The segment lives
+/// until ``unlink()`` is called, typically by the creator once every worker
+/// is done (or by ``decode_shared(buf, unlink=True)`` in the last reader).
+///
+/// Example:
+///     >>> buf = toonpy.encode_shared(rows)
+///     >>> pool.map(worker, [buf] * 8)   # each worker: toonpy.decode_shared(buf)
+///     >>> buf.unlink()
+#[pyclass(module = "toon_parser", frozen)]
+pub(crate) struct SharedBuffer {
+    name: String,
+    /// Length of the text; the segment may be rounded up to whole pages
+    size: usize,
+    memory: Py<PyAny>,
+}
+
+fn shared_memory(py: Python<'_>) -> PyResult<Bound<'_, PyAny>> {
+    py.import("multiprocessing.shared_memory")?.getattr("SharedMemory")
+}
+
+impl SharedBuffer {
+    /// Copy `bytes` into a new segment.
+    pub(crate) fn create<'py>(py: Python<'py>, bytes: &[u8]) -> PyResult<Bound<'py, Self>> {
+        let kwargs = PyDict::new(py);
+        kwargs.set_item("create", true)?;
+        // Zero-sized segments are not allowed
+        kwargs.set_item("size", bytes.len().max(1))?;
+        let memory = shared_memory(py)?.call((), Some(&kwargs))?;
+        if !bytes.is_empty() {
+            let range = PySlice::new(py, 0, bytes.len() as isize, 1);
+            memory.getattr("buf")?.set_item(range, PyBytes::new(py, bytes))?;
+        }
+        let name = memory.getattr("name")?.extract()?;
+        Bound::new(py, SharedBuffer { name, size: bytes.len(), memory: memory.unbind() })
+    }
+
+    /// A private copy of the encoded text. Any process attached to the
+    /// segment may write to it, so it is copied out (by Python, not through a
+    /// Rust reference) before it is validated and parsed.
+    pub(crate) fn bytes<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyBytes>> {
+        let buf = self.memory.bind(py).getattr("buf")?;
+        if buf.is_none() {
+            return Err(PyValueError::new_err("the SharedBuffer is closed"));
+        }
+        let range = PySlice::new(py, 0, self.size as isize, 1);
+        let view = buf.get_item(range)?;
+        let bytes = view.call_method0("tobytes")?.cast_into::<PyBytes>()?;
+        view.call_method0("release")?;
+        Ok(bytes)
+    }
+}
+
+#[pymethods]
+impl SharedBuffer {
+    /// Attach to the segment `name` created by ``encode_shared``.
+    #[new]
+    fn new(py: Python<'_>, name: String, size: usize) -> PyResult<Self> {
+        let kwargs = PyDict::new(py);
+        kwargs.set_item("name", &name)?;
+        if py.version_info() >= (3, 13) {
+            // Only the creator's resource tracker should ever unlink it
+            kwargs.set_item("track", false)?;
+        }
+        let memory = shared_memory(py)?.call((), Some(&kwargs))?;
+        if py.version_info() < (3, 13) && cfg!(unix) {
+            // Older versions register every attach with this process's
+            // resource tracker, which would unlink the segment when a worker
+            // exits; hand it back so only the creator's tracker owns it
+            py.import("multiprocessing.resource_tracker")?
+                .call_method1("unregister", (memory.getattr("_name")?, "shared_memory"))?;
+        }
+        Ok(SharedBuffer { name, size, memory: memory.unbind() })
+    }
+
+    #[getter]
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    #[getter]
+    fn size(&self) -> usize {
+        self.size
+    }
+
+    fn __len__(&self) -> usize {
+        self.size
+    }
+
+    fn __bytes__<'py>(&self, py: Python<'py>) -> PyResult<Bound<'py, PyBytes>> {
+        self.bytes(py)
+    }
+
+    fn __reduce__<'py>(slf: &Bound<'py, Self>) -> (Bound<'py, PyAny>, (String, usize)) {
+        let this = slf.get();
+        (slf.get_type().into_any(), (this.name.clone(), this.size))
+    }
+
+    /// Detach this process from the segment; other processes keep their mappings.
+    fn close(&self, py: Python<'_>) -> PyResult<()> {
+        self.memory.bind(py).call_method0("close").map(drop)
+    }
+
+    /// Free the segment once every process has detached. Call it exactly once.
+    pub(crate) fn unlink(&self, py: Python<'_>) -> PyResult<()> {
+        let memory = self.memory.bind(py);
+        if py.version_info() >= (3, 13) || cfg!(not(unix)) {
+            return memory.call_method0("unlink").map(drop);
+        }
+        // Attaching unregistered the segment, possibly from a tracker this
+        // process shares with the creator; register it again so the
+        // unregister in `unlink` finds it, and take it back if that never runs
+        let tracker = py.import("multiprocessing.resource_tracker")?;
+        let name = memory.getattr("_name")?;
+        tracker.call_method1("register", (&name, "shared_memory"))?;
+        memory.call_method0("unlink").map(drop).inspect_err(|_| {
+            let _ = tracker.call_method1("unregister", (&name, "shared_memory"));
+        })
+    }
+
+    fn __repr__(&self) -> String {
+        format!("<SharedBuffer '{}' of {} bytes>", self.name, self.size)
+    }
+}
@@ -0,0 +1,92 @@
+//! Chunked output for `encode_to(data, sink)`.
+//!
+//! The encoder writes into a small buffer that is handed to a Python callable
+//! whenever it fills up, so the full encoding never exists in memory. `str`
+//! chunks end on a character boundary; `bytes` chunks are exactly
+//! `chunk_size` long except the last.
+
+use pyo3::prelude::*;
+use pyo3::types::{PyBytes, PyString};
+use std::io::{self, Write};
+
+pub(crate) struct Sink<'py> {
+    callable: Bound<'py, PyAny>,
+    buf: Vec<u8>,
+    chunk_size: usize,
+    binary: bool,
+    written: usize,
+    /// Raised by the callable; the encoder only sees an io::Error
+    error: Option<PyErr>,
+}
+
+impl<'py> Sink<'py> {
+    pub(crate) fn new(callable: Bound<'py, PyAny>, chunk_size: usize, binary: bool) -> Self {
+        Sink { callable, buf: Vec::with_capacity(chunk_size), chunk_size, binary, written: 0, error: None }
+    }
+
+    /// Length of the next chunk, or None until enough output is buffered.
+    fn next_chunk(&self, last: bool) -> Option<usize> {
+        if self.buf.is_empty() || (!last && self.buf.len() < self.chunk_size) {
+            return None;
+        }
+        let len = self.chunk_size.min(self.buf.len());
+        if self.binary {
+            return Some(len);
+        }
+        match std::str::from_utf8(&self.buf[..len]) {
+            Ok(_) => Some(len),
+            Err(e) if e.valid_up_to() > 0 => Some(e.valid_up_to()),
+            // A chunk_size below the width of the next character
+            Err(_) => (1..=self.buf.len().min(4)).find(|&n| std::str::from_utf8(&self.buf[..n]).is_ok()),
+        }
+    }
+
+    fn emit(&mut self, len: usize) -> PyResult<()> {
+        let py = self.callable.py();
+        let chunk = if self.binary {
+            PyBytes::new(py, &self.buf[..len]).into_any()
+        } else {
+            // The encoder only writes UTF-8 and chunks end on a boundary
+            PyString::new(py, &String::from_utf8_lossy(&self.buf[..len])).into_any()
+        };
+        self.callable.call1((chunk,))?;
+        self.buf.drain(..len);
+        self.written += len;
+        Ok(())
+    }
+
+    /// Hand over `text` as if the encoder had written it.
+    pub(crate) fn write_text(&mut self, text: &str) -> PyResult<()> {
+        self.write_all(text.as_bytes()).map_err(|e| self.error.take().unwrap_or_else(|| e.into()))
+    }
+
+    /// Emit what is left and return the number of bytes written in total.
+    pub(crate) fn finish(mut self) -> PyResult<usize> {
+        while let Some(len) = self.next_chunk(true) {
+            self.emit(len)?;
+        }
+        Ok(self.written)
+    }
+
+    /// The callable's exception, if a write failed because of it.
+    pub(crate) fn take_error(&mut self) -> Option<PyErr> {
+        self.error.take()
+    }
+}
+
+impl Write for Sink<'_> {
+    fn write(&mut self, data: &[u8]) -> io::Result<usize> {
+        self.buf.extend_from_slice(data);
+        while let Some(len) = self.next_chunk(false) {
+            if let Err(err) = self.emit(len) {
+                self.error = Some(err);
+                return Err(io::Error::other("sink raised an exception"));
+            }
+        }
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
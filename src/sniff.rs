@@ -0,0 +1,175 @@
+//! Cheap guesses about a document from its text alone.
+
+use crate::{diagnostics, scan};
+
+/// Delimiters in the order reports list them.
+pub(crate) const DELIMITERS: [(char, &str); 3] = [(',', "comma"), ('\t', "tab"), ('|', "pipe")];
+
+/// Which delimiter a document uses, and the evidence for it.
+pub(crate) struct DelimiterReport {
+    pub(crate) delimiter: &'static str,
+    /// Share of the evidence that agrees with `delimiter`, 0.0 without any
+    pub(crate) confidence: f64,
+    /// Array headers declaring each delimiter, in [`DELIMITERS`] order
+    pub(crate) declared: [usize; 3],
+    /// Each delimiter character outside quotes in array values and table rows
+    pub(crate) counts: [usize; 3],
+}
+
+impl DelimiterReport {
+    /// An explanation when the rows use another delimiter than the headers
+    /// declare, the usual reason a tab-separated file parses as comma.
+    pub(crate) fn note(&self) -> Option<String> {
+        let chosen = DELIMITERS.iter().position(|&(_, name)| name == self.delimiter)?;
+        let most = (0..3).fold(0, |best, i| if self.counts[i] > self.counts[best] { i } else { best });
+        if most == chosen || self.counts[most] <= self.counts[chosen] {
+            return None;
+        }
+        let marker = match DELIMITERS[most].0 {
+            '\t' => "a tab after the length, as in [3\\t]",
+            _ => "'|' after the length, as in [3|]",
+        };
+        Some(format!(
+            "values contain more {} than {} delimiters; to split on {}, headers need {}",
+            DELIMITERS[most].1, self.delimiter, DELIMITERS[most].1, marker
+        ))
+    }
+}
+
+fn count_unquoted(text: &str, counts: &mut [usize; 3]) {
+    let mut quoted = false;
+    let mut escaped = false;
+    for c in text.chars() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if quoted => escaped = true,
+            '"' => quoted = !quoted,
+            _ if !quoted => {
+                if let Some(i) = DELIMITERS.iter().position(|&(d, _)| d == c) {
+                    counts[i] += 1;
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// The delimiter the parser will use for `source`: each array header declares
+/// one (comma unless it carries a `|` or tab marker), so the most declared
+/// wins. Without headers the most frequent delimiter character decides.
+pub(crate) fn detect_delimiter(source: &str) -> DelimiterReport {
+    let mut declared = [0; 3];
+    let mut counts = [0; 3];
+    for line in source.lines() {
+        if let Some((delimiter, values)) = diagnostics::array_header(line.trim()) {
+            if let Some(i) = DELIMITERS.iter().position(|&(d, _)| d == delimiter) {
+                declared[i] += 1;
+            }
+            count_unquoted(values, &mut counts);
+        }
+    }
+    let lines: Vec<&str> = source.lines().collect();
+    for (row, _) in diagnostics::table_rows(source) {
+        count_unquoted(lines[row - 1].trim(), &mut counts);
+    }
+    let evidence = if declared.iter().any(|&n| n > 0) { declared } else { counts };
+    // Ties go to the earlier delimiter, so comma wins when nothing is known
+    let best = (0..3).fold(0, |best, i| if evidence[i] > evidence[best] { i } else { best });
+    let total: usize = evidence.iter().sum();
+    DelimiterReport {
+        delimiter: DELIMITERS[best].1,
+        confidence: if total == 0 { 0.0 } else { evidence[best] as f64 / total as f64 },
+        declared,
+        counts,
+    }
+}
+
+/// What kind of text an upload holds.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Format {
+    Toon,
+    Json,
+    Csv,
+    Unknown,
+}
+
+impl Format {
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            Format::Toon => "toon",
+            Format::Json => "json",
+            Format::Csv => "csv",
+            Format::Unknown => "unknown",
+        }
+    }
+}
+
+/// Bytes of input the heuristics look at.
+const SAMPLE: usize = 64 * 1024;
+/// Lines of the sample that are classified.
+const SAMPLE_LINES: usize = 50;
+
+/// `key: value`, `key:` or an array header, with a bare or quoted key.
+fn is_entry(line: &str) -> bool {
+    if diagnostics::array_header(line).is_some() {
+        return true;
+    }
+    let line = line.strip_prefix("- ").unwrap_or(line);
+    let key_end = if let Some(quoted) = line.strip_prefix('"') {
+        match quoted.find('"') {
+            Some(at) => at + 2,
+            None => return false,
+        }
+    } else {
+        line.find(|c: char| !(c.is_alphanumeric() || matches!(c, '_' | '.' | '-'))).unwrap_or(line.len())
+    };
+    key_end > 0 && line[key_end..].strip_prefix(':').is_some_and(|rest| rest.is_empty() || rest.starts_with(' '))
+}
+
+/// Classify input by cheap heuristics over its first 64 KiB: JSON by its
+/// opening bracket, TOON when nearly every line is an entry, list item or
+/// table row, and CSV-like when lines split into the same number of fields
+/// on a comma, tab or semicolon.
+pub(crate) fn detect_format(bytes: &[u8]) -> Format {
+    let sample = &bytes[..bytes.len().min(SAMPLE)];
+    let text = match std::str::from_utf8(sample) {
+        Ok(text) => text,
+        // A character cut off by the sample boundary is fine
+        Err(err) if err.error_len().is_none() => std::str::from_utf8(&sample[..err.valid_up_to()]).unwrap_or(""),
+        Err(_) => return Format::Unknown,
+    };
+    let text = text.trim_start_matches('\u{feff}').trim();
+    if text.is_empty() {
+        return Format::Unknown;
+    }
+    let first_line = text.lines().next().unwrap_or("");
+    if text.starts_with('{') || (text.starts_with('[') && diagnostics::array_header(first_line).is_none()) {
+        return Format::Json;
+    }
+
+    let lines: Vec<&str> = text.lines().filter(|l| !l.trim().is_empty()).take(SAMPLE_LINES).collect();
+    let sampled = lines.join("\n");
+    let rows: Vec<usize> = diagnostics::table_rows(&sampled).into_iter().map(|(row, _)| row).collect();
+    let toon_lines = lines
+        .iter()
+        .enumerate()
+        .filter(|(i, line)| {
+            let line = line.trim_start_matches(' ');
+            rows.contains(&(i + 1)) || line == "-" || line.starts_with("- ") || is_entry(line)
+        })
+        .count();
+    if toon_lines * 10 >= lines.len() * 9 {
+        return Format::Toon;
+    }
+
+    let csv = [',', '\t', ';'].iter().any(|&delimiter| {
+        let mut widths = lines.iter().map(|line| scan::split_cells(line, delimiter).len());
+        let first = widths.next().unwrap_or(1);
+        first > 1 && lines.len() > 1 && widths.all(|width| width == first)
+    });
+    if csv {
+        Format::Csv
+    } else {
+        Format::Unknown
+    }
+}
@@ -0,0 +1,61 @@
+//! Empty cells in table rows (`1,,Ann`), as written by exporters that leave
+//! missing values blank.
+//!
+//! With `Options(empty_cells_as_none=True)` each empty cell is read as `null`;
+//! strict mode rejects them, naming the row's line. Otherwise they are left
+//! to the parser.
+
+use std::borrow::Cow;
+
+use crate::{diagnostics, scan};
+
+/// True when some delimiter has nothing but spaces before or after it within
+/// its line, the only way a table row can hold an empty cell. Cheap enough to
+/// run on every document before looking for tables.
+pub(crate) fn maybe_empty(bytes: &[u8]) -> bool {
+    let is_delimiter = |b: u8| matches!(b, b',' | b'|' | b'\t');
+    bytes.iter().enumerate().filter(|(_, &b)| is_delimiter(b)).any(|(i, _)| {
+        let before = bytes[..i].iter().rev().find(|&&b| b != b' ');
+        let after = bytes[i + 1..].iter().find(|&&b| b != b' ');
+        before.is_none_or(|&b| b == b'\n' || is_delimiter(b)) || after.is_none_or(|&b| b == b'\n' || is_delimiter(b))
+    })
+}
+
+/// `source` with every empty table cell replaced by `null`, or a syntax error
+/// at the first one when `strict` is set.
+pub(crate) fn fill_empty(source: &str, strict: bool) -> Result<Cow<'_, str>, toon::Error> {
+    if !maybe_empty(source.as_bytes()) {
+        return Ok(Cow::Borrowed(source));
+    }
+    let mut rows = diagnostics::table_rows(source).into_iter().peekable();
+    let mut out = String::with_capacity(source.len());
+    let mut changed = false;
+    for (idx, line) in source.split_inclusive('\n').enumerate() {
+        let delimiter = match rows.next_if(|&(number, _)| number == idx + 1) {
+            Some((_, delimiter)) => delimiter,
+            None => {
+                out.push_str(line);
+                continue;
+            }
+        };
+        let body = line.trim_end_matches('\n');
+        let content = body.trim_start_matches(' ');
+        let cells = scan::split_cells(content, delimiter);
+        if cells.iter().all(|cell| !cell.trim().is_empty()) {
+            out.push_str(line);
+            continue;
+        }
+        if strict {
+            return Err(toon::Error::Syntax {
+                line: idx + 1,
+                message: "empty cell in table row; strict mode requires a value (write null)".to_string(),
+            });
+        }
+        changed = true;
+        out.push_str(&body[..body.len() - content.len()]);
+        let filled: Vec<&str> = cells.iter().map(|cell| if cell.trim().is_empty() { "null" } else { cell }).collect();
+        out.push_str(&filled.join(delimiter.encode_utf8(&mut [0; 4])));
+        out.push_str(&line[body.len()..]);
+    }
+    Ok(if changed { Cow::Owned(out) } else { Cow::Borrowed(source) })
+}
@@ -0,0 +1,116 @@
+//! Recovery hints for syntax errors (`ToonSyntaxError.suggestion`).
+//!
+//! The parser reports where it gave up, not why. After a failure these
+//! heuristics look at the text up to the error for the usual causes: rows
+//! split on another delimiter than their header declares, indentation that
+//! is off by a space or uses tabs, and a header length that disagrees with
+//! the items below it. The first that applies names the fix.
+
+use crate::{diagnostics, scan};
+use crate::sniff::DELIMITERS;
+
+struct Line<'a> {
+    number: usize,
+    /// Leading spaces and tabs
+    lead: &'a str,
+    content: &'a str,
+}
+
+fn lines(source: &str) -> Vec<Line<'_>> {
+    source
+        .lines()
+        .enumerate()
+        .filter(|(_, raw)| !raw.trim().is_empty())
+        .map(|(idx, raw)| {
+            let raw = raw.strip_suffix('\r').unwrap_or(raw);
+            let trimmed = raw.trim_start_matches([' ', '\t']);
+            Line { number: idx + 1, lead: &raw[..raw.len() - trimmed.len()], content: trimmed.trim_end() }
+        })
+        .collect()
+}
+
+/// A fix for the syntax error the parser reported at 1-based `line`, such as
+/// `use delimiter='tab'`.
+pub(crate) fn suggest(source: &str, line: usize) -> Option<String> {
+    let lines = lines(source);
+    delimiter(source, &lines, line)
+        .or_else(|| indentation(&lines, line))
+        .or_else(|| length(source, &lines, line))
+}
+
+/// Rows or inline values that only fit their header when split on another
+/// delimiter.
+fn delimiter(source: &str, lines: &[Line<'_>], line: usize) -> Option<String> {
+    let text: Vec<&str> = source.lines().collect();
+    for table in diagnostics::tables(source) {
+        if table.header > line {
+            break;
+        }
+        let header = text[table.header - 1];
+        let width = scan::split_cells(&header[table.fields.clone()], table.delimiter).len();
+        let rows: Vec<&str> = table.rows.iter().map(|&row| text[row - 1].trim()).collect();
+        let fits = |d: char| rows.iter().all(|row| scan::split_cells(row, d).len() == width);
+        if fits(table.delimiter) {
+            continue;
+        }
+        if let Some(&(_, name)) = DELIMITERS.iter().find(|&&(d, _)| d != table.delimiter && fits(d)) {
+            return Some(format!("use delimiter='{}'", name));
+        }
+    }
+    let at = lines.iter().rev().find(|l| l.number <= line)?;
+    let (declared_delimiter, values) = diagnostics::array_header(at.content)?;
+    let declared = diagnostics::declared_length(at.content)?;
+    if values.is_empty() || scan::split_cells(values, declared_delimiter).len() == declared {
+        return None;
+    }
+    DELIMITERS
+        .iter()
+        .find(|&&(d, _)| d != declared_delimiter && scan::split_cells(values, d).len() == declared)
+        .map(|&(_, name)| format!("use delimiter='{}'", name))
+}
+
+/// The nearest line up to the error indented with tabs or an odd number of
+/// spaces, and the indentation it most likely meant.
+fn indentation(lines: &[Line<'_>], line: usize) -> Option<String> {
+    let end = lines.iter().rposition(|l| l.number <= line)?;
+    (0..=end).rev().find_map(|i| {
+        let current = &lines[i];
+        if current.lead.contains('\t') {
+            return Some(format!("indent line {} with spaces instead of tabs", current.number));
+        }
+        let indent = current.lead.len();
+        if indent.is_multiple_of(2) {
+            return None;
+        }
+        let target = match i.checked_sub(1).map(|p| &lines[p]) {
+            // Under a line opening a block the child level is the likely aim
+            Some(prev) if prev.content.ends_with(':') && indent > prev.lead.len() => prev.lead.len() + 2,
+            Some(prev) => (indent - 1).min(prev.lead.len()),
+            None => 0,
+        };
+        Some(format!("indent line {} with {} spaces", current.number, target))
+    })
+}
+
+/// The nearest array header up to the error whose length disagrees with the
+/// values, rows or list items that follow it.
+fn length(source: &str, lines: &[Line<'_>], line: usize) -> Option<String> {
+    let tables = diagnostics::tables(source);
+    let end = lines.iter().rposition(|l| l.number <= line)?;
+    (0..=end).rev().find_map(|i| {
+        let header = &lines[i];
+        let declared = diagnostics::declared_length(header.content)?;
+        let (delimiter, values) = diagnostics::array_header(header.content)?;
+        let found = if !values.is_empty() {
+            scan::split_cells(values, delimiter).len()
+        } else if let Some(table) = tables.iter().find(|t| t.header == header.number) {
+            table.rows.len()
+        } else {
+            let indent = header.lead.len();
+            let children = lines[i + 1..].iter().take_while(|l| l.lead.len() > indent);
+            let first = children.clone().next().map_or(0, |l| l.lead.len());
+            children.filter(|l| l.lead.len() == first && (l.content == "-" || l.content.starts_with("- "))).count()
+        };
+        (found != declared).then(|| format!("set the length on line {} to {}", header.number, found))
+    })
+}
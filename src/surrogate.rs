@@ -0,0 +1,135 @@
+//! Unpaired UTF-16 surrogates in `\uXXXX` escapes, common in scraped text.
+//!
+//! Rust strings cannot hold them, so escapes are checked before parsing and
+//! handled by policy: reject them with the line number, replace them with
+//! U+FFFD, or pass them through to Python. Passing through maps each one to a
+//! code point in U+10F800..=U+10FFFF (the top of Supplementary Private Use
+//! Area-B) while the document is a Rust value; [`restore`] turns those back
+//! into surrogates when the Python string is built.
+
+use std::borrow::Cow;
+
+/// First code point of the range lone surrogates are parked in.
+const PARKED: u32 = 0x10F800;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub(crate) enum Policy {
+    #[default]
+    Error,
+    Replace,
+    Pass,
+}
+
+impl Policy {
+    pub(crate) fn parse(name: &str) -> Option<Self> {
+        match name {
+            "error" => Some(Policy::Error),
+            "replace" => Some(Policy::Replace),
+            "surrogatepass" => Some(Policy::Pass),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            Policy::Error => "error",
+            Policy::Replace => "replace",
+            Policy::Pass => "surrogatepass",
+        }
+    }
+}
+
+fn hex4(s: &str) -> Option<u32> {
+    let digits = s.get(..4)?;
+    if digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+        u32::from_str_radix(digits, 16).ok()
+    } else {
+        None
+    }
+}
+
+/// Rewrite lone surrogate escapes in quoted strings according to `policy`.
+/// Returns the source to parse and whether any surrogate was parked.
+pub(crate) fn apply(source: &str, policy: Policy) -> Result<(Cow<'_, str>, bool), toon::Error> {
+    // Fast path: no escapes that could be surrogates
+    if !source.contains("\\u") {
+        return Ok((Cow::Borrowed(source), false));
+    }
+    let mut out = String::new();
+    let mut copied = 0;
+    let mut parked = false;
+    for (line_no, line) in source.split_inclusive('\n').enumerate() {
+        let start = line.as_ptr() as usize - source.as_ptr() as usize;
+        let mut in_quotes = false;
+        let mut i = 0;
+        let bytes = line.as_bytes();
+        while i < bytes.len() {
+            match bytes[i] {
+                b'"' => in_quotes = !in_quotes,
+                b'\\' if in_quotes => {
+                    let escape = line.get(i + 1..i + 2);
+                    let code = line.get(i + 2..).and_then(hex4);
+                    match (escape, code) {
+                        (Some("u"), Some(0xD800..=0xDBFF))
+                            if line.get(i + 6..i + 8) == Some("\\u")
+                                && line.get(i + 8..).and_then(hex4).is_some_and(|low| (0xDC00..=0xDFFF).contains(&low)) =>
+                        {
+                            // A proper pair; skip both escapes
+                            i += 12;
+                            continue;
+                        }
+                        (Some("u"), Some(code @ 0xD800..=0xDFFF)) => {
+                            let replacement = match policy {
+                                Policy::Error => {
+                                    return Err(toon::Error::Syntax {
+                                        line: line_no + 1,
+                                        message: format!("unpaired surrogate escape \\u{:04X}", code),
+                                    })
+                                }
+                                Policy::Replace => '\u{FFFD}',
+                                Policy::Pass => {
+                                    parked = true;
+                                    char::from_u32(PARKED + (code - 0xD800)).unwrap_or('\u{FFFD}')
+                                }
+                            };
+                            out.push_str(&source[copied..start + i]);
+                            out.push(replacement);
+                            copied = start + i + 6;
+                            i += 6;
+                            continue;
+                        }
+                        _ => i += 1,
+                    }
+                }
+                _ => {}
+            }
+            i += 1;
+        }
+    }
+    if copied == 0 {
+        return Ok((Cow::Borrowed(source), false));
+    }
+    out.push_str(&source[copied..]);
+    Ok((Cow::Owned(out), parked))
+}
+
+/// True when `s` holds parked surrogates that [`restore`] must convert.
+pub(crate) fn has_parked(s: &str) -> bool {
+    s.chars().any(|c| (PARKED..=0x10FFFF).contains(&(c as u32)))
+}
+
+/// UTF-16 code units of `s` with parked code points turned back into lone
+/// surrogates, for decoding with Python's `surrogatepass` handler.
+pub(crate) fn restore(s: &str) -> Vec<u16> {
+    let mut units = Vec::with_capacity(s.len());
+    for c in s.chars() {
+        let code = c as u32;
+        if code >= PARKED {
+            units.push((code - PARKED + 0xD800) as u16);
+        } else {
+            let mut buf = [0u16; 2];
+            units.extend_from_slice(c.encode_utf16(&mut buf));
+        }
+    }
+    units
+}
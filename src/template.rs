@@ -0,0 +1,151 @@
+//! Prompt templates: `Placeholder` values and `render()`.
+//!
+//! A placeholder reaches the encoder as a marker string that no quoting rule
+//! applies to, `<token>name<token>`, and [`finish`] turns the markers in the
+//! encoded text into `{{name}}` tokens. Only conversions run through
+//! [`accepting`] produce markers, so encoders that never call [`finish`]
+//! reject placeholders instead of writing the markers out. [`render`] later
+//! replaces each token outside quoted strings with a scalar written the way
+//! the encoder would write it, so substituted values cannot break the
+//! document's structure.
+
+use serde_json::Value;
+use std::borrow::Cow;
+use std::cell::RefCell;
+
+use crate::scan::{Quotes, Token};
+
+/// Opens and closes the [`Token`] delimiting placeholder names in encoder
+/// input.
+pub(crate) const MARK: char = '\u{FDD1}';
+
+/// Letters, digits, `_`, `.` and `-`, starting with a letter or `_`.
+pub(crate) fn is_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-'))
+}
+
+thread_local! {
+    /// The token of the conversion running on this thread, if it accepts
+    /// placeholders, and whether it has met one.
+    static SCOPE: RefCell<Option<(Token, bool)>> = const { RefCell::new(None) };
+}
+
+/// Run `convert` accepting placeholders, marked with `token`. Also returns
+/// whether it converted any, that is whether the encoded text needs
+/// [`finish`].
+pub(crate) fn accepting<T>(token: &Token, convert: impl FnOnce() -> T) -> (T, bool) {
+    let outer = SCOPE.replace(Some((token.clone(), false)));
+    let result = convert();
+    let seen = SCOPE.replace(outer).is_some_and(|(_, seen)| seen);
+    (result, seen)
+}
+
+/// The string a placeholder named `name` encodes through, or `None` outside
+/// [`accepting`].
+pub(crate) fn marker(name: &str) -> Option<String> {
+    SCOPE.with_borrow_mut(|scope| {
+        let (token, seen) = scope.as_mut()?;
+        *seen = true;
+        Some(format!("{}{}{}", token, name, token))
+    })
+}
+
+/// Encoded `text` with the placeholder markers of `token` written as
+/// `{{name}}`.
+pub(crate) fn finish<'a>(text: &'a str, token: &Token) -> Cow<'a, str> {
+    let token = token.as_str();
+    if !text.contains(token) {
+        return Cow::Borrowed(text);
+    }
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find(token) {
+        let after = &rest[start + token.len()..];
+        let Some(end) = after.find(token).filter(|&end| is_name(&after[..end])) else {
+            out.push_str(&rest[..start + token.len()]);
+            rest = after;
+            continue;
+        };
+        out.push_str(&rest[..start]);
+        out.push_str("{{");
+        out.push_str(&after[..end]);
+        out.push_str("}}");
+        rest = &after[end + token.len()..];
+    }
+    out.push_str(rest);
+    Cow::Owned(out)
+}
+
+/// Replace every `{{name}}` token outside quoted strings with the scalar
+/// `lookup` returns for it.
+pub(crate) fn render<E>(
+    template: &str,
+    mut lookup: impl FnMut(&str) -> Result<Value, E>,
+    invalid: impl Fn(String) -> E,
+) -> Result<String, E> {
+    let mut out = String::with_capacity(template.len());
+    let mut copied = 0;
+    let mut quotes = Quotes::default();
+    for (i, c) in template.char_indices() {
+        // Inside a token already replaced
+        if i < copied {
+            continue;
+        }
+        if !quotes.step(c) || !template[i..].starts_with("{{") {
+            continue;
+        }
+        let body = &template[i + 2..];
+        let Some(end) = body.find("}}").filter(|&end| is_name(&body[..end])) else {
+            continue;
+        };
+        let name = &body[..end];
+        let value = lookup(name)?;
+        let text = scalar(&value).ok_or_else(|| {
+            invalid(format!("value for placeholder '{}' must be a str, number, bool or None", name))
+        })?;
+        out.push_str(&template[copied..i]);
+        out.push_str(&text);
+        copied = i + 2 + end + 2;
+    }
+    out.push_str(&template[copied..]);
+    Ok(out)
+}
+
+/// `value` as the encoder writes a scalar, quoting any string that could be
+/// read as something else or that holds a delimiter of any kind.
+fn scalar(value: &Value) -> Option<String> {
+    match value {
+        Value::Null => Some("null".to_string()),
+        Value::Bool(b) => Some(b.to_string()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::String(s) if !needs_quotes(s) => Some(s.clone()),
+        Value::String(s) => {
+            let mut out = String::with_capacity(s.len() + 2);
+            out.push('"');
+            for c in s.chars() {
+                match c {
+                    '"' => out.push_str("\\\""),
+                    '\\' => out.push_str("\\\\"),
+                    '\n' => out.push_str("\\n"),
+                    '\r' => out.push_str("\\r"),
+                    '\t' => out.push_str("\\t"),
+                    c => out.push(c),
+                }
+            }
+            out.push('"');
+            Some(out)
+        }
+        Value::Array(_) | Value::Object(_) => None,
+    }
+}
+
+fn needs_quotes(s: &str) -> bool {
+    s.is_empty()
+        || s != s.trim()
+        || matches!(s, "true" | "false" | "null")
+        || s.starts_with('-')
+        || s.parse::<f64>().is_ok()
+        || s.contains(|c: char| c.is_control() || ":\"\\[]{},|#".contains(c) || c == MARK)
+}
@@ -0,0 +1,78 @@
+//! Fast approximation of BPE token counts.
+//!
+//! Mirrors how GPT-style tokenizers pre-split text (words with their leading
+//! space, digit groups of up to three, punctuation runs, whitespace runs) and
+//! estimates how many merges each piece needs. Good enough for budgeting;
+//! pass a real tokenizer when exact numbers matter.
+
+#[derive(PartialEq, Eq, Clone, Copy)]
+enum Class {
+    Letter,
+    Digit,
+    Space,
+    Newline,
+    Punct,
+    Other,
+}
+
+fn class(c: char) -> Class {
+    if c == '\n' || c == '\r' {
+        Class::Newline
+    } else if c.is_whitespace() {
+        Class::Space
+    } else if c.is_ascii_alphabetic() {
+        Class::Letter
+    } else if c.is_ascii_digit() {
+        Class::Digit
+    } else if c.is_ascii() {
+        Class::Punct
+    } else if c.is_alphabetic() && (c as u32) < 0x2E80 {
+        // Latin-1, Greek, Cyrillic, ...: words behave like ASCII words, only
+        // with fewer learned merges
+        Class::Letter
+    } else {
+        Class::Other
+    }
+}
+
+/// Estimated token count of `text`.
+pub(crate) fn estimate(text: &str) -> usize {
+    let chars: Vec<char> = text.chars().collect();
+    let mut tokens = 0;
+    let mut i = 0;
+
+    while i < chars.len() {
+        let start = i;
+        let kind = class(chars[i]);
+        while i < chars.len() && class(chars[i]) == kind {
+            i += 1;
+        }
+        let len = i - start;
+
+        tokens += match kind {
+            // Common words are single tokens; longer ones split every ~4 chars
+            Class::Letter => {
+                let non_ascii = chars[start..i].iter().any(|c| !c.is_ascii());
+                if non_ascii {
+                    len.div_ceil(2)
+                } else if len <= 6 {
+                    1
+                } else {
+                    len.div_ceil(4)
+                }
+            }
+            Class::Digit => len.div_ceil(3),
+            // A single space merges into the following word; indentation runs
+            // collapse into one token
+            Class::Space => {
+                let merges_with_next = i < chars.len() && class(chars[i]) == Class::Letter;
+                usize::from(!(len == 1 && merges_with_next))
+            }
+            Class::Newline => 1,
+            Class::Punct => len.div_ceil(2),
+            // CJK and symbols: roughly one token per character
+            Class::Other => len,
+        };
+    }
+    tokens
+}
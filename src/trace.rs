@@ -0,0 +1,122 @@
+//! Optional `tracing` instrumentation.
+//!
+//! `span!` opens a span that lasts until the end of the enclosing block and
+//! compiles to nothing without the `tracing` feature, so the hot paths carry
+//! no cost in default builds. Subscribers measure durations when spans close;
+//! `enable_tracing()` forwards closed spans to a Python callable.
+
+#[cfg(feature = "tracing")]
+macro_rules! span {
+    ($name:literal $(, $($fields:tt)*)?) => {
+        let _span = tracing::info_span!($name $(, $($fields)*)?).entered();
+    };
+}
+
+#[cfg(not(feature = "tracing"))]
+macro_rules! span {
+    ($($tt:tt)*) => {};
+}
+
+/// Record a field on the current span once its value is known, e.g. output size.
+#[cfg(feature = "tracing")]
+macro_rules! record {
+    ($field:literal, $value:expr) => {
+        tracing::Span::current().record($field, $value);
+    };
+}
+
+#[cfg(not(feature = "tracing"))]
+macro_rules! record {
+    ($($tt:tt)*) => {};
+}
+
+pub(crate) use {record, span};
+
+#[cfg(all(feature = "tracing", feature = "python"))]
+pub(crate) mod bridge {
+    use pyo3::exceptions::PyRuntimeError;
+    use pyo3::prelude::*;
+    use serde_json::{Map, Value};
+    use std::fmt;
+    use std::time::Instant;
+    use tracing::field::{Field, Visit};
+    use tracing::{span, Subscriber};
+    use tracing_subscriber::layer::{Context, Layer, SubscriberExt};
+    use tracing_subscriber::registry::LookupSpan;
+    use tracing_subscriber::util::SubscriberInitExt;
+
+    use crate::python::json_to_python;
+
+    struct Timing {
+        start: Instant,
+        fields: Map<String, Value>,
+    }
+
+    struct Fields<'a>(&'a mut Map<String, Value>);
+
+    impl Visit for Fields<'_> {
+        fn record_u64(&mut self, field: &Field, value: u64) {
+            self.0.insert(field.name().to_string(), value.into());
+        }
+
+        fn record_i64(&mut self, field: &Field, value: i64) {
+            self.0.insert(field.name().to_string(), value.into());
+        }
+
+        fn record_bool(&mut self, field: &Field, value: bool) {
+            self.0.insert(field.name().to_string(), value.into());
+        }
+
+        fn record_str(&mut self, field: &Field, value: &str) {
+            self.0.insert(field.name().to_string(), value.into());
+        }
+
+        fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+            self.0.insert(field.name().to_string(), format!("{:?}", value).into());
+        }
+    }
+
+    /// Calls `callback(name, seconds, fields)` for every span that closes.
+    struct PythonLayer {
+        callback: Py<PyAny>,
+    }
+
+    impl<S: Subscriber + for<'a> LookupSpan<'a>> Layer<S> for PythonLayer {
+        fn on_new_span(&self, attrs: &span::Attributes<'_>, id: &span::Id, ctx: Context<'_, S>) {
+            let mut fields = Map::new();
+            attrs.record(&mut Fields(&mut fields));
+            if let Some(span) = ctx.span(id) {
+                span.extensions_mut().insert(Timing { start: Instant::now(), fields });
+            }
+        }
+
+        fn on_record(&self, id: &span::Id, values: &span::Record<'_>, ctx: Context<'_, S>) {
+            if let Some(span) = ctx.span(id) {
+                if let Some(timing) = span.extensions_mut().get_mut::<Timing>() {
+                    values.record(&mut Fields(&mut timing.fields));
+                }
+            }
+        }
+
+        fn on_close(&self, id: span::Id, ctx: Context<'_, S>) {
+            let Some(span) = ctx.span(&id) else { return };
+            let Some(timing) = span.extensions_mut().remove::<Timing>() else { return };
+            let seconds = timing.start.elapsed().as_secs_f64();
+            Python::attach(|py| {
+                let result = json_to_python(py, &Value::Object(timing.fields))
+                    .and_then(|fields| self.callback.call1(py, (span.name(), seconds, fields)));
+                if let Err(err) = result {
+                    err.write_unraisable(py, None);
+                }
+            });
+        }
+    }
+
+    /// Install the process-wide subscriber. Fails if one is already set.
+    pub(crate) fn install(callback: Py<PyAny>) -> PyResult<()> {
+        tracing_subscriber::registry()
+            .with(PythonLayer { callback })
+            .try_init()
+            .map_err(|e| PyRuntimeError::new_err(format!("tracing is already enabled: {}", e)))
+    }
+}
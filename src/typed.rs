@@ -0,0 +1,911 @@
+//! Typed decoding driven by Python type hints.
+//!
+//! A Python type (`list[User]`, a dataclass, a TypedDict, ...) is introspected
+//! once into a [`TypeSpec`] tree, which is then used to build typed Python
+//! objects straight from the decoded `serde_json::Value`.
+
+use pyo3::exceptions::PyTypeError;
+use pyo3::prelude::*;
+use pyo3::types::{PyBool, PyDict, PyFloat, PyInt, PyList, PyString, PyTuple, PyType};
+use serde_json::Map;
+use serde_json::Value;
+
+use crate::{locate, newline};
+use crate::python::{build_options, convert_toon_error, json_to_python, python_to_json, ToonSchemaError};
+
+/// Compiled description of a Python type hint.
+pub(crate) enum TypeSpec {
+    Any,
+    NoneType,
+    Bool,
+    Int,
+    Float,
+    Str,
+    List(Box<TypeSpec>),
+    Tuple(Vec<TypeSpec>),
+    VarTuple(Box<TypeSpec>),
+    Dict(Box<TypeSpec>),
+    Union(Vec<TypeSpec>),
+    Literal(Vec<Value>),
+    Dataclass { cls: Py<PyAny>, name: String, fields: Vec<Field> },
+    TypedDict { name: String, fields: Vec<Field> },
+}
+
+pub(crate) struct Field {
+    pub(crate) name: String,
+    pub(crate) spec: TypeSpec,
+    pub(crate) required: bool,
+    pub(crate) default: Option<FieldDefault>,
+}
+
+/// Dataclass default used to fill missing keys when encoding.
+pub(crate) enum FieldDefault {
+    Value(Py<PyAny>),
+    Factory(Py<PyAny>),
+}
+
+impl TypeSpec {
+    fn describe(&self) -> String {
+        match self {
+            TypeSpec::Any => "Any".to_string(),
+            TypeSpec::NoneType => "None".to_string(),
+            TypeSpec::Bool => "bool".to_string(),
+            TypeSpec::Int => "int".to_string(),
+            TypeSpec::Float => "float".to_string(),
+            TypeSpec::Str => "str".to_string(),
+            TypeSpec::List(item) => format!("list[{}]", item.describe()),
+            TypeSpec::Tuple(items) => format!(
+                "tuple[{}]",
+                items.iter().map(|t| t.describe()).collect::<Vec<_>>().join(", ")
+            ),
+            TypeSpec::VarTuple(item) => format!("tuple[{}, ...]", item.describe()),
+            TypeSpec::Dict(value) => format!("dict[str, {}]", value.describe()),
+            TypeSpec::Union(variants) => match variants.as_slice() {
+                [inner, TypeSpec::NoneType] => format!("Optional[{}]", inner.describe()),
+                _ => format!(
+                    "Union[{}]",
+                    variants.iter().map(|t| t.describe()).collect::<Vec<_>>().join(", ")
+                ),
+            },
+            TypeSpec::Literal(values) => format!(
+                "Literal[{}]",
+                values.iter().map(|v| v.to_string()).collect::<Vec<_>>().join(", ")
+            ),
+            TypeSpec::Dataclass { name, .. } | TypeSpec::TypedDict { name, .. } => name.clone(),
+        }
+    }
+}
+
+/// Introspect a Python type hint into a [`TypeSpec`].
+pub(crate) fn compile(tp: &Bound<'_, PyAny>) -> PyResult<TypeSpec> {
+    let mut stack = Vec::new();
+    compile_inner(tp, &mut stack)
+}
+
+fn compile_inner(tp: &Bound<'_, PyAny>, stack: &mut Vec<Py<PyAny>>) -> PyResult<TypeSpec> {
+    let py = tp.py();
+    let typing = py.import("typing")?;
+
+    if tp.is_none() || tp.is(py.None().bind(py).get_type()) {
+        return Ok(TypeSpec::NoneType);
+    }
+    if tp.is(typing.getattr("Any")?) {
+        return Ok(TypeSpec::Any);
+    }
+    if tp.is(py.get_type::<PyBool>()) {
+        return Ok(TypeSpec::Bool);
+    }
+    if tp.is(py.get_type::<PyInt>()) {
+        return Ok(TypeSpec::Int);
+    }
+    if tp.is(py.get_type::<PyFloat>()) {
+        return Ok(TypeSpec::Float);
+    }
+    if tp.is(py.get_type::<PyString>()) {
+        return Ok(TypeSpec::Str);
+    }
+    if tp.is(py.get_type::<PyList>()) {
+        return Ok(TypeSpec::List(Box::new(TypeSpec::Any)));
+    }
+    if tp.is(py.get_type::<PyTuple>()) {
+        return Ok(TypeSpec::VarTuple(Box::new(TypeSpec::Any)));
+    }
+    if tp.is(py.get_type::<PyDict>()) {
+        return Ok(TypeSpec::Dict(Box::new(TypeSpec::Any)));
+    }
+
+    let origin = typing.call_method1("get_origin", (tp,))?;
+    if !origin.is_none() {
+        let args = typing.call_method1("get_args", (tp,))?.cast_into::<PyTuple>()?;
+        let mut arg = |i: usize| -> PyResult<TypeSpec> {
+            match args.get_item(i) {
+                Ok(a) => compile_inner(&a, stack),
+                Err(_) => Ok(TypeSpec::Any),
+            }
+        };
+        // `Required[T]` / `NotRequired[T]` only affect key presence, which is
+        // read from the TypedDict itself
+        for marker in ["Required", "NotRequired"] {
+            if let Some(m) = typing.getattr_opt(marker)? {
+                if origin.is(&m) {
+                    return arg(0);
+                }
+            }
+        }
+        // `X | Y` (PEP 604) has `types.UnionType` as its origin instead of `typing.Union`
+        let is_union = origin.is(typing.getattr("Union")?)
+            || py.import("types")?.getattr_opt("UnionType")?.is_some_and(|u| origin.is(&u));
+        if is_union {
+            let mut variants = Vec::with_capacity(args.len());
+            for a in args.iter() {
+                variants.push(compile_inner(&a, stack)?);
+            }
+            return Ok(TypeSpec::Union(variants));
+        }
+        if origin.is(typing.getattr("Literal")?) {
+            let mut values = Vec::with_capacity(args.len());
+            for a in args.iter() {
+                values.push(python_to_json(py, &a)?);
+            }
+            return Ok(TypeSpec::Literal(values));
+        }
+        if origin.is(py.get_type::<PyList>()) {
+            return Ok(TypeSpec::List(Box::new(arg(0)?)));
+        }
+        if origin.is(py.get_type::<PyTuple>()) {
+            if args.len() == 2 && args.get_item(1)?.is(py.Ellipsis()) {
+                return Ok(TypeSpec::VarTuple(Box::new(arg(0)?)));
+            }
+            let mut items = Vec::with_capacity(args.len());
+            for a in args.iter() {
+                items.push(compile_inner(&a, stack)?);
+            }
+            return Ok(TypeSpec::Tuple(items));
+        }
+        if origin.is(py.get_type::<PyDict>()) {
+            if !matches!(arg(0)?, TypeSpec::Str | TypeSpec::Any) {
+                return Err(PyTypeError::new_err(format!(
+                    "Unsupported type {}: TOON object keys are always str", tp.repr()?
+                )));
+            }
+            return Ok(TypeSpec::Dict(Box::new(arg(1)?)));
+        }
+        return Err(PyTypeError::new_err(format!("Unsupported type for typed decode: {}", tp.repr()?)));
+    }
+
+    let Ok(cls) = tp.cast::<PyType>() else {
+        return Err(PyTypeError::new_err(format!("Unsupported type for typed decode: {}", tp.repr()?)));
+    };
+    if stack.iter().any(|seen| cls.is(seen)) {
+        return Err(PyTypeError::new_err(format!(
+            "Recursive type {} is not supported by typed decode", cls.name()?
+        )));
+    }
+
+    let dataclasses = py.import("dataclasses")?;
+    if dataclasses.call_method1("is_dataclass", (cls,))?.is_truthy()? {
+        stack.push(cls.clone().into_any().unbind());
+        let hints = typing.call_method1("get_type_hints", (cls,))?;
+        let missing = dataclasses.getattr("MISSING")?;
+        let mut fields = Vec::new();
+        for f in dataclasses.call_method1("fields", (cls,))?.try_iter()? {
+            let f = f?;
+            if !f.getattr("init")?.is_truthy()? {
+                continue;
+            }
+            let name: String = f.getattr("name")?.extract()?;
+            let default = f.getattr("default")?;
+            let factory = f.getattr("default_factory")?;
+            let default = if !default.is(&missing) {
+                Some(FieldDefault::Value(default.unbind()))
+            } else if !factory.is(&missing) {
+                Some(FieldDefault::Factory(factory.unbind()))
+            } else {
+                None
+            };
+            let spec = compile_inner(&hints.get_item(&name)?, stack)?;
+            fields.push(Field { name, spec, required: default.is_none(), default });
+        }
+        stack.pop();
+        return Ok(TypeSpec::Dataclass {
+            cls: cls.clone().into_any().unbind(),
+            name: cls.name()?.extract()?,
+            fields,
+        });
+    }
+
+    if cls.is_subclass_of::<PyDict>()? && cls.hasattr("__total__")? {
+        stack.push(cls.clone().into_any().unbind());
+        let hints = typing.call_method1("get_type_hints", (cls,))?.cast_into::<PyDict>()?;
+        // `__required_keys__` already accounts for `total=False` and
+        // `Required`/`NotRequired`; Python 3.8 only exposes `__total__`.
+        let required_keys = cls.getattr_opt("__required_keys__")?;
+        let total = cls.getattr("__total__")?.is_truthy()?;
+        let mut fields = Vec::with_capacity(hints.len());
+        for (name, hint) in hints.iter() {
+            let required = match &required_keys {
+                Some(keys) => keys.contains(&name)?,
+                None => total,
+            };
+            fields.push(Field {
+                name: name.extract()?,
+                spec: compile_inner(&hint, stack)?,
+                required,
+                default: None,
+            });
+        }
+        stack.pop();
+        return Ok(TypeSpec::TypedDict { name: cls.name()?.extract()?, fields });
+    }
+
+    Err(PyTypeError::new_err(format!("Unsupported type for typed decode: {}", tp.repr()?)))
+}
+
+/// A type hint compiled once for repeated typed decoding.
+///
+/// Introspecting type hints is far more expensive than decoding small
+/// documents, so services decoding the same shape over and over should build
+/// a Schema up front and reuse it.
+///
+/// Example:
+///     >>> users = toonpy.Schema(list[User])
+///     >>> users.decode(payload)
+///     [User(id=1, name='Alice')]
+#[pyclass(frozen, module = "toon_parser")]
+pub struct Schema {
+    pub(crate) spec: TypeSpec,
+    tp: Py<PyAny>,
+}
+
+#[pymethods]
+impl Schema {
+    #[new]
+    fn new(tp: &Bound<'_, PyAny>) -> PyResult<Self> {
+        Ok(Schema { spec: compile(tp)?, tp: tp.clone().unbind() })
+    }
+
+    /// The type hint this schema was compiled from.
+    #[getter]
+    fn tp(&self, py: Python<'_>) -> Py<PyAny> {
+        self.tp.clone_ref(py)
+    }
+
+    /// Decode a TOON string into an instance of the schema's type.
+    ///
+    /// With ``coerce=True`` scalar cells are converted to the declared type
+    /// (``"42"`` becomes ``42`` for an int field) and every cell that cannot be
+    /// converted is reported, grouped by column.
+    #[pyo3(signature = (toon_str, delimiter=None, strict=None, coerce=false), text_signature = "(toon_str, delimiter=None, strict=None, coerce=False)")]
+    fn decode<'py>(
+        &self,
+        py: Python<'py>,
+        toon_str: &str,
+        delimiter: Option<&str>,
+        strict: Option<bool>,
+        coerce: bool,
+    ) -> PyResult<Bound<'py, PyAny>> {
+        let opts = build_options(delimiter, strict)?;
+        decode_typed(py, &self.spec, toon_str, &opts, &newline::Reading::default(), coerce)
+    }
+
+    fn __repr__(&self, py: Python<'_>) -> PyResult<String> {
+        Ok(format!("Schema({})", self.tp.bind(py).repr()?))
+    }
+}
+
+/// Decode `toon_str` and build it according to `spec`.
+pub(crate) fn decode_typed<'py>(
+    py: Python<'py>,
+    spec: &TypeSpec,
+    toon_str: &str,
+    opts: &toon::Options,
+    reading: &newline::Reading,
+    coerce: bool,
+) -> PyResult<Bound<'py, PyAny>> {
+    let json_value: Value = py.detach(|| {
+        newline::decode_str(toon_str, opts, reading).map_err(convert_toon_error)
+    })?;
+
+    let mut ctx = if coerce { Ctx::coercing() } else { Ctx::default() };
+    let result = build(py, spec, &json_value, &mut ctx).map_err(|e| into_pyerr(py, e, Some(toon_str)))?;
+    if !ctx.failures.is_empty() {
+        return Err(failures_to_pyerr(py, &ctx.failures, Some(toon_str)));
+    }
+    Ok(result)
+}
+
+/// State threaded through [`build`].
+#[derive(Default)]
+pub(crate) struct Ctx {
+    pub(crate) path: Vec<Seg>,
+    /// Convert mismatched scalar cells to the declared type where possible.
+    pub(crate) coerce: bool,
+    /// Record coercion failures and keep going instead of stopping at the first.
+    collect: bool,
+    failures: Vec<Failure>,
+}
+
+impl Ctx {
+    pub(crate) fn coercing() -> Self {
+        Ctx { coerce: true, collect: true, ..Ctx::default() }
+    }
+}
+
+struct Failure {
+    path: Vec<Seg>,
+    expected: String,
+    value: Value,
+}
+
+/// Report every cell that could not be coerced, grouped by column.
+fn failures_to_pyerr(py: Python<'_>, failures: &[Failure], source: Option<&str>) -> PyErr {
+    let column = |f: &Failure| {
+        f.path
+            .iter()
+            .rev()
+            .find_map(|seg| match seg {
+                Seg::Key(k) => Some(k.clone()),
+                Seg::Index(_) => None,
+            })
+            .unwrap_or_else(|| display_path(&f.path))
+    };
+    let line_of = |f: &Failure| source.and_then(|s| locate::line_for_path(s, &path_keys(&f.path)));
+
+    let mut columns: Vec<(String, &str, Vec<&Failure>)> = Vec::new();
+    for f in failures {
+        let name = column(f);
+        match columns.iter_mut().find(|(c, _, _)| *c == name) {
+            Some((_, _, list)) => list.push(f),
+            None => columns.push((name, f.expected.as_str(), vec![f])),
+        }
+    }
+
+    let mut message = format!("{} value(s) could not be coerced to the declared column types:", failures.len());
+    for (name, expected, list) in &columns {
+        let first = list[0];
+        let at = match line_of(first) {
+            Some(line) => format!("line {} ", line),
+            None => String::new(),
+        };
+        message.push_str(&format!(
+            "\n  column '{}' ({}): {} failure(s), first at {}{} = {}",
+            name, expected, list.len(), at, display_path(&first.path), first.value
+        ));
+    }
+
+    let err = ToonSchemaError::new_err(message);
+    let build_attrs = || -> PyResult<()> {
+        let value = err.value(py);
+        let errors = PyList::empty(py);
+        for f in failures {
+            let entry = PyDict::new(py);
+            entry.set_item("path", display_path(&f.path))?;
+            entry.set_item("column", column(f))?;
+            entry.set_item("expected", &f.expected)?;
+            entry.set_item("value", json_to_python(py, &f.value)?)?;
+            entry.set_item("line", line_of(f))?;
+            errors.append(entry)?;
+        }
+        value.setattr("errors", errors)?;
+        value.setattr("instance_path", pointer(&failures[0].path))?;
+        value.setattr("schema_path", py.None())?;
+        value.setattr("line", line_of(&failures[0]))
+    };
+    match build_attrs() {
+        Ok(()) => err,
+        Err(e) => e,
+    }
+}
+
+fn path_keys(path: &[Seg]) -> Vec<String> {
+    path.iter()
+        .map(|seg| match seg {
+            Seg::Key(k) => k.clone(),
+            Seg::Index(i) => i.to_string(),
+        })
+        .collect()
+}
+
+#[derive(Clone)]
+pub(crate) enum Seg {
+    Key(String),
+    Index(usize),
+}
+
+pub(crate) enum BuildError {
+    Mismatch { path: Vec<Seg>, message: String },
+    Py(PyErr),
+}
+
+impl From<PyErr> for BuildError {
+    fn from(err: PyErr) -> Self {
+        BuildError::Py(err)
+    }
+}
+
+/// Human-readable path such as `$.users[3].email`.
+pub(crate) fn display_path(path: &[Seg]) -> String {
+    let mut out = String::from("$");
+    for seg in path {
+        match seg {
+            Seg::Key(k) => {
+                out.push('.');
+                out.push_str(k);
+            }
+            Seg::Index(i) => out.push_str(&format!("[{}]", i)),
+        }
+    }
+    out
+}
+
+fn pointer(path: &[Seg]) -> String {
+    path.iter()
+        .map(|seg| match seg {
+            Seg::Key(k) => format!("/{}", k.replace('~', "~0").replace('/', "~1")),
+            Seg::Index(i) => format!("/{}", i),
+        })
+        .collect()
+}
+
+fn value_kind(value: &Value) -> &'static str {
+    match value {
+        Value::Null => "None",
+        Value::Bool(_) => "bool",
+        Value::Number(n) if n.is_f64() => "float",
+        Value::Number(_) => "int",
+        Value::String(_) => "str",
+        Value::Array(_) => "list",
+        Value::Object(_) => "dict",
+    }
+}
+
+fn mismatch(path: &[Seg], spec: &TypeSpec, value: &Value) -> BuildError {
+    BuildError::Mismatch {
+        path: path.to_vec(),
+        message: format!("expected {}, got {}", spec.describe(), value_kind(value)),
+    }
+}
+
+/// Turn a [`BuildError`] into a `ToonSchemaError` carrying the field path and,
+/// when the source text is known, the line that produced the value.
+pub(crate) fn into_pyerr(py: Python<'_>, err: BuildError, source: Option<&str>) -> PyErr {
+    let (path, message) = match err {
+        BuildError::Py(err) => return err,
+        BuildError::Mismatch { path, message } => (path, message),
+    };
+    let line = source.and_then(|s| locate::line_for_path(s, &path_keys(&path)));
+    let text = match line {
+        Some(line) => format!("Line {}: {}: {}", line, display_path(&path), message),
+        None => format!("{}: {}", display_path(&path), message),
+    };
+    let err = ToonSchemaError::new_err(text);
+    let value = err.value(py);
+    let attrs = value
+        .setattr("instance_path", pointer(&path))
+        .and_then(|_| value.setattr("schema_path", py.None()))
+        .and_then(|_| value.setattr("line", line));
+    match attrs {
+        Ok(()) => err,
+        Err(e) => e,
+    }
+}
+
+/// Build a typed Python object for `value` following `spec`.
+pub(crate) fn build<'py>(
+    py: Python<'py>,
+    spec: &TypeSpec,
+    value: &Value,
+    ctx: &mut Ctx,
+) -> Result<Bound<'py, PyAny>, BuildError> {
+    match (spec, value) {
+        (TypeSpec::Any, _) => Ok(json_to_python(py, value)?),
+        (TypeSpec::NoneType, Value::Null) => Ok(py.None().into_bound(py)),
+        (TypeSpec::Bool, Value::Bool(_)) => Ok(json_to_python(py, value)?),
+        (TypeSpec::Int, Value::Number(n)) if !n.is_f64() => Ok(json_to_python(py, value)?),
+        (TypeSpec::Float, Value::Number(n)) => {
+            let f = n.as_f64().ok_or_else(|| mismatch(&ctx.path, spec, value))?;
+            Ok(PyFloat::new(py, f).into_any())
+        }
+        (TypeSpec::Str, Value::String(_)) => Ok(json_to_python(py, value)?),
+        (TypeSpec::List(item), Value::Array(arr)) => {
+            let mut items = Vec::with_capacity(arr.len());
+            for (i, v) in arr.iter().enumerate() {
+                ctx.path.push(Seg::Index(i));
+                items.push(build(py, item, v, ctx)?);
+                ctx.path.pop();
+            }
+            Ok(PyList::new(py, items)?.into_any())
+        }
+        (TypeSpec::VarTuple(item), Value::Array(arr)) => {
+            let mut items = Vec::with_capacity(arr.len());
+            for (i, v) in arr.iter().enumerate() {
+                ctx.path.push(Seg::Index(i));
+                items.push(build(py, item, v, ctx)?);
+                ctx.path.pop();
+            }
+            Ok(PyTuple::new(py, items)?.into_any())
+        }
+        (TypeSpec::Tuple(specs), Value::Array(arr)) => {
+            if specs.len() != arr.len() {
+                return Err(BuildError::Mismatch {
+                    path: ctx.path.clone(),
+                    message: format!("expected {} with {} items, got {}", spec.describe(), specs.len(), arr.len()),
+                });
+            }
+            let mut items = Vec::with_capacity(arr.len());
+            for (i, (s, v)) in specs.iter().zip(arr).enumerate() {
+                ctx.path.push(Seg::Index(i));
+                items.push(build(py, s, v, ctx)?);
+                ctx.path.pop();
+            }
+            Ok(PyTuple::new(py, items)?.into_any())
+        }
+        (TypeSpec::Dict(item), Value::Object(obj)) => {
+            let dict = PyDict::new(py);
+            for (k, v) in obj {
+                ctx.path.push(Seg::Key(k.clone()));
+                dict.set_item(k, build(py, item, v, ctx)?)?;
+                ctx.path.pop();
+            }
+            Ok(dict.into_any())
+        }
+        (TypeSpec::Union(variants), _) => {
+            let depth = ctx.path.len();
+            let mut failures = Vec::with_capacity(variants.len());
+            // A variant that fails must not leave coercion failures behind
+            let collect = std::mem::replace(&mut ctx.collect, false);
+            for variant in variants {
+                let attempt = build(py, variant, value, ctx);
+                match attempt {
+                    Ok(obj) => {
+                        ctx.collect = collect;
+                        return Ok(obj);
+                    }
+                    Err(BuildError::Mismatch { path: at, message }) => {
+                        failures.push(if at.len() > depth {
+                            format!("{}: {}: {}", variant.describe(), display_path(&at), message)
+                        } else {
+                            format!("{}: {}", variant.describe(), message)
+                        });
+                    }
+                    Err(BuildError::Py(err)) => failures.push(format!("{}: {}", variant.describe(), err)),
+                }
+                ctx.path.truncate(depth);
+            }
+            ctx.collect = collect;
+            Err(BuildError::Mismatch {
+                path: ctx.path.clone(),
+                message: format!("no variant of {} matched ({})", spec.describe(), failures.join("; ")),
+            })
+        }
+        (TypeSpec::Literal(values), _) => {
+            if values.contains(value) {
+                Ok(json_to_python(py, value)?)
+            } else {
+                Err(BuildError::Mismatch {
+                    path: ctx.path.clone(),
+                    message: format!("expected {}, got {}", spec.describe(), value),
+                })
+            }
+        }
+        (TypeSpec::Dataclass { cls, fields, .. }, Value::Object(obj)) => {
+            let kwargs = build_fields(py, fields, obj, ctx)?;
+            Ok(cls.bind(py).call((), Some(&kwargs))?)
+        }
+        (TypeSpec::TypedDict { fields, .. }, Value::Object(obj)) => {
+            let dict = build_fields(py, fields, obj, ctx)?;
+            // Undeclared keys are kept as-is; only declared keys are enforced
+            for (k, v) in obj {
+                if !fields.iter().any(|f| f.name == *k) {
+                    dict.set_item(k, json_to_python(py, v)?)?;
+                }
+            }
+            Ok(dict.into_any())
+        }
+        (TypeSpec::Int | TypeSpec::Float | TypeSpec::Str | TypeSpec::Bool, _) if ctx.coerce => {
+            match coerce_scalar(py, spec, value) {
+                Some(obj) => Ok(obj),
+                None if ctx.collect => {
+                    ctx.failures.push(Failure {
+                        path: ctx.path.clone(),
+                        expected: spec.describe(),
+                        value: value.clone(),
+                    });
+                    Ok(py.None().into_bound(py))
+                }
+                None => Err(mismatch(&ctx.path, spec, value)),
+            }
+        }
+        _ => Err(mismatch(&ctx.path, spec, value)),
+    }
+}
+
+/// Convert a scalar cell to the declared column type, e.g. `"42"` for an int
+/// column or `1` for a bool column.
+fn coerce_scalar<'py>(py: Python<'py>, spec: &TypeSpec, value: &Value) -> Option<Bound<'py, PyAny>> {
+    match (spec, value) {
+        (TypeSpec::Int, Value::String(s)) => {
+            let s = s.trim();
+            if let Ok(i) = s.parse::<i64>() {
+                Some(PyInt::new(py, i).into_any())
+            } else {
+                s.parse::<u64>().ok().map(|u| PyInt::new(py, u).into_any())
+            }
+        }
+        (TypeSpec::Int, Value::Number(n)) => n
+            .as_f64()
+            .filter(|f| f.fract() == 0.0 && f.abs() < i64::MAX as f64)
+            .map(|f| PyInt::new(py, f as i64).into_any()),
+        (TypeSpec::Float, Value::String(s)) => s
+            .trim()
+            .parse::<f64>()
+            .ok()
+            .filter(|f| f.is_finite())
+            .map(|f| PyFloat::new(py, f).into_any()),
+        (TypeSpec::Str, Value::Number(n)) => Some(PyString::new(py, &n.to_string()).into_any()),
+        (TypeSpec::Str, Value::Bool(b)) => Some(PyString::new(py, if *b { "true" } else { "false" }).into_any()),
+        (TypeSpec::Bool, Value::String(s)) => match s.trim().to_ascii_lowercase().as_str() {
+            "true" | "yes" | "1" => Some(PyBool::new(py, true).to_owned().into_any()),
+            "false" | "no" | "0" => Some(PyBool::new(py, false).to_owned().into_any()),
+            _ => None,
+        },
+        (TypeSpec::Bool, Value::Number(n)) => match n.as_i64() {
+            Some(1) => Some(PyBool::new(py, true).to_owned().into_any()),
+            Some(0) => Some(PyBool::new(py, false).to_owned().into_any()),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Build the declared fields of a dataclass or TypedDict into a dict.
+fn build_fields<'py>(
+    py: Python<'py>,
+    fields: &[Field],
+    obj: &serde_json::Map<String, Value>,
+    ctx: &mut Ctx,
+) -> Result<Bound<'py, PyDict>, BuildError> {
+    let dict = PyDict::new(py);
+    for field in fields {
+        match obj.get(&field.name) {
+            Some(v) => {
+                ctx.path.push(Seg::Key(field.name.clone()));
+                dict.set_item(&field.name, build(py, &field.spec, v, ctx)?)?;
+                ctx.path.pop();
+            }
+            None if field.required => {
+                return Err(BuildError::Mismatch {
+                    path: ctx.path.clone(),
+                    message: format!("missing required key '{}'", field.name),
+                });
+            }
+            None => {}
+        }
+    }
+    Ok(dict)
+}
+
+/// Produce the value to encode for `data` under a schema.
+///
+/// `schema` may be a [`Schema`], any type hint accepted by [`compile`], or the
+/// string `"infer"`, which derives one column layout per table from the rows
+/// themselves.
+pub(crate) fn conform_with(py: Python<'_>, data: &Bound<'_, PyAny>, schema: &Bound<'_, PyAny>) -> PyResult<Value> {
+    if let Ok(mode) = schema.cast::<PyString>() {
+        let mode = mode.to_cow()?;
+        if mode != "infer" {
+            return Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "Invalid schema '{}'. Must be a type, a Schema or 'infer'", mode
+            )));
+        }
+        let mut value = python_to_json(py, data)?;
+        return infer_tables(&mut value, &mut Vec::new()).map(|_| value).map_err(|e| into_pyerr(py, e, None));
+    }
+    let result = match schema.cast::<Schema>() {
+        Ok(compiled) => conform(py, &compiled.get().spec, data, &mut Vec::new()),
+        Err(_) => conform(py, &compile(schema)?, data, &mut Vec::new()),
+    };
+    result.map_err(|e| into_pyerr(py, e, None))
+}
+
+fn py_kind(obj: &Bound<'_, PyAny>) -> String {
+    obj.get_type().name().map(|n| n.to_string()).unwrap_or_else(|_| "object".to_string())
+}
+
+fn expected(path: &[Seg], spec: &TypeSpec, obj: &Bound<'_, PyAny>) -> BuildError {
+    BuildError::Mismatch {
+        path: path.to_vec(),
+        message: format!("expected {}, got {}", spec.describe(), py_kind(obj)),
+    }
+}
+
+/// Convert `obj` to a `Value`, enforcing `spec` and laying out object keys in
+/// declaration order.
+fn conform(py: Python<'_>, spec: &TypeSpec, obj: &Bound<'_, PyAny>, path: &mut Vec<Seg>) -> Result<Value, BuildError> {
+    match spec {
+        TypeSpec::Any => Ok(python_to_json(py, obj)?),
+        TypeSpec::NoneType if obj.is_none() => Ok(Value::Null),
+        TypeSpec::Bool if obj.is_instance_of::<PyBool>() => Ok(python_to_json(py, obj)?),
+        TypeSpec::Int if obj.is_instance_of::<PyInt>() && !obj.is_instance_of::<PyBool>() => {
+            Ok(python_to_json(py, obj)?)
+        }
+        TypeSpec::Float if obj.is_instance_of::<PyFloat>()
+            || (obj.is_instance_of::<PyInt>() && !obj.is_instance_of::<PyBool>()) =>
+        {
+            let f: f64 = obj.extract()?;
+            serde_json::Number::from_f64(f)
+                .map(Value::Number)
+                .ok_or_else(|| BuildError::Mismatch {
+                    path: path.clone(),
+                    message: "Invalid float value (NaN or Infinity)".to_string(),
+                })
+        }
+        TypeSpec::Str if obj.is_instance_of::<PyString>() => Ok(python_to_json(py, obj)?),
+        TypeSpec::List(item) | TypeSpec::VarTuple(item)
+            if obj.is_instance_of::<PyList>() || obj.is_instance_of::<PyTuple>() =>
+        {
+            let mut out = Vec::with_capacity(obj.len()?);
+            for (i, v) in obj.try_iter()?.enumerate() {
+                path.push(Seg::Index(i));
+                out.push(conform(py, item, &v?, path)?);
+                path.pop();
+            }
+            Ok(Value::Array(out))
+        }
+        TypeSpec::Tuple(specs) if obj.is_instance_of::<PyList>() || obj.is_instance_of::<PyTuple>() => {
+            if obj.len()? != specs.len() {
+                return Err(BuildError::Mismatch {
+                    path: path.clone(),
+                    message: format!("expected {} with {} items, got {}", spec.describe(), specs.len(), obj.len()?),
+                });
+            }
+            let mut out = Vec::with_capacity(specs.len());
+            for (i, (s, v)) in specs.iter().zip(obj.try_iter()?).enumerate() {
+                path.push(Seg::Index(i));
+                out.push(conform(py, s, &v?, path)?);
+                path.pop();
+            }
+            Ok(Value::Array(out))
+        }
+        TypeSpec::Dict(item) if obj.is_instance_of::<PyDict>() => {
+            let dict = obj.cast::<PyDict>().map_err(PyErr::from)?;
+            let mut map = Map::with_capacity(dict.len());
+            for (k, v) in dict.iter() {
+                let key: String = k.str()?.extract()?;
+                path.push(Seg::Key(key.clone()));
+                map.insert(key, conform(py, item, &v, path)?);
+                path.pop();
+            }
+            Ok(Value::Object(map))
+        }
+        TypeSpec::Union(variants) => {
+            let depth = path.len();
+            for variant in variants {
+                if let Ok(value) = conform(py, variant, obj, path) {
+                    return Ok(value);
+                }
+                path.truncate(depth);
+            }
+            Err(expected(path, spec, obj))
+        }
+        TypeSpec::Literal(values) => {
+            let value = python_to_json(py, obj)?;
+            if values.contains(&value) {
+                Ok(value)
+            } else {
+                Err(BuildError::Mismatch {
+                    path: path.clone(),
+                    message: format!("expected {}, got {}", spec.describe(), value),
+                })
+            }
+        }
+        TypeSpec::Dataclass { cls, fields, .. } if obj.is_instance(cls.bind(py))? => {
+            let mut map = Map::with_capacity(fields.len());
+            for field in fields {
+                path.push(Seg::Key(field.name.clone()));
+                map.insert(field.name.clone(), conform(py, &field.spec, &obj.getattr(field.name.as_str())?, path)?);
+                path.pop();
+            }
+            Ok(Value::Object(map))
+        }
+        TypeSpec::Dataclass { fields, .. } | TypeSpec::TypedDict { fields, .. } if obj.is_instance_of::<PyDict>() => {
+            let dict = obj.cast::<PyDict>().map_err(PyErr::from)?;
+            for key in dict.keys() {
+                let key: String = key.str()?.extract()?;
+                if !fields.iter().any(|f| f.name == key) {
+                    return Err(BuildError::Mismatch {
+                        path: path.clone(),
+                        message: format!("unexpected key '{}' for {}", key, spec.describe()),
+                    });
+                }
+            }
+            let mut map = Map::with_capacity(fields.len());
+            for field in fields {
+                path.push(Seg::Key(field.name.clone()));
+                let value = match (dict.get_item(&field.name)?, &field.default) {
+                    (Some(v), _) => conform(py, &field.spec, &v, path)?,
+                    (None, Some(FieldDefault::Value(d))) => conform(py, &field.spec, d.bind(py), path)?,
+                    (None, Some(FieldDefault::Factory(f))) => conform(py, &field.spec, &f.bind(py).call0()?, path)?,
+                    (None, None) if field.required => {
+                        path.pop();
+                        return Err(BuildError::Mismatch {
+                            path: path.clone(),
+                            message: format!("missing required key '{}'", field.name),
+                        });
+                    }
+                    (None, None) => {
+                        path.pop();
+                        continue;
+                    }
+                };
+                map.insert(field.name.clone(), value);
+                path.pop();
+            }
+            Ok(Value::Object(map))
+        }
+        _ => Err(expected(path, spec, obj)),
+    }
+}
+
+fn json_kind(value: &Value) -> Option<&'static str> {
+    match value {
+        Value::Null => None,
+        Value::Bool(_) => Some("bool"),
+        Value::Number(_) => Some("number"),
+        Value::String(_) => Some("str"),
+        Value::Array(_) => Some("list"),
+        Value::Object(_) => Some("dict"),
+    }
+}
+
+/// Give every array of objects a single column layout: keys in order of first
+/// appearance, missing cells filled with null, and one value kind per column.
+fn infer_tables(value: &mut Value, path: &mut Vec<Seg>) -> Result<(), BuildError> {
+    match value {
+        Value::Object(map) => {
+            for (k, v) in map.iter_mut() {
+                path.push(Seg::Key(k.clone()));
+                infer_tables(v, path)?;
+                path.pop();
+            }
+        }
+        Value::Array(rows) => {
+            for (i, row) in rows.iter_mut().enumerate() {
+                path.push(Seg::Index(i));
+                infer_tables(row, path)?;
+                path.pop();
+            }
+            if rows.is_empty() || !rows.iter().all(Value::is_object) {
+                return Ok(());
+            }
+            let mut columns: Vec<(String, Option<&'static str>)> = Vec::new();
+            for (i, row) in rows.iter().enumerate() {
+                for (k, v) in row.as_object().into_iter().flatten() {
+                    let kind = json_kind(v);
+                    match columns.iter_mut().find(|(name, _)| name == k) {
+                        None => columns.push((k.clone(), kind)),
+                        Some((_, seen @ None)) => *seen = kind,
+                        Some((_, Some(seen))) if kind.is_some_and(|kind| kind != *seen) => {
+                            return Err(BuildError::Mismatch {
+                                path: [path.as_slice(), &[Seg::Index(i), Seg::Key(k.clone())]].concat(),
+                                message: format!("column '{}' mixes {} and {} values", k, seen, kind.unwrap_or("null")),
+                            });
+                        }
+                        Some(_) => {}
+                    }
+                }
+            }
+            for row in rows.iter_mut() {
+                if let Value::Object(map) = row {
+                    let mut ordered = Map::with_capacity(columns.len());
+                    for (name, _) in &columns {
+                        ordered.insert(name.clone(), map.remove(name).unwrap_or(Value::Null));
+                    }
+                    *map = ordered;
+                }
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
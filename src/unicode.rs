@@ -0,0 +1,62 @@
+//! Unicode normalization of decoded strings, so text produced by different
+//! tools (precomposed `é` vs `e` + combining accent) compares and hashes equal.
+
+use serde_json::{Map, Value};
+use unicode_normalization::UnicodeNormalization;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub(crate) enum Form {
+    Nfc,
+    Nfd,
+    Nfkc,
+    Nfkd,
+}
+
+impl Form {
+    pub(crate) fn parse(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "nfc" => Some(Form::Nfc),
+            "nfd" => Some(Form::Nfd),
+            "nfkc" => Some(Form::Nfkc),
+            "nfkd" => Some(Form::Nfkd),
+            _ => None,
+        }
+    }
+
+    pub(crate) fn name(self) -> &'static str {
+        match self {
+            Form::Nfc => "nfc",
+            Form::Nfd => "nfd",
+            Form::Nfkc => "nfkc",
+            Form::Nfkd => "nfkd",
+        }
+    }
+
+    fn apply(self, s: &str) -> String {
+        match self {
+            Form::Nfc => s.nfc().collect(),
+            Form::Nfd => s.nfd().collect(),
+            Form::Nfkc => s.nfkc().collect(),
+            Form::Nfkd => s.nfkd().collect(),
+        }
+    }
+}
+
+/// Normalize every string value, and keys too when `keys` is set.
+pub(crate) fn normalize(value: Value, form: Form, keys: bool) -> Value {
+    match value {
+        // ASCII is already normalized in every form
+        Value::String(s) if s.is_ascii() => Value::String(s),
+        Value::String(s) => Value::String(form.apply(&s)),
+        Value::Array(items) => Value::Array(items.into_iter().map(|v| normalize(v, form, keys)).collect()),
+        Value::Object(map) => {
+            let mut out = Map::with_capacity(map.len());
+            for (k, v) in map {
+                let k = if keys && !k.is_ascii() { form.apply(&k) } else { k };
+                out.insert(k, normalize(v, form, keys));
+            }
+            Value::Object(out)
+        }
+        other => other,
+    }
+}
@@ -0,0 +1,179 @@
+//! `ToonView`, rich notebook display for documents.
+//!
+//! Jupyter calls `_repr_html_` on the last value of a cell. Arrays of dicts
+//! with the same keys and scalar values, TOON's tables, render as HTML tables;
+//! everything else as a tree of `<details>` elements that expand on click.
+
+use pyo3::prelude::*;
+use serde_json::Value;
+use std::fmt::Write;
+
+use crate::python::value_from_data_or_toon;
+use crate::shape;
+
+/// Rows shown per table before the rest are summarized
+const MAX_ROWS: usize = 100;
+/// Levels of the tree expanded initially
+const OPEN_LEVELS: usize = 2;
+
+const STYLE: &str = "<style>\
+.toon-view{font-family:monospace;font-size:13px}\
+.toon-view ul{list-style:none;margin:0;padding-left:1.2em}\
+.toon-view summary{cursor:pointer}\
+.toon-view .k{font-weight:bold}\
+.toon-view .n{color:#888}\
+.toon-view table{border-collapse:collapse;margin:2px 0}\
+.toon-view th,.toon-view td{border:1px solid #ccc;padding:2px 6px;text-align:left}\
+</style>";
+
+/// Display wrapper for Python data or a TOON document.
+///
+/// In Jupyter, tables render as HTML tables and nested data as collapsible
+/// trees. Outside notebooks ``repr()`` gives a one-line summary.
+///
+/// Example:
+///     >>> toonpy.ToonView("users[2]{id,name}:\n  1,Ann\n  2,Bo\n")
+///     <ToonView dict with 1 keys>
+#[pyclass(module = "toon_parser", frozen)]
+pub(crate) struct ToonView {
+    value: Value,
+}
+
+#[pymethods]
+impl ToonView {
+    #[new]
+    fn new(py: Python<'_>, data_or_str: &Bound<'_, PyAny>) -> PyResult<Self> {
+        Ok(ToonView { value: value_from_data_or_toon(py, data_or_str)? })
+    }
+
+    fn _repr_html_(&self, py: Python<'_>) -> String {
+        py.detach(|| html(&self.value))
+    }
+
+    fn __repr__(&self) -> String {
+        match &self.value {
+            Value::Object(map) => format!("<ToonView dict with {} keys>", map.len()),
+            Value::Array(items) => format!("<ToonView list with {} items>", items.len()),
+            other => format!("<ToonView {}>", shape::kind(other)),
+        }
+    }
+}
+
+/// The whole document as an HTML fragment.
+pub(crate) fn html(value: &Value) -> String {
+    let mut out = String::from(STYLE);
+    out.push_str("<div class=\"toon-view\">");
+    node(&mut out, value, 0);
+    out.push_str("</div>");
+    out
+}
+
+fn node(out: &mut String, value: &Value, depth: usize) {
+    if let Some(columns) = table_columns(value) {
+        table(out, value, &columns);
+        return;
+    }
+    match value {
+        Value::Object(map) => {
+            out.push_str("<ul>");
+            for (key, child) in map {
+                out.push_str("<li>");
+                labeled(out, &escape(key), child, depth);
+                out.push_str("</li>");
+            }
+            out.push_str("</ul>");
+        }
+        Value::Array(items) => {
+            out.push_str("<ul>");
+            for (i, child) in items.iter().enumerate() {
+                out.push_str("<li>");
+                labeled(out, &format!("[{}]", i), child, depth);
+                out.push_str("</li>");
+            }
+            out.push_str("</ul>");
+        }
+        scalar => out.push_str(&scalar_html(scalar)),
+    }
+}
+
+/// One entry: `label: value` for scalars, an expandable subtree otherwise.
+fn labeled(out: &mut String, label: &str, value: &Value, depth: usize) {
+    let summary = match value {
+        Value::Object(map) if !map.is_empty() => format!("{{{} keys}}", map.len()),
+        Value::Array(items) if !items.is_empty() => format!("[{} items]", items.len()),
+        scalar => {
+            let _ = write!(out, "<span class=\"k\">{}</span>: {}", label, scalar_html(scalar));
+            return;
+        }
+    };
+    let open = if depth + 1 < OPEN_LEVELS { " open" } else { "" };
+    let _ = write!(out, "<details{}><summary><span class=\"k\">{}</span> <span class=\"n\">{}</span></summary>", open, label, summary);
+    node(out, value, depth + 1);
+    out.push_str("</details>");
+}
+
+/// Column names when `value` is an array of dicts sharing their keys and
+/// holding only scalars.
+fn table_columns(value: &Value) -> Option<Vec<&str>> {
+    let Value::Array(items) = value else {
+        return None;
+    };
+    let Value::Object(first) = items.first()? else {
+        return None;
+    };
+    let uniform = items.iter().all(|item| match item {
+        Value::Object(map) => {
+            map.len() == first.len()
+                && map.keys().eq(first.keys())
+                && map.values().all(|v| !v.is_array() && !v.is_object())
+        }
+        _ => false,
+    });
+    (uniform && !first.is_empty()).then(|| first.keys().map(String::as_str).collect())
+}
+
+fn table(out: &mut String, value: &Value, columns: &[&str]) {
+    let Value::Array(rows) = value else {
+        return;
+    };
+    out.push_str("<table><thead><tr>");
+    for column in columns {
+        let _ = write!(out, "<th>{}</th>", escape(column));
+    }
+    out.push_str("</tr></thead><tbody>");
+    for row in rows.iter().take(MAX_ROWS) {
+        out.push_str("<tr>");
+        for column in columns {
+            let _ = write!(out, "<td>{}</td>", scalar_html(&row[*column]));
+        }
+        out.push_str("</tr>");
+    }
+    out.push_str("</tbody></table>");
+    if rows.len() > MAX_ROWS {
+        let _ = write!(out, "<span class=\"n\">... {} more rows</span>", rows.len() - MAX_ROWS);
+    }
+}
+
+fn scalar_html(value: &Value) -> String {
+    match value {
+        Value::String(s) => escape(s),
+        Value::Object(_) => "<span class=\"n\">{}</span>".to_string(),
+        Value::Array(_) => "<span class=\"n\">[]</span>".to_string(),
+        other => format!("<span class=\"n\">{}</span>", other),
+    }
+}
+
+fn escape(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&#39;"),
+            c => out.push(c),
+        }
+    }
+    out
+}
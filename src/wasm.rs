@@ -0,0 +1,86 @@
+//! WebAssembly bindings, built from the same core as the Python module:
+//!
+//! ```sh
+//! wasm-pack build --no-default-features --features wasm
+//! ```
+//!
+//! ```js
+//! import { encode, decode, jsonToToon } from "toonpy";
+//! const text = encode({ users: [{ id: 1, name: "Alice" }] });
+//! ```
+
+use wasm_bindgen::prelude::*;
+
+use crate::core;
+
+fn js_error(err: core::Error) -> JsError {
+    JsError::new(&err.to_string())
+}
+
+/// Encode a JSON-compatible JavaScript value to TOON.
+#[wasm_bindgen]
+pub fn encode(value: JsValue, delimiter: Option<String>, strict: Option<bool>) -> Result<String, JsError> {
+    let json = js_sys::JSON::stringify(&value).map_err(|_| JsError::new("Value cannot be converted to JSON"))?;
+    let opts = core::options(delimiter.as_deref(), strict).map_err(js_error)?;
+    core::json_to_toon(&String::from(json), &opts).map_err(js_error)
+}
+
+/// Decode TOON to a JavaScript value.
+#[wasm_bindgen]
+pub fn decode(toon: &str, strict: Option<bool>) -> Result<JsValue, JsError> {
+    let opts = core::options(None, strict).map_err(js_error)?;
+    let json = core::toon_to_json(toon, false, &opts).map_err(js_error)?;
+    js_sys::JSON::parse(&json).map_err(|_| JsError::new("Decoded document is not valid JSON"))
+}
+
+/// Convert a JSON string to TOON.
+#[wasm_bindgen(js_name = jsonToToon)]
+pub fn json_to_toon(json: &str, delimiter: Option<String>, strict: Option<bool>) -> Result<String, JsError> {
+    let opts = core::options(delimiter.as_deref(), strict).map_err(js_error)?;
+    core::json_to_toon(json, &opts).map_err(js_error)
+}
+
+/// Convert TOON to a JSON string.
+#[wasm_bindgen(js_name = toonToJson)]
+pub fn toon_to_json(toon: &str, pretty: Option<bool>, strict: Option<bool>) -> Result<String, JsError> {
+    let opts = core::options(None, strict).map_err(js_error)?;
+    core::toon_to_json(toon, pretty.unwrap_or(false), &opts).map_err(js_error)
+}
+
+#[cfg(all(test, target_arch = "wasm32"))]
+mod tests {
+    use super::*;
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    const USERS_JSON: &str = r#"{"users":[{"id":1,"name":"Ann"},{"id":2,"name":"Bo"}]}"#;
+    const USERS_TOON: &str = "users[2]{id,name}:\n  1,Ann\n  2,Bo";
+
+    #[wasm_bindgen_test]
+    fn values_round_trip() {
+        let value = js_sys::JSON::parse(USERS_JSON).unwrap();
+        let toon = encode(value, None, None).unwrap();
+        assert_eq!(toon, USERS_TOON);
+        let back = decode(&toon, None).unwrap();
+        assert_eq!(String::from(js_sys::JSON::stringify(&back).unwrap()), USERS_JSON);
+    }
+
+    #[wasm_bindgen_test]
+    fn encode_takes_a_delimiter() {
+        let value = js_sys::JSON::parse(r#"{"xs":[1,2]}"#).unwrap();
+        assert_eq!(encode(value, Some("pipe".into()), None).unwrap(), "xs[2|]: 1|2");
+    }
+
+    #[wasm_bindgen_test]
+    fn strings_convert_both_ways() {
+        assert_eq!(json_to_toon(USERS_JSON, None, None).unwrap(), USERS_TOON);
+        assert_eq!(toon_to_json(USERS_TOON, None, None).unwrap(), USERS_JSON);
+        assert!(toon_to_json(USERS_TOON, Some(true), None).unwrap().contains("\n  \"users\": [\n"));
+    }
+
+    #[wasm_bindgen_test]
+    fn errors_are_thrown() {
+        assert!(json_to_toon("{", None, None).is_err());
+        assert!(json_to_toon(USERS_JSON, Some("semicolon".into()), None).is_err());
+        assert!(decode("xs[3]: 1,2", Some(true)).is_err());
+    }
+}
@@ -0,0 +1,105 @@
+"""Streaming encodes to async consumers with ``toon_parser_async.aencode_stream``.
+
+Run with ``python -m unittest tests.test_aencode_stream``.
+"""
+
+import asyncio
+import unittest
+
+import toon_parser
+import toon_parser_async
+
+DATA = {"rows": [{"id": i, "name": f"n{i}"} for i in range(5000)]}
+
+
+class Counting:
+    """Stands in for ``toon_parser.encode_to``, counting the chunks handed to the sink."""
+
+    def __init__(self):
+        self.chunks = 0
+        self.finished = False
+
+    def __call__(self, data, sink, *args):
+        def counted(chunk):
+            self.chunks += 1
+            sink(chunk)
+
+        try:
+            return ENCODE_TO(data, counted, *args)
+        finally:
+            self.finished = True
+
+
+ENCODE_TO = toon_parser.encode_to
+
+
+class AencodeStreamTest(unittest.IsolatedAsyncioTestCase):
+    def setUp(self):
+        self.encoder = Counting()
+        toon_parser.encode_to = self.encoder
+
+    def tearDown(self):
+        toon_parser.encode_to = ENCODE_TO
+
+    async def collect(self, *args, **kwargs):
+        return [chunk async for chunk in toon_parser_async.aencode_stream(*args, **kwargs)]
+
+    async def test_chunks_join_to_the_encoding(self):
+        chunks = await self.collect(DATA, chunk_size=100)
+        self.assertGreater(len(chunks), 100)
+        self.assertTrue(all(isinstance(chunk, str) for chunk in chunks))
+        self.assertEqual("".join(chunks), toon_parser.encode(DATA))
+
+    async def test_binary(self):
+        chunks = await self.collect(DATA, chunk_size=100, binary=True)
+        self.assertTrue(all(isinstance(chunk, bytes) for chunk in chunks))
+        self.assertEqual(b"".join(chunks), toon_parser.encode(DATA).encode())
+
+    async def test_small_documents_and_options(self):
+        self.assertEqual("".join(await self.collect({}, chunk_size=1)), toon_parser.encode({}))
+        self.assertEqual(await self.collect({"b": 1, "a": 2}, options=toon_parser.Options(sort_keys=True)), ["a: 2\nb: 1"])
+
+    async def test_backpressure(self):
+        stream = toon_parser_async.aencode_stream(DATA, chunk_size=100, max_buffered=2)
+        consumed = 0
+        async for _ in stream:
+            consumed += 1
+            await asyncio.sleep(0.01 if consumed < 3 else 0.2)
+            # Two queued, plus the one the encoder waits to hand over
+            self.assertLessEqual(self.encoder.chunks - consumed, 3)
+            if consumed == 5:
+                break
+        await stream.aclose()
+
+    async def test_leaving_early_stops_the_encoder(self):
+        stream = toon_parser_async.aencode_stream(DATA, chunk_size=100, max_buffered=1)
+        async for _ in stream:
+            break
+        await stream.aclose()
+        self.assertTrue(self.encoder.finished)
+        self.assertLess(self.encoder.chunks, 10)
+
+    async def test_cancelling_the_consumer_stops_the_encoder(self):
+        async def consume():
+            async for _ in toon_parser_async.aencode_stream(DATA, chunk_size=100, max_buffered=1):
+                await asyncio.sleep(0.01)
+
+        task = asyncio.ensure_future(consume())
+        await asyncio.sleep(0.1)
+        task.cancel()
+        with self.assertRaises(asyncio.CancelledError):
+            await task
+        # The abandoned stream is closed by the event loop's finalizer
+        for _ in range(100):
+            if self.encoder.finished:
+                break
+            await asyncio.sleep(0.01)
+        self.assertTrue(self.encoder.finished)
+
+    async def test_encoding_errors_propagate(self):
+        with self.assertRaisesRegex(ValueError, "Cannot convert type 'object'"):
+            await self.collect({"x": object()})
+
+
+if __name__ == "__main__":
+    unittest.main()
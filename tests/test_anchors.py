@@ -0,0 +1,62 @@
+"""Anchors for repeated sub-objects (``encode(anchors=True)``, ``decode(anchors=True)``).
+
+Run with ``python -m unittest tests.test_anchors``.
+"""
+
+import unittest
+
+import toon_parser
+
+ADDRESS = {"street": "Main", "city": "Springfield"}
+
+
+def round_trip(data):
+    return toon_parser.decode(toon_parser.encode(data, anchors=True), anchors=True)
+
+
+class AnchorsTest(unittest.TestCase):
+    def test_repeats_become_references(self):
+        data = {"home": ADDRESS, "work": ADDRESS, "people": [{"n": 1, "a": ADDRESS}, {"n": 2, "a": ADDRESS}]}
+        text = toon_parser.encode(data, anchors=True)
+        self.assertEqual(text.count("Springfield"), 1)
+        self.assertIn('"&": a1', text)
+        self.assertIn("work: *a1", text)
+        self.assertIn("people[2]{n,a}:\n  1,*a1\n  2,*a1", text)
+        self.assertEqual(toon_parser.decode(text, anchors=True), data)
+
+    def test_small_objects_are_repeated(self):
+        data = {"a": {"x": 1}, "b": {"x": 1}}
+        self.assertEqual(toon_parser.encode(data, anchors=True), toon_parser.encode(data))
+
+    def test_without_anchors_on_decode_tags_stay(self):
+        text = toon_parser.encode({"home": ADDRESS, "work": ADDRESS}, anchors=True)
+        value = toon_parser.decode(text)
+        self.assertEqual(value["work"], "*a1")
+        self.assertEqual(value["home"]["&"], "a1")
+
+    def test_option_flag(self):
+        data = {"home": ADDRESS, "work": ADDRESS}
+        self.assertEqual(toon_parser.encode(data, option=toon_parser.OPT_ANCHORS), toon_parser.encode(data, anchors=True))
+
+    def test_unknown_references_are_left_alone(self):
+        self.assertEqual(toon_parser.decode("a: *nope\nb: *\n", anchors=True), {"a": "*nope", "b": "*"})
+
+    def test_data_that_looks_like_anchors(self):
+        cases = [
+            {"x": "*a1", "home": ADDRESS, "work": ADDRESS},
+            {"x": "**a1", "y": "*", "z": ["*a1", "&"]},
+            {"x": {"&": "q", "v": 1}, "&&": 2, "&x": 3},
+            {"home": {"&": "a1", "street": "Main", "c": "x" * 20}, "work": ADDRESS, "v": ADDRESS},
+            {"rows": [{"id": "*a1", "a": ADDRESS}, {"id": "*a2", "a": ADDRESS}]},
+        ]
+        for data in cases:
+            with self.subTest(data=data):
+                self.assertEqual(round_trip(data), data)
+
+    def test_self_reference_is_an_error(self):
+        with self.assertRaisesRegex(toon_parser.ToonError, "nest too deeply"):
+            toon_parser.decode('a:\n  "&": a1\n  b: *a1\n', anchors=True)
+
+
+if __name__ == "__main__":
+    unittest.main()
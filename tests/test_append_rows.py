@@ -0,0 +1,104 @@
+"""Appending rows to the table that ends a TOON file with ``append_rows``.
+
+Run with ``python -m unittest tests.test_append_rows``.
+"""
+
+import os
+import pathlib
+import tempfile
+import unittest
+
+import toon_parser
+from toon_parser import ToonIOError
+
+LOG = b"a: 1\nlogs[2]{ts,msg}:\n  1,a\n  2,b\n"
+
+
+class AppendRowsTest(unittest.TestCase):
+    def setUp(self):
+        self.dir = tempfile.TemporaryDirectory()
+        self.path = os.path.join(self.dir.name, "log.toon")
+
+    def tearDown(self):
+        self.dir.cleanup()
+
+    def append(self, content, rows, **kwargs):
+        with open(self.path, "wb") as f:
+            f.write(content)
+        length = toon_parser.append_rows(self.path, rows, **kwargs)
+        with open(self.path, "rb") as f:
+            return length, f.read()
+
+    def test_appends_and_raises_the_length(self):
+        length, content = self.append(LOG, [{"ts": 3, "msg": "c"}])
+        self.assertEqual(length, 3)
+        self.assertEqual(content, b"a: 1\nlogs[3]{ts,msg}:\n  1,a\n  2,b\n  3,c\n")
+        length, content = self.append(content, [{"ts": 4, "msg": "d"}, {"ts": 5, "msg": "e"}])
+        self.assertEqual(length, 5)
+        self.assertEqual(toon_parser.decode(content.decode())["logs"][-1], {"ts": 5, "msg": "e"})
+
+    def test_length_gaining_a_digit(self):
+        rows = b"".join(b"  %d,x\n" % i for i in range(9))
+        length, content = self.append(b"logs[9]{ts,msg}:\n" + rows, [{"msg": "y", "ts": 9}])
+        self.assertEqual(length, 10)
+        self.assertEqual(content, b"logs[10]{ts,msg}:\n" + rows + b"  9,y\n")
+
+    def test_table_layouts(self):
+        cases = [
+            (b"logs[1]{ts,msg}:\n  1,a\n\n\n", {"ts": 2, "msg": "c, d"}, b'logs[2]{ts,msg}:\n  1,a\n  2,"c, d"\n'),
+            (b"logs[1]{ts,msg}:\r\n  1,a\r\n", {"ts": 2, "msg": "c"}, b"logs[2]{ts,msg}:\r\n  1,a\r\n  2,c\r\n"),
+            (b"logs[1]{ts,msg}:\n  1,a", {"ts": 2, "msg": "c"}, b"logs[2]{ts,msg}:\n  1,a\n  2,c\n"),
+            (b"logs[1|]{ts|msg}:\n  1|a\n", {"ts": 2, "msg": "c|d"}, b'logs[2|]{ts|msg}:\n  1|a\n  2|"c|d"\n'),
+            (b"x:\n  logs[1]{ts,msg}:\n    1,a\n", {"ts": 2, "msg": "c"}, b"x:\n  logs[2]{ts,msg}:\n    1,a\n    2,c\n"),
+            (b'"my logs"[1]{ts,"m g"}:\n  1,a\n', {"ts": 2, "m g": "c"}, b'"my logs"[2]{ts,"m g"}:\n  1,a\n  2,c\n'),
+        ]
+        for content, row, expected in cases:
+            with self.subTest(content=content):
+                self.assertEqual(self.append(content, [row]), (2, expected))
+
+    def test_no_rows(self):
+        self.assertEqual(self.append(LOG, []), (2, LOG))
+
+    def test_path_like_and_exclusive(self):
+        with open(self.path, "wb") as f:
+            f.write(LOG)
+        self.assertEqual(toon_parser.append_rows(pathlib.Path(self.path), [{"ts": 3, "msg": "c"}], exclusive=True), 3)
+
+    def test_files_that_do_not_end_with_a_table(self):
+        cases = [
+            (b"a: 1\n", "the file does not end with a table"),
+            (b"", "the file does not end with a table"),
+            (b"xs[2]: 1,2\n", "the file does not end with a table"),
+            (b"logs[1]{ts,msg}:\n  1,a\nb: 2\n", "the file does not end with a table"),
+            (b"logs[1]{ts,msg}:\n  1,a\n# sha256:abc\n", "cannot append to a file with a checksum footer"),
+        ]
+        for content, message in cases:
+            with self.subTest(content=content):
+                with self.assertRaisesRegex(ValueError, message):
+                    self.append(content, [{"ts": 2, "msg": "c"}])
+                with open(self.path, "rb") as f:
+                    self.assertEqual(f.read(), content)
+
+    def test_rows_that_do_not_fit(self):
+        cases = [
+            ([{"ts": 2}], "row 0 has keys"),
+            ([{"ts": 2, "msg": "c", "x": 1}], "row 0 has keys"),
+            ([{"ts": 2, "msg": [1]}], "rows must hold only scalar values"),
+            ([5], "row 0 is not a dict"),
+        ]
+        for rows, message in cases:
+            with self.subTest(rows=rows):
+                with self.assertRaisesRegex(ValueError, message):
+                    self.append(LOG, rows)
+                with open(self.path, "rb") as f:
+                    self.assertEqual(f.read(), LOG)
+        with self.assertRaisesRegex(TypeError, "rows must be a list of dicts"):
+            self.append(LOG, {"ts": 2, "msg": "c"})
+
+    def test_missing_file(self):
+        with self.assertRaises(ToonIOError):
+            toon_parser.append_rows(os.path.join(self.dir.name, "missing.toon"), [])
+
+
+if __name__ == "__main__":
+    unittest.main()
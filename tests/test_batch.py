@@ -0,0 +1,240 @@
+"""Batch encoding and decoding: ``encode_batch``, ``decode_batch`` and friends.
+
+Run with ``python -m unittest tests.test_batch``.
+"""
+
+import unittest
+
+import toon_parser
+
+ROWS = [{"id": 1, "name": "Ann"}, {"id": 2, "name": "Bo"}]
+ENCODED = ["id: 1\nname: Ann", "id: 2\nname: Bo"]
+
+
+class EncodeBatchIterablesTest(unittest.TestCase):
+    def test_any_iterable(self):
+        cases = {
+            "list": lambda: list(ROWS),
+            "tuple": lambda: tuple(ROWS),
+            "generator": lambda: (row for row in ROWS),
+            "map": lambda: map(dict, ROWS),
+            "iterator": lambda: iter(ROWS),
+        }
+        for name, make in cases.items():
+            with self.subTest(name):
+                self.assertEqual(toon_parser.encode_batch(make()), ENCODED)
+
+    def test_empty(self):
+        self.assertEqual(toon_parser.encode_batch(iter([])), [])
+
+    def test_iterator_is_consumed_lazily(self):
+        seen = []
+
+        def rows():
+            for row in ROWS:
+                seen.append(row["id"])
+                yield row
+
+        gen = rows()
+        self.assertEqual(seen, [])
+        toon_parser.encode_batch(gen)
+        self.assertEqual(seen, [1, 2])
+        self.assertEqual(list(gen), [])
+
+    def test_single_objects_are_rejected(self):
+        for obj in ("ab", b"ab", bytearray(b"ab"), {"a": 1}):
+            with self.subTest(obj=obj):
+                with self.assertRaisesRegex(TypeError, "not a single '{}'; wrap it in a list".format(type(obj).__name__)):
+                    toon_parser.encode_batch(obj)
+
+    def test_non_iterables(self):
+        with self.assertRaises(TypeError):
+            toon_parser.encode_batch(5)
+
+    def test_errors_raised_by_the_iterator_propagate(self):
+        def rows():
+            yield ROWS[0]
+            raise RuntimeError("cursor closed")
+
+        with self.assertRaisesRegex(RuntimeError, "cursor closed"):
+            toon_parser.encode_batch(rows())
+
+
+class DecodeBatchIterTest(unittest.TestCase):
+    def test_yields_values_in_order(self):
+        it = toon_parser.decode_batch_iter(ENCODED + ["xs[2|]: 1|2"])
+        self.assertIs(iter(it), it)
+        self.assertEqual(list(it), ROWS + [{"xs": [1, 2]}])
+        self.assertEqual(list(toon_parser.decode_batch_iter(tuple(ENCODED))), ROWS)
+
+    def test_empty(self):
+        self.assertEqual(list(toon_parser.decode_batch_iter([])), [])
+
+    def test_malformed_string_raises_at_its_position(self):
+        it = toon_parser.decode_batch_iter(["a: 1", "xs[3]: 1,2", "a: 3"], strict=True)
+        self.assertEqual(next(it), {"a": 1})
+        with self.assertRaisesRegex(toon_parser.ToonSyntaxError, "^Line 1: "):
+            next(it)
+        self.assertEqual(next(it), {"a": 3})
+        with self.assertRaises(StopIteration):
+            next(it)
+
+    def test_limits_apply_per_string(self):
+        it = toon_parser.decode_batch_iter(["a: 1", "xs[2]: 1,2"], options=toon_parser.Options(max_array_length=1))
+        self.assertEqual(next(it), {"a": 1})
+        with self.assertRaisesRegex(toon_parser.ToonLimitError, "max_array_length=1"):
+            next(it)
+
+    def test_dropping_the_iterator_early(self):
+        it = toon_parser.decode_batch_iter(["a: 1"] * 1000)
+        self.assertEqual(next(it), {"a": 1})
+        del it
+
+
+BATCH = ["a: 1", "xs[3]: 1,2", "a: 3"]
+
+
+class DecodeBatchOnErrorTest(unittest.TestCase):
+    def test_raise_by_default(self):
+        with self.assertRaisesRegex(toon_parser.ToonSyntaxError, "^Line 1: "):
+            toon_parser.decode_batch(BATCH, strict=True)
+        self.assertEqual(toon_parser.decode_batch(ENCODED, on_error="raise"), ROWS)
+
+    def test_skip_and_none(self):
+        self.assertEqual(toon_parser.decode_batch(BATCH, strict=True, on_error="skip"), [{"a": 1}, {"a": 3}])
+        self.assertEqual(toon_parser.decode_batch(BATCH, strict=True, on_error="none"), [{"a": 1}, None, {"a": 3}])
+
+    def test_collect(self):
+        values, errors = toon_parser.decode_batch(BATCH, strict=True, on_error="collect")
+        self.assertEqual(values, [{"a": 1}, None, {"a": 3}])
+        self.assertEqual(len(errors), 1)
+        index, error = errors[0]
+        self.assertEqual(index, 1)
+        self.assertIsInstance(error, toon_parser.ToonSyntaxError)
+        self.assertEqual(toon_parser.decode_batch(ENCODED, on_error="collect"), (ROWS, []))
+
+    def test_limit_errors_are_per_item(self):
+        values, errors = toon_parser.decode_batch(
+            ["a: 1", "xs[2]: 1,2"], on_error="collect", options=toon_parser.Options(max_array_length=1)
+        )
+        self.assertEqual(values, [{"a": 1}, None])
+        self.assertEqual([(i, type(e)) for i, e in errors], [(1, toon_parser.ToonLimitError)])
+
+    def test_invalid_policy(self):
+        with self.assertRaisesRegex(ValueError, "Invalid on_error 'ignore'"):
+            toon_parser.decode_batch(BATCH, on_error="ignore")
+
+
+# The middle object cannot be encoded
+MIXED = [{"a": 1}, {"b": object()}, {"a": 3}]
+
+
+class EncodeBatchOnErrorTest(unittest.TestCase):
+    def test_raise_by_default(self):
+        with self.assertRaisesRegex(ValueError, "Cannot convert type 'object'"):
+            toon_parser.encode_batch(MIXED)
+
+    def test_skip_and_none(self):
+        self.assertEqual(toon_parser.encode_batch(MIXED, on_error="skip"), ["a: 1", "a: 3"])
+        self.assertEqual(toon_parser.encode_batch(MIXED, on_error="none"), ["a: 1", None, "a: 3"])
+
+    def test_collect(self):
+        encoded, errors = toon_parser.encode_batch(MIXED, on_error="collect")
+        self.assertEqual(encoded, ["a: 1", None, "a: 3"])
+        self.assertEqual([(i, type(e)) for i, e in errors], [(1, ValueError)])
+        self.assertEqual(toon_parser.encode_batch(ROWS, on_error="collect"), (ENCODED, []))
+
+    def test_generator_with_failures(self):
+        encoded = toon_parser.encode_batch((obj for obj in MIXED), on_error="skip")
+        self.assertEqual(encoded, ["a: 1", "a: 3"])
+
+    def test_invalid_policy(self):
+        with self.assertRaisesRegex(ValueError, "Invalid on_error 'ignore'"):
+            toon_parser.encode_batch(ROWS, on_error="ignore")
+
+
+class JoinTest(unittest.TestCase):
+    def test_array(self):
+        for join in (True, "array"):
+            with self.subTest(join=join):
+                self.assertEqual(toon_parser.encode_batch(ROWS, join=join), "[2]{id,name}:\n  1,Ann\n  2,Bo")
+        self.assertEqual(toon_parser.decode(toon_parser.encode_batch(ROWS, join=True)), ROWS)
+        self.assertEqual(toon_parser.encode_batch([], join=True), "[0]:")
+
+    def test_documents(self):
+        self.assertEqual(toon_parser.encode_batch(ROWS, join="documents"), "id: 1\nname: Ann\n---\nid: 2\nname: Bo")
+
+    def test_documents_round_trip(self):
+        cases = [
+            ROWS,
+            [{}, {"a": 1}],
+            ["---", {"a": "---"}],
+            [[1, 2], "x", 3, None],
+            [{"a": {"b": 1}}, {"c": [{"d": 1}, {"d": 2}]}],
+        ]
+        for objects in cases:
+            with self.subTest(objects=objects):
+                joined = toon_parser.encode_batch(objects, join="documents")
+                self.assertEqual(toon_parser.decode_documents(joined), objects)
+
+    def test_no_join(self):
+        for join in (False, None):
+            with self.subTest(join=join):
+                self.assertEqual(toon_parser.encode_batch(ROWS, join=join), ENCODED)
+
+    def test_failures_with_join(self):
+        self.assertEqual(toon_parser.encode_batch(MIXED, on_error="skip", join=True), "[2]{a}:\n  1\n  3")
+        joined, errors = toon_parser.encode_batch(MIXED, on_error="collect", join="documents")
+        self.assertEqual(joined, "a: 1\n---\nnull\n---\na: 3")
+        self.assertEqual([i for i, _ in errors], [1])
+
+    def test_invalid_join(self):
+        for join in ("x", 1):
+            with self.subTest(join=join):
+                with self.assertRaisesRegex(ValueError, "Invalid join"):
+                    toon_parser.encode_batch(ROWS, join=join)
+
+
+class DecodeDocumentsTest(unittest.TestCase):
+    def test_leading_separator_is_not_front_matter(self):
+        self.assertEqual(toon_parser.decode_documents("---\na: 1\n---\na: 2\n"), [{}, {"a": 1}, {"a": 2}])
+
+    def test_errors_count_lines_in_the_stream(self):
+        stream = "a: 1\n---\nb: 1\nxs[3]: 1,2\n"
+        with self.assertRaisesRegex(toon_parser.ToonSyntaxError, "^Line 4: "):
+            toon_parser.decode_documents(stream, strict=True)
+
+    def test_on_error(self):
+        stream = "a: 1\n---\nxs[3]: 1,2\n---\nb: 2\n"
+        values, errors = toon_parser.decode_documents(stream, strict=True, on_error="collect")
+        self.assertEqual(values, [{"a": 1}, None, {"b": 2}])
+        self.assertEqual([i for i, _ in errors], [1])
+        self.assertEqual(toon_parser.decode_documents(stream, strict=True, on_error="skip"), [{"a": 1}, {"b": 2}])
+
+    def test_limits_cover_the_whole_stream(self):
+        with self.assertRaises(toon_parser.ToonLimitError):
+            toon_parser.decode_documents("a: 1\n---\na: 2\n", options=toon_parser.Options(max_input_bytes=8))
+
+
+class UniformRecordsTest(unittest.TestCase):
+    """Batches of records with shared keys encode exactly like single calls."""
+
+    def assert_matches_encode(self, objects):
+        self.assertEqual(toon_parser.encode_batch(objects), [toon_parser.encode(obj) for obj in objects])
+
+    def test_uniform_records(self):
+        self.assert_matches_encode([{"id": i, "name": "n{}".format(i), "tags": ["a", "b"]} for i in range(100)])
+
+    def test_same_keys_in_another_order(self):
+        self.assert_matches_encode([{"a": 1, "b": 2}, {"b": 3, "a": 4}])
+
+    def test_records_that_break_the_pattern(self):
+        self.assert_matches_encode([{"a": 1, "b": 2}, {"a": 1}, {"a": 1, "b": 2, "c": 3}, {1: "x", 2: "y"}, [1, 2], "x"])
+
+    def test_table_from_uniform_records(self):
+        rows = [{"id": i, "ok": i % 2 == 0} for i in range(3)]
+        self.assertEqual(toon_parser.encode_batch(rows, join=True), "[3]{id,ok}:\n  0,true\n  1,false\n  2,true")
+
+
+if __name__ == "__main__":
+    unittest.main()
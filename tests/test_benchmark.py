@@ -0,0 +1,55 @@
+"""Throughput measurement with ``benchmark``.
+
+Timings vary from run to run, so these tests check the report's shape and
+the figures that do not depend on the clock.
+
+Run with ``python -m unittest tests.test_benchmark``.
+"""
+
+import json
+import unittest
+
+import toon_parser
+
+DATA = {"users": [{"id": i, "name": "user{}".format(i), "active": i % 2 == 0} for i in range(50)]}
+
+
+class BenchmarkTest(unittest.TestCase):
+    def test_report(self):
+        report = toon_parser.benchmark(DATA, iterations=3)
+        self.assertEqual(set(report), {"iterations", "toon", "json", "speedup"})
+        self.assertEqual(report["iterations"], 3)
+        for side in ("toon", "json"):
+            with self.subTest(side=side):
+                entry = report[side]
+                self.assertEqual(
+                    set(entry), {"bytes", "encode_seconds", "decode_seconds", "encode_mb_s", "decode_mb_s"}
+                )
+                for op in ("encode", "decode"):
+                    self.assertGreater(entry[op + "_seconds"], 0)
+                    self.assertAlmostEqual(entry[op + "_mb_s"], entry["bytes"] / entry[op + "_seconds"] / 1e6)
+        self.assertAlmostEqual(
+            report["speedup"]["encode"], report["json"]["encode_seconds"] / report["toon"]["encode_seconds"]
+        )
+        self.assertAlmostEqual(
+            report["speedup"]["decode"], report["json"]["decode_seconds"] / report["toon"]["decode_seconds"]
+        )
+
+    def test_sizes_are_those_of_the_encodings(self):
+        report = toon_parser.benchmark(DATA, iterations=1)
+        self.assertEqual(report["toon"]["bytes"], len(toon_parser.encode(DATA).encode("utf-8")))
+        self.assertEqual(report["json"]["bytes"], len(json.dumps(DATA).encode("utf-8")))
+
+    def test_options_apply_to_the_toon_side(self):
+        report = toon_parser.benchmark([1, 2], iterations=1, options=toon_parser.Options(delimiter="pipe"))
+        self.assertEqual(report["toon"]["bytes"], len("[2|]: 1|2"))
+
+    def test_invalid_arguments(self):
+        with self.assertRaisesRegex(ValueError, "at least 1"):
+            toon_parser.benchmark(DATA, iterations=0)
+        with self.assertRaisesRegex(ValueError, "Cannot convert type 'set'"):
+            toon_parser.benchmark({1, 2}, iterations=1)
+
+
+if __name__ == "__main__":
+    unittest.main()
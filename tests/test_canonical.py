@@ -0,0 +1,94 @@
+"""Comparing documents by content: ``content_hash``, ``deep_equal`` and ``normalize``.
+
+Run with ``python -m unittest tests.test_canonical``.
+"""
+
+import hashlib
+import unittest
+
+import toon_parser
+
+DATA = {"b": 1, "a": [1, 2], "c": {"z": 1, "y": None}}
+
+
+class ContentHashTest(unittest.TestCase):
+    def test_sha256_hex_digest(self):
+        digest = toon_parser.content_hash(DATA)
+        self.assertEqual(len(digest), 64)
+        int(digest, 16)
+
+    def test_ignores_key_order_and_number_spelling(self):
+        digest = toon_parser.content_hash(DATA)
+        self.assertEqual(toon_parser.content_hash({"c": {"y": None, "z": 1.0}, "a": [1, 2.0], "b": 1}), digest)
+
+    def test_accepts_toon_text_and_bytes(self):
+        digest = toon_parser.content_hash(DATA)
+        text = "c:\n  z: 1\n  y: null\nb: 1\na[2]: 1,2\n"
+        self.assertEqual(toon_parser.content_hash(text), digest)
+        self.assertEqual(toon_parser.content_hash(text.encode()), digest)
+
+    def test_different_data_hashes_differently(self):
+        self.assertNotEqual(toon_parser.content_hash({"a": [2, 1]}), toon_parser.content_hash({"a": [1, 2]}))
+        self.assertNotEqual(toon_parser.content_hash({"a": "1"}), toon_parser.content_hash({"a": 1}))
+
+    def test_hashes_the_canonical_encoding(self):
+        canonical = "a[2]: 1,2\nb: 1\nc:\n  y: null\n  z: 1"
+        for algorithm in ("sha224", "sha256", "sha384", "sha512"):
+            with self.subTest(algorithm=algorithm):
+                self.assertEqual(
+                    toon_parser.content_hash(DATA, algorithm=algorithm),
+                    hashlib.new(algorithm, canonical.encode()).hexdigest(),
+                )
+
+    def test_unsupported_algorithm(self):
+        with self.assertRaisesRegex(ValueError, "Unsupported hash algorithm 'md5'"):
+            toon_parser.content_hash(DATA, algorithm="md5")
+
+
+class DeepEqualTest(unittest.TestCase):
+    def test_text_against_data(self):
+        self.assertTrue(toon_parser.deep_equal("a: 1\nb: 2", {"b": 2, "a": 1.0}))
+        self.assertTrue(toon_parser.deep_equal({"a": [1, {"b": 2}]}, "a[2]:\n  - 1\n  - b: 2"))
+        self.assertTrue(toon_parser.deep_equal("a: x", b"a: x"))
+
+    def test_differences(self):
+        self.assertFalse(toon_parser.deep_equal({"a": 1}, {"a": 1, "b": 2}))
+        self.assertFalse(toon_parser.deep_equal({"a": 1, "b": 2}, {"a": 1}))
+        self.assertFalse(toon_parser.deep_equal([1, 2], [2, 1]))
+        self.assertFalse(toon_parser.deep_equal({"a": True}, {"a": 1}))
+        self.assertFalse(toon_parser.deep_equal({"a": None}, {}))
+
+    def test_float_tolerance(self):
+        self.assertFalse(toon_parser.deep_equal({"a": 1.0}, {"a": 1.05}))
+        self.assertTrue(toon_parser.deep_equal({"a": 1.0}, {"a": 1.05}, float_tolerance=0.1))
+        self.assertFalse(toon_parser.deep_equal({"a": 1.0}, {"a": 1.2}, float_tolerance=0.1))
+
+    def test_key_order(self):
+        a, b = {"x": {"a": 1, "b": 2}}, {"x": {"b": 2, "a": 1}}
+        self.assertTrue(toon_parser.deep_equal(a, b))
+        self.assertFalse(toon_parser.deep_equal(a, b, ignore_key_order=False))
+        self.assertTrue(toon_parser.deep_equal(a, dict(a), ignore_key_order=False))
+
+
+class NormalizeTest(unittest.TestCase):
+    def test_sorts_keys_and_columns(self):
+        doc = "b: 2.0\nc[2|]{y|x}:\n  1|2\n  3|4\na:\n    k: v\n"
+        self.assertEqual(toon_parser.normalize(doc), "a:\n  k: v\nb: 2\nc[2]{x,y}:\n  2,1\n  4,3")
+
+    def test_is_idempotent(self):
+        once = toon_parser.normalize("b: 2.0\na[2]: x,y\n")
+        self.assertEqual(toon_parser.normalize(once), once)
+
+    def test_equal_data_normalizes_identically(self):
+        self.assertEqual(
+            toon_parser.normalize(toon_parser.encode({"b": 1, "a": [1.0, 2]})),
+            toon_parser.normalize("a[2]: 1,2\nb: 1.0\n"),
+        )
+
+    def test_strict_parsing(self):
+        with self.assertRaises(toon_parser.ToonSyntaxError):
+            toon_parser.normalize("a:\n   b: 1\n", strict=True)
+
+
+if __name__ == "__main__":
+    unittest.main()
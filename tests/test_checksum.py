@@ -0,0 +1,52 @@
+"""Checksum footers (``encode(checksum=True)``, ``decode(verify_checksum=True)``).
+
+Run with ``python -m unittest tests.test_checksum``.
+"""
+
+import hashlib
+import unittest
+
+import toon_parser
+from toon_parser import ToonChecksumError, ToonError
+
+DATA = {"a": 1, "b": [1, 2]}
+
+
+class ChecksumTest(unittest.TestCase):
+    def test_footer_covers_the_text_above_it(self):
+        text = toon_parser.encode(DATA, checksum=True)
+        body, footer = text.rsplit("# sha256:", 1)
+        self.assertEqual(body, "a: 1\nb[2]: 1,2\n")
+        self.assertEqual(footer, hashlib.sha256(body.encode()).hexdigest() + "\n")
+
+    def test_verified_decode(self):
+        self.assertEqual(toon_parser.decode(toon_parser.encode(DATA, checksum=True), verify_checksum=True), DATA)
+
+    def test_footer_is_ignored_without_verification(self):
+        text = toon_parser.encode(DATA, checksum=True).replace("a: 1", "a: 2")
+        self.assertEqual(toon_parser.decode(text), {"a": 2, "b": [1, 2]})
+
+    def test_tampered_content(self):
+        text = toon_parser.encode(DATA, checksum=True).replace("a: 1", "a: 2")
+        with self.assertRaisesRegex(ToonChecksumError, "Checksum mismatch: footer records"):
+            toon_parser.decode(text, verify_checksum=True)
+
+    def test_tampered_footer(self):
+        text = toon_parser.encode(DATA, checksum=True).replace("sha256:", "sha256:0")
+        with self.assertRaises(ToonChecksumError):
+            toon_parser.decode(text, verify_checksum=True)
+
+    def test_missing_footer(self):
+        with self.assertRaisesRegex(ToonChecksumError, "no checksum footer"):
+            toon_parser.decode("a: 1\n", verify_checksum=True)
+
+    def test_error_is_a_toon_error(self):
+        self.assertTrue(issubclass(ToonChecksumError, ToonError))
+
+    def test_uppercase_digest_is_accepted(self):
+        body, digest = toon_parser.encode(DATA, checksum=True).rsplit("# sha256:", 1)
+        self.assertEqual(toon_parser.decode(body + "# sha256:" + digest.upper(), verify_checksum=True), DATA)
+
+
+if __name__ == "__main__":
+    unittest.main()
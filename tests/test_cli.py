@@ -0,0 +1,212 @@
+"""The ``toon`` command line tool (``toon_parser_cli``).
+
+Run with ``python -m unittest tests.test_cli``.
+"""
+
+import contextlib
+import gzip
+import io
+import json
+import os
+import sys
+import tempfile
+import unittest
+from unittest import mock
+
+import toon_parser_cli
+
+USERS_JSON = '{"users":[{"id":1,"name":"Ann"},{"id":2,"name":"Bo"}]}'
+USERS_TOON = "users[2]{id,name}:\n  1,Ann\n  2,Bo\n"
+
+
+def run(*argv, stdin=""):
+    """Run the tool; return its exit status, stdout and stderr."""
+    out, err = io.StringIO(), io.StringIO()
+    fake_stdin = io.TextIOWrapper(io.BytesIO(stdin.encode("utf-8")), encoding="utf-8")
+    with mock.patch.object(sys, "stdin", fake_stdin), contextlib.redirect_stdout(out), contextlib.redirect_stderr(err):
+        status = toon_parser_cli.main(list(argv))
+    return status, out.getvalue(), err.getvalue()
+
+
+class CliTest(unittest.TestCase):
+    def setUp(self):
+        self._dir = tempfile.TemporaryDirectory()
+        self.addCleanup(self._dir.cleanup)
+
+    def path(self, name, content=None):
+        path = os.path.join(self._dir.name, name)
+        if content is not None:
+            mode = "wb" if isinstance(content, bytes) else "w"
+            with open(path, mode) as f:
+                f.write(content)
+        return path
+
+    def read(self, name):
+        with open(self.path(name), encoding="utf-8") as f:
+            return f.read()
+
+
+class ConvertTest(CliTest):
+    def test_json_file_to_toon(self):
+        self.assertEqual(run("convert", self.path("a.json", USERS_JSON)), (0, USERS_TOON, ""))
+
+    def test_toon_file_to_json(self):
+        status, out, _ = run("convert", self.path("a.toon", USERS_TOON))
+        self.assertEqual(status, 0)
+        self.assertEqual(json.loads(out), json.loads(USERS_JSON))
+
+    def test_pretty_json(self):
+        _, out, _ = run("convert", self.path("a.toon", USERS_TOON), "--pretty")
+        self.assertIn('\n  "users": [\n', out)
+
+    def test_output_file(self):
+        self.assertEqual(run("convert", self.path("a.json", USERS_JSON), "-o", self.path("b.toon"))[0], 0)
+        self.assertEqual(self.read("b.toon"), USERS_TOON.rstrip("\n"))
+
+    def test_gzip_input(self):
+        _, out, _ = run("convert", self.path("a.toon.gz", gzip.compress(USERS_TOON.encode())))
+        self.assertEqual(json.loads(out), json.loads(USERS_JSON))
+
+    def test_stdin_direction_from_content(self):
+        self.assertEqual(run("convert", stdin=USERS_JSON)[1], USERS_TOON)
+        self.assertEqual(json.loads(run("convert", "-", stdin=USERS_TOON)[1]), json.loads(USERS_JSON))
+
+    def test_explicit_source_format(self):
+        self.assertEqual(run("convert", self.path("a.txt", USERS_JSON), "--from", "json")[1], USERS_TOON)
+
+    def test_delimiter(self):
+        self.assertEqual(run("convert", "--delimiter", "pipe", stdin=USERS_JSON)[1], "users[2|]{id|name}:\n  1|Ann\n  2|Bo\n")
+
+    def test_errors_exit_1(self):
+        status, out, err = run("convert", self.path("missing.json"))
+        self.assertEqual((status, out), (1, ""))
+        self.assertTrue(err.startswith("toon: "))
+        self.assertEqual(run("convert", "--from", "json", stdin="{bad")[0], 1)
+
+
+class FmtTest(CliTest):
+    def test_rewrites_in_canonical_form(self):
+        path = self.path("a.toon", "b: 1\na: 2.0\n")
+        self.assertEqual(run("fmt", path), (0, "", "reformatted {}\n".format(path)))
+        self.assertEqual(self.read("a.toon"), "a: 2\nb: 1\n")
+
+    def test_formatted_files_are_left_alone(self):
+        path = self.path("a.toon", "a: 2\nb: 1\n")
+        self.assertEqual(run("fmt", path), (0, "", ""))
+        self.assertEqual(run("fmt", "--check", path), (0, "", ""))
+
+    def test_missing_final_newline_is_added(self):
+        path = self.path("a.toon", "a: 2\nb: 1")
+        run("fmt", path)
+        self.assertEqual(self.read("a.toon"), "a: 2\nb: 1\n")
+
+    def test_check_reports_without_writing(self):
+        changed = self.path("a.toon", "b: 1\na: 2\n")
+        clean = self.path("b.toon", "a: 2\nb: 1\n")
+        self.assertEqual(run("fmt", "--check", changed, clean), (1, "", "would reformat {}\n".format(changed)))
+        self.assertEqual(self.read("a.toon"), "b: 1\na: 2\n")
+
+    def test_stdin_to_stdout(self):
+        self.assertEqual(run("fmt", "-", stdin="b: 1\na: 2\n"), (0, "a: 2\nb: 1\n", ""))
+
+    def test_syntax_error(self):
+        status, _, err = run("fmt", "--strict", self.path("a.toon", "a:\n   b: 1\n"))
+        self.assertEqual(status, 1)
+        self.assertIn("indentation", err)
+
+
+class ValidateTest(CliTest):
+    def test_clean_files(self):
+        self.assertEqual(run("validate", self.path("a.toon", USERS_TOON)), (0, "", ""))
+
+    def test_warnings_do_not_fail(self):
+        path = self.path("a.toon", "a: 1 \n")
+        self.assertEqual(run("validate", path), (0, "{}:1:5: warning: trailing whitespace\n".format(path), ""))
+
+    def test_strict_fails_on_style(self):
+        path = self.path("a.toon", "a: 1 \n")
+        self.assertEqual(run("validate", "--strict", path), (1, "{}:1:5: error: trailing whitespace\n".format(path), ""))
+
+    def test_errors_across_files(self):
+        bad = self.path("bad.toon", "xs[3]: 1,2\n")
+        status, out, _ = run("lint", self.path("a.toon", USERS_TOON), bad)
+        self.assertEqual(status, 1)
+        self.assertEqual(out, "{}:1:3: error: header declares 3 values but 2 found\n".format(bad))
+
+    def test_stdin_and_unknown_column(self):
+        self.assertEqual(run("validate", "-", stdin='a: "open\n'), (1, "<stdin>:1: error: unterminated string\n", ""))
+
+
+ROWS_TOON = "rows[3]{id,status}:\n  1,ok\n  2,failed\n  3,failed\nname: x\n"
+
+
+class GetTest(CliTest):
+    def test_scalar(self):
+        self.assertEqual(run("get", self.path("a.toon", ROWS_TOON), "rows[1].status"), (0, "failed\n", ""))
+
+    def test_value_as_toon_or_json(self):
+        path = self.path("a.toon", ROWS_TOON)
+        self.assertEqual(run("get", path, "rows[1]"), (0, "id: 2\nstatus: failed\n", ""))
+        status, out, _ = run("get", path, "rows[1]", "--format", "json")
+        self.assertEqual((status, json.loads(out)), (0, {"id": 2, "status": "failed"}))
+
+    def test_stdin(self):
+        self.assertEqual(run("get", "-", "name", stdin=ROWS_TOON), (0, "x\n", ""))
+
+    def test_missing_path(self):
+        self.assertEqual(run("get", "-", "rows[9]", stdin=ROWS_TOON), (1, "", "toon: '$.rows[9]' not found\n"))
+
+    def test_several_values(self):
+        status, _, err = run("get", "-", "rows[*]", stdin=ROWS_TOON)
+        self.assertEqual(status, 1)
+        self.assertIn("use query()", err)
+
+
+class QueryTest(CliTest):
+    def test_filter_as_toon(self):
+        status, out, _ = run("query", self.path("a.toon", ROWS_TOON), '$.rows[?(@.status=="failed")]')
+        self.assertEqual((status, out), (0, "[2]{id,status}:\n  2,failed\n  3,failed\n"))
+
+    def test_json(self):
+        status, out, _ = run("query", "-", '$.rows[?(@.status=="failed")].id', "--format", "json", stdin=ROWS_TOON)
+        self.assertEqual((status, json.loads(out)), (0, [2, 3]))
+
+    def test_no_match(self):
+        self.assertEqual(run("query", "-", "$.nothing", stdin=ROWS_TOON), (0, "[0]:\n", ""))
+
+    def test_malformed_expression(self):
+        status, _, err = run("query", "-", "$.[", stdin=ROWS_TOON)
+        self.assertEqual(status, 1)
+        self.assertIn("expected a name after '.'", err)
+
+
+class DiffTest(CliTest):
+    OLD = "a: 1\nb: x\nc[2]: 1,2\n"
+    NEW = "a: 2\nc[1]: 1\nd: true\n"
+
+    def test_identical_files(self):
+        self.assertEqual(run("diff", self.path("a.toon", self.OLD), self.path("b.toon", self.OLD)), (0, "", ""))
+
+    def test_text(self):
+        status, out, _ = run("diff", self.path("a.toon", self.OLD), self.path("b.toon", self.NEW))
+        self.assertEqual(status, 1)
+        self.assertEqual(out, '~ $.a: 1 -> 2\n- $.b: "x"\n- $.c[1]: 2\n+ $.d: true\n')
+
+    def test_color(self):
+        old, new = self.path("a.toon", "a: 1\n"), self.path("b.toon", "b: 1\n")
+        _, out, _ = run("diff", old, new, "--color", "always")
+        self.assertEqual(out, "\033[31m- $.a: 1\033[0m\n\033[32m+ $.b: 1\033[0m\n")
+        self.assertEqual(run("diff", old, new, "--color", "never")[1], "- $.a: 1\n+ $.b: 1\n")
+        self.assertEqual(run("diff", old, new)[1], "- $.a: 1\n+ $.b: 1\n")
+
+    def test_json(self):
+        status, out, _ = run("diff", "-", self.path("b.toon", "a: 2\n"), "--format", "json", stdin="a: 1\n")
+        self.assertEqual((status, json.loads(out)), (1, [{"op": "changed", "path": "$.a", "old": 1, "new": 2}]))
+        self.assertEqual(run("diff", "-", self.path("b.toon", "a: 1\n"), "--format", "json", stdin="a: 1\n")[:2], (0, "[]\n"))
+
+    def test_stdin_only_once(self):
+        self.assertEqual(run("diff", "-", "-", stdin="a: 1\n"), (1, "", "toon: only one of OLD and NEW can be '-'\n"))
+
+
+if __name__ == "__main__":
+    unittest.main()
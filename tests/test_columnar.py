@@ -0,0 +1,109 @@
+"""Tables as dicts of columns: ``decode_columns`` and ``encode_columns``.
+
+Run with ``python -m unittest tests.test_columnar``.
+"""
+
+import unittest
+
+import toon_parser
+
+
+class DecodeColumnsTest(unittest.TestCase):
+    def test_table(self):
+        self.assertEqual(
+            toon_parser.decode_columns("users[2]{id,name}:\n  1,Ada\n  2,Bob"),
+            {"id": [1, 2], "name": ["Ada", "Bob"]},
+        )
+
+    def test_missing_cells_are_none(self):
+        self.assertEqual(
+            toon_parser.decode_columns("[2]:\n  - a: 1\n  - b: 2\n"),
+            {"a": [1, None], "b": [None, 2]},
+        )
+
+    def test_only_list_of_a_root_dict(self):
+        self.assertEqual(toon_parser.decode_columns("meta:\n  n: 1\nrows[1]{x}:\n  1\n"), {"x": [1]})
+
+    def test_empty_table(self):
+        self.assertEqual(toon_parser.decode_columns("rows[0]:\n"), {})
+
+    def test_path(self):
+        doc = "a:\n  rows[2]{x}:\n    1\n    2\nb[1]: 2\n"
+        self.assertEqual(toon_parser.decode_columns(doc, path="a.rows"), {"x": [1, 2]})
+        self.assertEqual(toon_parser.decode_columns(doc, path="$.a.rows"), {"x": [1, 2]})
+
+    def test_no_single_table(self):
+        cases = [
+            ("a: 1", "no table"),
+            ("5", "no table"),
+            ("a[1]: 1\nb[1]: 2", "several tables"),
+            ("xs[2]: 1,2", "row 0 is not an object"),
+        ]
+        for doc, message in cases:
+            with self.subTest(doc=doc):
+                with self.assertRaisesRegex(ValueError, message):
+                    toon_parser.decode_columns(doc)
+
+    def test_bad_paths(self):
+        with self.assertRaises(KeyError):
+            toon_parser.decode_columns("a: 1", path="b")
+        with self.assertRaisesRegex(ValueError, "'a' is not a table"):
+            toon_parser.decode_columns("a: 1", path="a")
+        with self.assertRaisesRegex(ValueError, "several values"):
+            toon_parser.decode_columns("a[1]: 1\nb[1]: 2", path="a[*]")
+        with self.assertRaises(ValueError):
+            toon_parser.decode_columns("a: 1", path="a[")
+
+    def test_options(self):
+        self.assertEqual(toon_parser.decode_columns("rows[1|]{a|b}:\n  1|2\n", delimiter="pipe"), {"a": [1], "b": [2]})
+        with self.assertRaises(toon_parser.ToonSyntaxError):
+            toon_parser.decode_columns("rows[2]{a}:\n  1\n", strict=True)
+        with self.assertRaises(toon_parser.ToonLimitError):
+            toon_parser.decode_columns("rows[1]{a}:\n  1\n", options=toon_parser.Options(max_input_bytes=4))
+
+
+class EncodeColumnsTest(unittest.TestCase):
+    def test_table(self):
+        self.assertEqual(
+            toon_parser.encode_columns({"id": [1, 2], "name": ["Ada", "Bob"]}, name="users"),
+            "users[2]{id,name}:\n  1,Ada\n  2,Bob",
+        )
+
+    def test_root_table_from_tuples(self):
+        self.assertEqual(
+            toon_parser.encode_columns({"id": (1, 2), "note": [None, "a,b"]}),
+            '[2]{id,note}:\n  1,null\n  2,"a,b"',
+        )
+
+    def test_same_as_encoding_the_rows(self):
+        columns = {"id": [1, 2, 3], "ok": [True, False, None], "score": [1.5, 2, -3]}
+        rows = [dict(zip(columns, cells)) for cells in zip(*columns.values())]
+        self.assertEqual(toon_parser.encode_columns(columns), toon_parser.encode(rows))
+        self.assertEqual(toon_parser.encode_columns(columns, name="t"), toon_parser.encode({"t": rows}))
+
+    def test_round_trip(self):
+        columns = {"id": [1, 2], "name": ["Ada", "Bob"]}
+        self.assertEqual(toon_parser.decode_columns(toon_parser.encode_columns(columns, name="users")), columns)
+
+    def test_empty(self):
+        self.assertEqual(toon_parser.encode_columns({}), "[0]:")
+        self.assertEqual(toon_parser.encode_columns({"a": []}, name="e"), "e[0]:")
+
+    def test_delimiter(self):
+        self.assertEqual(toon_parser.encode_columns({"a": [1, 2], "b": [3, 4]}, delimiter="pipe"), "[2|]{a|b}:\n  1|3\n  2|4")
+
+    def test_invalid_columns(self):
+        cases = [
+            ({"a": [1], "b": [1, 2]}, "column 'b' has 2 values, but 'a' has 1"),
+            ({"a": 5}, "column 'a' is not a list"),
+            ({"a": "xy"}, "column 'a' is not a list"),
+        ]
+        for columns, message in cases:
+            with self.subTest(columns=columns):
+                with self.assertRaisesRegex(ValueError, message):
+                    toon_parser.encode_columns(columns)
+        self.assertRaises(TypeError, toon_parser.encode_columns, [("a", [1])])
+
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,66 @@
+"""Column projection: ``decode(..., columns=[...])``.
+
+Run with ``python -m unittest tests.test_columns``.
+"""
+
+import unittest
+
+import toon_parser
+
+
+class ColumnProjectionTest(unittest.TestCase):
+    def test_keeps_listed_fields(self):
+        doc = "users[2]{id,name,score}:\n  1,Ann,9.5\n  2,Bo,7\n"
+        self.assertEqual(
+            toon_parser.decode(doc, columns=["id", "score"]),
+            {"users": [{"id": 1, "score": 9.5}, {"id": 2, "score": 7}]},
+        )
+
+    def test_quoted_cells_containing_the_delimiter(self):
+        doc = 'rows[2]{id,note,tag}:\n  1,"a, b",x\n  2,"say \\"hi\\", ok",y\n'
+        self.assertEqual(
+            toon_parser.decode(doc, columns=["note", "tag"]),
+            {"rows": [{"note": "a, b", "tag": "x"}, {"note": 'say "hi", ok', "tag": "y"}]},
+        )
+
+    def test_quoted_cells_containing_a_pipe_delimiter(self):
+        doc = 'rows[1|]{id|note}:\n  1|"a|b"\n'
+        self.assertEqual(toon_parser.decode(doc, columns=["note"]), {"rows": [{"note": "a|b"}]})
+
+    def test_multiple_tables(self):
+        doc = "a[1]{id,x}:\n  1,2\nb[2]{id,y}:\n  3,4\n  5,6\n"
+        self.assertEqual(
+            toon_parser.decode(doc, columns=["id"]),
+            {"a": [{"id": 1}], "b": [{"id": 3}, {"id": 5}]},
+        )
+
+    def test_nested_tables(self):
+        doc = "outer:\n  inner[2]{id,name}:\n    1,Ann\n    2,Bo\n  other: 1\n"
+        self.assertEqual(
+            toon_parser.decode(doc, columns=["name"]),
+            {"outer": {"inner": [{"name": "Ann"}, {"name": "Bo"}], "other": 1}},
+        )
+
+    def test_tables_in_list_items(self):
+        doc = "groups[2]:\n  - rows[1]{id,name}:\n      1,Ann\n  - rows[1]{id,name}:\n      2,Bo\n"
+        self.assertEqual(
+            toon_parser.decode(doc, columns=["id"]),
+            {"groups": [{"rows": [{"id": 1}]}, {"rows": [{"id": 2}]}]},
+        )
+
+    def test_table_without_listed_fields(self):
+        doc = "users[2]{id,name}:\n  1,Ann\n  2,Bo\n"
+        self.assertEqual(toon_parser.decode(doc, columns=["score"]), {"users": [{}, {}]})
+
+    def test_non_tabular_data_is_unaffected(self):
+        doc = "name: Ann\ntags[2]: a,b\n"
+        self.assertEqual(toon_parser.decode(doc, columns=["id"]), {"name": "Ann", "tags": ["a", "b"]})
+
+    def test_row_length_errors_point_at_the_document(self):
+        doc = "users[2]{id,name}:\n  1,Ann\n  2\n"
+        with self.assertRaisesRegex(toon_parser.ToonSyntaxError, "[Ll]ine 3"):
+            toon_parser.decode(doc, columns=["id"])
+
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,49 @@
+"""Merging several documents into one (``concat``).
+
+Run with ``python -m unittest tests.test_concat``.
+"""
+
+import unittest
+
+import toon_parser
+
+
+class AppendTablesTest(unittest.TestCase):
+    def test_tables_are_concatenated(self):
+        self.assertEqual(toon_parser.concat(["rows[1]{id}:\n  1", "rows[1]{id}:\n  2"]), "rows[2]{id}:\n  1\n  2")
+
+    def test_mixes_text_bytes_and_data(self):
+        merged = toon_parser.concat([{"rows": [{"id": 1}], "a": {"x": 1}}, b"rows[1]{id}:\n  2\na:\n  y: 2"])
+        self.assertEqual(toon_parser.decode(merged), {"rows": [{"id": 1}, {"id": 2}], "a": {"x": 1, "y": 2}})
+
+    def test_root_arrays(self):
+        self.assertEqual(toon_parser.decode(toon_parser.concat([[1], [2, 3]])), [1, 2, 3])
+
+    def test_later_scalars_win(self):
+        self.assertEqual(toon_parser.decode(toon_parser.concat([{"s": 1}, {"s": 2}])), {"s": 2})
+
+    def test_mismatched_columns(self):
+        with self.assertRaisesRegex(ValueError, r"\$\.rows: cannot append tables with different columns"):
+            toon_parser.concat(["rows[1]{id}:\n  1", "rows[1]{x}:\n  2"])
+
+    def test_any_iterable_and_delimiter(self):
+        docs = iter(["rows[1]{id,n}:\n  1,a", "rows[1]{id,n}:\n  2,b"])
+        self.assertEqual(toon_parser.concat(docs, delimiter="pipe"), "rows[2|]{id|n}:\n  1|a\n  2|b")
+
+
+class DeepMergeTest(unittest.TestCase):
+    def test_arrays_are_replaced(self):
+        docs = [{"a": {"x": 1, "l": [1]}, "s": 1}, {"a": {"y": 2, "l": [2]}, "s": 2}]
+        self.assertEqual(
+            toon_parser.decode(toon_parser.concat(docs, strategy="deep_merge")),
+            {"a": {"x": 1, "l": [2], "y": 2}, "s": 2},
+        )
+        self.assertEqual(toon_parser.decode(toon_parser.concat(docs))["a"]["l"], [1, 2])
+
+    def test_unknown_strategy(self):
+        with self.assertRaisesRegex(ValueError, "Invalid strategy 'x'"):
+            toon_parser.concat([], strategy="x")
+
+
+if __name__ == "__main__":
+    unittest.main()
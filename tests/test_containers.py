@@ -0,0 +1,245 @@
+"""Container types chosen by ``decode``'s output options.
+
+Run with ``python -m unittest tests.test_containers``.
+"""
+
+import collections
+import collections.abc
+import copy
+import dataclasses
+import pickle
+import types
+import unittest
+
+import toon_parser
+
+DOC = "xs[2]: 1,2\nrows[2]{a}:\n  1\n  2\nnested[2]:\n  - [2]: 1,2\n  - x\ne[0]:\n"
+
+
+class TupleArraysTest(unittest.TestCase):
+    def test_every_array_is_a_tuple(self):
+        self.assertEqual(
+            toon_parser.decode(DOC, arrays="tuple"),
+            {"xs": (1, 2), "rows": ({"a": 1}, {"a": 2}), "nested": ((1, 2), "x"), "e": ()},
+        )
+
+    def test_root_array(self):
+        self.assertEqual(toon_parser.decode("[2]: 1,2", arrays="tuple"), (1, 2))
+
+    def test_scalar_arrays_are_hashable(self):
+        value = toon_parser.decode(DOC, arrays="tuple")
+        self.assertEqual({value["xs"], value["nested"]}, {(1, 2), ((1, 2), "x")})
+
+    def test_list_is_the_default(self):
+        self.assertEqual(toon_parser.decode(DOC), toon_parser.decode(DOC, arrays="list"))
+        self.assertIsInstance(toon_parser.decode(DOC)["xs"], list)
+
+    def test_combines_with_dict_options(self):
+        self.assertEqual(toon_parser.decode(DOC, arrays="tuple", attrdict=True).xs, (1, 2))
+        self.assertEqual(toon_parser.decode(DOC, arrays="tuple", dict_factory=dict)["e"], ())
+
+    def test_invalid_arguments(self):
+        with self.assertRaisesRegex(ValueError, "Invalid arrays 'set'"):
+            toon_parser.decode(DOC, arrays="set")
+        with self.assertRaisesRegex(ValueError, "cannot be combined"):
+            toon_parser.decode(DOC, arrays="tuple", list_factory=list)
+
+
+class FrozenTest(unittest.TestCase):
+    def setUp(self):
+        self.value = toon_parser.decode("a:\n  b[2]: 1,2\n  c: x\nrows[1]{k}:\n  1\n", frozen=True)
+
+    def test_mappings_and_tuples_throughout(self):
+        self.assertIsInstance(self.value, types.MappingProxyType)
+        self.assertIsInstance(self.value["a"], types.MappingProxyType)
+        self.assertEqual(self.value["a"]["b"], (1, 2))
+        self.assertIsInstance(self.value["rows"], tuple)
+        self.assertIsInstance(self.value["rows"][0], types.MappingProxyType)
+        self.assertEqual(dict(self.value["rows"][0]), {"k": 1})
+
+    def test_read_only(self):
+        with self.assertRaises(TypeError):
+            self.value["z"] = 1
+        with self.assertRaises(TypeError):
+            self.value["a"]["c"] = "y"
+        with self.assertRaises(AttributeError):
+            self.value["a"]["b"].append(3)
+
+    def test_roots(self):
+        self.assertEqual(toon_parser.decode("[1]: 1", frozen=True), (1,))
+        self.assertEqual(toon_parser.decode("5", frozen=True), 5)
+
+    def test_into_fills_with_frozen_values(self):
+        target = {}
+        toon_parser.decode("a:\n  b: 1\n", frozen=True, into=target)
+        self.assertIsInstance(target["a"], types.MappingProxyType)
+
+    def test_conflicting_options(self):
+        for kwargs in ({"attrdict": True}, {"dict_factory": dict}, {"list_factory": list}):
+            with self.subTest(kwargs=kwargs):
+                with self.assertRaisesRegex(ValueError, "cannot be combined"):
+                    toon_parser.decode("a: 1", frozen=True, **kwargs)
+
+
+class AttrDictTest(unittest.TestCase):
+    DOC = "db:\n  host: localhost\n  port: 5432\nitems[1]: x\nrows[1]{k}:\n  1\n"
+
+    def setUp(self):
+        self.value = toon_parser.decode(self.DOC, attrdict=True)
+
+    def test_attribute_and_item_access(self):
+        self.assertEqual(self.value.db.host, "localhost")
+        self.assertEqual(self.value["db"]["port"], 5432)
+        self.assertEqual(self.value.rows[0].k, 1)
+        self.assertIsInstance(self.value.rows[0], toon_parser.AttrDict)
+
+    def test_methods_shadow_keys(self):
+        self.assertEqual(self.value["items"], ["x"])
+        self.assertEqual([k for k, _ in self.value.items()], ["db", "items", "rows"])
+
+    def test_compares_like_a_dict(self):
+        self.assertIsInstance(self.value, collections.abc.MutableMapping)
+        self.assertEqual(self.value, toon_parser.decode(self.DOC))
+        self.assertEqual(self.value, toon_parser.decode(self.DOC, attrdict=True))
+
+    def test_mutation(self):
+        self.value.port = 1
+        self.assertEqual(self.value["port"], 1)
+        del self.value.port
+        self.assertNotIn("port", self.value)
+        self.value["x"] = 2
+        self.assertEqual(self.value.x, 2)
+        self.assertEqual(self.value.pop("x"), 2)
+        self.assertEqual(self.value.pop("x", None), None)
+        self.assertEqual(self.value.setdefault("y", 3), 3)
+        self.value.update({"z": 4}, w=5)
+        self.assertEqual((self.value.z, self.value.w, self.value.get("v", 6)), (4, 5, 6))
+        self.value.clear()
+        self.assertEqual(len(self.value), 0)
+
+    def test_missing_keys(self):
+        with self.assertRaisesRegex(AttributeError, "no attribute 'nope'"):
+            self.value.nope
+        with self.assertRaisesRegex(AttributeError, "no attribute 'nope'"):
+            del self.value.nope
+        with self.assertRaises(KeyError):
+            self.value["nope"]
+
+    def test_dir_lists_keys_and_methods(self):
+        names = dir(self.value)
+        self.assertLessEqual({"db", "rows", "keys", "to_dict"}, set(names))
+
+    def test_to_dict(self):
+        plain = self.value.to_dict()
+        self.assertEqual(plain, toon_parser.decode(self.DOC))
+        self.assertIs(type(plain["db"]), dict)
+        self.assertIs(type(plain["rows"][0]), dict)
+
+    def test_copy_and_pickle(self):
+        for clone in (pickle.loads(pickle.dumps(self.value)), copy.deepcopy(self.value)):
+            self.assertEqual(clone, self.value)
+            self.assertIsInstance(clone.db, toon_parser.AttrDict)
+
+    def test_constructor(self):
+        self.assertEqual(toon_parser.AttrDict({"a": 1}, b=2), {"a": 1, "b": 2})
+        self.assertEqual(toon_parser.AttrDict([("a", 1)]).a, 1)
+        self.assertEqual(len(toon_parser.AttrDict()), 0)
+
+    def test_encodes_back(self):
+        self.assertEqual(toon_parser.decode(toon_parser.encode(self.value)), toon_parser.decode(self.DOC))
+
+
+class FactoryTest(unittest.TestCase):
+    DOC = "a:\n  b[2]: 1,2\nrows[2]{k}:\n  1\n  2\ne:\n"
+
+    def test_dict_and_list_factories(self):
+        value = toon_parser.decode(self.DOC, dict_factory=collections.OrderedDict, list_factory=collections.deque)
+        self.assertIsInstance(value, collections.OrderedDict)
+        self.assertIsInstance(value["a"]["b"], collections.deque)
+        self.assertIsInstance(value["rows"][0], collections.OrderedDict)
+        self.assertIsInstance(value["e"], collections.OrderedDict)
+        self.assertEqual(list(value["rows"]), [{"k": 1}, {"k": 2}])
+
+    def test_factories_receive_built_containers(self):
+        seen = []
+        toon_parser.decode(self.DOC, dict_factory=lambda d: seen.append(d) or d)
+        self.assertEqual(seen[0], {"b": [1, 2]})
+        self.assertEqual(seen[-1]["e"], {})
+        self.assertEqual(toon_parser.decode(self.DOC, dict_factory=sorted), ["a", "e", "rows"])
+        self.assertEqual(toon_parser.decode("[2]: 1,2", list_factory=tuple), (1, 2))
+
+    def test_combines_with_tuples(self):
+        value = toon_parser.decode(self.DOC, dict_factory=collections.OrderedDict, arrays="tuple")
+        self.assertEqual(value["a"]["b"], (1, 2))
+
+    def test_factory_errors_propagate(self):
+        with self.assertRaises(ZeroDivisionError):
+            toon_parser.decode(self.DOC, dict_factory=lambda d: 1 / 0)
+
+    def test_factories_must_be_callable(self):
+        for kwargs in ({"dict_factory": 5}, {"list_factory": "list"}):
+            with self.subTest(kwargs=kwargs):
+                with self.assertRaisesRegex(TypeError, "must be callable"):
+                    toon_parser.decode("5", **kwargs)
+
+
+@dataclasses.dataclass
+class Db:
+    host: str = "localhost"
+    port: int = 5432
+
+
+@dataclasses.dataclass
+class Config:
+    db: Db = dataclasses.field(default_factory=Db)
+    name: str = ""
+
+
+class IntoTest(unittest.TestCase):
+    def test_dict_is_updated_in_place(self):
+        config = {"db": {"host": "a", "port": 1}, "keep": 1}
+        db = config["db"]
+        result = toon_parser.decode("db:\n  host: b\nnew: 2\n", into=config)
+        self.assertIs(result, config)
+        self.assertIs(config["db"], db)
+        self.assertEqual(config, {"db": {"host": "b", "port": 1}, "keep": 1, "new": 2})
+
+    def test_non_dicts_are_replaced(self):
+        config = {"db": {"host": "a"}, "tags": [1]}
+        toon_parser.decode("db: 5\ntags[1]: 2\n", into=config)
+        self.assertEqual(config, {"db": 5, "tags": [2]})
+
+    def test_dataclass_attributes_are_set(self):
+        config = Config()
+        db = config.db
+        self.assertIs(toon_parser.decode("db:\n  host: h\nname: n\n", into=config), config)
+        self.assertIs(config.db, db)
+        self.assertEqual(config, Config(Db("h", 5432), "n"))
+
+    def test_unknown_dataclass_field_changes_nothing(self):
+        config = Config()
+        with self.assertRaisesRegex(ValueError, "'nope' is not a field of Db"):
+            toon_parser.decode("name: z\ndb:\n  nope: 1\n", into=config)
+        self.assertEqual(config, Config())
+
+    def test_plain_objects(self):
+        class Settings:
+            pass
+
+        settings = Settings()
+        toon_parser.decode("a: 1\nb:\n  c: 2\n", into=settings)
+        self.assertEqual(vars(settings), {"a": 1, "b": {"c": 2}})
+
+    def test_output_options_apply_to_new_values(self):
+        config = {"a": {"z": 0}}
+        toon_parser.decode("a:\n  b[1]: 1\nc:\n  d: 2\n", into=config, arrays="tuple", attrdict=True)
+        self.assertEqual(config["a"]["b"], (1,))
+        self.assertEqual(config["c"].d, 2)
+
+    def test_root_must_be_a_dict(self):
+        with self.assertRaisesRegex(ValueError, "root is a dict"):
+            toon_parser.decode("[1]: 1", into={})
+
+
+if __name__ == "__main__":
+    unittest.main()
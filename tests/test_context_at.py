@@ -0,0 +1,88 @@
+"""Editor context at a position with ``context_at``.
+
+Run with ``python -m unittest tests.test_context_at``.
+"""
+
+import unittest
+
+import toon_parser
+
+DOC = 'users[2]{id,name}:\n  1,Ann\n  2,"Bo, Jr"\nmeta:\n  tags[2]: a,b\nitems[1]:\n  - k: v\n\n'
+
+
+def at(doc, fragment, delta=0, *fields):
+    """The context at `fragment` in `doc`, moved by `delta` characters."""
+    context = toon_parser.context_at(doc, doc.index(fragment) + delta)
+    return {name: context[name] for name in fields} if fields else context
+
+
+class ContextAtTest(unittest.TestCase):
+    def test_report(self):
+        self.assertEqual(
+            at(DOC, "Ann"),
+            {
+                "line": 2,
+                "column": 5,
+                "kind": "table_row",
+                "path": "$.users[0].name",
+                "key": None,
+                "row": 0,
+                "cell": 1,
+                "field": "name",
+                "in_string": False,
+                "expected": ["value", "newline"],
+            },
+        )
+
+    def test_kinds(self):
+        cases = [
+            ("users", 0, "indent", "$", ["key"]),
+            ("users", 3, "key", "$.users", [":", "["]),
+            ("2]{id", 0, "header", "$.users", ["length", "]"]),
+            ("id,name", 0, "header", "$.users", ["field", "}"]),
+            ("1,Ann", 1, "table_row", "$.users[0].id", ["value", ","]),
+            ("  2,", 0, "indent", "$.users", ["row"]),
+            ("Bo", 0, "table_row", "$.users[1].name", ['"']),
+            ("tags", 1, "key", "$.meta.tags", [":", "["]),
+            ("a,b", 2, "value", "$.meta.tags[1]", ["value", "newline"]),
+            ("  - k", 0, "indent", "$.items", ["-"]),
+            ("k: v", 3, "value", "$.items[0].k", ["value"]),
+        ]
+        for fragment, delta, kind, path, expected in cases:
+            with self.subTest(fragment=fragment, delta=delta):
+                self.assertEqual(at(DOC, fragment, delta, "kind", "path", "expected"), {"kind": kind, "path": path, "expected": expected})
+
+    def test_blank_lines_follow_the_cursor(self):
+        self.assertEqual(at(DOC, "\n\n", 1, "line", "kind", "path"), {"line": 8, "kind": "blank", "path": "$"})
+        doc = "a:\n  b: 1\n  \n"
+        self.assertEqual(toon_parser.context_at(doc, len(doc) - 1)["path"], "$.a")
+
+    def test_unfinished_documents(self):
+        doc = "users[2]{id,name}:\n  1,Ann\n  2,"
+        self.assertEqual(toon_parser.context_at(doc, len(doc))["field"], "name")
+        self.assertEqual(at('a: "open', "open", 0, "kind", "in_string", "expected"), {"kind": "value", "in_string": True, "expected": ['"']})
+
+    def test_paths_quote_keys_that_are_not_names(self):
+        doc = 'rows[1]{"full name"}:\n  Ann\n"my key":\n  a: 1\n'
+        self.assertEqual(at(doc, "Ann", 1, "path", "field"), {"path": '$.rows[0]["full name"]', "field": "full name"})
+        self.assertEqual(at(doc, "a: 1", 3, "path"), {"path": '$["my key"].a'})
+
+    def test_line_endings(self):
+        for ending in ("\r\n", "\r"):
+            with self.subTest(ending=ending):
+                doc = DOC.replace("\n", ending)
+                for fragment in ("Ann", "tags", "k: v"):
+                    self.assertEqual(at(doc, fragment), at(DOC, fragment))
+
+    def test_offsets_count_characters(self):
+        doc = "a: é\nb: 1"
+        self.assertEqual(at(doc, "b", 0, "line", "column", "key"), {"line": 2, "column": 1, "key": None})
+        self.assertEqual(toon_parser.context_at(doc, 6)["key"], "b")
+
+    def test_offset_past_the_end(self):
+        with self.assertRaisesRegex(IndexError, "^offset 4 is past the end of the document \\(3 characters\\)$"):
+            toon_parser.context_at("a:é", 4)
+
+
+if __name__ == "__main__":
+    unittest.main()
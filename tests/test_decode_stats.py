@@ -0,0 +1,63 @@
+"""Phase timings and node counts from ``decode(..., stats=True)``.
+
+Run with ``python -m unittest tests.test_decode_stats``.
+"""
+
+import unittest
+
+import toon_parser
+
+DOC = "users[2]{id,name}:\n  1,Ann\n  2,Bo\ntags[2]: a,b\nmeta:\n  ok: true\n  n: null\n"
+
+
+class DecodeStatsTest(unittest.TestCase):
+    def test_value_is_unchanged(self):
+        value, _ = toon_parser.decode(DOC, stats=True)
+        self.assertEqual(value, toon_parser.decode(DOC))
+
+    def test_counts(self):
+        _, stats = toon_parser.decode(DOC, stats=True)
+        self.assertEqual(
+            {k: v for k, v in stats.items() if not k.endswith("_seconds")},
+            {
+                "bytes": len(DOC),
+                "nodes": 14,
+                # 4 dicts, 2 lists, 9 keys and 4 string values, 2 numbers
+                "allocations": 21,
+                "dicts": 4,
+                "lists": 2,
+                "strings": 13,
+                "numbers": 2,
+                "tables": 1,
+                "table_rows": 2,
+                "max_depth": 4,
+            },
+        )
+
+    def test_timings(self):
+        _, stats = toon_parser.decode(DOC, stats=True)
+        self.assertGreater(stats["parse_seconds"], 0)
+        self.assertGreater(stats["convert_seconds"], 0)
+
+    def test_scalar_document(self):
+        value, stats = toon_parser.decode("42", stats=True)
+        self.assertEqual(value, 42)
+        self.assertEqual((stats["nodes"], stats["allocations"], stats["max_depth"]), (1, 1, 1))
+
+    def test_empty_arrays_are_not_tables(self):
+        _, stats = toon_parser.decode("xs[0]:\nys[1]:\n  - a: 1\n", stats=True)
+        self.assertEqual((stats["lists"], stats["tables"], stats["table_rows"]), (2, 1, 1))
+
+    def test_with_into(self):
+        target = {}
+        value, stats = toon_parser.decode("a: 1\n", stats=True, into=target)
+        self.assertIs(value, target)
+        self.assertEqual(target, {"a": 1})
+        self.assertEqual(stats["nodes"], 2)
+
+    def test_off_by_default(self):
+        self.assertEqual(toon_parser.decode("a: 1\n"), {"a": 1})
+
+
+if __name__ == "__main__":
+    unittest.main()
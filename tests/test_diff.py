@@ -0,0 +1,65 @@
+"""Value-level differences between two documents with ``diff``.
+
+Run with ``python -m unittest tests.test_diff``.
+"""
+
+import unittest
+
+import toon_parser
+
+
+class DiffTest(unittest.TestCase):
+    def test_equal_documents(self):
+        self.assertEqual(toon_parser.diff({"a": [1, {"b": None}]}, {"a": [1, {"b": None}]}), [])
+
+    def test_numbers_compare_by_value(self):
+        self.assertEqual(toon_parser.diff({"a": 1}, {"a": 1.0}), [])
+
+    def test_key_order_is_ignored(self):
+        self.assertEqual(toon_parser.diff({"a": 1, "b": 2}, {"b": 2, "a": 1}), [])
+
+    def test_changes_in_document_order(self):
+        old = {"a": 1, "b": [1, 2], "c": {"x": 1}}
+        new = {"a": 1, "b": [1], "c": {"x": 2, "y": 3}, "d": None}
+        self.assertEqual(
+            toon_parser.diff(old, new),
+            [
+                {"op": "removed", "path": "$.b[1]", "old": 2},
+                {"op": "changed", "path": "$.c.x", "old": 1, "new": 2},
+                {"op": "added", "path": "$.c.y", "new": 3},
+                {"op": "added", "path": "$.d", "new": None},
+            ],
+        )
+
+    def test_appended_items(self):
+        self.assertEqual(toon_parser.diff([1], [1, 2, 3]), [
+            {"op": "added", "path": "$[1]", "new": 2},
+            {"op": "added", "path": "$[2]", "new": 3},
+        ])
+
+    def test_type_change_is_one_change(self):
+        self.assertEqual(
+            toon_parser.diff({"a": {"b": 1}}, {"a": [1]}),
+            [{"op": "changed", "path": "$.a", "old": {"b": 1}, "new": [1]}],
+        )
+
+    def test_root_change(self):
+        self.assertEqual(toon_parser.diff([1], {"a": 1}), [{"op": "changed", "path": "$", "old": [1], "new": {"a": 1}}])
+
+    def test_odd_keys_are_bracket_quoted(self):
+        changes = toon_parser.diff({}, {"full name": 1, "a.b": 2, "": 3})
+        self.assertEqual([c["path"] for c in changes], ['$["full name"]', '$["a.b"]', '$[""]'])
+        new = {"full name": 1, "a.b": 2, "": 3}
+        for change in changes:
+            with self.subTest(path=change["path"]):
+                self.assertEqual(toon_parser.get(new, change["path"]), change["new"])
+
+    def test_toon_input(self):
+        self.assertEqual(
+            toon_parser.diff("users[2]{id,name}:\n  1,Ann\n  2,Bo\n", b"users[2]{id,name}:\n  1,Ann\n  2,Bob\n"),
+            [{"op": "changed", "path": "$.users[1].name", "old": "Bo", "new": "Bob"}],
+        )
+
+
+if __name__ == "__main__":
+    unittest.main()
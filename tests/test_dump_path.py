@@ -0,0 +1,83 @@
+"""Writing documents to files with ``dump_path``, in place or atomically.
+
+Run with ``python -m unittest tests.test_dump_path``.
+"""
+
+import os
+import pathlib
+import tempfile
+import unittest
+
+import toon_parser
+from toon_parser import Options, ToonIOError
+
+
+class DumpPathTest(unittest.TestCase):
+    def setUp(self):
+        self.dir = tempfile.TemporaryDirectory()
+        self.path = os.path.join(self.dir.name, "out.toon")
+        with open(self.path, "w") as f:
+            f.write("old: a longer document than the new one")
+
+    def tearDown(self):
+        self.dir.cleanup()
+
+    def read(self):
+        with open(self.path) as f:
+            return f.read()
+
+    def test_in_place(self):
+        inode = os.stat(self.path).st_ino
+        toon_parser.dump_path({"a": 1}, self.path)
+        self.assertEqual(self.read(), "a: 1")
+        self.assertEqual(os.stat(self.path).st_ino, inode)
+
+    def test_atomic_replaces_the_file(self):
+        inode = os.stat(self.path).st_ino
+        toon_parser.dump_path({"a": 1}, self.path, atomic=True)
+        self.assertEqual(self.read(), "a: 1")
+        self.assertNotEqual(os.stat(self.path).st_ino, inode)
+        self.assertEqual(os.listdir(self.dir.name), ["out.toon"])
+
+    @unittest.skipIf(os.name != "posix", "needs POSIX permissions")
+    def test_atomic_keeps_permissions(self):
+        os.chmod(self.path, 0o640)
+        toon_parser.dump_path({"a": 1}, self.path, atomic=True)
+        self.assertEqual(os.stat(self.path).st_mode & 0o777, 0o640)
+
+    def test_creates_files(self):
+        for atomic in (False, True):
+            with self.subTest(atomic=atomic):
+                path = pathlib.Path(self.dir.name, f"new-{atomic}.toon")
+                toon_parser.dump_path({"a": [1, 2]}, path, atomic=atomic)
+                self.assertEqual(path.read_text(), "a[2]: 1,2")
+
+    def test_fsync_either_way(self):
+        for atomic, fsync in ((False, True), (True, False), (True, None)):
+            with self.subTest(atomic=atomic, fsync=fsync):
+                toon_parser.dump_path({"n": 1}, self.path, atomic=atomic, fsync=fsync)
+                self.assertEqual(self.read(), "n: 1")
+
+    def test_options(self):
+        toon_parser.dump_path({"a": [1, 2]}, self.path, options=Options(delimiter="pipe"))
+        self.assertEqual(self.read(), "a[2|]: 1|2")
+
+    def test_encoding_errors_leave_the_file_alone(self):
+        for atomic in (False, True):
+            with self.subTest(atomic=atomic):
+                with self.assertRaises(ValueError):
+                    toon_parser.dump_path({"x": object()}, self.path, atomic=atomic)
+                self.assertEqual(self.read(), "old: a longer document than the new one")
+                self.assertEqual(os.listdir(self.dir.name), ["out.toon"])
+
+    def test_write_errors(self):
+        with self.assertRaises(ToonIOError):
+            toon_parser.dump_path({"a": 1}, os.path.join(self.dir.name, "missing", "out.toon"), atomic=True)
+        os.mkdir(os.path.join(self.dir.name, "sub"))
+        with self.assertRaises(ToonIOError):
+            toon_parser.dump_path({"a": 1}, os.path.join(self.dir.name, "sub"), atomic=True)
+        self.assertEqual(sorted(os.listdir(self.dir.name)), ["out.toon", "sub"])
+
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,79 @@
+"""Explicit ``{}`` and ``[]`` markers for empty dicts and lists.
+
+Run with ``python -m unittest tests.test_empty_collections``.
+"""
+
+import unittest
+
+import toon_parser
+from toon_parser import Options
+
+DATA = {"a": {}, "b": [], "c": [{}, [], 1], "d": {"e": {}}, "s": "{}", "t": ["[]"]}
+BARE = 'a:\nb[0]:\nc[3]:\n  -\n  - [0]:\n  - 1\nd:\n  e:\ns: "{}"\nt[1]: "[]"'
+EXPLICIT = 'a: {}\nb: []\nc[3]:\n  - {}\n  - []\n  - 1\nd:\n  e: {}\ns: "{}"\nt[1]: "[]"'
+
+
+class EncodeTest(unittest.TestCase):
+    def test_styles(self):
+        self.assertEqual(toon_parser.encode(DATA), BARE)
+        self.assertEqual(toon_parser.encode(DATA, empty_collections="bare"), BARE)
+        self.assertEqual(toon_parser.encode(DATA, empty_collections="explicit"), EXPLICIT)
+
+    def test_list_items_with_fields(self):
+        cases = [
+            ({"r": [{"a": {}}, {"a": {}}]}, "r[2]:\n  - a: {}\n  - a: {}"),
+            ({"r": [{"a": []}, {"a": []}]}, "r[2]:\n  - a: []\n  - a: []"),
+            ({"r": [{"a": {}, "b": 1}]}, "r[1]:\n  - a: {}\n    b: 1"),
+        ]
+        for data, text in cases:
+            with self.subTest(data=data):
+                self.assertEqual(toon_parser.encode(data, empty_collections="explicit"), text)
+
+    def test_with_other_formatting(self):
+        self.assertEqual(toon_parser.encode({"a": {"b": {}}}, empty_collections="explicit", indent=4), "a:\n    b: {}")
+
+    def test_options(self):
+        opts = Options(empty_collections="explicit")
+        self.assertEqual(toon_parser.encode_with_options(DATA, opts), EXPLICIT)
+        self.assertEqual(toon_parser.encode_bytes(DATA, opts), EXPLICIT.encode())
+        self.assertEqual(Options().empty_collections, "bare")
+        self.assertEqual(Options().with_empty_collections("explicit"), opts)
+        self.assertIn("empty_collections='explicit'", repr(opts))
+
+    def test_invalid_style(self):
+        message = "^Invalid empty_collections 'x'. Must be 'bare' or 'explicit'$"
+        with self.assertRaisesRegex(ValueError, message):
+            toon_parser.encode({}, empty_collections="x")
+        with self.assertRaisesRegex(ValueError, message):
+            Options(empty_collections="x")
+
+
+class DecodeTest(unittest.TestCase):
+    def test_both_styles(self):
+        self.assertEqual(toon_parser.decode(BARE), DATA)
+        self.assertEqual(toon_parser.decode(EXPLICIT), DATA)
+        self.assertEqual(toon_parser.decode(EXPLICIT, strict=True), DATA)
+
+    def test_spacing_around_markers(self):
+        cases = [
+            ("a: {} ", {"a": {}}),
+            ("a:  []", {"a": []}),
+            ("xs[2]:\n  -   []\n  - a:  {} ", {"xs": [[], {"a": {}}]}),
+            ("a: {}\r\nb: []\r\n", {"a": {}, "b": []}),
+        ]
+        for text, value in cases:
+            with self.subTest(text=text):
+                self.assertEqual(toon_parser.decode(text), value)
+
+    def test_strings_that_are_not_markers(self):
+        cases = [
+            ('a: "{}"', {"a": "{}"}),
+            ("xs[2]: {},[]", {"xs": ["{}", "[]"]}),
+        ]
+        for text, value in cases:
+            with self.subTest(text=text):
+                self.assertEqual(toon_parser.decode(text), value)
+
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,52 @@
+"""Output digests with ``encode(..., hash=...)``.
+
+Run with ``python -m unittest tests.test_encode_hash``.
+"""
+
+import hashlib
+import unittest
+
+import toon_parser
+
+DATA = {"rows": [{"id": 1, "name": "é"}], "b": 2}
+
+
+class EncodeHashTest(unittest.TestCase):
+    def test_algorithms(self):
+        for algorithm in ("sha224", "sha256", "sha384", "sha512"):
+            with self.subTest(algorithm):
+                text, digest = toon_parser.encode(DATA, hash=algorithm)
+                self.assertEqual(text, toon_parser.encode(DATA))
+                self.assertEqual(digest, hashlib.new(algorithm, text.encode()).hexdigest())
+
+    def test_digest_covers_the_whole_output(self):
+        cases = [
+            {"checksum": True},
+            {"meta": {"v": 1}},
+            {"indent": 4, "sort_keys": True},
+            {"empty_collections": "explicit"},
+            {"ensure_ascii": True},
+            {"typed_headers": True},
+            {"delimiter": "tab"},
+        ]
+        for kwargs in cases:
+            with self.subTest(**kwargs):
+                text, digest = toon_parser.encode(DATA, hash="sha256", **kwargs)
+                self.assertEqual(text, toon_parser.encode(DATA, **kwargs))
+                self.assertEqual(digest, hashlib.sha256(text.encode()).hexdigest())
+
+    def test_empty_output(self):
+        self.assertEqual(toon_parser.encode({}, hash="sha256"), ("", hashlib.sha256(b"").hexdigest()))
+
+    def test_plain_string_without_hash(self):
+        self.assertIsInstance(toon_parser.encode(DATA), str)
+
+    def test_unsupported_algorithms(self):
+        for name in ("md5", "SHA256", ""):
+            with self.subTest(name):
+                with self.assertRaisesRegex(ValueError, "^Unsupported hash algorithm '{}'. Must be 'sha224', 'sha256', 'sha384' or 'sha512'$".format(name)):
+                    toon_parser.encode(DATA, hash=name)
+
+
+if __name__ == "__main__":
+    unittest.main()
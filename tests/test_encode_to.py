@@ -0,0 +1,73 @@
+"""Chunked output with ``encode_to``.
+
+Run with ``python -m unittest tests.test_encode_to``.
+"""
+
+import hashlib
+import unittest
+
+import toon_parser
+from toon_parser import Options
+
+DATA = {"rows": [{"id": i, "name": "é" * i} for i in range(20)]}
+TEXT = toon_parser.encode(DATA)
+
+
+class EncodeToTest(unittest.TestCase):
+    def collect(self, data=DATA, **kwargs):
+        chunks = []
+        written = toon_parser.encode_to(data, chunks.append, **kwargs)
+        return written, chunks
+
+    def test_str_chunks(self):
+        for chunk_size in (1, 2, 7, 65536):
+            with self.subTest(chunk_size=chunk_size):
+                written, chunks = self.collect(chunk_size=chunk_size)
+                self.assertEqual("".join(chunks), TEXT)
+                self.assertEqual(written, len(TEXT.encode()))
+                self.assertTrue(all(type(chunk) is str for chunk in chunks))
+                # Chunks end on a character: shorter than chunk_size, or one character
+                for chunk in chunks:
+                    self.assertTrue(len(chunk.encode()) <= chunk_size or len(chunk) == 1)
+
+    def test_bytes_chunks(self):
+        for chunk_size in (1, 7, 65536):
+            with self.subTest(chunk_size=chunk_size):
+                written, chunks = self.collect(chunk_size=chunk_size, binary=True)
+                self.assertEqual(b"".join(chunks), TEXT.encode())
+                self.assertEqual(written, len(TEXT.encode()))
+                self.assertTrue(all(len(chunk) == chunk_size for chunk in chunks[:-1]))
+
+    def test_into_a_hash(self):
+        h = hashlib.sha256()
+        toon_parser.encode_to(DATA, h.update, binary=True)
+        self.assertEqual(h.hexdigest(), hashlib.sha256(TEXT.encode()).hexdigest())
+
+    def test_options(self):
+        opts = Options(delimiter="pipe", indent=4)
+        _, chunks = self.collect(options=opts)
+        self.assertEqual("".join(chunks), toon_parser.encode_with_options(DATA, opts))
+
+    def test_empty_output(self):
+        self.assertEqual(self.collect({}), (0, []))
+
+    def test_errors(self):
+        with self.assertRaisesRegex(ValueError, "^chunk_size must be at least 1$"):
+            toon_parser.encode_to(DATA, print, chunk_size=0)
+        with self.assertRaisesRegex(ValueError, "Cannot convert type 'object'"):
+            toon_parser.encode_to(object(), print)
+
+    def test_sink_errors_stop_the_encoding(self):
+        calls = []
+
+        def sink(chunk):
+            calls.append(chunk)
+            raise RuntimeError("stop")
+
+        with self.assertRaisesRegex(RuntimeError, "^stop$"):
+            toon_parser.encode_to(DATA, sink, chunk_size=8)
+        self.assertEqual(len(calls), 1)
+
+
+if __name__ == "__main__":
+    unittest.main()
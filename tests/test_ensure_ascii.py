@@ -0,0 +1,88 @@
+"""ASCII-only output with ``encode(..., ensure_ascii=True)`` and ``Options(ensure_ascii=True)``.
+
+Run with ``python -m unittest tests.test_ensure_ascii``.
+"""
+
+import hashlib
+import unittest
+
+import toon_parser
+from toon_parser import Options, ToonOutputLimitError
+
+CASES = [
+    ({"name": "café"}, 'name: "caf\\u00e9"'),
+    ({"ключ": "x"}, '"\\u043a\\u043b\\u044e\\u0447": x'),
+    ({"e": "😀"}, 'e: "\\ud83d\\ude00"'),
+    ({"rows": [{"id": 1, "n": "é"}, {"id": 2, "n": "ü"}]}, 'rows[2]{id,n}:\n  1,"\\u00e9"\n  2,"\\u00fc"'),
+    ({"xs": ["é", "a b", "ü,x"]}, 'xs[3]: "\\u00e9",a b,"\\u00fc,x"'),
+    ({"é": [1, 2]}, '"\\u00e9"[2]: 1,2'),
+    ({"nested": {"ü": {"ö": "ä"}}}, 'nested:\n  "\\u00fc":\n    "\\u00f6": "\\u00e4"'),
+    ({"ascii": "plain"}, "ascii: plain"),
+]
+
+
+class EnsureAsciiTest(unittest.TestCase):
+    def test_escapes(self):
+        for data, expected in CASES:
+            with self.subTest(data=data):
+                text = toon_parser.encode(data, ensure_ascii=True)
+                self.assertEqual(text, expected)
+                self.assertTrue(text.isascii())
+                self.assertEqual(toon_parser.decode(text), data)
+
+    def test_off_by_default(self):
+        self.assertEqual(toon_parser.encode({"s": "é"}), "s: é")
+        self.assertEqual(toon_parser.encode({"s": "é"}, ensure_ascii=False), "s: é")
+
+    def test_text_that_looks_like_an_escape(self):
+        # The character standing for the escapes until they are finished, and
+        # backslashes the data already has, survive as themselves
+        for data, expected in (
+            ({"m": "\ufdd5"}, 'm: "\\ufdd5"'),
+            ({"m": "\ufdd5é"}, 'm: "\\ufdd5\\u00e9"'),
+            ({"lit": "\\u00e9"}, 'lit: "\\\\u00e9"'),
+            ({"lit": "\\u00e9 é"}, 'lit: "\\\\u00e9 \\u00e9"'),
+        ):
+            with self.subTest(data=data):
+                text = toon_parser.encode(data, ensure_ascii=True)
+                self.assertEqual(text, expected)
+                self.assertEqual(toon_parser.decode(text), data)
+
+    def test_with_other_encode_options(self):
+        self.assertEqual(toon_parser.encode({"n": 255, "s": "é"}, hex=["n"], ensure_ascii=True), 'n: 0xFF\ns: "\\u00e9"')
+        self.assertEqual(toon_parser.encode({"s": "é"}, meta={"v": "ü"}, ensure_ascii=True), '---\nv: "\\u00fc"\n---\ns: "\\u00e9"')
+        self.assertEqual(toon_parser.encode({"s": "é", "a": 1}, indent=4, sort_keys=True, ensure_ascii=True), 'a: 1\ns: "\\u00e9"')
+        self.assertEqual(toon_parser.encode({"s": "é", "b": {}}, empty_collections="explicit", ensure_ascii=True), 's: "\\u00e9"\nb: {}')
+        text = toon_parser.encode({"s": "é"}, checksum=True, ensure_ascii=True)
+        self.assertTrue(text.isascii())
+        self.assertEqual(toon_parser.decode(text), {"s": "é"})
+
+    def test_hash_covers_the_escaped_text(self):
+        text, digest = toon_parser.encode({"s": "é"}, hash="sha256", ensure_ascii=True)
+        self.assertEqual(text, 's: "\\u00e9"')
+        self.assertEqual(digest, hashlib.sha256(text.encode()).hexdigest())
+
+    def test_output_limit_counts_the_escaped_text(self):
+        self.assertEqual(toon_parser.encode({"s": "é"}, max_output_bytes=11, ensure_ascii=True), 's: "\\u00e9"')
+        with self.assertRaises(ToonOutputLimitError):
+            toon_parser.encode({"s": "é"}, max_output_bytes=10, ensure_ascii=True)
+        self.assertEqual(len(toon_parser.encode({"s": "😀" * 100}, max_output_bytes=1205, ensure_ascii=True)), 1205)
+        with self.assertRaises(ToonOutputLimitError):
+            toon_parser.encode({"s": "😀" * 100}, max_output_bytes=1204, ensure_ascii=True)
+
+    def test_options(self):
+        options = Options(ensure_ascii=True)
+        self.assertTrue(options.ensure_ascii)
+        self.assertFalse(Options().ensure_ascii)
+        self.assertEqual(Options().with_ensure_ascii(True), options)
+        self.assertEqual(repr(options), "Options(delimiter='comma', strict=false, ensure_ascii=True)")
+        data = {"s": "é", "ü": [{"a": "ö"}]}
+        text = toon_parser.encode_with_options(data, options)
+        self.assertEqual(text, 's: "\\u00e9"\n"\\u00fc"[1]{a}:\n  "\\u00f6"')
+        self.assertEqual(toon_parser.decode_with_options(text, options), data)
+        self.assertEqual(toon_parser.encode_bytes({"s": "é"}, options), b's: "\\u00e9"')
+        self.assertEqual(toon_parser.encode_with_options({"s": "é"}, Options()), "s: é")
+
+
+if __name__ == "__main__":
+    unittest.main()
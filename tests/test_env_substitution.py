@@ -0,0 +1,67 @@
+"""``${VAR}`` expansion with ``decode(..., env_substitution=True, env=...)``.
+
+Run with ``python -m unittest tests.test_env_substitution``.
+"""
+
+import os
+import unittest
+
+import toon_parser
+from toon_parser import ToonError
+
+ENV = {"HOST": "db", "PORT": "5432"}
+
+
+def expanded(text, env=ENV):
+    return toon_parser.decode(text, env_substitution=True, env=env)
+
+
+class EnvSubstitutionTest(unittest.TestCase):
+    def test_expansion(self):
+        cases = [
+            ('url: "postgres://${HOST}:${PORT}"', {"url": "postgres://db:5432"}),
+            ("a: ${HOST}x${HOST}", {"a": "dbxdb"}),
+            ("a: ${PORT}", {"a": "5432"}),
+            ("a: ${MISSING:-fallback}", {"a": "fallback"}),
+            ('a: "${MISSING:-}"', {"a": ""}),
+            ("a: ${HOST:-other}", {"a": "db"}),
+            ('a: "$${HOST}"', {"a": "${HOST}"}),
+            ("a: $5", {"a": "$5"}),
+            ("xs[2]: ${HOST},${PORT}", {"xs": ["db", "5432"]}),
+            ("r[1]{h}:\n  ${HOST}", {"r": [{"h": "db"}]}),
+        ]
+        for text, value in cases:
+            with self.subTest(text=text):
+                self.assertEqual(expanded(text), value)
+
+    def test_keys_are_left_alone(self):
+        self.assertEqual(expanded('"${HOST}": ${HOST}'), {"${HOST}": "db"})
+
+    def test_errors(self):
+        cases = [
+            ("a: ${MISSING}", "undefined variable 'MISSING'"),
+            ('a: "${HOST"', "unterminated '\\$\\{'"),
+            ('a: "${}"', 'invalid variable name ""'),
+            ('a: "${A-B}"', 'invalid variable name "A-B"'),
+        ]
+        for text, message in cases:
+            with self.subTest(text=text):
+                with self.assertRaisesRegex(ToonError, message):
+                    expanded(text)
+
+    def test_off_by_default(self):
+        self.assertEqual(toon_parser.decode("a: ${HOST}"), {"a": "${HOST}"})
+        self.assertEqual(toon_parser.decode("a: ${HOST}", env=ENV), {"a": "${HOST}"})
+
+    def test_process_environment_only_when_passed(self):
+        with self.assertRaisesRegex(ValueError, "env_substitution requires env"):
+            toon_parser.decode("a: ${HOME}", env_substitution=True)
+        self.assertEqual(expanded("a: ${PATH}", env=os.environ), {"a": os.environ["PATH"]})
+
+    def test_values_must_be_strings(self):
+        with self.assertRaises(TypeError):
+            expanded("a: ${N}", env={"N": 1})
+
+
+if __name__ == "__main__":
+    unittest.main()
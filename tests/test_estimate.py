@@ -0,0 +1,93 @@
+"""Predicting the memory of a decode with ``estimate_decoded_size``.
+
+Run with ``python -m unittest tests.test_estimate``.
+"""
+
+import tracemalloc
+import unittest
+
+import toon_parser
+
+DOCUMENTS = [
+    "a: 1",
+    "users[2]{id,name}:\n  1,Ann\n  2,Bo",
+    "[3]: 1,2.5,x",
+    "xs[2]:\n  - a: 1\n    b: 2\n  - 3",
+    "a:\n  b:\n    c: 1.5\nd: null",
+    "xs[2]:\n  -\n  - k[1]: 1",
+    "xs[1]:\n  - [2]: 1,2",
+    '"a b": "c: d"',
+    "xs[0]:",
+    "",
+]
+
+
+def count(value, counts):
+    """Containers and numbers in decoded `value`."""
+    if isinstance(value, dict):
+        counts["dicts"] += 1
+        for item in value.values():
+            count(item, counts)
+    elif isinstance(value, list):
+        counts["lists"] += 1
+        for item in value:
+            count(item, counts)
+    elif isinstance(value, float):
+        counts["floats"] += 1
+    elif isinstance(value, int) and not isinstance(value, bool):
+        counts["ints"] += 1
+    return counts
+
+
+class EstimateTest(unittest.TestCase):
+    def test_report(self):
+        self.assertEqual(
+            toon_parser.estimate_decoded_size("a: 1"),
+            {"bytes": 178, "dicts": 1, "lists": 0, "strings": 1, "ints": 1, "floats": 0},
+        )
+
+    def test_counts_match_decode(self):
+        for text in DOCUMENTS:
+            with self.subTest(text=text):
+                estimate = toon_parser.estimate_decoded_size(text)
+                decoded = count(toon_parser.decode(text), dict(dicts=0, lists=0, ints=0, floats=0))
+                self.assertEqual({key: estimate[key] for key in decoded}, decoded)
+
+    def test_strings(self):
+        self.assertEqual(toon_parser.estimate_decoded_size('a: x\nb: "1"\nc: 1e400')["strings"], 6)
+        # Short repeats are shared, as table keys and values are when decoding
+        self.assertEqual(toon_parser.estimate_decoded_size("r[3]{k}:\n  x\n  x\n  x")["strings"], 3)
+        long = "y" * 40
+        self.assertEqual(toon_parser.estimate_decoded_size(f"r[2]{{k}}:\n  {long}\n  {long}")["strings"], 4)
+
+    def test_small_ints_cost_nothing(self):
+        small = toon_parser.estimate_decoded_size("a: 256")["bytes"]
+        self.assertEqual(toon_parser.estimate_decoded_size("a: -5")["bytes"], small)
+        self.assertEqual(toon_parser.estimate_decoded_size("a: 257")["bytes"], small + 28)
+
+    def test_bytes_close_to_decode(self):
+        documents = {
+            "table": {"users": [{"id": i * 1000, "name": f"user{i}", "score": i / 3, "tag": "x"} for i in range(5000)]},
+            "nested": {f"k{i}": {"a": [i * 1000, i * 1001], "b": f"text value {i}"} for i in range(2000)},
+            "unicode": {"xs": ["é" * 40 + str(i) for i in range(2000)]},
+        }
+        for name, data in documents.items():
+            with self.subTest(name):
+                text = toon_parser.encode(data)
+                tracemalloc.start()
+                try:
+                    value = toon_parser.decode(text)
+                    used, _ = tracemalloc.get_traced_memory()
+                finally:
+                    tracemalloc.stop()
+                del value
+                self.assertAlmostEqual(toon_parser.estimate_decoded_size(text)["bytes"] / used, 1, delta=0.25)
+
+    def test_never_fails(self):
+        for text in ("xs[3]: 1", 'a: "open', "   b: 1\nc", "xs[18446744073709551615]:"):
+            with self.subTest(text=text):
+                self.assertIsInstance(toon_parser.estimate_decoded_size(text)["bytes"], int)
+
+
+if __name__ == "__main__":
+    unittest.main()
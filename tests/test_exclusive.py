@@ -0,0 +1,101 @@
+"""Advisory file locks taken by ``dump_path`` and ``append_rows`` with ``exclusive=True``.
+
+Run with ``python -m unittest tests.test_exclusive``.
+"""
+
+import os
+import tempfile
+import threading
+import time
+import unittest
+
+import toon_parser
+
+try:
+    import fcntl
+except ImportError:
+    fcntl = None
+
+
+@unittest.skipIf(fcntl is None, "needs fcntl.flock")
+class ExclusiveTest(unittest.TestCase):
+    def setUp(self):
+        self.dir = tempfile.TemporaryDirectory()
+        self.path = os.path.join(self.dir.name, "log.toon")
+        with open(self.path, "w") as f:
+            f.write("logs[1]{ts,msg}:\n  1,a\n")
+
+    def tearDown(self):
+        self.dir.cleanup()
+
+    def lock(self):
+        held = open(self.path, "rb")
+        fcntl.flock(held, fcntl.LOCK_EX)
+        self.addCleanup(held.close)
+        return held
+
+    def start(self, target):
+        thread = threading.Thread(target=target)
+        thread.start()
+        self.addCleanup(thread.join)
+        time.sleep(0.2)
+        return thread
+
+    def read(self):
+        with open(self.path) as f:
+            return f.read()
+
+    def test_append_waits_for_the_lock(self):
+        held = self.lock()
+        thread = self.start(lambda: toon_parser.append_rows(self.path, [{"ts": 2, "msg": "b"}], exclusive=True))
+        self.assertTrue(thread.is_alive())
+        self.assertEqual(self.read(), "logs[1]{ts,msg}:\n  1,a\n")
+        fcntl.flock(held, fcntl.LOCK_UN)
+        thread.join()
+        self.assertEqual(self.read(), "logs[2]{ts,msg}:\n  1,a\n  2,b\n")
+
+    def test_dump_path_waits_for_the_lock(self):
+        for atomic in (False, True):
+            with self.subTest(atomic=atomic):
+                held = self.lock()
+                thread = self.start(lambda: toon_parser.dump_path({"b": 2}, self.path, exclusive=True, atomic=atomic))
+                self.assertTrue(thread.is_alive())
+                fcntl.flock(held, fcntl.LOCK_UN)
+                thread.join()
+                self.assertEqual(self.read(), "b: 2")
+
+    def test_lock_follows_a_replaced_file(self):
+        held = self.lock()
+        thread = self.start(lambda: toon_parser.append_rows(self.path, [{"ts": 9, "msg": "z"}], exclusive=True))
+        replacement = self.path + ".new"
+        with open(replacement, "w") as f:
+            f.write("logs[2]{ts,msg}:\n  5,x\n  6,y\n")
+        os.replace(replacement, self.path)
+        fcntl.flock(held, fcntl.LOCK_UN)
+        thread.join()
+        self.assertEqual(self.read(), "logs[3]{ts,msg}:\n  5,x\n  6,y\n  9,z\n")
+
+    def test_without_exclusive_nothing_waits(self):
+        self.lock()
+        self.assertEqual(toon_parser.append_rows(self.path, [{"ts": 2, "msg": "b"}]), 2)
+        toon_parser.dump_path({"a": 1}, self.path)
+        self.assertEqual(self.read(), "a: 1")
+
+    def test_concurrent_appenders_take_turns(self):
+        def append(worker):
+            for i in range(20):
+                toon_parser.append_rows(self.path, [{"ts": 10 + worker, "msg": f"m{i}"}], exclusive=True)
+
+        threads = [threading.Thread(target=append, args=(worker,)) for worker in range(8)]
+        for thread in threads:
+            thread.start()
+        for thread in threads:
+            thread.join()
+        logs = toon_parser.decode(self.read())["logs"]
+        self.assertEqual(len(logs), 161)
+        for worker in range(8):
+            self.assertEqual([row["msg"] for row in logs if row["ts"] == 10 + worker], [f"m{i}" for i in range(20)])
+
+
+if __name__ == "__main__":
+    unittest.main()
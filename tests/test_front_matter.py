@@ -0,0 +1,49 @@
+"""Front matter metadata blocks (``encode(meta=...)``, ``decode_with_meta``).
+
+Run with ``python -m unittest tests.test_front_matter``.
+"""
+
+import unittest
+
+import toon_parser
+
+META = {"version": 2, "tags": ["x"]}
+
+
+class FrontMatterTest(unittest.TestCase):
+    def test_block_precedes_the_body(self):
+        self.assertEqual(toon_parser.encode({"a": 1}, meta=META), "---\nversion: 2\ntags[1]: x\n---\na: 1")
+
+    def test_decode_with_meta(self):
+        text = toon_parser.encode({"a": 1}, meta=META)
+        self.assertEqual(toon_parser.decode_with_meta(text), ({"a": 1}, META))
+
+    def test_decode_skips_the_block(self):
+        self.assertEqual(toon_parser.decode(toon_parser.encode({"a": 1}, meta=META)), {"a": 1})
+
+    def test_no_block(self):
+        self.assertEqual(toon_parser.decode_with_meta("a: 1\n"), ({"a": 1}, None))
+
+    def test_empty_meta_and_root_arrays(self):
+        self.assertEqual(toon_parser.decode_with_meta(toon_parser.encode({"a": 1}, meta={})), ({"a": 1}, {}))
+        self.assertEqual(toon_parser.decode_with_meta(toon_parser.encode([1, 2], meta={"v": 1})), ([1, 2], {"v": 1}))
+
+    def test_fence_text_in_values(self):
+        data = {"x": "---", "y": {"k": "---"}, "z": ["---"]}
+        text = toon_parser.encode(data, meta={"m": "---"})
+        self.assertEqual(toon_parser.decode_with_meta(text), (data, {"m": "---"}))
+        self.assertEqual(toon_parser.decode(toon_parser.encode(data)), data)
+
+    def test_with_checksum(self):
+        text = toon_parser.encode({"a": 1}, meta={"v": 1}, checksum=True)
+        self.assertEqual(toon_parser.decode(text, verify_checksum=True), {"a": 1})
+        self.assertEqual(toon_parser.decode_with_meta(text), ({"a": 1}, {"v": 1}))
+
+    def test_syntax_errors_name_the_document_line(self):
+        with self.assertRaises(toon_parser.ToonSyntaxError) as ctx:
+            toon_parser.decode("---\nv: 1\n---\na: 1\nb[2]: x\n", strict=True)
+        self.assertIn("Line 5", str(ctx.exception))
+
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,84 @@
+"""Arrays of dicts with different keys, and ``encode(..., heterogeneous=...)``.
+
+Run with ``python -m unittest tests.test_heterogeneous``.
+"""
+
+import unittest
+
+import toon_parser
+
+RAGGED = {"rows": [{"a": 1, "b": 2}, {"a": 3, "c": 4}]}
+
+
+class PolicyTest(unittest.TestCase):
+    def test_expand_by_default(self):
+        expected = "rows[2]:\n  - a: 1\n    b: 2\n  - a: 3\n    c: 4"
+        self.assertEqual(toon_parser.encode(RAGGED), expected)
+        self.assertEqual(toon_parser.encode(RAGGED, heterogeneous="expand"), expected)
+
+    def test_union(self):
+        text = toon_parser.encode(RAGGED, heterogeneous="union")
+        self.assertEqual(text, "rows[2]{a,b,c}:\n  1,2,null\n  3,null,4")
+        self.assertEqual(toon_parser.decode(text), {"rows": [{"a": 1, "b": 2, "c": None}, {"a": 3, "b": None, "c": 4}]})
+
+    def test_drop(self):
+        self.assertEqual(toon_parser.encode(RAGGED, heterogeneous="drop"), "rows[2]{a}:\n  1\n  3")
+
+    def test_drop_without_shared_keys(self):
+        value = {"xs": [{"a": 1}, {"b": 2}]}
+        self.assertEqual(toon_parser.decode(toon_parser.encode(value, heterogeneous="drop")), {"xs": [{}, {}]})
+
+    def test_error_names_the_array_and_row(self):
+        with self.assertRaisesRegex(ValueError, r"^\$\.rows: rows have different keys; row 0 lacks 'c'$"):
+            toon_parser.encode(RAGGED, heterogeneous="error")
+
+    def test_uniform_arrays_pass_every_policy(self):
+        value = {"rows": [{"a": 1}, {"a": 2}]}
+        for policy in ("expand", "union", "drop", "error"):
+            with self.subTest(policy=policy):
+                self.assertEqual(toon_parser.encode(value, heterogeneous=policy), "rows[2]{a}:\n  1\n  2")
+
+    def test_invalid_policy(self):
+        with self.assertRaisesRegex(ValueError, "Invalid heterogeneous 'x'"):
+            toon_parser.encode(RAGGED, heterogeneous="x")
+
+
+class MixedTypesTest(unittest.TestCase):
+    def test_dicts_mixed_with_other_values_stay_a_list(self):
+        value = {"xs": [{"a": 1}, 5]}
+        for policy in ("union", "drop"):
+            with self.subTest(policy=policy):
+                self.assertEqual(toon_parser.encode(value, heterogeneous=policy), "xs[2]:\n  - a: 1\n  - 5")
+
+    def test_error(self):
+        with self.assertRaisesRegex(ValueError, r"^\$\.xs: array mixes dict and int elements$"):
+            toon_parser.encode({"xs": [{"a": 1}, 5]}, heterogeneous="error")
+
+    def test_arrays_without_dicts_are_left_alone(self):
+        self.assertEqual(toon_parser.encode({"xs": [1, "a", [2]]}, heterogeneous="error"), toon_parser.encode({"xs": [1, "a", [2]]}))
+
+
+class NestedTest(unittest.TestCase):
+    def test_nested_arrays(self):
+        value = {"o": {"xs": [{"a": 1}, {"a": 1, "b": 2}]}, "ys": [[{"a": 1}, {"b": 1}]]}
+        self.assertEqual(
+            toon_parser.decode(toon_parser.encode(value, heterogeneous="union")),
+            {"o": {"xs": [{"a": 1, "b": None}, {"a": 1, "b": 2}]}, "ys": [[{"a": 1, "b": None}, {"a": None, "b": 1}]]},
+        )
+
+    def test_error_paths(self):
+        cases = [
+            ({"o": {"xs": [{"a": 1}, {"b": 1}]}}, "$.o.xs"),
+            ({"xs": [[{"a": 1}, {"b": 1}]]}, "$.xs[0]"),
+            ({"my key": [{"a": 1}, {"b": 1}]}, '$["my key"]'),
+            ([{"a": 1}, {"b": 1}], "$"),
+        ]
+        for value, path in cases:
+            with self.subTest(path=path):
+                with self.assertRaises(ValueError) as cm:
+                    toon_parser.encode(value, heterogeneous="error")
+                self.assertTrue(str(cm.exception).startswith(path + ": "), str(cm.exception))
+
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,91 @@
+"""``!include`` directives: ``decode(..., include_base=...)``.
+
+Run with ``python -m unittest tests.test_include``.
+"""
+
+import os
+import tempfile
+import unittest
+
+import toon_parser
+from toon_parser import Options, ToonError, ToonLimitError
+
+
+class IncludeTest(unittest.TestCase):
+    def setUp(self):
+        self.dir = tempfile.TemporaryDirectory()
+        self.base = self.dir.name
+
+    def tearDown(self):
+        self.dir.cleanup()
+
+    def write(self, name, text):
+        path = os.path.join(self.base, name)
+        os.makedirs(os.path.dirname(path), exist_ok=True)
+        with open(path, "w", encoding="utf-8") as f:
+            f.write(text)
+
+    def decode(self, doc, **kwargs):
+        return toon_parser.decode(doc, include_base=self.base, **kwargs)
+
+    def test_value_and_list_items(self):
+        self.write("db.toon", "host: localhost\nport: 5432\n")
+        self.write("workers/a.toon", "name: a\n")
+        self.write("workers/b.toon", "name: b\n")
+        doc = "service: api\ndatabase: !include db.toon\nworkers[2]:\n  - !include workers/a.toon\n  - !include workers/b.toon\n"
+        self.assertEqual(
+            self.decode(doc),
+            {
+                "service": "api",
+                "database": {"host": "localhost", "port": 5432},
+                "workers": [{"name": "a"}, {"name": "b"}],
+            },
+        )
+
+    def test_nested_includes_are_relative_to_the_including_file(self):
+        self.write("sub/outer.toon", "inner: !include inner.toon\n")
+        self.write("sub/inner.toon", "x: 1\n")
+        self.assertEqual(self.decode("a: !include sub/outer.toon\n"), {"a": {"inner": {"x": 1}}})
+
+    def test_quoted_directive_stays_a_string(self):
+        self.write("db.toon", "x: 1\n")
+        self.assertEqual(self.decode('a: "!include db.toon"\n'), {"a": "!include db.toon"})
+
+    def test_strings_starting_with_the_marker_character_stay_strings(self):
+        self.write("db.toon", "x: 1\n")
+        for doc in ('a: "\ufdd4db.toon"\n', 'a: "\\ufdd4db.toon"\n', "a: \ufdd4db.toon\n"):
+            with self.subTest(doc=doc):
+                self.assertEqual(self.decode(doc), {"a": "\ufdd4db.toon"})
+
+    def test_marker_character_inside_an_included_file(self):
+        self.write("db.toon", 'x: "\ufdd4other.toon"\n')
+        self.write("other.toon", "y: 1\n")
+        self.assertEqual(self.decode("a: !include db.toon\n"), {"a": {"x": "\ufdd4other.toon"}})
+
+    def test_disabled_without_include_base(self):
+        self.assertEqual(toon_parser.decode("a: !include db.toon\n"), {"a": "!include db.toon"})
+
+    def test_path_outside_base(self):
+        self.write("outside.toon", "x: 1\n")
+        self.write("root/inside.toon", "x: 2\n")
+        with self.assertRaisesRegex(ToonError, "outside the include base"):
+            toon_parser.decode("a: !include ../outside.toon\n", include_base=os.path.join(self.base, "root"))
+
+    def test_cycle(self):
+        self.write("a.toon", "b: !include b.toon\n")
+        self.write("b.toon", "a: !include a.toon\n")
+        with self.assertRaisesRegex(ToonError, "include cycle"):
+            self.decode("root: !include a.toon\n")
+
+    def test_missing_file(self):
+        with self.assertRaises(toon_parser.ToonIOError):
+            self.decode("a: !include missing.toon\n")
+
+    def test_limits_cover_includes_together(self):
+        self.write("big.toon", "k: " + "x" * 400 + "\n")
+        with self.assertRaises(ToonLimitError):
+            self.decode("a: !include big.toon\nb: !include big.toon\n", options=Options(max_input_bytes=600))
+
+
+if __name__ == "__main__":
+    unittest.main()
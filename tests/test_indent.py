@@ -0,0 +1,67 @@
+"""Indentation width and character with ``indent`` on ``encode`` and ``Options``.
+
+Run with ``python -m unittest tests.test_indent``.
+"""
+
+import re
+import unittest
+
+import toon_parser
+from toon_parser import Options
+
+DATA = {"a": {"b": {"c": 1}, "xs": [{"k": 1}, {"k": 2}], "l": [[1, 2], {"q": "x", "r": 2}]}, "t": "multi word"}
+
+
+class IndentTest(unittest.TestCase):
+    def test_encode(self):
+        self.assertEqual(
+            toon_parser.encode(DATA, indent=4),
+            "a:\n    b:\n        c: 1\n    xs[2]{k}:\n        1\n        2\n    l[2]:\n"
+            "        - [2]: 1,2\n        - q: x\n            r: 2\nt: multi word",
+        )
+        self.assertEqual(toon_parser.encode({"a": {"b": [1]}}, indent="\t"), "a:\n\tb[1]: 1")
+        self.assertEqual(toon_parser.encode({"a": {"b": 1}}, indent=1), "a:\n b: 1")
+
+    def test_default_is_two_spaces(self):
+        self.assertEqual(toon_parser.encode(DATA, indent=2), toon_parser.encode(DATA))
+        self.assertEqual(toon_parser.encode(DATA, indent=None), toon_parser.encode(DATA))
+        self.assertEqual(Options().indent, 2)
+
+    def test_round_trip_through_options(self):
+        for indent in (1, 3, 4, 16, "\t"):
+            with self.subTest(indent=indent):
+                options = Options(indent=indent)
+                text = toon_parser.encode_with_options(DATA, options)
+                self.assertEqual(text, toon_parser.encode(DATA, indent=indent))
+                self.assertEqual(toon_parser.decode_with_options(text, options), DATA)
+                self.assertEqual(toon_parser.decode(text, options=options), DATA)
+                self.assertEqual(toon_parser.decode_bytes(toon_parser.encode_bytes(DATA, options), options), DATA)
+
+    def test_options_setting(self):
+        self.assertEqual(Options(indent=4).indent, 4)
+        self.assertEqual(Options(indent="\t").indent, "\t")
+        self.assertEqual(Options().with_indent(4), Options(indent=4))
+        self.assertEqual(repr(Options(indent=4)), "Options(delimiter='comma', strict=false, indent=4)")
+        self.assertEqual(repr(Options(indent="\t")), "Options(delimiter='comma', strict=false, indent='\\t')")
+
+    def test_invalid(self):
+        cases = [
+            (0, ValueError, "Invalid indent 0. Must be between 1 and 16 spaces"),
+            (-1, ValueError, "Invalid indent -1. Must be between 1 and 16 spaces"),
+            (17, ValueError, "Invalid indent 17. Must be between 1 and 16 spaces"),
+            (2**70, ValueError, f"Invalid indent {2**70}. Must be between 1 and 16 spaces"),
+            ("  ", ValueError, "Invalid indent \"  \". Must be a number of spaces or '\\t'"),
+            (True, TypeError, "indent must be a number of spaces or '\\t'"),
+            (1.5, TypeError, "indent must be a number of spaces or '\\t'"),
+            ([2], TypeError, "indent must be a number of spaces or '\\t'"),
+        ]
+        for indent, error, message in cases:
+            with self.subTest(indent=indent):
+                with self.assertRaisesRegex(error, "^" + re.escape(message)):
+                    toon_parser.encode(DATA, indent=indent)
+                with self.assertRaises(error):
+                    Options(indent=indent)
+
+
+if __name__ == "__main__":
+    unittest.main()
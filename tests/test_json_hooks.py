@@ -0,0 +1,85 @@
+"""Encoding objects through their ``for_json()`` or ``__json__()`` methods.
+
+Run with ``python -m unittest tests.test_json_hooks``.
+"""
+
+import unittest
+
+import toon_parser
+
+
+class ForJson:
+    def for_json(self):
+        return {"a": 1}
+
+    def __json__(self):
+        return {"b": 2}
+
+
+class DunderJson:
+    def __json__(self):
+        return [ForJson(), 3]
+
+
+class NotCallable:
+    for_json = 5
+
+    def __json__(self):
+        return "c"
+
+
+class ReturnsSelf:
+    def for_json(self):
+        return self
+
+
+class Raises:
+    def for_json(self):
+        raise KeyError("boom")
+
+
+class Chain:
+    def __init__(self, n):
+        self.n = n
+
+    def for_json(self):
+        return Chain(self.n - 1) if self.n > 1 else "end"
+
+
+class HooksTest(unittest.TestCase):
+    def test_hook_result_is_encoded(self):
+        cases = [
+            (ForJson(), "a: 1"),
+            ({"x": DunderJson()}, "x[2]:\n  - a: 1\n  - 3"),
+            (NotCallable(), "c"),
+            ([ForJson(), ForJson()], "[2]{a}:\n  1\n  1"),
+        ]
+        for data, text in cases:
+            with self.subTest(data=data):
+                self.assertEqual(toon_parser.encode(data), text)
+
+    def test_other_entry_points(self):
+        self.assertEqual(toon_parser.dumps({"x": ForJson()}), "x:\n  a: 1")
+        self.assertEqual(toon_parser.encode_batch([ForJson()]), ["a: 1"])
+        self.assertEqual(toon_parser.stats(ForJson())["keys"], 1)
+
+    def test_hook_returning_its_own_object(self):
+        with self.assertRaisesRegex(ValueError, "^for_json\\(\\) of type 'ReturnsSelf' returned the object itself$"):
+            toon_parser.encode(ReturnsSelf())
+
+    def test_hook_errors_propagate(self):
+        with self.assertRaisesRegex(KeyError, "boom"):
+            toon_parser.encode(Raises())
+
+    def test_chained_hooks_are_bounded(self):
+        self.assertEqual(toon_parser.encode(Chain(64)), "end")
+        with self.assertRaisesRegex(ValueError, "^for_json\\(\\) of type 'Chain' led through more than 64 conversion hooks"):
+            toon_parser.encode(Chain(65))
+
+    def test_objects_without_hooks(self):
+        with self.assertRaisesRegex(ValueError, "Cannot convert type 'object'"):
+            toon_parser.encode(object())
+
+
+if __name__ == "__main__":
+    unittest.main()
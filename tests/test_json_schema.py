@@ -0,0 +1,83 @@
+"""JSON Schema validation on decode (``decode(schema=...)``, ``validate_schema``).
+
+Run with ``python -m unittest tests.test_json_schema``.
+"""
+
+import unittest
+
+import toon_parser
+from toon_parser import ToonError, ToonSchemaError
+
+USERS = {
+    "type": "object",
+    "required": ["users"],
+    "properties": {
+        "users": {
+            "type": "array",
+            "items": {
+                "type": "object",
+                "required": ["id", "name"],
+                "properties": {"id": {"type": "integer"}, "name": {"type": "string"}},
+            },
+        }
+    },
+}
+
+
+class DecodeSchemaTest(unittest.TestCase):
+    def test_valid_document_decodes(self):
+        doc = "users[2]{id,name}:\n  1,Ann\n  2,Bo\n"
+        self.assertEqual(
+            toon_parser.decode(doc, schema=USERS),
+            {"users": [{"id": 1, "name": "Ann"}, {"id": 2, "name": "Bo"}]},
+        )
+
+    def test_violation_names_path_and_line(self):
+        doc = "users[2]{id,name}:\n  1,Ann\n  two,Bo\n"
+        with self.assertRaises(ToonSchemaError) as ctx:
+            toon_parser.decode(doc, schema=USERS)
+        err = ctx.exception
+        self.assertEqual(err.instance_path, "/users/1/id")
+        self.assertIn("type", err.schema_path)
+        self.assertEqual(err.line, 3)
+        self.assertIn("Line 3", str(err))
+
+    def test_violation_in_root_table(self):
+        schema = {"type": "array", "items": {"properties": {"id": {"type": "integer"}}}}
+        with self.assertRaises(ToonSchemaError) as ctx:
+            toon_parser.decode("[3]{id}:\n  1\n  2\n  x\n", schema=schema)
+        self.assertEqual(ctx.exception.instance_path, "/2/id")
+        self.assertEqual(ctx.exception.line, 4)
+
+    def test_violation_in_root_list(self):
+        schema = {"type": "array", "items": {"properties": {"b": {"type": "integer"}}}}
+        with self.assertRaises(ToonSchemaError) as ctx:
+            toon_parser.decode("[2]:\n  - a: 1\n  - a: 2\n    b: x\n", schema=schema)
+        self.assertEqual(ctx.exception.line, 4)
+
+    def test_missing_required_key(self):
+        with self.assertRaises(ToonSchemaError) as ctx:
+            toon_parser.decode("other: 1\n", schema=USERS)
+        self.assertEqual(ctx.exception.instance_path, "")
+
+    def test_schema_error_is_a_toon_error(self):
+        self.assertTrue(issubclass(ToonSchemaError, ToonError))
+
+    def test_invalid_schema_is_a_value_error(self):
+        with self.assertRaises(ValueError):
+            toon_parser.decode("a: 1\n", schema={"type": 12})
+
+
+class ValidateSchemaTest(unittest.TestCase):
+    def test_valid_data_returns_none(self):
+        self.assertIsNone(toon_parser.validate_schema({"users": []}, USERS))
+
+    def test_violation_has_no_line(self):
+        with self.assertRaises(ToonSchemaError) as ctx:
+            toon_parser.validate_schema({"users": [{"id": 1}]}, USERS)
+        self.assertEqual(ctx.exception.instance_path, "/users/0")
+        self.assertIsNone(ctx.exception.line)
+
+
+if __name__ == "__main__":
+    unittest.main()
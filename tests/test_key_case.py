@@ -0,0 +1,69 @@
+"""Key case conversion between Python and the document (``Options(key_transform=...)``).
+
+Run with ``python -m unittest tests.test_key_case``.
+"""
+
+import io
+import unittest
+
+import toon_parser
+from toon_parser import Options
+
+DATA = {"user_id": 1, "profile": {"first_name": "Ann"}, "login_events": [{"event_id": 1, "ip_addr": "x"}]}
+
+
+class EncodeTest(unittest.TestCase):
+    def test_camel(self):
+        text = toon_parser.encode_with_options(DATA, Options(key_transform="camel"))
+        self.assertEqual(text, "userId: 1\nprofile:\n  firstName: Ann\nloginEvents[1]{eventId,ipAddr}:\n  1,x")
+
+    def test_kebab(self):
+        text = toon_parser.encode_with_options({"user_id": 1, "firstName": 2}, Options(key_transform="kebab"))
+        self.assertEqual(toon_parser.decode(text), {"user-id": 1, "first-name": 2})
+
+    def test_snake_splits_acronyms(self):
+        text = toon_parser.encode_with_options({"HTTPServer": 1, "userID": 2}, Options(key_transform="snake"))
+        self.assertEqual(text, "http_server: 1\nuser_id: 2")
+
+    def test_values_are_untouched(self):
+        text = toon_parser.encode_with_options({"a_b": "c_d"}, Options(key_transform="camel"))
+        self.assertEqual(text, "aB: c_d")
+
+
+class DecodeTest(unittest.TestCase):
+    CAMEL = "userId: 1\nprofile:\n  firstName: Ann\nloginEvents[1]{eventId,ipAddr}:\n  1,x\n"
+
+    def test_round_trip(self):
+        opts = Options(key_transform="camel")
+        self.assertEqual(toon_parser.decode_with_options(toon_parser.encode_with_options(DATA, opts), opts), DATA)
+
+    def test_every_options_entry_point(self):
+        opts = Options(key_transform="camel")
+        self.assertEqual(toon_parser.decode_with_options(self.CAMEL, opts), DATA)
+        self.assertEqual(toon_parser.decode(self.CAMEL, options=opts), DATA)
+        self.assertEqual(toon_parser.loads(self.CAMEL, options=opts), DATA)
+        self.assertEqual(toon_parser.load(io.StringIO(self.CAMEL), options=opts), DATA)
+        self.assertEqual(toon_parser.decode_bytes(self.CAMEL.encode(), opts), DATA)
+
+    def test_schema_sees_python_names(self):
+        schema = {"required": ["user_id"]}
+        self.assertEqual(toon_parser.decode(self.CAMEL, schema=schema, options=Options(key_transform="camel")), DATA)
+
+    def test_without_transform_keys_stay(self):
+        self.assertEqual(toon_parser.decode(self.CAMEL, options=Options())["userId"], 1)
+
+
+class OptionsTest(unittest.TestCase):
+    def test_attribute_and_repr(self):
+        opts = Options(key_transform="camel")
+        self.assertEqual(opts.key_transform, "camel")
+        self.assertIn("key_transform='camel'", repr(opts))
+        self.assertIsNone(Options().key_transform)
+
+    def test_invalid_case(self):
+        with self.assertRaisesRegex(ValueError, "Invalid key_transform 'pascal'"):
+            Options(key_transform="pascal")
+
+
+if __name__ == "__main__":
+    unittest.main()
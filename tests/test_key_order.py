@@ -0,0 +1,54 @@
+"""Dict insertion order kept through ``encode``, ``decode`` and the round trip.
+
+Run with ``python -m unittest tests.test_key_order``.
+"""
+
+import unittest
+
+import toon_parser
+
+DATA = {"z": 1, "a": {"y": 2, "b": 3, "x": [{"q": 1, "c": 2}, {"q": 3, "c": 4}]}, "m": "s", "b": None}
+
+
+def keys(value):
+    """Key order of every dict in `value`, depth first."""
+    if isinstance(value, dict):
+        return [list(value)] + [k for v in value.values() for k in keys(v)]
+    if isinstance(value, list):
+        return [k for v in value for k in keys(v)]
+    return []
+
+
+class KeyOrderTest(unittest.TestCase):
+    def test_encode_writes_insertion_order(self):
+        self.assertEqual(toon_parser.encode({"name": "Alice", "age": 30, "active": True}), "name: Alice\nage: 30\nactive: true")
+        self.assertEqual(toon_parser.encode({"rows": [{"q": 1, "c": 2}]}), "rows[1]{q,c}:\n  1,2")
+
+    def test_decode_reads_document_order(self):
+        value = toon_parser.decode("z: 1\na:\n  y: 2\n  b: 3\nrows[1]{q,c}:\n  1,2")
+        self.assertEqual(keys(value), [["z", "a", "rows"], ["y", "b"], ["q", "c"]])
+
+    def test_round_trips(self):
+        cases = [
+            ("encode", lambda: toon_parser.decode(toon_parser.encode(DATA))),
+            ("dumps", lambda: toon_parser.loads(toon_parser.dumps(DATA))),
+            ("batch", lambda: toon_parser.decode_batch(toon_parser.encode_batch([DATA]))[0]),
+            ("bytes", lambda: toon_parser.decode_bytes(toon_parser.encode(DATA).encode())),
+            ("anchors", lambda: toon_parser.decode(toon_parser.encode(DATA, anchors=True), anchors=True)),
+        ]
+        for name, round_trip in cases:
+            with self.subTest(name):
+                value = round_trip()
+                self.assertEqual(value, DATA)
+                self.assertEqual(keys(value), keys(DATA))
+
+    def test_redacted_keys_keep_their_place(self):
+        value = toon_parser.decode(toon_parser.encode(DATA, redact=["y"]))
+        self.assertEqual(keys(value), keys(DATA))
+
+    def test_sorting_is_explicit(self):
+        self.assertEqual(keys(toon_parser.decode(toon_parser.encode(DATA, sort_keys=True))), [["a", "b", "m", "z"], ["b", "x", "y"], ["c", "q"], ["c", "q"]])
+
+
+if __name__ == "__main__":
+    unittest.main()
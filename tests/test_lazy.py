@@ -0,0 +1,84 @@
+"""Lazy decoding with ``decode_lazy`` and its ``ToonValue`` proxies.
+
+Run with ``python -m unittest tests.test_lazy``.
+"""
+
+import unittest
+
+import toon_parser
+
+DOC = "users[3]{id,email}:\n  1,a@x\n  2,b@x\n  3,c@x\nmeta:\n  n: 3\n  tags[2]: a,b\n"
+
+
+class DecodeLazyTest(unittest.TestCase):
+    def setUp(self):
+        self.doc = toon_parser.decode_lazy(DOC)
+
+    def test_containers_are_proxies(self):
+        self.assertIsInstance(self.doc, toon_parser.ToonValue)
+        self.assertIsInstance(self.doc["users"], toon_parser.ToonValue)
+        self.assertIsInstance(self.doc["users"][0], toon_parser.ToonValue)
+        self.assertEqual((self.doc.kind, self.doc["users"].kind), ("dict", "list"))
+
+    def test_scalars_are_plain_values(self):
+        self.assertEqual(self.doc["users"][0]["email"], "a@x")
+        self.assertEqual(self.doc["users"][-1]["id"], 3)
+        self.assertEqual(self.doc["meta"]["n"], 3)
+
+    def test_scalar_root(self):
+        self.assertEqual(toon_parser.decode_lazy("5"), 5)
+
+    def test_mapping_protocol(self):
+        self.assertEqual(len(self.doc), 2)
+        self.assertEqual(list(self.doc), ["users", "meta"])
+        self.assertEqual(self.doc.keys(), ["users", "meta"])
+        self.assertIn("meta", self.doc)
+        self.assertNotIn("nope", self.doc)
+        self.assertEqual(self.doc.get("nope"), None)
+        self.assertEqual(self.doc.get("nope", 1), 1)
+
+    def test_sequence_protocol(self):
+        users = self.doc["users"]
+        self.assertEqual(len(users), 3)
+        self.assertEqual([user["id"] for user in users], [1, 2, 3])
+        self.assertIn("a", self.doc["meta"]["tags"])
+        self.assertEqual(users.get(9, "d"), "d")
+
+    def test_missing_keys_and_indices(self):
+        with self.assertRaises(KeyError):
+            self.doc["nope"]
+        with self.assertRaisesRegex(IndexError, "out of range"):
+            self.doc["users"][3]
+        with self.assertRaisesRegex(TypeError, "dict keys are str"):
+            self.doc[0]
+        with self.assertRaisesRegex(TypeError, "list indices must be integers"):
+            self.doc["users"]["id"]
+        with self.assertRaisesRegex(TypeError, "only dict values have keys"):
+            self.doc["users"].keys()
+
+    def test_to_python(self):
+        self.assertEqual(self.doc.to_python(), toon_parser.decode(DOC))
+        self.assertEqual(self.doc["meta"]["tags"].to_python(), ["a", "b"])
+
+    def test_equality(self):
+        self.assertEqual(self.doc, toon_parser.decode_lazy(DOC))
+        self.assertEqual(self.doc["meta"]["tags"], ["a", "b"])
+        self.assertNotEqual(self.doc["meta"]["tags"], ["a"])
+        self.assertRaises(TypeError, hash, self.doc)
+
+    def test_repr(self):
+        self.assertEqual(repr(self.doc), "<ToonValue dict with 2 keys>")
+        self.assertEqual(repr(self.doc["users"]), "<ToonValue list with 3 items>")
+
+    def test_front_matter_is_skipped(self):
+        self.assertEqual(toon_parser.decode_lazy("---\nv: 1\n---\na: 1\n").to_python(), {"a": 1})
+
+    def test_errors_and_limits(self):
+        with self.assertRaises(toon_parser.ToonSyntaxError):
+            toon_parser.decode_lazy("xs[3]: 1,2", strict=True)
+        with self.assertRaises(toon_parser.ToonLimitError):
+            toon_parser.decode_lazy(DOC, options=toon_parser.Options(max_input_bytes=5))
+
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,77 @@
+"""Long string values decoded as ``LazyString`` with ``decode(..., lazy_strings=n)``.
+
+Run with ``python -m unittest tests.test_lazy_strings``.
+"""
+
+import unittest
+
+import toon_parser
+from toon_parser import LazyString
+
+TEXT = 'a: hello\nb: hi\nxs[2]: hello,yo\nr[1]{k}:\n  hello\nlongkey: 1\nc: "é"'
+
+
+class DecodeTest(unittest.TestCase):
+    def test_only_values_longer_than_the_threshold(self):
+        doc = toon_parser.decode(TEXT, lazy_strings=3)
+        self.assertIsInstance(doc["a"], LazyString)
+        self.assertIsInstance(doc["xs"][0], LazyString)
+        self.assertIsInstance(doc["r"][0]["k"], LazyString)
+        for value in (doc["b"], doc["xs"][1], doc["c"]):
+            self.assertIs(type(value), str)
+        self.assertTrue(all(type(key) is str for key in doc))
+
+    def test_threshold_counts_bytes(self):
+        self.assertIs(type(toon_parser.decode("a: hello", lazy_strings=5)["a"]), str)
+        self.assertIsInstance(toon_parser.decode("a: hello", lazy_strings=4)["a"], LazyString)
+        self.assertIsInstance(toon_parser.decode("c: é", lazy_strings=1)["c"], LazyString)
+
+    def test_off_by_default(self):
+        self.assertEqual(toon_parser.decode("a: hello"), {"a": "hello"})
+        self.assertIs(type(toon_parser.decode("a: hello")["a"]), str)
+
+
+class LazyStringTest(unittest.TestCase):
+    def setUp(self):
+        doc = toon_parser.decode("a: hello\nb: world\nc: éé", lazy_strings=1)
+        self.hello, self.world, self.accents = doc["a"], doc["b"], doc["c"]
+
+    def test_without_building_the_str(self):
+        self.assertEqual(repr(self.accents), "<LazyString of 2 chars>")
+        self.assertEqual(len(self.accents), 2)
+        self.assertEqual(bytes(self.accents), "éé".encode())
+
+    def test_str_and_methods(self):
+        self.assertEqual(str(self.hello), "hello")
+        self.assertEqual(self.hello.upper(), "HELLO")
+        self.assertEqual(self.hello[1:3], "el")
+        self.assertIn("ell", self.hello)
+        self.assertEqual(bytes(self.hello), b"hello")
+
+    def test_comparison_and_hashing(self):
+        self.assertEqual(self.hello, "hello")
+        self.assertNotEqual(self.hello, self.world)
+        self.assertNotEqual(self.hello, 5)
+        self.assertLess(self.hello, self.world)
+        self.assertGreater("z", self.hello)
+        self.assertEqual(sorted([self.world, "c", self.hello]), ["c", "hello", "world"])
+        self.assertEqual(hash(self.hello), hash("hello"))
+        self.assertEqual({self.hello: 1}["hello"], 1)
+        with self.assertRaises(TypeError):
+            self.hello < 5
+
+    def test_concatenation(self):
+        self.assertEqual(self.hello + "!", "hello!")
+        self.assertEqual(">" + self.hello, ">hello")
+        self.assertEqual(self.hello + self.world, "helloworld")
+        with self.assertRaises(TypeError):
+            self.hello + 5
+
+    def test_encoded_like_a_str(self):
+        doc = toon_parser.decode(TEXT, lazy_strings=3)
+        self.assertEqual(toon_parser.encode(doc), toon_parser.encode(toon_parser.decode(TEXT)))
+        self.assertEqual(toon_parser.dumps({"x": self.hello}), "x: hello")
+
+
+if __name__ == "__main__":
+    unittest.main()
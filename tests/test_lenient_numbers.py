@@ -0,0 +1,53 @@
+"""Numbers as people write them: ``decode(..., lenient_numbers=True)``.
+
+Run with ``python -m unittest tests.test_lenient_numbers``.
+"""
+
+import unittest
+
+import toon_parser
+
+
+def decode(value, **kwargs):
+    return toon_parser.decode("v: {}\n".format(value), lenient_numbers=True, **kwargs)["v"]
+
+
+class LenientNumbersTest(unittest.TestCase):
+    def test_grouped_and_signed(self):
+        cases = {
+            "1_000_000": 1000000,
+            "+5": 5,
+            "-1_0": -10,
+            "0.000_1": 0.0001,
+            "+1_000.5e3": 1000500.0,
+            "1e+1_0": 1e10,
+        }
+        for text, expected in cases.items():
+            with self.subTest(text=text):
+                self.assertEqual(decode(text), expected)
+
+    def test_malformed_values_stay_strings(self):
+        for text in ("+", "_1", "1_", "1__0", "1_e5", "1e_5", "1e+_5", "+-1", "++1", "1_.5", "+.5"):
+            with self.subTest(text=text):
+                self.assertEqual(decode(text), text)
+
+    def test_quoted_values_stay_strings(self):
+        self.assertEqual(decode('"1_000"'), "1_000")
+        self.assertEqual(decode('"+5"'), "+5")
+
+    def test_off_by_default(self):
+        self.assertEqual(toon_parser.decode("v: 1_000\n"), {"v": "1_000"})
+
+    def test_tables_and_inline_arrays(self):
+        doc = "rows[2]{a,b}:\n  1_0,+2\n  \"3_0\",4\nxs[2]: +1,2_2\n"
+        self.assertEqual(
+            toon_parser.decode(doc, lenient_numbers=True),
+            {"rows": [{"a": 10, "b": 2}, {"a": "3_0", "b": 4}], "xs": [1, 22]},
+        )
+
+    def test_keys_are_left_alone(self):
+        self.assertEqual(toon_parser.decode("1_0: +1\n", lenient_numbers=True), {"1_0": 1})
+
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,54 @@
+"""Collecting every diagnostic for a document (``lint``).
+
+Run with ``python -m unittest tests.test_lint``.
+"""
+
+import unittest
+
+import toon_parser
+
+
+def summary(doc, **kwargs):
+    return [(d["line"], d["column"], d["severity"], d["message"]) for d in toon_parser.lint(doc, **kwargs)]
+
+
+class LintTest(unittest.TestCase):
+    def test_clean_document(self):
+        self.assertEqual(toon_parser.lint("a: 1\nrows[2]{x,y}:\n  1,2\n  3,4\n"), [])
+
+    def test_style_warnings(self):
+        self.assertEqual(
+            summary("a:\n   b: 1\nc: 2 \n"),
+            [
+                (2, 1, "warning", "indentation of 3 spaces is not a multiple of 2"),
+                (3, 5, "warning", "trailing whitespace"),
+            ],
+        )
+
+    def test_strict_turns_warnings_into_errors(self):
+        self.assertEqual(
+            [(line, severity) for line, _, severity, _ in summary("a:\n   b: 1\nc: 2 \n", strict=True)],
+            [(2, "error"), (3, "error")],
+        )
+
+    def test_every_problem_is_reported(self):
+        doc = "xs[3]: 1,2\nrows[2]{a,b}:\n  1,2\n  3\nys[1]: 1,2\n"
+        self.assertEqual(
+            summary(doc),
+            [
+                (1, 3, "error", "header declares 3 values but 2 found"),
+                (4, 3, "error", "row has 1 values but the table declares 2 fields"),
+                (5, 3, "error", "header declares 1 values but 2 found"),
+            ],
+        )
+
+    def test_parser_errors_are_included_once(self):
+        self.assertEqual(summary('a: "open\n'), [(1, None, "error", "unterminated string")])
+        self.assertEqual(summary("a: 1\n  b: 2\n"), [(2, None, "error", "unexpected indentation")])
+
+    def test_quoted_delimiters_are_not_cells(self):
+        self.assertEqual(toon_parser.lint('xs[2]: "a,b",c\nrows[1]{a,b}:\n  "x,y",z\n'), [])
+
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,64 @@
+"""Repeated table sections concatenated with ``decode(..., merge_tables=True)``.
+
+Run with ``python -m unittest tests.test_merge_tables``.
+"""
+
+import unittest
+
+import toon_parser
+from toon_parser import ToonSyntaxError
+
+LOG = "logs[2]{ts,msg}:\n  1,a\n  2,b\nother: x\nlogs[1]{ts,msg}:\n  3,c\n"
+
+
+class MergeTablesTest(unittest.TestCase):
+    def test_sections_concatenate_in_document_order(self):
+        self.assertEqual(
+            toon_parser.decode(LOG, merge_tables=True),
+            {"logs": [{"ts": 1, "msg": "a"}, {"ts": 2, "msg": "b"}, {"ts": 3, "msg": "c"}], "other": "x"},
+        )
+
+    def test_last_section_wins_by_default(self):
+        self.assertEqual(toon_parser.decode(LOG), {"logs": [{"ts": 3, "msg": "c"}], "other": "x"})
+
+    def test_any_repeated_array(self):
+        cases = [
+            ("xs[1]: 1\nxs[2]: 2,3\nxs[1]: 4", {"xs": [1, 2, 3, 4]}),
+            ("r[1]{a}:\n  1\nr[1]{b}:\n  2", {"r": [{"a": 1}, {"b": 2}]}),
+            ("xs[1]: 1\nxs[1]:\n  - a: 2", {"xs": [1, {"a": 2}]}),
+        ]
+        for text, value in cases:
+            with self.subTest(text=text):
+                self.assertEqual(toon_parser.decode(text, merge_tables=True), value)
+
+    def test_within_one_object(self):
+        self.assertEqual(
+            toon_parser.decode("a:\n  xs[1]: 1\n  xs[1]: 2\nxs[1]: 9", merge_tables=True),
+            {"a": {"xs": [1, 2]}, "xs": [9]},
+        )
+        self.assertEqual(
+            toon_parser.decode("l[2]:\n  - xs[1]: 1\n    xs[1]: 2\n  - xs[1]: 3", merge_tables=True),
+            {"l": [{"xs": [1, 2]}, {"xs": [3]}]},
+        )
+
+    def test_other_repeated_keys_keep_the_last_value(self):
+        self.assertEqual(toon_parser.decode("a: 1\nb: 2\na: 3", merge_tables=True), {"a": 3, "b": 2})
+
+    def test_with_multidict(self):
+        self.assertEqual(
+            toon_parser.decode("a: 1\nb: 2\na: 3\nxs[1]: 1\nxs[1]: 2", merge_tables=True, multidict=True),
+            [("a", 1), ("a", 3), ("b", 2), ("xs", [1, 2])],
+        )
+
+    def test_built_like_other_arrays(self):
+        self.assertEqual(toon_parser.decode("xs[1]: 1\nxs[1]: 2", merge_tables=True, frozen=True), {"xs": (1, 2)})
+
+    def test_each_section_length_is_checked(self):
+        for text in ("xs[2]: 1\nxs[1]: 2", "xs[1]: 1\nxs[1]: 2,3"):
+            with self.subTest(text=text):
+                with self.assertRaises(ToonSyntaxError):
+                    toon_parser.decode(text, merge_tables=True)
+
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,48 @@
+"""Repeated keys kept with ``decode(..., multidict=True)``.
+
+Run with ``python -m unittest tests.test_multidict``.
+"""
+
+import unittest
+
+import toon_parser
+
+HEADERS = "b: 1\na: 2\nb: 3\nc: 4\na: 5\nn:\n  x: 1\n  x: 2"
+PAIRS = [("b", 1), ("b", 3), ("a", 2), ("a", 5), ("c", 4), ("n", [("x", 1), ("x", 2)])]
+
+
+class MultidictTest(unittest.TestCase):
+    def test_pairs_grouped_in_order_of_first_appearance(self):
+        self.assertEqual(toon_parser.decode(HEADERS, multidict=True), PAIRS)
+
+    def test_last_value_wins_by_default(self):
+        self.assertEqual(toon_parser.decode(HEADERS), {"b": 3, "a": 5, "c": 4, "n": {"x": 2}})
+
+    def test_objects_without_repeats_stay_dicts(self):
+        self.assertEqual(toon_parser.decode("a: 1\nb: 2", multidict=True), {"a": 1, "b": 2})
+        self.assertEqual(
+            toon_parser.decode("h[2]:\n  - k: 1\n    k: 2\n  - k: 3", multidict=True),
+            {"h": [[("k", 1), ("k", 2)], {"k": 3}]},
+        )
+
+    def test_pairs_follow_the_array_settings(self):
+        frozen = (("b", 1), ("b", 3), ("a", 2), ("a", 5), ("c", 4), ("n", (("x", 1), ("x", 2))))
+        for kwargs in ({"frozen": True}, {"arrays": "tuple"}, {"list_factory": tuple}):
+            with self.subTest(**kwargs):
+                self.assertEqual(toon_parser.decode(HEADERS, multidict=True, **kwargs), frozen)
+
+    def test_repeated_arrays(self):
+        text = "xs[1]: 1\nxs[1]: 2"
+        self.assertEqual(toon_parser.decode(text, multidict=True), [("xs", [1]), ("xs", [2])])
+        self.assertEqual(toon_parser.decode(text, multidict=True, merge_tables=True), {"xs": [1, 2]})
+
+    def test_quoted_keys_and_table_rows(self):
+        text = '"a b": 1\n"a b": 2\nrows[2]{k,k2}:\n  1,2\n  3,4'
+        self.assertEqual(
+            toon_parser.decode(text, multidict=True),
+            [("a b", 1), ("a b", 2), ("rows", [{"k": 1, "k2": 2}, {"k": 3, "k2": 4}])],
+        )
+
+
+if __name__ == "__main__":
+    unittest.main()
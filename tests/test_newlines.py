@@ -0,0 +1,105 @@
+"""``\\r\\n`` and lone ``\\r`` line endings, and ``Options(newlines=...)``.
+
+Run with ``python -m unittest tests.test_newlines``.
+"""
+
+import io
+import json
+import os
+import tempfile
+import unittest
+
+import toon_parser
+
+LF = "a: 1\nrows[2]{x,y}:\n  1,a\n  2,b\n"
+CRLF = LF.replace("\n", "\r\n")
+CR = LF.replace("\n", "\r")
+EXPECTED = {"a": 1, "rows": [{"x": 1, "y": "a"}, {"x": 2, "y": "b"}]}
+
+
+class AnyNewlinesTest(unittest.TestCase):
+    def setUp(self):
+        self._dir = tempfile.TemporaryDirectory()
+        self.addCleanup(self._dir.cleanup)
+
+    def write(self, text):
+        path = os.path.join(self._dir.name, "doc.toon")
+        with open(path, "w", encoding="utf-8", newline="") as f:
+            f.write(text)
+        return path
+
+    def test_decode(self):
+        for text in (CRLF, CR):
+            with self.subTest(text=text):
+                self.assertEqual(toon_parser.decode(text), EXPECTED)
+                self.assertEqual(toon_parser.decode(text, strict=True), EXPECTED)
+                self.assertEqual(toon_parser.decode_bytes(text.encode()), EXPECTED)
+                self.assertEqual(toon_parser.loads(text), EXPECTED)
+                self.assertEqual(toon_parser.decode_with_meta(text)[0], EXPECTED)
+                self.assertEqual(toon_parser.decode_lazy(text).to_python(), EXPECTED)
+                self.assertEqual(json.loads(toon_parser.toon_to_json(text)), EXPECTED)
+
+    def test_load_text_and_binary_files(self):
+        for text in (CRLF, CR):
+            with self.subTest(text=text):
+                self.assertEqual(toon_parser.load(io.StringIO(text, newline="")), EXPECTED)
+                self.assertEqual(toon_parser.load(io.BytesIO(text.encode())), EXPECTED)
+
+    def test_binary_file_must_be_utf8(self):
+        with self.assertRaises(toon_parser.ToonError) as cm:
+            toon_parser.load(io.BytesIO(b"a: \xff\n"))
+        self.assertIn("invalid UTF-8", str(cm.exception))
+
+    def test_batches_and_streams(self):
+        self.assertEqual(toon_parser.decode_batch([CRLF, CR]), [EXPECTED, EXPECTED])
+        self.assertEqual(list(toon_parser.decode_batch_iter([CRLF, CR])), [EXPECTED, EXPECTED])
+        self.assertEqual(toon_parser.decode_documents("a: 1\r\n---\r\na: 2\r\n"), [{"a": 1}, {"a": 2}])
+        self.assertEqual(toon_parser.decode_documents("a: 1\r---\ra: 2\r"), [{"a": 1}, {"a": 2}])
+
+    def test_columns(self):
+        for text in (CRLF, CR):
+            with self.subTest(text=text):
+                self.assertEqual(toon_parser.decode_columns(text, "rows"), {"x": [1, 2], "y": ["a", "b"]})
+
+    def test_files(self):
+        for text in (CRLF, CR):
+            with self.subTest(text=text):
+                path = self.write(text)
+                self.assertEqual(toon_parser.validate_file(path), [])
+                chunks = list(toon_parser.iter_table_chunks(path, 1, path="rows"))
+                self.assertEqual(chunks, [{"x": [1], "y": ["a"]}, {"x": [2], "y": ["b"]}])
+
+    def test_syntax_errors_count_every_line_ending(self):
+        for text in ("a: 1\r\nxs[3]: 1,2\r\n", "a: 1\rxs[3]: 1,2\r"):
+            with self.subTest(text=text):
+                with self.assertRaisesRegex(toon_parser.ToonSyntaxError, "^Line 2: "):
+                    toon_parser.decode(text, strict=True)
+
+
+class LfOnlyTest(unittest.TestCase):
+    def test_carriage_return_is_rejected_at_its_line(self):
+        opts = toon_parser.Options(newlines="lf")
+        self.assertEqual(toon_parser.decode(LF, options=opts), EXPECTED)
+        message = "carriage return in line ending; newlines='lf' requires '\\n'"
+        for text, line in (("a: 1\nb: 2\r\n", 2), (CR, 1)):
+            with self.subTest(text=text):
+                with self.assertRaises(toon_parser.ToonSyntaxError) as cm:
+                    toon_parser.decode(text, options=opts)
+                self.assertEqual(str(cm.exception), "Line {}: {}".format(line, message))
+
+    def test_documents_are_rejected_as_a_whole(self):
+        opts = toon_parser.Options(newlines="lf")
+        with self.assertRaisesRegex(toon_parser.ToonSyntaxError, "^Line 3: carriage return"):
+            toon_parser.decode_documents("a: 1\n---\na: 2\r\n", on_error="collect", options=opts)
+
+    def test_option(self):
+        self.assertEqual(toon_parser.Options().newlines, "any")
+        opts = toon_parser.Options().with_newlines("lf")
+        self.assertEqual(opts.newlines, "lf")
+        self.assertIn("newlines='lf'", repr(opts))
+        with self.assertRaisesRegex(ValueError, "Invalid newlines 'crlf'"):
+            toon_parser.Options(newlines="crlf")
+
+
+if __name__ == "__main__":
+    unittest.main()
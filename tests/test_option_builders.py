@@ -0,0 +1,81 @@
+"""Chainable ``Options.with_<name>()`` builders.
+
+Run with ``python -m unittest tests.test_option_builders``.
+"""
+
+import inspect
+import unittest
+
+import toon_parser
+from toon_parser import Options
+
+# A non-default value for every setting
+VALUES = {
+    "delimiter": "tab",
+    "strict": True,
+    "key_transform": "camel",
+    "normalize_unicode": "nfc",
+    "normalize_keys": True,
+    "surrogates": "replace",
+    "empty_collections": "explicit",
+    "strict_encode": True,
+    "strict_decode": True,
+    "max_input_bytes": 10,
+    "max_depth": 3,
+    "max_array_length": 4,
+    "sort_keys": True,
+    "indent": 4,
+    "ensure_ascii": True,
+    "newlines": "lf",
+    "empty_cells_as_none": True,
+}
+
+
+class BuildersTest(unittest.TestCase):
+    def test_every_setting_has_a_builder(self):
+        self.assertEqual(set(inspect.signature(Options).parameters), set(VALUES))
+        for name in VALUES:
+            with self.subTest(name):
+                self.assertTrue(callable(getattr(Options, "with_" + name, None)))
+
+    def test_builder_matches_the_constructor(self):
+        for name, value in VALUES.items():
+            with self.subTest(name):
+                built = getattr(Options(), "with_" + name)(value)
+                self.assertEqual(built, Options(**{name: value}))
+                self.assertEqual(getattr(built, name), value)
+
+    def test_original_is_unchanged(self):
+        opts = Options()
+        for name, value in VALUES.items():
+            with self.subTest(name):
+                self.assertIsNot(getattr(opts, "with_" + name)(value), opts)
+                self.assertEqual(opts, Options())
+
+    def test_chaining(self):
+        opts = Options().with_delimiter("pipe").with_strict(True).with_sort_keys(True).with_indent("\t")
+        self.assertEqual(opts, Options(delimiter="pipe", strict=True, sort_keys=True, indent="\t"))
+        self.assertEqual(toon_parser.encode_with_options({"b": [1, 2], "a": 1}, opts), "a: 1\nb[2|]: 1|2")
+
+    def test_optional_settings_can_be_cleared(self):
+        self.assertEqual(Options().with_key_transform("camel").with_key_transform(None), Options())
+        self.assertEqual(Options().with_max_depth(3).with_max_depth(None), Options())
+
+    def test_invalid_values(self):
+        cases = [
+            ("delimiter", "x", ValueError, "^Invalid delimiter 'x'"),
+            ("key_transform", "x", ValueError, "^Invalid key_transform 'x'"),
+            ("normalize_unicode", "x", ValueError, "^Invalid normalize_unicode 'x'"),
+            ("surrogates", "x", ValueError, "^Invalid surrogates 'x'"),
+            ("empty_collections", "x", ValueError, "^Invalid empty_collections 'x'"),
+            ("newlines", "crlf", ValueError, "^Invalid newlines 'crlf'"),
+            ("indent", "x", ValueError, "^Invalid indent \"x\""),
+        ]
+        for name, value, error, message in cases:
+            with self.subTest(name):
+                with self.assertRaisesRegex(error, message):
+                    getattr(Options(), "with_" + name)(value)
+
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,86 @@
+"""orjson-style bit flags for ``encode(data, option=...)``.
+
+Run with ``python -m unittest tests.test_option_flags``.
+"""
+
+import hashlib
+import unittest
+
+import toon_parser
+
+DATA = {"b": [1, 2], "a": "x"}
+SHARED = {"host": "db.example.com", "port": 5432}
+
+
+class OptionFlagsTest(unittest.TestCase):
+    def test_flags_are_distinct_bits(self):
+        names = [
+            "OPT_DELIMITER_TAB",
+            "OPT_DELIMITER_PIPE",
+            "OPT_STRICT",
+            "OPT_SORT_KEYS",
+            "OPT_APPEND_NEWLINE",
+            "OPT_ANCHORS",
+            "OPT_CHECKSUM",
+        ]
+        bits = [getattr(toon_parser, name) for name in names]
+        for name, bit in zip(names, bits):
+            with self.subTest(name=name):
+                self.assertIsInstance(bit, int)
+                self.assertEqual(bin(bit).count("1"), 1)
+        self.assertEqual(len(set(bits)), len(bits))
+
+    def test_flags_match_keyword_arguments(self):
+        data = {"primary": SHARED, "replica": SHARED, **DATA}
+        cases = [
+            ("OPT_DELIMITER_TAB", {"delimiter": "tab"}),
+            ("OPT_DELIMITER_PIPE", {"delimiter": "pipe"}),
+            ("OPT_STRICT", {"strict": True}),
+            ("OPT_SORT_KEYS", {"sort_keys": True}),
+            ("OPT_ANCHORS", {"anchors": True}),
+            ("OPT_CHECKSUM", {"checksum": True}),
+        ]
+        for name, kwargs in cases:
+            with self.subTest(name=name):
+                self.assertEqual(
+                    toon_parser.encode(data, option=getattr(toon_parser, name)),
+                    toon_parser.encode(data, **kwargs),
+                )
+
+    def test_append_newline(self):
+        self.assertEqual(toon_parser.encode(DATA, option=toon_parser.OPT_APPEND_NEWLINE), "b[2]: 1,2\na: x\n")
+        self.assertEqual(toon_parser.encode({}, option=toon_parser.OPT_APPEND_NEWLINE), "\n")
+
+    def test_append_newline_after_checksum(self):
+        text = toon_parser.encode(DATA, option=toon_parser.OPT_CHECKSUM | toon_parser.OPT_APPEND_NEWLINE)
+        self.assertRegex(text, r"\n# sha256:[0-9a-f]{64}\n$")
+        self.assertEqual(toon_parser.decode(text, verify_checksum=True), DATA)
+
+    def test_append_newline_is_hashed(self):
+        text, digest = toon_parser.encode({"a": 1}, option=toon_parser.OPT_APPEND_NEWLINE, hash="sha256")
+        self.assertEqual(text, "a: 1\n")
+        self.assertEqual(digest, hashlib.sha256(b"a: 1\n").hexdigest())
+
+    def test_combined_flags(self):
+        option = toon_parser.OPT_DELIMITER_TAB | toon_parser.OPT_SORT_KEYS | toon_parser.OPT_APPEND_NEWLINE
+        self.assertEqual(toon_parser.encode(DATA, option=option), "a: x\nb[2\t]: 1\t2\n")
+
+    def test_keyword_arguments_win(self):
+        self.assertEqual(
+            toon_parser.encode(DATA, option=toon_parser.OPT_DELIMITER_PIPE, delimiter="comma"),
+            "b[2]: 1,2\na: x",
+        )
+
+    def test_zero_is_no_flags(self):
+        self.assertEqual(toon_parser.encode(DATA, option=0), toon_parser.encode(DATA))
+
+    def test_invalid_flags(self):
+        with self.assertRaisesRegex(ValueError, "unknown option bits 0x400"):
+            toon_parser.encode(DATA, option=1 << 10)
+        with self.assertRaisesRegex(ValueError, "mutually exclusive"):
+            toon_parser.encode(DATA, option=toon_parser.OPT_DELIMITER_TAB | toon_parser.OPT_DELIMITER_PIPE)
+        self.assertRaises(OverflowError, toon_parser.encode, DATA, option=-1)
+
+
+if __name__ == "__main__":
+    unittest.main()
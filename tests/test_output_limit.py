@@ -0,0 +1,56 @@
+"""The ``encode(..., max_output_bytes=n)`` guard.
+
+Run with ``python -m unittest tests.test_output_limit``.
+"""
+
+import unittest
+
+import toon_parser
+from toon_parser import ToonError, ToonOutputLimitError
+
+DATA = {"rows": [{"id": i, "name": "n"} for i in range(5)]}
+TEXT = toon_parser.encode(DATA)
+
+
+class MaxOutputBytesTest(unittest.TestCase):
+    def test_output_at_the_limit_passes(self):
+        self.assertEqual(toon_parser.encode(DATA, max_output_bytes=len(TEXT)), TEXT)
+
+    def test_output_past_the_limit_raises(self):
+        with self.assertRaisesRegex(ToonOutputLimitError, "^Encoded output exceeds max_output_bytes={}$".format(len(TEXT) - 1)):
+            toon_parser.encode(DATA, max_output_bytes=len(TEXT) - 1)
+
+    def test_error_is_a_toon_error(self):
+        self.assertTrue(issubclass(ToonOutputLimitError, ToonError))
+
+    def test_counts_bytes_not_characters(self):
+        self.assertEqual(toon_parser.encode({"a": "é"}, max_output_bytes=5), "a: é")
+        with self.assertRaises(ToonOutputLimitError):
+            toon_parser.encode({"a": "é"}, max_output_bytes=4)
+
+    def test_zero(self):
+        self.assertEqual(toon_parser.encode({}, max_output_bytes=0), "")
+        with self.assertRaises(ToonOutputLimitError):
+            toon_parser.encode({"a": 1}, max_output_bytes=0)
+
+    def test_additions_to_the_body_count(self):
+        cases = [
+            {"checksum": True},
+            {"meta": {"version": 1}},
+            {"typed_headers": True},
+            {"indent": 4},
+        ]
+        for kwargs in cases:
+            with self.subTest(**kwargs):
+                full = toon_parser.encode(DATA, **kwargs)
+                self.assertEqual(toon_parser.encode(DATA, max_output_bytes=len(full), **kwargs), full)
+                with self.assertRaises(ToonOutputLimitError):
+                    toon_parser.encode(DATA, max_output_bytes=len(full) - 1, **kwargs)
+
+    def test_with_hash(self):
+        text, digest = toon_parser.encode({"a": 1}, max_output_bytes=10, hash="sha256")
+        self.assertEqual((text, len(digest)), ("a: 1", 64))
+
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,106 @@
+"""Path lookups with ``get`` and JSONPath selection with ``query``.
+
+Run with ``python -m unittest tests.test_path``.
+"""
+
+import unittest
+
+import toon_parser
+
+DATA = {
+    "users": [
+        {"id": 1, "email": "ann@example.com", "full name": "Ann Lee"},
+        {"id": 2, "tags": ["admin", "ops"]},
+    ],
+    "meta": {"owner": {"id": 9}},
+}
+
+
+class GetTest(unittest.TestCase):
+    def test_paths(self):
+        cases = [
+            ("users[0].email", "ann@example.com"),
+            ("$.users[0].email", "ann@example.com"),
+            ("users[-1].tags[1]", "ops"),
+            ('$.users[0]["full name"]', "Ann Lee"),
+            ("$.users[0]['full name']", "Ann Lee"),
+            ("meta.owner.id", 9),
+            ("$", DATA),
+        ]
+        for path, expected in cases:
+            with self.subTest(path=path):
+                self.assertEqual(toon_parser.get(DATA, path), expected)
+
+    def test_toon_input(self):
+        self.assertEqual(toon_parser.get("a:\n  b: 1\n", "a.b"), 1)
+        self.assertEqual(toon_parser.get(b"xs[3]: 1,2,3\n", "xs[-1]"), 3)
+
+    def test_missing_segment_names_the_path_walked(self):
+        cases = [
+            ("users[5].email", "$.users[5]"),
+            ("users[0].nope", "$.users[0].nope"),
+            ("users.id", "$.users.id"),
+            ('users[1]["full name"]', '$.users[1]["full name"]'),
+        ]
+        for path, walked in cases:
+            with self.subTest(path=path):
+                with self.assertRaises(KeyError) as ctx:
+                    toon_parser.get(DATA, path)
+                self.assertEqual(ctx.exception.args[0], "'{}' not found".format(walked))
+
+    def test_reported_path_parses_back(self):
+        for key in ["x.y", "", "a b", "[0]", "*"]:
+            with self.subTest(key=key):
+                with self.assertRaises(KeyError) as ctx:
+                    toon_parser.get({"a": {}}, "$.a[{!r}]".format(key))
+                walked = ctx.exception.args[0][1:-len("' not found")]
+                self.assertEqual(toon_parser.get({"a": {key: 1}}, walked), 1)
+
+    def test_paths_selecting_several_values_are_rejected(self):
+        for path in ["users[*]", "$..id", "users[0:1]", "users[?(@.id)]"]:
+            with self.subTest(path=path):
+                with self.assertRaisesRegex(ValueError, "use query"):
+                    toon_parser.get(DATA, path)
+
+    def test_malformed_paths(self):
+        for path in ["users[", "users.", "users[x]", "$.."]:
+            with self.subTest(path=path):
+                self.assertRaises(ValueError, toon_parser.get, DATA, path)
+
+
+class QueryTest(unittest.TestCase):
+    def test_expressions(self):
+        cases = [
+            ("$.users[*].id", [1, 2]),
+            ("$.users.*.id", [1, 2]),
+            ("$..id", [1, 2, 9]),
+            ("$.users[0:1].id", [1]),
+            ("$.users[-1:].id", [2]),
+            ("$.users[?(@.tags)].id", [2]),
+            ('$.users[?(@.email == "ann@example.com")].id', [1]),
+            ("$.users[?(@.id != 1)].id", [2]),
+            ("$.users[?(@.id >= 1)].id", [1, 2]),
+            ("$.users[?(@.id < 2)].id", [1]),
+            ('$.users[*]["full name"]', ["Ann Lee"]),
+            ("$.missing", []),
+            ("users[0].id", [1]),
+        ]
+        for expr, expected in cases:
+            with self.subTest(expr=expr):
+                self.assertEqual(toon_parser.query(DATA, expr), expected)
+
+    def test_toon_input(self):
+        doc = "rows[3]{id,status}:\n  1,ok\n  2,failed\n  3,failed\n"
+        self.assertEqual(
+            toon_parser.query(doc, '$.rows[?(@.status=="failed")]'),
+            [{"id": 2, "status": "failed"}, {"id": 3, "status": "failed"}],
+        )
+
+    def test_malformed_expressions(self):
+        for expr in ["$.users[", "$.users[?(id)]", "$.users[?(@.id == nope)]", "$.users[1:x]"]:
+            with self.subTest(expr=expr):
+                self.assertRaises(ValueError, toon_parser.query, DATA, expr)
+
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,87 @@
+"""Warnings for number literals that decode lossily, and ``strict_numbers``.
+
+Run with ``python -m unittest tests.test_precision``.
+"""
+
+import unittest
+import warnings
+
+import toon_parser
+from toon_parser import ToonError, ToonWarning
+
+BIG = "18446744073709551617"
+
+
+def decode_with_warnings(text, **kwargs):
+    """Decode `text`; return the value and the messages of every ToonWarning."""
+    with warnings.catch_warnings(record=True) as caught:
+        warnings.simplefilter("always")
+        value = toon_parser.decode(text, **kwargs)
+    return value, [str(w.message) for w in caught if issubclass(w.category, ToonWarning)]
+
+
+class WarningTest(unittest.TestCase):
+    def test_lossy_literals(self):
+        cases = [
+            ("a: " + BIG, "18446744073709552000"),
+            ("a: -9223372036854775809", "-9223372036854776000"),
+            ("a: 0.1000000000000000055511", "0.1"),
+            ("a: 3.14159265358979323846", "3.141592653589793"),
+        ]
+        for text, decoded in cases:
+            with self.subTest(text=text):
+                literal = text[3:]
+                _, messages = decode_with_warnings(text)
+                self.assertEqual(messages, ["Line 1: {} cannot be represented exactly; decoded as {}".format(literal, decoded)])
+
+    def test_value_is_still_decoded(self):
+        value, _ = decode_with_warnings("a: " + BIG)
+        self.assertEqual(value, {"a": float(BIG)})
+
+    def test_exact_literals_are_quiet(self):
+        for text in ("a: 18446744073709551615", "a: -9223372036854775808", "a: 0.1000000000000000000000", "a: 0.5"):
+            with self.subTest(text=text):
+                self.assertEqual(decode_with_warnings(text)[1], [])
+
+    def test_strings_are_quiet(self):
+        for text in ('a: "{}"'.format(BIG), "note: order {} shipped".format(BIG), "xs[2]: a {},1".format(BIG), BIG + ": 1"):
+            with self.subTest(text=text):
+                self.assertEqual(decode_with_warnings(text)[1], [])
+
+    def test_arrays_tables_and_lists(self):
+        cases = [
+            ("xs[2]: 1,{}".format(BIG), 1),
+            ("xs[2|]: 1|{}".format(BIG), 1),
+            ("xs[2\t]: 1\t{}".format(BIG), 1),
+            ("rows[1]{{a,b}}:\n  1,{}".format(BIG), 2),
+            ("items[1]:\n  - {}".format(BIG), 2),
+            ("a: 1\rb: {}\r".format(BIG), 2),
+        ]
+        for text, line in cases:
+            with self.subTest(text=text):
+                messages = decode_with_warnings(text)[1]
+                self.assertEqual(len(messages), 1)
+                self.assertTrue(messages[0].startswith("Line {}: {} ".format(line, BIG)), messages[0])
+
+    def test_one_warning_per_literal(self):
+        _, messages = decode_with_warnings("a: {}\nb: {}\n".format(BIG, BIG))
+        self.assertEqual([m.split(":")[0] for m in messages], ["Line 1", "Line 2"])
+
+
+class StrictNumbersTest(unittest.TestCase):
+    def test_raises_instead(self):
+        with self.assertRaisesRegex(ToonError, "^Line 2: {} cannot be represented exactly".format(BIG)):
+            toon_parser.decode("a: 1\nb: {}\n".format(BIG), strict_numbers=True)
+
+    def test_exact_documents_pass(self):
+        self.assertEqual(toon_parser.decode("a: 18446744073709551615\n", strict_numbers=True), {"a": 18446744073709551615})
+
+    def test_warning_can_be_made_an_error(self):
+        with warnings.catch_warnings():
+            warnings.simplefilter("error", ToonWarning)
+            with self.assertRaises(ToonWarning):
+                toon_parser.decode("a: " + BIG)
+
+
+if __name__ == "__main__":
+    unittest.main()
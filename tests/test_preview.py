@@ -0,0 +1,52 @@
+"""Display previews of large documents (``preview``).
+
+Run with ``python -m unittest tests.test_preview``.
+"""
+
+import unittest
+
+import toon_parser
+
+DOC = toon_parser.encode({
+    "name": "x",
+    "rows": [{"id": i, "v": i * 2} for i in range(25)],
+    "deep": {"a": {"b": {"c": 1, "d": 2}}},
+    "items": ["s"] * 23,
+})
+
+
+class PreviewTest(unittest.TestCase):
+    def test_rows_are_cut_with_a_count(self):
+        text = toon_parser.preview(DOC)
+        self.assertIn("rows[25]{id,v}:\n  0,0\n", text)
+        self.assertIn("  9,18\n  ... (15 more rows)\n", text)
+        self.assertNotIn("10,20", text)
+        self.assertIn("items[23]: s,s,s,s,s,s,s,s,s,s ... (13 more values)\n", text)
+
+    def test_row_limit(self):
+        text = toon_parser.preview(DOC, rows=2, depth=3)
+        self.assertIn("rows[25]{id,v}:\n  0,0\n  1,2\n  ... (23 more rows)\n", text)
+        self.assertIn("items[23]: s,s ... (21 more values)\n", text)
+
+    def test_list_items_are_cut(self):
+        doc = toon_parser.encode({"items": [{"k": i, "v": [i]} for i in range(5)]})
+        self.assertEqual(toon_parser.preview(doc, rows=2, depth=3), "items[5]:\n  - k: 0\n    v[1]: 0\n  - k: 1\n    v[1]: 1\n  ... (3 more items)\n")
+
+    def test_inline_values_respect_delimiter_and_quotes(self):
+        doc = 'xs[4|]: "a|b"|c|d|e\n'
+        self.assertEqual(toon_parser.preview(doc, rows=2), 'xs[4|]: "a|b"|c ... (2 more values)\n')
+
+    def test_deep_lines_are_elided(self):
+        self.assertIn("deep:\n  a:\n    ... (3 nested lines)\n", toon_parser.preview(DOC))
+        self.assertIn("deep:\n  a:\n    b:\n      ... (2 nested lines)\n", toon_parser.preview(DOC, depth=3))
+        self.assertIn("deep:\n  ... (4 nested lines)\n", toon_parser.preview(DOC, depth=1))
+
+    def test_small_documents_are_unchanged(self):
+        self.assertEqual(toon_parser.preview("a: 1\nb[2]: x,y\n"), "a: 1\nb[2]: x,y\n")
+
+    def test_malformed_text_is_still_previewed(self):
+        self.assertEqual(toon_parser.preview("a: [\nb:\n"), "a: [\nb:\n")
+
+
+if __name__ == "__main__":
+    unittest.main()
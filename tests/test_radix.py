@@ -0,0 +1,80 @@
+"""Hex integers written by ``encode(..., hex=[...])`` and read by ``decode(..., radix_integers=True)``.
+
+Run with ``python -m unittest tests.test_radix``.
+"""
+
+import unittest
+
+import toon_parser
+
+
+def round_trip(data, patterns):
+    return toon_parser.decode(toon_parser.encode(data, hex=patterns), radix_integers=True)
+
+
+class EncodeHexTest(unittest.TestCase):
+    def test_matching_integers(self):
+        self.assertEqual(
+            toon_parser.encode({"user": {"mode": 493}, "mode": 7, "size": 5}, hex=["mode"]),
+            "user:\n  mode: 0x1ED\nmode: 0x7\nsize: 5",
+        )
+        self.assertEqual(toon_parser.encode({"user": {"mode": 493}, "mode": 7}, hex=["user.mode"]), "user:\n  mode: 0x1ED\nmode: 7")
+
+    def test_lists_negatives_and_tables(self):
+        self.assertEqual(toon_parser.encode({"masks": [255, 240]}, hex=["masks"]), "masks[2]: 0xFF,0xF0")
+        self.assertEqual(toon_parser.encode({"delta": -16}, hex=["delta"]), "delta: -0x10")
+        self.assertEqual(toon_parser.encode({"rows": [{"m": 8}, {"m": 9}]}, hex=["m"]), "rows[2]{m}:\n  0x8\n  0x9")
+
+    def test_other_values_are_untouched(self):
+        data = {"m": 1.5, "b": True, "n": None, "s": "x"}
+        self.assertEqual(toon_parser.encode(data, hex=["*"]), toon_parser.encode(data))
+
+    def test_strings_that_look_like_literals_are_quoted(self):
+        self.assertEqual(toon_parser.encode({"s": "0xFF", "mask": 255}, hex=["mask"]), 's: "0xFF"\nmask: 0xFF')
+        self.assertEqual(toon_parser.encode({"xs": ["0b1", 3]}, hex=["xs"]), 'xs[2]: "0b1",0x3')
+
+    def test_round_trip(self):
+        cases = [
+            ({"s": "0xFF", "mask": 255}, ["mask"]),
+            ({"xs": ["0b1", 3, "-0x1_0", -1]}, ["xs"]),
+            ({"rows": [{"a": "0o7", "m": 8}, {"a": "x", "m": -1}]}, ["m"]),
+            ({"big": 2**64 - 1, "small": -(2**63)}, ["*"]),
+        ]
+        for data, patterns in cases:
+            with self.subTest(data=data):
+                self.assertEqual(round_trip(data, patterns), data)
+
+
+class DecodeRadixTest(unittest.TestCase):
+    def test_literals(self):
+        cases = [
+            ("0b101", 5),
+            ("0o17", 15),
+            ("0xff", 255),
+            ("0XFF", 255),
+            ("-0x10", -16),
+            ("+0x10", 16),
+            ("0x1_0", 16),
+        ]
+        for literal, value in cases:
+            with self.subTest(literal=literal):
+                self.assertEqual(toon_parser.decode(f"a: {literal}", radix_integers=True), {"a": value})
+
+    def test_not_literals(self):
+        for literal in ("0x", "0xG", '"0x10"', "0x10000000000000000", "x0x1"):
+            with self.subTest(literal=literal):
+                value = toon_parser.decode(f"a: {literal}", radix_integers=True)["a"]
+                self.assertIsInstance(value, str)
+
+    def test_arrays_and_tables(self):
+        self.assertEqual(
+            toon_parser.decode("xs[2]: 0x1,0b10\nr[1]{a,b}:\n  0o7,\"0x1\"", radix_integers=True),
+            {"xs": [1, 2], "r": [{"a": 7, "b": "0x1"}]},
+        )
+
+    def test_off_by_default(self):
+        self.assertEqual(toon_parser.decode("a: 0xFF"), {"a": "0xFF"})
+
+
+if __name__ == "__main__":
+    unittest.main()
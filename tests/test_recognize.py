@@ -0,0 +1,86 @@
+"""Duration and byte-size values read with ``decode(..., recognize=[...])``.
+
+Run with ``python -m unittest tests.test_recognize``.
+"""
+
+import unittest
+
+import toon_parser
+
+BOTH = ["duration", "byte_size"]
+
+
+def value(cell, recognize=BOTH):
+    return toon_parser.decode(f"a: {cell}", recognize=recognize)["a"]
+
+
+class DurationTest(unittest.TestCase):
+    def test_seconds(self):
+        cases = [
+            ("30s", 30),
+            ("5m", 300),
+            ("1.5h", 5400),
+            ("1h30m", 5400),
+            ("1m30.5s", 90.5),
+            ("-2s", -2),
+            ("100ms", 0.1),
+            ("250us", 0.00025),
+            ("90µs", 9e-05),
+            ("500ns", 5e-07),
+            ("1d", 86400),
+            ("2w", 1209600),
+        ]
+        for cell, seconds in cases:
+            with self.subTest(cell=cell):
+                self.assertEqual(repr(value(cell, ["duration"])), repr(seconds))
+
+    def test_not_durations(self):
+        for cell in ("s", "-", "1x", "h1", "1.s", ".5s", "+5s", "1e3s", "1h 30m"):
+            with self.subTest(cell=cell):
+                self.assertEqual(value(cell, ["duration"]), cell)
+
+
+class ByteSizeTest(unittest.TestCase):
+    def test_bytes(self):
+        cases = [
+            ("0B", 0),
+            ("512MB", 512000000),
+            ("1KB", 1000),
+            ("1.5kB", 1500),
+            ("2GiB", 2147483648),
+            ("1EiB", 1152921504606846976),
+            ("1.5B", 2),
+        ]
+        for cell, size in cases:
+            with self.subTest(cell=cell):
+                self.assertEqual(value(cell, ["byte_size"]), size)
+                self.assertIs(type(value(cell, ["byte_size"])), int)
+
+    def test_not_sizes(self):
+        for cell in ("16EiB", "GiB", "2 GiB", "2gib", "2Gi"):
+            with self.subTest(cell=cell):
+                self.assertEqual(value(cell, ["byte_size"]), cell)
+
+
+class RecognizeTest(unittest.TestCase):
+    def test_only_enabled_recognizers(self):
+        self.assertEqual(value("30s", ["byte_size"]), "30s")
+        self.assertEqual(value("2GiB", ["duration"]), "2GiB")
+        self.assertEqual(toon_parser.decode("a: 30s\nb: 2GiB"), {"a": "30s", "b": "2GiB"})
+
+    def test_unquoted_values_everywhere(self):
+        self.assertEqual(
+            toon_parser.decode('xs[2]: 1s,2KiB\nr[1]{a,b}:\n  5m,"5m"\nq: "30s"', recognize=BOTH),
+            {"xs": [1, 2048], "r": [{"a": 300, "b": "5m"}], "q": "30s"},
+        )
+
+    def test_plain_numbers_unchanged(self):
+        self.assertEqual(toon_parser.decode("a: 30\nb: 1.5", recognize=BOTH), {"a": 30, "b": 1.5})
+
+    def test_unknown_name(self):
+        with self.assertRaisesRegex(ValueError, "Invalid recognizer 'nope'. Must be 'duration' or 'byte_size'"):
+            toon_parser.decode("a: 1", recognize=["nope"])
+
+
+if __name__ == "__main__":
+    unittest.main()
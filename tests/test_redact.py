@@ -0,0 +1,59 @@
+"""Scrubbing sensitive fields while encoding (``encode(redact=...)``).
+
+Run with ``python -m unittest tests.test_redact``.
+"""
+
+import unittest
+
+import toon_parser
+
+DATA = {
+    "password": "p",
+    "user": {"password": "q", "token": "t", "name": "n"},
+    "rows": [{"token": "a", "id": 1}, {"token": "b", "id": 2}],
+    "api_token": "z",
+}
+
+
+def redacted(patterns, **kwargs):
+    return toon_parser.decode(toon_parser.encode(DATA, redact=patterns, **kwargs))
+
+
+class RedactTest(unittest.TestCase):
+    def test_bare_key_matches_at_any_depth(self):
+        value = redacted(["password"])
+        self.assertEqual(value["password"], "***")
+        self.assertEqual(value["user"]["password"], "***")
+        self.assertEqual(value["user"]["token"], "t")
+
+    def test_double_star_and_table_columns(self):
+        value = redacted(["**.token"], redact_with="[X]")
+        self.assertEqual(value["user"]["token"], "[X]")
+        self.assertEqual(value["rows"], [{"token": "[X]", "id": 1}, {"token": "[X]", "id": 2}])
+        self.assertEqual(value["api_token"], "z")
+
+    def test_dotted_path_with_single_star(self):
+        value = redacted(["user.*"])
+        self.assertEqual(value["user"], {"password": "***", "token": "***", "name": "***"})
+        self.assertEqual(value["password"], "p")
+
+    def test_glob_within_a_key(self):
+        value = redacted(["*_token"])
+        self.assertEqual(value["api_token"], "***")
+        self.assertEqual(value["user"]["token"], "t")
+
+    def test_rows_by_column_path(self):
+        self.assertEqual(redacted(["rows.id"])["rows"], [{"token": "a", "id": "***"}, {"token": "b", "id": "***"}])
+
+    def test_nested_values_are_replaced_whole(self):
+        text = toon_parser.encode({"secret": {"a": [1, 2]}, "k": 1}, redact=["secret"])
+        self.assertEqual(toon_parser.decode(text), {"secret": "***", "k": 1})
+
+    def test_input_is_not_modified(self):
+        data = {"user": {"password": "q"}}
+        toon_parser.encode(data, redact=["password"])
+        self.assertEqual(data, {"user": {"password": "q"}})
+
+
+if __name__ == "__main__":
+    unittest.main()
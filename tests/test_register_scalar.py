@@ -0,0 +1,81 @@
+"""Domain literals taught to ``decode`` with ``register_scalar``.
+
+Run with ``python -m unittest tests.test_register_scalar``.
+"""
+
+import ipaddress
+import re
+import unittest
+
+import toon_parser
+
+IP = r"\d+\.\d+\.\d+\.\d+"
+
+
+class RegisterScalarTest(unittest.TestCase):
+    def setUp(self):
+        toon_parser.clear_scalars()
+
+    def tearDown(self):
+        toon_parser.clear_scalars()
+
+    def test_unquoted_values_everywhere(self):
+        toon_parser.register_scalar(IP, ipaddress.ip_address)
+        self.assertEqual(
+            toon_parser.decode('host: 10.0.0.1\nq: "10.0.0.1"\nxs[2]: 1.2.3.4,x\nr[1]{ip}:\n  5.6.7.8\nys[1]:\n  - 9.9.9.9'),
+            {
+                "host": ipaddress.ip_address("10.0.0.1"),
+                "q": "10.0.0.1",
+                "xs": [ipaddress.ip_address("1.2.3.4"), "x"],
+                "r": [{"ip": ipaddress.ip_address("5.6.7.8")}],
+                "ys": [ipaddress.ip_address("9.9.9.9")],
+            },
+        )
+        self.assertEqual(toon_parser.loads("h: 1.1.1.1"), {"h": ipaddress.ip_address("1.1.1.1")})
+
+    def test_pattern_must_match_the_whole_value(self):
+        toon_parser.register_scalar(IP, ipaddress.ip_address)
+        self.assertEqual(toon_parser.decode("a: 10.0.0.1x"), {"a": "10.0.0.1x"})
+
+    def test_compiled_patterns_and_predicates(self):
+        toon_parser.register_scalar(re.compile(r"v\d+"), str.upper)
+        toon_parser.register_scalar(lambda s: s.startswith("v"), lambda s: ("ver", s))
+        self.assertEqual(toon_parser.decode("a: v1\nb: vx"), {"a": "V1", "b": ("ver", "vx")})
+
+    def test_registration_order(self):
+        toon_parser.register_scalar(lambda s: True, lambda s: "first")
+        toon_parser.register_scalar(lambda s: True, lambda s: "second")
+        self.assertEqual(toon_parser.decode("a: x"), {"a": "first"})
+
+    def test_only_values_that_would_be_strings(self):
+        seen = []
+        toon_parser.register_scalar(lambda s: seen.append(s), str)
+        self.assertEqual(toon_parser.decode("a: 12\nb: 1.5\nc: true\nd: null\ne: x"), {"a": 12, "b": 1.5, "c": True, "d": None, "e": "x"})
+        self.assertEqual(seen, ["x"])
+
+    def test_clear_scalars(self):
+        toon_parser.register_scalar(IP, ipaddress.ip_address)
+        toon_parser.clear_scalars()
+        self.assertEqual(toon_parser.decode("host: 10.0.0.1"), {"host": "10.0.0.1"})
+
+    def test_errors_propagate(self):
+        toon_parser.register_scalar("x", lambda s: 1 / 0)
+        with self.assertRaises(ZeroDivisionError):
+            toon_parser.decode("a: x")
+        toon_parser.clear_scalars()
+        toon_parser.register_scalar(lambda s: 1 / 0, str)
+        with self.assertRaises(ZeroDivisionError):
+            toon_parser.decode("a: x")
+
+    def test_invalid_arguments(self):
+        with self.assertRaisesRegex(TypeError, "pattern_or_fn must be a str, a compiled pattern or a callable"):
+            toon_parser.register_scalar(5, str)
+        with self.assertRaisesRegex(TypeError, "constructor must be callable"):
+            toon_parser.register_scalar("x", 5)
+        with self.assertRaises(re.error):
+            toon_parser.register_scalar("(", str)
+        self.assertEqual(toon_parser.decode("a: x"), {"a": "x"})
+
+
+if __name__ == "__main__":
+    unittest.main()
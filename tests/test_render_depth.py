@@ -0,0 +1,40 @@
+"""Shallow overviews of nested data (``encode(max_render_depth=...)``).
+
+Run with ``python -m unittest tests.test_render_depth``.
+"""
+
+import unittest
+
+import toon_parser
+
+DATA = {"a": {"b": {"c": 1, "d": 2, "e": 3}}, "l": [[1, 2], [3, 4, 5]], "x": 1, "rows": [{"id": 1, "t": {"q": 1}}]}
+
+
+class MaxRenderDepthTest(unittest.TestCase):
+    def test_depth_one(self):
+        value = toon_parser.decode(toon_parser.encode(DATA, max_render_depth=1))
+        self.assertEqual(
+            value,
+            {"a": {"b": "{...3 keys}"}, "l": ["[...2 items]", "[...3 items]"], "x": 1, "rows": ["{...2 keys}"]},
+        )
+
+    def test_depth_zero_keeps_only_root_scalars(self):
+        value = toon_parser.decode(toon_parser.encode(DATA, max_render_depth=0))
+        self.assertEqual(value, {"a": "{...1 keys}", "l": "[...2 items]", "x": 1, "rows": "[...1 items]"})
+
+    def test_table_cells(self):
+        text = toon_parser.encode(DATA, max_render_depth=2)
+        self.assertIn('rows[1]{id,t}:\n  1,"{...1 keys}"', text)
+        self.assertIn("    e: 3", text)
+
+    def test_deep_enough_is_unchanged(self):
+        self.assertEqual(toon_parser.encode(DATA, max_render_depth=3), toon_parser.encode(DATA))
+
+    def test_input_is_not_modified(self):
+        data = {"a": {"b": {"c": 1}}}
+        toon_parser.encode(data, max_render_depth=0)
+        self.assertEqual(data, {"a": {"b": {"c": 1}}})
+
+
+if __name__ == "__main__":
+    unittest.main()
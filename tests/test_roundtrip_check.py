@@ -0,0 +1,71 @@
+"""Fidelity reports from ``roundtrip_check``.
+
+Run with ``python -m unittest tests.test_roundtrip_check``.
+"""
+
+import unittest
+
+import toon_parser
+from toon_parser import Options
+
+
+def issue(path, kind, original, decoded):
+    return {"path": path, "kind": kind, "original": original, "decoded": decoded}
+
+
+class RoundtripCheckTest(unittest.TestCase):
+    def test_data_that_survives(self):
+        cases = [
+            5,
+            {"a": [1, 2], "b": {"c": "1"}, "d": None, "e": True},
+            {"users": [{"id": 1, "name": "Ann"}, {"id": 2, "name": "Bo"}]},
+            {"a": [{}, []], "b": {}},
+            {"x": 1.0, "y": -0.0, "z": 0.1 + 0.2, "w": 5e-324},
+        ]
+        for data in cases:
+            with self.subTest(data=data):
+                self.assertEqual(toon_parser.roundtrip_check(data), {"ok": True, "issues": []})
+
+    def test_tuples(self):
+        self.assertEqual(
+            toon_parser.roundtrip_check({"point": (1, 2)}),
+            {"ok": False, "issues": [issue("$.point", "type", (1, 2), [1, 2])]},
+        )
+        self.assertEqual(toon_parser.roundtrip_check([1, (2, 3)])["issues"], [issue("$[1]", "type", (2, 3), [2, 3])])
+
+    def test_non_string_keys(self):
+        self.assertEqual(toon_parser.roundtrip_check({1: "a"})["issues"], [issue("$.1", "key", 1, "1")])
+
+    def test_keys_that_collide(self):
+        self.assertEqual(
+            toon_parser.roundtrip_check({"1": "a", 1: "b"})["issues"],
+            [issue("$.1", "value", "a", "b"), issue("$.1", "key", 1, "1")],
+        )
+
+    def test_renamed_keys(self):
+        report = toon_parser.roundtrip_check({"userName": 1}, Options(key_transform="snake"))
+        self.assertEqual(
+            report["issues"],
+            [issue("$.userName", "missing", 1, None), issue("$.user_name", "extra", None, 1)],
+        )
+
+    def test_paths_quote_keys_that_are_not_names(self):
+        cases = [
+            ({"my key": (1,)}, '$["my key"]'),
+            ({"a.b": (1,)}, '$["a.b"]'),
+            ({"": (1,)}, '$[""]'),
+            ({"a": {"b": (1,)}}, "$.a.b"),
+        ]
+        for data, path in cases:
+            with self.subTest(data=data):
+                self.assertEqual([i["path"] for i in toon_parser.roundtrip_check(data)["issues"]], [path])
+
+    def test_data_that_cannot_be_encoded(self):
+        with self.assertRaisesRegex(ValueError, "Cannot convert type 'set'"):
+            toon_parser.roundtrip_check({"s": {1, 2}})
+        with self.assertRaisesRegex(ValueError, "Invalid float value"):
+            toon_parser.roundtrip_check({"x": float("nan")})
+
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,134 @@
+"""Values that look like the text rewrites' own syntax survive a round trip.
+
+Several encode and decode options rewrite the text around the parser, and
+some carry values through it in marker strings built on Unicode
+noncharacters (U+FDD0 to U+FDD5). Strings holding those characters, comment
+or front matter syntax, or text shaped like array headers must come back
+unchanged whichever options are on.
+
+Run with ``python -m unittest tests.test_roundtrip_text``.
+"""
+
+import os
+import tempfile
+import unittest
+
+import toon_parser
+from toon_parser import Options, Placeholder
+
+TEXTS = [
+    "﷐", "a﷐b", "a﷐2",
+    "﷑x﷑", "﷑name﷑",
+    "﷒", "﷒0X1F",
+    "﷓x", "﷓0﷓x",
+    "﷔db.toon",
+    "﷕", "\\﷕", "\\﷕0041",
+    "#", "# comment", "a # b", "# sha256:00",
+    "---", "--- x", "...",
+    "[3]", "a[2]: x", "[0]:", "x[2]{a,b}:",
+    "{}", "[]", "x: {}", "- []", "{{name}}",
+    "\\u0041", "1,,2", "a|b", "a\tb", "", " pad", "-", "- x", "key: v",
+    "café", "\U0001F600",
+]
+
+
+def shapes(s):
+    """`s` as a value, a key, table cells, list items and mixed items."""
+    yield {"v": s}
+    yield {s or "k": 1}
+    yield {"rows": [{"id": 1, "s": s}, {"id": 2, "s": s}]}
+    yield {"xs": [s, s]}
+    yield {"items": [{"a": s, "b": [1]}, [s], s]}
+
+
+# encode() keyword arguments, and the decode() ones that read the result back
+VARIANTS = [
+    ({}, {}),
+    ({"empty_collections": "explicit"}, {}),
+    ({"hex": ["n"]}, {"radix_integers": True}),
+    ({"ensure_ascii": True}, {}),
+    ({"ensure_ascii": True, "hex": ["n"]}, {"radix_integers": True}),
+    ({"indent": 4}, {"options": Options(indent=4)}),
+    ({"indent": "\t"}, {"options": Options(indent="\t")}),
+    ({"delimiter": "pipe"}, {}),
+    ({"delimiter": "tab"}, {}),
+    ({"typed_headers": True}, {}),
+    ({"checksum": True}, {"verify_checksum": True}),
+    ({"meta": {"source": "test"}}, {}),
+    ({"strict": True}, {"strict": True}),
+    ({}, {"multidict": True}),
+    ({}, {"merge_tables": True}),
+]
+
+
+class EncodeDecodeTest(unittest.TestCase):
+    def test_round_trip(self):
+        for s in TEXTS:
+            for data in shapes(s):
+                data = dict(data, n=31)
+                for encode_kwargs, decode_kwargs in VARIANTS:
+                    with self.subTest(text=s, data=data, encode=encode_kwargs, decode=decode_kwargs):
+                        text = toon_parser.encode(data, **encode_kwargs)
+                        self.assertEqual(toon_parser.decode(text, **decode_kwargs), data)
+
+    def test_ensure_ascii_output_is_ascii(self):
+        for s in TEXTS:
+            with self.subTest(text=s):
+                text = toon_parser.encode({"v": s, "n": 31}, ensure_ascii=True, hex=["n"])
+                self.assertTrue(text.isascii(), text)
+
+    def test_hex_only_rewrites_listed_fields(self):
+        data = {"n": 31, "v": "﷒0X1F", "xs": ["﷒", 5]}
+        text = toon_parser.encode(data, hex=["n"])
+        self.assertIn("n: 0x1F", text)
+        self.assertEqual(toon_parser.decode(text, radix_integers=True), data)
+
+
+class PlaceholderTest(unittest.TestCase):
+    def test_text_around_placeholders(self):
+        for s in TEXTS:
+            with self.subTest(text=s):
+                template = toon_parser.encode({"p": Placeholder("name"), "v": s, "xs": [s, s]})
+                self.assertEqual(
+                    toon_parser.decode(toon_parser.render(template, {"name": "X"})),
+                    {"p": "X", "v": s, "xs": [s, s]},
+                )
+
+    def test_placeholders_with_ensure_ascii(self):
+        template = toon_parser.encode({"p": Placeholder("name"), "v": "café﷑"}, ensure_ascii=True)
+        self.assertIn("{{name}}", template)
+        self.assertEqual(
+            toon_parser.decode(toon_parser.render(template, {"name": 1})),
+            {"p": 1, "v": "café﷑"},
+        )
+
+
+class DecodeMarkersTest(unittest.TestCase):
+    def tearDown(self):
+        toon_parser.clear_scalars()
+
+    def test_registered_scalars_leave_other_strings_alone(self):
+        toon_parser.register_scalar(r"@\w+", lambda text: ("at", text))
+        for s in TEXTS:
+            with self.subTest(text=s):
+                data = {"v": s, "xs": [s, s]}
+                text = toon_parser.encode(data) + "\nat: @home"
+                self.assertEqual(toon_parser.decode(text), dict(data, at=("at", "@home")))
+
+    def test_repeated_keys_holding_the_marker_character(self):
+        doc = '"a﷐": 1\n"a﷐": 2\nb: 3\n'
+        self.assertEqual(toon_parser.decode(doc, multidict=True), [("a﷐", 1), ("a﷐", 2), ("b", 3)])
+
+    def test_includes_leave_other_strings_alone(self):
+        with tempfile.TemporaryDirectory() as base:
+            with open(os.path.join(base, "db.toon"), "w", encoding="utf-8") as f:
+                f.write("x: 1\n")
+            for s in TEXTS:
+                with self.subTest(text=s):
+                    data = {"v": s, "xs": [s, s]}
+                    text = toon_parser.encode(data) + "\ndb: !include db.toon"
+                    self.assertEqual(toon_parser.decode(text, include_base=base), dict(data, db={"x": 1}))
+
+
+if __name__ == "__main__":
+    unittest.main()
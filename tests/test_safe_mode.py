@@ -0,0 +1,147 @@
+"""Resource limits for untrusted input (``Options.untrusted()``).
+
+Every decoding entry point that accepts ``options`` must reject hostile
+documents with ``ToonLimitError`` before building anything from them: deep
+nesting, huge length markers, gigantic declared table sizes and oversized
+input.
+
+Run with ``python -m unittest tests.test_safe_mode``.
+"""
+
+import io
+import unittest
+
+import toon_parser
+from toon_parser import Options, ToonError, ToonLimitError
+
+
+def deep_nesting(levels):
+    """A document nesting one key per level, `levels` deep."""
+    return "".join("{}k{}:\n".format("  " * i, i) for i in range(levels)) + "  " * levels + "leaf: 1\n"
+
+
+DEEP = deep_nesting(1000)
+HUGE_LENGTH = "items[2000000000]: 1,2,3\n"
+OVERFLOWING_LENGTH = "items[99999999999999999999999999]: 1\n"
+HUGE_TABLE = "users[999999999]{id,name}:\n  1,Ann\n  2,Bo\n"
+NESTED_HUGE_TABLE = "outer:\n  - rows[4000000000|]{a|b}:\n      1|2\n"
+
+# Each entry point that honors Options, called with a document and options
+ENTRY_POINTS = {
+    "decode": lambda doc, opts: toon_parser.decode(doc, options=opts),
+    "loads": lambda doc, opts: toon_parser.loads(doc, options=opts),
+    "load": lambda doc, opts: toon_parser.load(io.StringIO(doc), options=opts),
+    "decode_with_options": lambda doc, opts: toon_parser.decode_with_options(doc, opts),
+    "decode_bytes": lambda doc, opts: toon_parser.decode_bytes(doc.encode(), opts),
+    "decode_with_meta": lambda doc, opts: toon_parser.decode_with_meta(doc, options=opts),
+    "decode_lazy": lambda doc, opts: toon_parser.decode_lazy(doc, options=opts),
+    "decode_columns": lambda doc, opts: toon_parser.decode_columns(doc, options=opts),
+    "decode_as": lambda doc, opts: toon_parser.decode_as(doc, dict, options=opts),
+    "decode_batch": lambda doc, opts: toon_parser.decode_batch([doc], options=opts),
+    "decode_batch_iter": lambda doc, opts: list(toon_parser.decode_batch_iter([doc], options=opts)),
+    "toon_to_json": lambda doc, opts: toon_parser.toon_to_json(doc, options=opts),
+}
+
+
+class UntrustedPresetTest(unittest.TestCase):
+    def test_preset_sets_every_limit(self):
+        opts = Options.untrusted()
+        self.assertIsNotNone(opts.max_input_bytes)
+        self.assertIsNotNone(opts.max_depth)
+        self.assertIsNotNone(opts.max_array_length)
+        self.assertIn("max_depth=", repr(opts))
+
+    def test_default_options_are_unlimited(self):
+        opts = Options()
+        self.assertIsNone(opts.max_input_bytes)
+        self.assertIsNone(opts.max_depth)
+        self.assertIsNone(opts.max_array_length)
+
+    def test_limit_error_is_a_toon_error(self):
+        self.assertTrue(issubclass(ToonLimitError, ToonError))
+
+    def test_builders_and_equality(self):
+        opts = Options().with_max_depth(8).with_max_array_length(100)
+        self.assertEqual(opts, Options(max_depth=8, max_array_length=100))
+        self.assertNotEqual(opts, Options())
+
+
+class AdversarialInputTest(unittest.TestCase):
+    def assert_rejected(self, doc, opts=None):
+        opts = opts or Options.untrusted()
+        for name, call in ENTRY_POINTS.items():
+            with self.subTest(entry_point=name):
+                with self.assertRaises(ToonLimitError):
+                    call(doc, opts)
+
+    def test_deep_nesting(self):
+        self.assert_rejected(DEEP)
+
+    def test_huge_length_marker(self):
+        self.assert_rejected(HUGE_LENGTH)
+
+    def test_length_marker_past_integer_range(self):
+        self.assert_rejected(OVERFLOWING_LENGTH)
+
+    def test_gigantic_declared_table(self):
+        self.assert_rejected(HUGE_TABLE)
+
+    def test_nested_table_with_pipe_delimiter(self):
+        self.assert_rejected(NESTED_HUGE_TABLE)
+
+    def test_oversized_input(self):
+        self.assert_rejected("key: value\n" * 100, Options(max_input_bytes=512))
+
+    def test_error_names_the_line(self):
+        with self.assertRaisesRegex(ToonLimitError, "Line 2: .*max_array_length"):
+            toon_parser.decode("ok: 1\n" + HUGE_LENGTH, options=Options.untrusted())
+
+    def test_depth_limit_is_exact(self):
+        opts = Options(max_depth=3)
+        self.assertEqual(toon_parser.decode(deep_nesting(2), options=opts), {"k0": {"k1": {"leaf": 1}}})
+        with self.assertRaises(ToonLimitError):
+            toon_parser.decode(deep_nesting(3), options=opts)
+
+    def test_batch_on_error_applies_to_limit_errors(self):
+        values, errors = toon_parser.decode_batch(
+            ["a: 1", HUGE_LENGTH], on_error="collect", options=Options.untrusted()
+        )
+        self.assertEqual(values, [{"a": 1}, None])
+        self.assertEqual(errors[0][0], 1)
+        self.assertIsInstance(errors[0][1], ToonLimitError)
+
+    def test_ordinary_documents_pass(self):
+        doc = "users[2]{id,name}:\n  1,Ann\n  2,Bo\n"
+        expected = {"users": [{"id": 1, "name": "Ann"}, {"id": 2, "name": "Bo"}]}
+        for name, call in ENTRY_POINTS.items():
+            with self.subTest(entry_point=name):
+                result = call(doc, Options.untrusted())
+                if name == "toon_to_json":
+                    self.assertIn('"users"', result)
+                elif name in ("decode_batch", "decode_batch_iter"):
+                    self.assertEqual(result, [expected])
+                elif name == "decode_with_meta":
+                    self.assertEqual(result, (expected, None))
+                elif name == "decode_columns":
+                    self.assertEqual(result, {"id": [1, 2], "name": ["Ann", "Bo"]})
+                else:
+                    self.assertEqual(result, expected)
+
+
+class ImpossibleLengthTest(unittest.TestCase):
+    """Lengths the remaining input cannot hold are rejected even without limits."""
+
+    def test_rejected_without_options(self):
+        for doc in (HUGE_LENGTH, OVERFLOWING_LENGTH, HUGE_TABLE):
+            with self.subTest(doc=doc):
+                with self.assertRaisesRegex(toon_parser.ToonSyntaxError, "bytes of input follow"):
+                    toon_parser.decode(doc)
+                with self.assertRaisesRegex(toon_parser.ToonSyntaxError, "bytes of input follow"):
+                    toon_parser.decode_bytes(doc.encode())
+
+    def test_brackets_in_strings_are_not_lengths(self):
+        self.assertEqual(toon_parser.decode('note: "see [2024]"\n'), {"note": "see [2024]"})
+
+
+if __name__ == "__main__":
+    unittest.main()
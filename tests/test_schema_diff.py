@@ -0,0 +1,52 @@
+"""Schema drift between two documents (``schema_diff``).
+
+Run with ``python -m unittest tests.test_schema_diff``.
+"""
+
+import unittest
+
+import toon_parser
+
+OLD = {"users": [{"id": 1, "name": "Ann", "email": "a@x"}], "v": 1, "meta": {"x": 1}}
+NEW = {"users": [{"id": "1", "name": "Ann", "age": 3}], "v": 1, "meta": {"x": 1, "y": [1]}}
+
+
+class SchemaDiffTest(unittest.TestCase):
+    def test_identical_documents(self):
+        self.assertEqual(toon_parser.schema_diff(OLD, OLD), {"added": [], "removed": [], "retyped": [], "tables": []})
+
+    def test_values_alone_are_not_drift(self):
+        changed = {"users": [{"id": 9, "name": "Bo", "email": "b@x"}] * 3, "v": 2, "meta": {"x": 5}}
+        self.assertEqual(toon_parser.schema_diff(OLD, changed)["added"], [])
+        self.assertEqual(toon_parser.schema_diff(OLD, changed)["retyped"], [])
+
+    def test_added_and_removed_fields(self):
+        diff = toon_parser.schema_diff(OLD, NEW)
+        self.assertEqual(
+            diff["added"],
+            [
+                {"path": "$.meta.y", "type": "list"},
+                {"path": "$.meta.y[]", "type": "int"},
+                {"path": "$.users[].age", "type": "int"},
+            ],
+        )
+        self.assertEqual(diff["removed"], [{"path": "$.users[].email", "type": "str"}])
+
+    def test_retyped_fields(self):
+        self.assertEqual(toon_parser.schema_diff(OLD, NEW)["retyped"], [{"path": "$.users[].id", "old": "int", "new": "str"}])
+        self.assertEqual(toon_parser.schema_diff({"a": None}, {"a": 1})["retyped"], [{"path": "$.a", "old": "null", "new": "int"}])
+
+    def test_table_columns(self):
+        self.assertEqual(
+            toon_parser.schema_diff(OLD, NEW)["tables"],
+            [{"path": "$.users", "old_columns": ["id", "name", "email"], "new_columns": ["id", "name", "age"]}],
+        )
+
+    def test_accepts_toon_text(self):
+        diff = toon_parser.schema_diff("users[1]{id}:\n  1", b"users[1]{id,name}:\n  1,a")
+        self.assertEqual(diff["added"], [{"path": "$.users[].name", "type": "str"}])
+        self.assertEqual(diff["tables"][0]["new_columns"], ["id", "name"])
+
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,66 @@
+"""Schema-guided encoding (``encode(schema=...)``).
+
+Run with ``python -m unittest tests.test_schema_encode``.
+"""
+
+import unittest
+from dataclasses import dataclass
+
+import toon_parser
+from toon_parser import Schema, ToonSchemaError
+
+
+@dataclass
+class User:
+    id: int
+    name: str
+    role: str = "user"
+
+
+class DeclaredSchemaTest(unittest.TestCase):
+    def test_dataclasses_become_a_table(self):
+        text = toon_parser.encode([User(1, "Ann"), User(2, "Bo", "admin")], schema=list[User])
+        self.assertEqual(text.strip(), "[2]{id,name,role}:\n  1,Ann,user\n  2,Bo,admin")
+
+    def test_dicts_follow_declaration_order_and_defaults(self):
+        text = toon_parser.encode([{"name": "Ann", "id": 1}], schema=list[User])
+        self.assertEqual(text.strip(), "[1]{id,name,role}:\n  1,Ann,user")
+
+    def test_precompiled_schema(self):
+        users = Schema(list[User])
+        self.assertEqual(
+            toon_parser.encode([User(1, "Ann")], schema=users),
+            toon_parser.encode([User(1, "Ann")], schema=list[User]),
+        )
+
+    def test_round_trip_through_decode_as(self):
+        users = [User(1, "Ann"), User(2, "Bo", "admin")]
+        text = toon_parser.encode(users, schema=list[User])
+        self.assertEqual(toon_parser.decode_as(text, list[User]), users)
+
+    def test_wrong_type(self):
+        with self.assertRaisesRegex(ToonSchemaError, r"\$\[0\]\.id: expected int, got str"):
+            toon_parser.encode([{"id": "x", "name": "Ann"}], schema=list[User])
+
+    def test_undeclared_key(self):
+        with self.assertRaisesRegex(ToonSchemaError, "unexpected key 'extra'"):
+            toon_parser.encode([{"id": 1, "name": "Ann", "extra": 1}], schema=list[User])
+
+    def test_missing_key_without_default(self):
+        with self.assertRaisesRegex(ToonSchemaError, "missing required key 'id'"):
+            toon_parser.encode([{"name": "Ann"}], schema=list[User])
+
+
+class InferredSchemaTest(unittest.TestCase):
+    def test_infer_gives_rows_one_layout(self):
+        data = {"rows": [{"a": 1}, {"b": 2}]}
+        self.assertEqual(toon_parser.encode(data, schema="infer").strip(), "rows[2]{a,b}:\n  1,null\n  null,2")
+        self.assertTrue(toon_parser.encode(data).startswith("rows[2]:\n  - "))
+
+    def test_unknown_mode(self):
+        with self.assertRaisesRegex(ValueError, "Must be a type, a Schema or 'infer'"):
+            toon_parser.encode({}, schema="guess")
+
+
+if __name__ == "__main__":
+    unittest.main()
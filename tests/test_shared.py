@@ -0,0 +1,124 @@
+"""Passing documents between processes with ``encode_shared`` and ``decode_shared``.
+
+Run with ``python -m unittest tests.test_shared``.
+"""
+
+import multiprocessing
+import os
+import pickle
+import subprocess
+import sys
+import tempfile
+import textwrap
+import unittest
+from multiprocessing import shared_memory
+
+import toon_parser
+from toon_parser import Options, SharedBuffer
+
+DATA = {"rows": [{"id": i, "name": f"é{i}"} for i in range(1000)]}
+
+# Workers in a pool read the buffer, then the creator frees it
+POOL = textwrap.dedent(
+    """
+    import multiprocessing
+    import sys
+    from multiprocessing import shared_memory
+
+    import toon_parser
+
+    DATA = {"rows": [{"id": i} for i in range(1000)]}
+
+    def work(buf):
+        return toon_parser.decode_shared(buf) == DATA
+
+    if __name__ == "__main__":
+        buf = toon_parser.encode_shared(DATA)
+        with multiprocessing.get_context(sys.argv[1]).Pool(2) as pool:
+            assert all(pool.map(work, [buf] * 4))
+        assert toon_parser.decode_shared(buf) == DATA
+        buf.unlink()
+        try:
+            shared_memory.SharedMemory(name=buf.name)
+        except FileNotFoundError:
+            print("freed")
+        try:
+            buf.unlink()
+        except FileNotFoundError:
+            pass
+    """
+)
+
+
+class SharedTest(unittest.TestCase):
+    def setUp(self):
+        self.buf = toon_parser.encode_shared(DATA)
+
+    def tearDown(self):
+        try:
+            self.buf.unlink()
+        except FileNotFoundError:
+            pass
+
+    def test_round_trip(self):
+        text = toon_parser.encode(DATA).encode()
+        self.assertEqual(toon_parser.decode_shared(self.buf), DATA)
+        self.assertEqual(bytes(self.buf), text)
+        self.assertEqual(len(self.buf), len(text))
+        self.assertEqual(self.buf.size, len(text))
+        self.assertEqual(repr(self.buf), f"<SharedBuffer '{self.buf.name}' of {len(text)} bytes>")
+
+    def test_pickles_by_reference(self):
+        payload = pickle.dumps(self.buf)
+        self.assertLess(len(payload), 200)
+        attached = pickle.loads(payload)
+        self.assertIsInstance(attached, SharedBuffer)
+        self.assertEqual(attached.name, self.buf.name)
+        self.assertEqual(toon_parser.decode_shared(attached), DATA)
+        attached.close()
+        self.assertEqual(toon_parser.decode_shared(self.buf), DATA)
+
+    def test_options(self):
+        options = Options(delimiter="pipe")
+        buf = toon_parser.encode_shared({"x": [1, 2]}, options)
+        self.addCleanup(buf.unlink)
+        self.assertEqual(bytes(buf), b"x[2|]: 1|2")
+        self.assertEqual(toon_parser.decode_shared(buf, options), {"x": [1, 2]})
+
+    def test_empty_document(self):
+        buf = toon_parser.encode_shared({})
+        self.assertEqual(bytes(buf), b"")
+        self.assertEqual(toon_parser.decode_shared(buf, unlink=True), {})
+
+    def test_unlink_after_reading(self):
+        self.assertEqual(toon_parser.decode_shared(self.buf, unlink=True), DATA)
+        with self.assertRaises(FileNotFoundError):
+            shared_memory.SharedMemory(name=self.buf.name)
+
+    def test_closed(self):
+        self.buf.close()
+        with self.assertRaisesRegex(ValueError, "the SharedBuffer is closed"):
+            toon_parser.decode_shared(self.buf)
+
+    def test_not_a_buffer(self):
+        with self.assertRaises(TypeError):
+            toon_parser.decode_shared(toon_parser.encode(DATA).encode())
+
+    def test_worker_processes(self):
+        with tempfile.TemporaryDirectory() as tmp:
+            script = os.path.join(tmp, "pool.py")
+            with open(script, "w") as f:
+                f.write(POOL)
+            for method in ("spawn", "fork"):
+                if method not in multiprocessing.get_all_start_methods():
+                    continue
+                with self.subTest(method=method):
+                    result = subprocess.run([sys.executable, script, method], capture_output=True, text=True, timeout=120)
+                    self.assertEqual(result.returncode, 0, result.stderr)
+                    self.assertEqual(result.stdout.strip(), "freed")
+                    # The resource tracker neither complains nor warns about leaks
+                    self.assertEqual(result.stderr, "")
+
+
+if __name__ == "__main__":
+    unittest.main()
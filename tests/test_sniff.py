@@ -0,0 +1,87 @@
+"""Guesses made from the text alone: ``detect_delimiter``, ``detect_format`` and ``is_toon``.
+
+Run with ``python -m unittest tests.test_sniff``.
+"""
+
+import unittest
+
+import toon_parser
+
+
+class DetectDelimiterTest(unittest.TestCase):
+    def test_declared_by_headers(self):
+        cases = [
+            ("xs[3]: 1,2,3", "comma"),
+            ("xs[3|]: 1|2|3\nys[2|]: a|b", "pipe"),
+            ("t[2\t]{a\tb}:\n  1\t2\n  3\t4", "tab"),
+        ]
+        for text, delimiter in cases:
+            with self.subTest(text=text):
+                report = toon_parser.detect_delimiter(text)
+                self.assertEqual((report["delimiter"], report["confidence"], report["note"]), (delimiter, 1.0, None))
+
+    def test_report(self):
+        report = toon_parser.detect_delimiter("xs[2\t]: 1\t2\nys[2]: 1,2\nzs[2\t]: 1\t2")
+        self.assertEqual(report["delimiter"], "tab")
+        self.assertAlmostEqual(report["confidence"], 2 / 3)
+        self.assertEqual(report["declared"], {"comma": 1, "tab": 2, "pipe": 0})
+        self.assertEqual(report["counts"], {"comma": 1, "tab": 2, "pipe": 0})
+
+    def test_rows_that_disagree_with_their_header(self):
+        report = toon_parser.detect_delimiter("t[2]{a,b}:\n  1\t2\n  3\t4")
+        self.assertEqual(report["delimiter"], "comma")
+        self.assertEqual(report["counts"], {"comma": 0, "tab": 2, "pipe": 0})
+        self.assertEqual(
+            report["note"],
+            "values contain more tab than comma delimiters; to split on tab, headers need a tab after the length, as in [3\\t]",
+        )
+
+    def test_quoted_delimiters_do_not_count(self):
+        self.assertEqual(toon_parser.detect_delimiter('xs[2]: "a,b|c",d')["counts"], {"comma": 1, "tab": 0, "pipe": 0})
+
+    def test_no_evidence(self):
+        for text in ("", "a: 1", "a: x|y|z"):
+            with self.subTest(text=text):
+                report = toon_parser.detect_delimiter(text)
+                self.assertEqual((report["delimiter"], report["confidence"], report["note"]), ("comma", 0.0, None))
+
+
+class DetectFormatTest(unittest.TestCase):
+    def test_formats(self):
+        cases = [
+            ("users[2]{id,name}:\n  1,Ann\n  2,Bo", "toon"),
+            ("a: 1\nb:\n  c: x", "toon"),
+            ("[2]: 1,2", "toon"),
+            ("- a\n- b", "toon"),
+            ('{"id": 1}', "json"),
+            ("[1, 2]", "json"),
+            ('\ufeff{"a": 1}', "json"),
+            ("id,name\n1,Ann\n2,Bo", "csv"),
+            ("a\tb\n1\t2", "csv"),
+            ("a;b\n1;2", "csv"),
+            ("hello world", "unknown"),
+            ("a: 1\nhello there\nb: 2", "unknown"),
+            ("id,name", "unknown"),
+            ("", "unknown"),
+            ("  \n", "unknown"),
+        ]
+        for text, fmt in cases:
+            with self.subTest(text=text):
+                self.assertEqual(toon_parser.detect_format(text), fmt)
+                self.assertEqual(toon_parser.detect_format(text.encode()), fmt)
+                self.assertEqual(toon_parser.is_toon(text), fmt == "toon")
+
+    def test_bytes(self):
+        self.assertEqual(toon_parser.detect_format(b"\xff\xfe"), "unknown")
+        # A character cut off at the end of the 64 KiB sample is fine
+        self.assertEqual(toon_parser.detect_format(b"a: " + "\u00e9".encode() * 40000), "toon")
+
+    def test_other_types(self):
+        for func in (toon_parser.detect_format, toon_parser.is_toon):
+            with self.subTest(func=func.__name__):
+                with self.assertRaisesRegex(TypeError, "Expected str or bytes, got 'int'"):
+                    func(5)
+
+
+if __name__ == "__main__":
+    unittest.main()
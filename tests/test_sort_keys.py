@@ -0,0 +1,62 @@
+"""Deterministic output with ``sort_keys`` on ``encode``, ``dumps`` and ``Options``.
+
+Run with ``python -m unittest tests.test_sort_keys``.
+"""
+
+import io
+import unittest
+
+import toon_parser
+from toon_parser import Options
+
+DATA = {"z": 1, "a": {"y": 2, "b": [{"q": 1, "c": 2}]}, "m": [{"k": {"z": 1, "a": 2}}]}
+SORTED = "a:\n  b[1]{c,q}:\n    2,1\n  y: 2\nm[1]:\n  - k:\n      a: 2\n      z: 1\nz: 1"
+
+
+class SortKeysTest(unittest.TestCase):
+    def test_every_entry_point(self):
+        stream = io.StringIO()
+        toon_parser.dump(DATA, stream, sort_keys=True)
+        cases = [
+            ("encode", toon_parser.encode(DATA, sort_keys=True)),
+            ("option", toon_parser.encode(DATA, option=toon_parser.OPT_SORT_KEYS)),
+            ("dumps", toon_parser.dumps(DATA, sort_keys=True)),
+            ("dump", stream.getvalue()),
+            ("encode_with_options", toon_parser.encode_with_options(DATA, Options(sort_keys=True))),
+            ("encode_bytes", toon_parser.encode_bytes(DATA, Options(sort_keys=True)).decode()),
+        ]
+        for name, text in cases:
+            with self.subTest(name):
+                self.assertEqual(text, SORTED)
+
+    def test_insertion_order_by_default(self):
+        self.assertEqual(list(toon_parser.decode(toon_parser.encode(DATA))), ["z", "a", "m"])
+        self.assertEqual(list(toon_parser.decode(toon_parser.dumps(DATA))), ["z", "a", "m"])
+        self.assertEqual(list(toon_parser.decode(toon_parser.encode_with_options(DATA, Options()))), ["z", "a", "m"])
+
+    def test_equal_data_encodes_equally(self):
+        reordered = {"m": [{"k": {"a": 2, "z": 1}}], "a": {"b": [{"c": 2, "q": 1}], "y": 2}, "z": 1}
+        self.assertEqual(toon_parser.encode(reordered, sort_keys=True), toon_parser.encode(DATA, sort_keys=True))
+
+    def test_order_matches_sorted(self):
+        keys = ["b", "a", "B", "é", "10", "9", "_"]
+        decoded = toon_parser.decode(toon_parser.encode(dict.fromkeys(keys, 1), sort_keys=True))
+        self.assertEqual(list(decoded), sorted(keys))
+
+    def test_table_columns(self):
+        self.assertEqual(toon_parser.encode({"r": [{"b": 1, "a": 2}, {"a": 3, "b": 4}]}, sort_keys=True), "r[2]{a,b}:\n  2,1\n  3,4")
+
+    def test_after_key_transform(self):
+        options = Options(sort_keys=True, key_transform="kebab")
+        self.assertEqual(toon_parser.encode_with_options({"b_a": 1, "a_z": 2, "aB": 3}, options), '"a-b": 3\n"a-z": 2\n"b-a": 1')
+
+    def test_options_setting(self):
+        self.assertFalse(Options().sort_keys)
+        self.assertTrue(Options(sort_keys=True).sort_keys)
+        self.assertEqual(Options().with_sort_keys(True), Options(sort_keys=True))
+        self.assertEqual(repr(Options(sort_keys=True)), "Options(delimiter='comma', strict=false, sort_keys=True)")
+        self.assertNotEqual(hash(Options(sort_keys=True)), hash(Options()))
+
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,70 @@
+"""Sparse tables: rows with missing fields on encode, empty cells on decode.
+
+Run with ``python -m unittest tests.test_sparse``.
+"""
+
+import unittest
+
+import toon_parser
+from toon_parser import Options, ToonSyntaxError
+
+SPARSE = "rows[3]{id,name,age}:\n  1,,30\n  2,Bo,\n  ,Cy,5\n"
+FILLED = {"rows": [{"id": 1, "name": None, "age": 30}, {"id": 2, "name": "Bo", "age": None}, {"id": None, "name": "Cy", "age": 5}]}
+ROWS = {"rows": [{"id": 1, "name": "Ann"}, {"id": 2}]}
+
+
+class EncodeMissingFieldsTest(unittest.TestCase):
+    def test_null_fill(self):
+        self.assertEqual(toon_parser.encode(ROWS, heterogeneous="union"), "rows[2]{id,name}:\n  1,Ann\n  2,null")
+
+    def test_drop_column(self):
+        self.assertEqual(toon_parser.encode(ROWS, heterogeneous="drop"), "rows[2]{id}:\n  1\n  2")
+
+    def test_error(self):
+        with self.assertRaisesRegex(ValueError, "row 1 lacks 'name'"):
+            toon_parser.encode(ROWS, heterogeneous="error")
+
+
+class EmptyCellsTest(unittest.TestCase):
+    def test_read_as_none(self):
+        opts = Options(empty_cells_as_none=True)
+        self.assertEqual(toon_parser.decode(SPARSE, options=opts), FILLED)
+        self.assertEqual(toon_parser.loads(SPARSE, options=opts), FILLED)
+        self.assertEqual(toon_parser.decode_with_options(SPARSE, opts), FILLED)
+        self.assertEqual(toon_parser.decode_bytes(SPARSE.encode(), options=opts), FILLED)
+        self.assertEqual(
+            toon_parser.decode_columns(SPARSE, "rows", options=opts),
+            {"id": [1, 2, None], "name": [None, "Bo", "Cy"], "age": [30, None, 5]},
+        )
+
+    def test_left_to_the_parser_by_default(self):
+        self.assertEqual(toon_parser.decode(SPARSE)["rows"][0], {"id": 1, "name": "", "age": 30})
+
+    def test_other_delimiters(self):
+        opts = Options(empty_cells_as_none=True)
+        expected = {"rows": [{"a": 1, "b": None}, {"a": None, "b": 2}]}
+        for text in ("rows[2|]{a|b}:\n  1|\n  |2\n", "rows[2\t]{a\tb}:\n  1\t\n  \t2\n"):
+            with self.subTest(text=text):
+                self.assertEqual(toon_parser.decode(text, options=opts), expected)
+
+    def test_only_table_cells(self):
+        opts = Options(empty_cells_as_none=True)
+        self.assertEqual(toon_parser.decode("a: x,,y\n", options=opts), {"a": "x,,y"})
+        self.assertEqual(toon_parser.decode('rows[1]{a,b}:\n  "",1\n', options=opts), {"rows": [{"a": "", "b": 1}]})
+
+    def test_strict_rejects_them_either_way(self):
+        message = "^Line 2: empty cell in table row; strict mode requires a value \\(write null\\)$"
+        for opts in (None, Options(empty_cells_as_none=True)):
+            with self.subTest(opts=opts):
+                with self.assertRaisesRegex(ToonSyntaxError, message):
+                    toon_parser.decode(SPARSE, strict=True, options=opts)
+
+    def test_option(self):
+        self.assertFalse(Options().empty_cells_as_none)
+        opts = Options().with_empty_cells_as_none(True)
+        self.assertEqual(opts, Options(empty_cells_as_none=True))
+        self.assertIn("empty_cells_as_none=True", repr(opts))
+
+
+if __name__ == "__main__":
+    unittest.main()
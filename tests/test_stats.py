@@ -0,0 +1,62 @@
+"""Document statistics with ``stats``.
+
+Run with ``python -m unittest tests.test_stats``.
+"""
+
+import unittest
+
+import toon_parser
+
+DATA = {
+    "users": [{"id": 1, "name": "Ann"}, {"id": 2, "name": "Bo"}],
+    "meta": {"n": 2, "tags": ["a", "b"], "ok": True, "x": None},
+}
+TEXT = toon_parser.encode(DATA)
+
+
+class StatsTest(unittest.TestCase):
+    def test_counts(self):
+        self.assertEqual(
+            toon_parser.stats(TEXT),
+            {
+                "objects": 4,
+                "arrays": 2,
+                "tables": 1,
+                "table_rows": 2,
+                "keys": 8,
+                "max_depth": 4,
+                "strings": 4,
+                "numbers": 3,
+                "encoded_bytes": len(TEXT),
+            },
+        )
+
+    def test_data_str_and_bytes_agree(self):
+        self.assertEqual(toon_parser.stats(DATA), toon_parser.stats(TEXT))
+        self.assertEqual(toon_parser.stats(TEXT.encode()), toon_parser.stats(TEXT))
+
+    def test_encoded_bytes_of_text_is_its_own_length(self):
+        self.assertEqual(toon_parser.stats("a: é")["encoded_bytes"], 5)
+        # Not re-encoded: extra spacing counts
+        self.assertEqual(toon_parser.stats("a:   1\n")["encoded_bytes"], 7)
+
+    def test_scalars_and_empty_containers(self):
+        cases = [
+            (5, {"numbers": 1, "max_depth": 1, "encoded_bytes": 1}),
+            ({}, {"objects": 1, "max_depth": 1}),
+            ([], {"arrays": 1, "max_depth": 1, "encoded_bytes": 4}),
+        ]
+        zero = dict.fromkeys(toon_parser.stats(5), 0)
+        for data, counts in cases:
+            with self.subTest(data=data):
+                self.assertEqual(toon_parser.stats(data), dict(zero, **counts))
+
+    def test_errors(self):
+        with self.assertRaisesRegex(toon_parser.ToonSyntaxError, "^Line 1: "):
+            toon_parser.stats("xs[3]: 1,2")
+        with self.assertRaisesRegex(ValueError, "Cannot convert type 'object'"):
+            toon_parser.stats(object())
+
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,88 @@
+"""Per-direction strict mode: ``Options(strict_encode=..., strict_decode=...)``.
+
+Run with ``python -m unittest tests.test_strict_directions``.
+"""
+
+import unittest
+
+import toon_parser
+from toon_parser import Options, ToonSyntaxError
+
+# Valid in lenient mode only: three-space indentation
+LENIENT = "a:\n   b: 1"
+VALUE = {"a": {"b": 1}}
+
+
+def decoders(options):
+    return [
+        ("decode_with_options", lambda: toon_parser.decode_with_options(LENIENT, options)),
+        ("decode", lambda: toon_parser.decode(LENIENT, options=options)),
+        ("loads", lambda: toon_parser.loads(LENIENT, options=options)),
+        ("decode_bytes", lambda: toon_parser.decode_bytes(LENIENT.encode(), options)),
+    ]
+
+
+class SettingsTest(unittest.TestCase):
+    def test_defaults_follow_strict(self):
+        cases = [
+            (Options(), (False, False, False)),
+            (Options(strict=True), (True, True, True)),
+            (Options(strict_encode=True), (False, True, False)),
+            (Options(strict_decode=True), (False, False, True)),
+            (Options(strict=True, strict_decode=False), (False, True, False)),
+        ]
+        for opts, flags in cases:
+            with self.subTest(opts=opts):
+                self.assertEqual((opts.strict, opts.strict_encode, opts.strict_decode), flags)
+
+    def test_setting_strict_sets_both(self):
+        opts = Options(strict_encode=True)
+        opts.strict = True
+        self.assertEqual(opts, Options(strict=True))
+        opts.strict = False
+        self.assertEqual(opts, Options())
+
+    def test_builders(self):
+        self.assertEqual(Options().with_strict_encode(True), Options(strict_encode=True))
+        self.assertEqual(Options().with_strict_decode(True), Options(strict_decode=True))
+        self.assertEqual(Options().with_strict(True), Options(strict_encode=True, strict_decode=True))
+
+    def test_repr_eq_and_hash(self):
+        self.assertEqual(repr(Options(strict=True)), "Options(delimiter='comma', strict=true)")
+        self.assertEqual(
+            repr(Options(strict_decode=True)),
+            "Options(delimiter='comma', strict_encode=false, strict_decode=true)",
+        )
+        self.assertNotEqual(Options(strict_encode=True), Options(strict_decode=True))
+        self.assertEqual(hash(Options(strict_encode=True)), hash(Options(strict_encode=True)))
+
+
+class DecodeTest(unittest.TestCase):
+    def test_strict_decode_rejects(self):
+        for name, call in decoders(Options(strict_decode=True)):
+            with self.subTest(name):
+                with self.assertRaisesRegex(ToonSyntaxError, "^Line 2: indentation of 3 spaces"):
+                    call()
+
+    def test_strict_encode_alone_reads_leniently(self):
+        for name, call in decoders(Options(strict_encode=True)):
+            with self.subTest(name):
+                self.assertEqual(call(), VALUE)
+
+    def test_explicit_strict_argument_wins(self):
+        self.assertEqual(toon_parser.decode(LENIENT, strict=False, options=Options(strict_decode=True)), VALUE)
+        with self.assertRaises(ToonSyntaxError):
+            toon_parser.decode(LENIENT, strict=True, options=Options(strict_encode=True))
+
+
+class EncodeTest(unittest.TestCase):
+    def test_strict_decode_does_not_change_encoding(self):
+        data = {"users": [{"id": 1, "name": "Ann"}], "tags": ["a", "b"]}
+        self.assertEqual(
+            toon_parser.encode_with_options(data, Options(strict_decode=True)),
+            toon_parser.encode(data),
+        )
+
+
+if __name__ == "__main__":
+    unittest.main()
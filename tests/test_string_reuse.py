@@ -0,0 +1,46 @@
+"""Repeated short strings share one Python object within a decode.
+
+Run with ``python -m unittest tests.test_string_reuse``.
+"""
+
+import unittest
+
+import toon_parser
+
+
+def table(rows, status=lambda i: "active"):
+    lines = ["users[{}]{{id,name,status}}:".format(rows)]
+    lines += ["  {},u{},{}".format(i, i, status(i)) for i in range(rows)]
+    return "\n".join(lines) + "\n"
+
+
+class StringReuseTest(unittest.TestCase):
+    def test_repeated_values_share_one_object(self):
+        rows = toon_parser.decode(table(10))["users"]
+        self.assertTrue(all(row["status"] is rows[0]["status"] for row in rows))
+
+    def test_keys_share_one_object(self):
+        rows = toon_parser.decode("items[2]:\n  - label: x\n  - label: y\n")["items"]
+        first, second = (next(iter(row)) for row in rows)
+        self.assertIs(first, second)
+
+    def test_unique_values_do_not_evict_recent_ones(self):
+        # 2000 distinct names pass through the cache between uses of each status
+        rows = toon_parser.decode(table(2000, lambda i: ("active", "paused")[i % 2]))["users"]
+        for status in ("active", "paused"):
+            matching = [row["status"] for row in rows if row["status"] == status]
+            self.assertTrue(all(value is matching[0] for value in matching), status)
+
+    def test_low_cardinality_columns_beyond_capacity(self):
+        # More distinct values than the cache holds still decode correctly
+        rows = toon_parser.decode(table(1000, lambda i: "s{}".format(i % 300)))["users"]
+        self.assertEqual([row["status"] for row in rows], ["s{}".format(i % 300) for i in range(1000)])
+
+    def test_long_strings_are_not_shared_but_equal(self):
+        value = "x" * 40
+        rows = toon_parser.decode(table(3, lambda i: value))["users"]
+        self.assertEqual([row["status"] for row in rows], [value] * 3)
+
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,75 @@
+"""Behaviour of toon_parser under multiple interpreters (PEP 684).
+
+Subinterpreters are not supported: PyO3 keeps module and type objects in
+per-process statics and refuses to initialise a module in a second
+interpreter (PyO3/pyo3#576). Importing from a subinterpreter must fail with a
+clean ImportError, without crashing or corrupting the main interpreter.
+
+Run with ``python -m unittest tests.test_subinterpreters``.
+"""
+
+import threading
+import unittest
+
+import toon_parser
+
+try:  # Python 3.13+
+    import _interpreters as interpreters
+except ImportError:  # pragma: no cover - depends on the Python version
+    try:  # Python 3.12
+        import _xxsubinterpreters as interpreters
+    except ImportError:
+        interpreters = None
+
+USE_MODULE = """
+import toon_parser
+assert toon_parser.decode(toon_parser.encode({"rows": [{"id": 1}, {"id": 2}]})) == {"rows": [{"id": 1}, {"id": 2}]}
+"""
+
+
+def run_in_subinterpreter(code):
+    """Run `code` in a fresh subinterpreter; return the error text or None."""
+    interp = interpreters.create()
+    try:
+        if hasattr(interpreters, "exec"):
+            err = interpreters.exec(interp, code)
+            if err is None:
+                return None
+            return "{}: {}".format(err.type.__name__, err.msg)
+        try:
+            interpreters.run_string(interp, code)
+        except interpreters.RunFailedError as e:
+            return str(e)
+        return None
+    finally:
+        interpreters.destroy(interp)
+
+
+@unittest.skipIf(interpreters is None, "this Python has no subinterpreter API")
+class SubinterpreterTest(unittest.TestCase):
+    def run_concurrently(self, count=2):
+        errors = [None] * count
+
+        def worker(i):
+            errors[i] = run_in_subinterpreter(USE_MODULE)
+
+        threads = [threading.Thread(target=worker, args=(i,)) for i in range(count)]
+        for t in threads:
+            t.start()
+        for t in threads:
+            t.join()
+        return errors
+
+    def test_concurrent_import_fails_cleanly(self):
+        for err in self.run_concurrently():
+            if err is not None:
+                self.assertIn("ImportError", err)
+
+    def test_main_interpreter_unaffected(self):
+        self.run_concurrently()
+        data = {"users": [{"id": 1, "name": "Alice"}, {"id": 2, "name": "Bob"}]}
+        self.assertEqual(toon_parser.decode(toon_parser.encode(data)), data)
+
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,64 @@
+"""Did-you-mean ``suggestion`` attributes on ``ToonSyntaxError``.
+
+Run with ``python -m unittest tests.test_suggestions``.
+"""
+
+import io
+import unittest
+
+import toon_parser
+from toon_parser import Options, ToonSyntaxError
+
+SHORT = "xs[3]: 1,2"
+HINT = "set the length on line 1 to 2"
+
+
+class SuggestionTest(unittest.TestCase):
+    def assertSuggests(self, call, suggestion):
+        with self.assertRaises(ToonSyntaxError) as caught:
+            call()
+        self.assertEqual(caught.exception.suggestion, suggestion)
+        if suggestion is not None:
+            self.assertTrue(str(caught.exception).endswith(" (suggestion: {})".format(suggestion)))
+        return caught.exception
+
+    def test_mistakes(self):
+        cases = [
+            ("t[2]{a,b}:\n  1\t2\n  3\t4", False, "use delimiter='tab'"),
+            ("t[2|]{a|b}:\n  1,2\n  3,4", False, "use delimiter='comma'"),
+            ("a:\n\tb: 1", True, "indent line 2 with spaces instead of tabs"),
+            ("a:\n   b: 1", True, "indent line 2 with 2 spaces"),
+            (SHORT, False, HINT),
+            ("xs[2]: 1,2,3", False, "set the length on line 1 to 3"),
+            ("xs[2]:\n  - 1\n  - 2\n  - 3", False, "set the length on line 1 to 3"),
+            ("t[1]{a,b}:\n  1,2\n  3,4", False, "set the length on line 1 to 2"),
+        ]
+        for text, strict, suggestion in cases:
+            with self.subTest(text=text):
+                self.assertSuggests(lambda: toon_parser.decode(text, strict=strict), suggestion)
+
+    def test_message(self):
+        error = self.assertSuggests(lambda: toon_parser.decode(SHORT), HINT)
+        self.assertEqual(str(error), "Line 1: array declares 3 items but has 2 (suggestion: {})".format(HINT))
+
+    def test_none_when_nothing_fits(self):
+        error = self.assertSuggests(lambda: toon_parser.decode('a: "open'), None)
+        self.assertEqual(str(error), "Line 1: unterminated string")
+
+    def test_entry_points(self):
+        calls = [
+            ("decode_with_meta", lambda: toon_parser.decode_with_meta(SHORT)),
+            ("decode_lazy", lambda: toon_parser.decode_lazy(SHORT)),
+            ("decode_with_options", lambda: toon_parser.decode_with_options(SHORT, Options())),
+            ("decode_bytes", lambda: toon_parser.decode_bytes(SHORT.encode())),
+            ("loads", lambda: toon_parser.loads(SHORT)),
+            ("load", lambda: toon_parser.load(io.StringIO(SHORT))),
+        ]
+        for name, call in calls:
+            with self.subTest(name):
+                self.assertSuggests(call, HINT)
+        self.assertSuggests(lambda: toon_parser.decode_columns("t[2]{a,b}:\n  1\t2\n  3\t4", "t"), "use delimiter='tab'")
+
+
+if __name__ == "__main__":
+    unittest.main()
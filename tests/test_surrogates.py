@@ -0,0 +1,74 @@
+"""Lone surrogate escapes such as ``\\ud800`` and the ``surrogates`` policy.
+
+Run with ``python -m unittest tests.test_surrogates``.
+"""
+
+import unittest
+
+import toon_parser
+from toon_parser import Options, ToonSyntaxError
+
+# A lone high surrogate, a proper pair, an escaped backslash and a lone low
+# surrogate in a key
+DOC = 'a: "x\\ud800y"\nb: "\\ud83d\\ude00"\nc: "\\\\ud800"\n"k\\udc00": 1\n'
+
+
+class PolicyTest(unittest.TestCase):
+    def test_error_names_the_line(self):
+        with self.assertRaisesRegex(ToonSyntaxError, r"^Line 1: unpaired surrogate escape \\uD800$"):
+            toon_parser.decode(DOC)
+        with self.assertRaisesRegex(ToonSyntaxError, r"^Line 2: unpaired surrogate escape \\uDFFF$"):
+            toon_parser.decode('a: 1\nb: "\\udfff"\n', surrogates="error")
+
+    def test_replace(self):
+        self.assertEqual(
+            toon_parser.decode(DOC, surrogates="replace"),
+            {"a": "x\ufffdy", "b": "\U0001f600", "c": "\\ud800", "k\ufffd": 1},
+        )
+
+    def test_surrogatepass(self):
+        value = toon_parser.decode(DOC, surrogates="surrogatepass")
+        self.assertEqual(value, {"a": "x\ud800y", "b": "\U0001f600", "c": "\\ud800", "k\udc00": 1})
+        self.assertEqual(value["a"].encode("utf-16-le", "surrogatepass"), b"x\x00\x00\xd8y\x00")
+
+    def test_surrogatepass_with_other_containers(self):
+        doc = 'xs[2]: "\\ud800",b\n'
+        self.assertEqual(toon_parser.decode(doc, surrogates="surrogatepass", arrays="tuple"), {"xs": ("\ud800", "b")})
+        frozen = toon_parser.decode(doc, surrogates="surrogatepass", frozen=True)
+        self.assertEqual(frozen["xs"], ("\ud800", "b"))
+
+    def test_unquoted_text_is_left_alone(self):
+        self.assertEqual(toon_parser.decode("a: \\ud800\n"), {"a": "\\ud800"})
+
+    def test_invalid_policy(self):
+        with self.assertRaisesRegex(ValueError, "Invalid surrogates 'ignore'"):
+            toon_parser.decode("a: 1\n", surrogates="ignore")
+
+
+class OptionsTest(unittest.TestCase):
+    def test_every_options_entry_point(self):
+        opts = Options(surrogates="surrogatepass")
+        expected = {"a": "x\ud800y", "b": "\U0001f600", "c": "\\ud800", "k\udc00": 1}
+        self.assertEqual(toon_parser.decode(DOC, options=opts), expected)
+        self.assertEqual(toon_parser.loads(DOC, options=opts), expected)
+        self.assertEqual(toon_parser.decode_with_options(DOC, opts), expected)
+        self.assertEqual(toon_parser.decode_bytes(DOC.encode(), options=opts), expected)
+
+    def test_argument_overrides_options(self):
+        value = toon_parser.decode(DOC, surrogates="replace", options=Options(surrogates="surrogatepass"))
+        self.assertEqual(value["a"], "x\ufffdy")
+        with self.assertRaises(ToonSyntaxError):
+            toon_parser.decode(DOC, surrogates="error", options=Options(surrogates="replace"))
+
+    def test_option(self):
+        self.assertEqual(Options().surrogates, "error")
+        opts = Options().with_surrogates("replace")
+        self.assertEqual(opts.surrogates, "replace")
+        self.assertEqual(opts, Options(surrogates="replace"))
+        self.assertIn("surrogates='replace'", repr(opts))
+        with self.assertRaisesRegex(ValueError, "Invalid surrogates 'ignore'"):
+            Options(surrogates="ignore")
+
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,109 @@
+"""Streaming a table of a TOON file in column chunks with ``iter_table_chunks``.
+
+Run with ``python -m unittest tests.test_table_chunks``.
+"""
+
+import os
+import tempfile
+import unittest
+
+import toon_parser
+from toon_parser import Options, ToonIOError, ToonSyntaxError
+
+try:
+    import pyarrow
+except ImportError:
+    pyarrow = None
+
+EXPORT = b'meta: 1\nrows[5]{id,name}:\n  1,a\n  2,"b, c"\n  3,d\n  4,e\n  5,f\nafter: 2\n'
+NESTED = b"a:\n  b:\n    rows[1]{x}:\n      1\nrows[2]{y}:\n  7\n  8\n"
+
+
+class TableChunksTest(unittest.TestCase):
+    def setUp(self):
+        self.dir = tempfile.TemporaryDirectory()
+        self.path = os.path.join(self.dir.name, "export.toon")
+
+    def tearDown(self):
+        self.dir.cleanup()
+
+    def chunks(self, content, **kwargs):
+        with open(self.path, "wb") as f:
+            f.write(content)
+        return list(toon_parser.iter_table_chunks(self.path, **kwargs))
+
+    def test_chunks_of_columns(self):
+        self.assertEqual(
+            self.chunks(EXPORT, chunk_rows=2),
+            [{"id": [1, 2], "name": ["a", "b, c"]}, {"id": [3, 4], "name": ["d", "e"]}, {"id": [5], "name": ["f"]}],
+        )
+        self.assertEqual(self.chunks(EXPORT), [{"id": [1, 2, 3, 4, 5], "name": ["a", "b, c", "d", "e", "f"]}])
+
+    def test_table_path(self):
+        self.assertEqual(self.chunks(NESTED), [{"x": [1]}])
+        self.assertEqual(self.chunks(NESTED, path="a.b.rows"), [{"x": [1]}])
+        self.assertEqual(self.chunks(NESTED, path="rows"), [{"y": [7, 8]}])
+        self.assertEqual(self.chunks(b'"my rows"[1]{a}:\n  1\n', path="my rows"), [{"a": [1]}])
+
+    def test_header_layouts(self):
+        self.assertEqual(self.chunks(b'r[2|]{a|"b c"}:\r\n  1|x\r\n\r\n  2|\r\n'), [{"a": [1, 2], "b c": ["x", ""]}])
+        self.assertEqual(self.chunks(b"r[2]{a,b}:\n  1,null\n  2.5,true\n"), [{"a": [1, 2.5], "b": [None, True]}])
+        self.assertEqual(self.chunks(b"r[0]{a}:\n"), [])
+
+    def test_iterator(self):
+        with open(self.path, "wb") as f:
+            f.write(EXPORT)
+        chunks = toon_parser.iter_table_chunks(self.path, chunk_rows=3)
+        self.assertIs(iter(chunks), chunks)
+        self.assertEqual(next(chunks)["id"], [1, 2, 3])
+        self.assertEqual(next(chunks)["id"], [4, 5])
+        self.assertIsNone(next(chunks, None))
+        self.assertIsNone(next(chunks, None))
+
+    def test_malformed_rows(self):
+        with self.assertRaisesRegex(ToonSyntaxError, "Line 3: row has 1 values but the table declares 2 fields"):
+            self.chunks(b"r[3]{a,b}:\n  1,x\n  2\n  3,y\n", chunk_rows=1)
+
+    def test_row_count_checked_when_the_table_ends(self):
+        for content, message in (
+            (b"r[3]{a,b}:\n  1,x\n  2,y\n", "Line 1: table declares 3 rows but has 2"),
+            (b"r[1]{a,b}:\n  1,x\n  2,y\n", "Line 1: table declares 1 rows but has 2"),
+        ):
+            with self.subTest(content=content):
+                with self.assertRaisesRegex(ToonSyntaxError, message):
+                    self.chunks(content)
+
+    def test_invalid_arguments(self):
+        cases = [
+            (b"a: 1\n", {}, "no table in"),
+            (NESTED, {"path": "a.rows"}, "no table 'a.rows' in"),
+            (EXPORT, {"chunk_rows": 0}, "chunk_rows must be at least 1"),
+            (EXPORT, {"format": "csv"}, "Invalid format 'csv'. Must be 'dict' or 'arrow'"),
+        ]
+        for content, kwargs, message in cases:
+            with self.subTest(kwargs=kwargs):
+                with self.assertRaisesRegex(ValueError, message):
+                    self.chunks(content, **kwargs)
+
+    def test_missing_file(self):
+        with self.assertRaises(ToonIOError):
+            toon_parser.iter_table_chunks(os.path.join(self.dir.name, "missing.toon"))
+
+    def test_options(self):
+        self.assertEqual(self.chunks(EXPORT, chunk_rows=5, options=Options(strict=True))[0]["id"], [1, 2, 3, 4, 5])
+
+    @unittest.skipIf(pyarrow is None, "needs pyarrow")
+    def test_arrow(self):
+        batches = self.chunks(EXPORT, chunk_rows=2, format="arrow")
+        self.assertTrue(all(isinstance(batch, pyarrow.RecordBatch) for batch in batches))
+        self.assertEqual([batch.num_rows for batch in batches], [2, 2, 1])
+        self.assertEqual(batches[0].to_pydict(), {"id": [1, 2], "name": ["a", "b, c"]})
+
+    @unittest.skipIf(pyarrow is not None, "pyarrow is installed")
+    def test_arrow_needs_pyarrow(self):
+        with self.assertRaises(ImportError):
+            self.chunks(EXPORT, format="arrow")
+
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,47 @@
+"""Table rows built from a shared key template in ``decode``.
+
+Run with ``python -m unittest tests.test_table_rows``.
+"""
+
+import unittest
+
+import toon_parser
+
+TABLE = "rows[3]{id,name}:\n  1,Ann\n  2,Bo\n  3,Cy"
+
+
+class TableRowsTest(unittest.TestCase):
+    def test_rows(self):
+        self.assertEqual(
+            toon_parser.decode(TABLE),
+            {"rows": [{"id": 1, "name": "Ann"}, {"id": 2, "name": "Bo"}, {"id": 3, "name": "Cy"}]},
+        )
+
+    def test_rows_are_independent(self):
+        rows = toon_parser.decode(TABLE)["rows"]
+        rows[0]["extra"] = True
+        rows[1]["id"] = 9
+        self.assertEqual(rows, [{"id": 1, "name": "Ann", "extra": True}, {"id": 9, "name": "Bo"}, {"id": 3, "name": "Cy"}])
+
+    def test_each_row_keeps_its_key_order(self):
+        rows = toon_parser.decode("xs[2]:\n  - a: 1\n    b: 2\n  - b: 3\n    a: 4")["xs"]
+        self.assertEqual([list(row) for row in rows], [["a", "b"], ["b", "a"]])
+
+    def test_items_that_do_not_share_keys(self):
+        cases = [
+            ("xs[3]:\n  - a: 1\n  - b: 2\n  - 3", [{"a": 1}, {"b": 2}, 3]),
+            ("xs[2]:\n  - a: 1\n  - a: 2\n    b: 3", [{"a": 1}, {"a": 2, "b": 3}]),
+            ("xs[2]:\n  - 1\n  - a: 2", [1, {"a": 2}]),
+            ("xs[2]:\n  -\n  -", [{}, {}]),
+        ]
+        for text, items in cases:
+            with self.subTest(text=text):
+                self.assertEqual(toon_parser.decode(text), {"xs": items})
+
+    def test_nested_values(self):
+        text = "xs[2]:\n  - a: 1\n    n:\n      m[1]: 1\n  - a: 2\n    n:\n      m: x"
+        self.assertEqual(toon_parser.decode(text), {"xs": [{"a": 1, "n": {"m": [1]}}, {"a": 2, "n": {"m": "x"}}]})
+
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,88 @@
+"""Prompt templates: ``Placeholder`` values and ``render``.
+
+Run with ``python -m unittest tests.test_template``.
+"""
+
+import unittest
+
+import toon_parser
+from toon_parser import Options, Placeholder
+
+TEMPLATE = toon_parser.encode(
+    {
+        "rows": [{"id": Placeholder("a"), "n": Placeholder("b")}, {"id": 2, "n": "x"}],
+        "xs": [Placeholder("a"), 1],
+        "s": "{{a}}",
+        "q": Placeholder("q"),
+    }
+)
+
+
+class PlaceholderTest(unittest.TestCase):
+    def test_encodes_as_a_token(self):
+        self.assertEqual(TEMPLATE, 'rows[2]{id,n}:\n  {{a}},{{b}}\n  2,x\nxs[2]: {{a}},1\ns: "{{a}}"\nq: {{q}}')
+
+    def test_template_aware_encoders(self):
+        data = {"a": Placeholder("x")}
+        self.assertEqual(toon_parser.dumps(data), "a: {{x}}")
+        self.assertEqual(toon_parser.encode_with_options(data, Options(indent=4)), "a: {{x}}")
+        self.assertEqual(toon_parser.encode_bytes(data), b"a: {{x}}")
+
+    def test_other_encoders_reject_it(self):
+        with self.assertRaisesRegex(TypeError, "^Placeholder values can only be encoded by encode, encode_bytes and encode_with_options$"):
+            toon_parser.encode_batch([{"a": Placeholder("x")}])
+
+    def test_names(self):
+        self.assertEqual(Placeholder("user.name-2").name, "user.name-2")
+        self.assertEqual(repr(Placeholder("x")), "Placeholder('x')")
+        self.assertEqual(Placeholder("x"), Placeholder("x"))
+        for name in ("bad name", "", "1x"):
+            with self.subTest(name=name):
+                with self.assertRaisesRegex(ValueError, "^Invalid placeholder name"):
+                    Placeholder(name)
+
+
+class RenderTest(unittest.TestCase):
+    def test_values_are_quoted_as_the_encoder_would(self):
+        text = toon_parser.render(TEMPLATE, {"a": 5, "b": "x:y", "q": "line\nbreak"})
+        self.assertEqual(text, 'rows[2]{id,n}:\n  5,"x:y"\n  2,x\nxs[2]: 5,1\ns: "{{a}}"\nq: "line\\nbreak"')
+        self.assertEqual(
+            toon_parser.decode(text),
+            {"rows": [{"id": 5, "n": "x:y"}, {"id": 2, "n": "x"}], "xs": [5, 1], "s": "{{a}}", "q": "line\nbreak"},
+        )
+
+    def test_scalars(self):
+        cases = [
+            ({"a": None, "b": True, "q": 1.5}, [None, True, 1.5]),
+            ({"a": "true", "b": "12", "q": ""}, ["true", "12", ""]),
+        ]
+        for mapping, (a, b, q) in cases:
+            with self.subTest(mapping=mapping):
+                value = toon_parser.decode(toon_parser.render(TEMPLATE, mapping))
+                self.assertEqual((value["rows"][0]["id"], value["rows"][0]["n"], value["q"], value["xs"][0]), (a, b, q, a))
+
+    def test_values_containing_any_delimiter(self):
+        value = "x|y,z\tw"
+        for delimiter in ("comma", "pipe", "tab"):
+            with self.subTest(delimiter):
+                template = toon_parser.encode({"xs": [Placeholder("v"), 1], "r": [{"k": Placeholder("v")}, {"k": 2}]}, delimiter=delimiter)
+                self.assertEqual(
+                    toon_parser.decode(toon_parser.render(template, {"v": value})),
+                    {"xs": [value, 1], "r": [{"k": value}, {"k": 2}]},
+                )
+
+    def test_quoted_tokens_and_plain_text_are_left_alone(self):
+        self.assertEqual(toon_parser.render('a: "{{x}}"', {}), 'a: "{{x}}"')
+        self.assertEqual(toon_parser.render("a: 1", {}), "a: 1")
+
+    def test_errors(self):
+        with self.assertRaisesRegex(KeyError, "'q'"):
+            toon_parser.render(TEMPLATE, {"a": 1, "b": 2})
+        for value in ([1], {"x": 1}):
+            with self.subTest(value=value):
+                with self.assertRaisesRegex(ValueError, "^value for placeholder 'a' must be a str, number, bool or None$"):
+                    toon_parser.render(TEMPLATE, {"a": value, "b": 1, "q": 1})
+
+
+if __name__ == "__main__":
+    unittest.main()
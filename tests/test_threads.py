@@ -0,0 +1,64 @@
+"""Sizing the thread pool of the parallel batch APIs.
+
+Run with ``python -m unittest tests.test_threads``.
+"""
+
+import os
+import subprocess
+import sys
+import unittest
+
+import toon_parser
+
+
+class NumThreadsTest(unittest.TestCase):
+    def setUp(self):
+        self.addCleanup(toon_parser.set_num_threads, toon_parser.get_num_threads())
+
+    def test_set_and_get(self):
+        for n in (1, 3, 2):
+            with self.subTest(n=n):
+                toon_parser.set_num_threads(n)
+                self.assertEqual(toon_parser.get_num_threads(), n)
+
+    def test_zero_means_one_per_cpu(self):
+        toon_parser.set_num_threads(0)
+        self.assertGreaterEqual(toon_parser.get_num_threads(), 1)
+
+    def test_negative_count(self):
+        self.assertRaises(OverflowError, toon_parser.set_num_threads, -1)
+
+    def test_batches_use_the_resized_pool(self):
+        docs = ["id: {}\n".format(i) for i in range(100)]
+        for n in (1, 4):
+            with self.subTest(n=n):
+                toon_parser.set_num_threads(n)
+                self.assertEqual(toon_parser.decode_batch(docs), [{"id": i} for i in range(100)])
+                self.assertEqual(toon_parser.encode_batch([{"id": 1}] * 3), ["id: 1"] * 3)
+
+
+class EnvironmentTest(unittest.TestCase):
+    def num_threads(self, value):
+        env = dict(os.environ, PYTHONPATH=os.pathsep.join(sys.path))
+        if value is None:
+            env.pop("TOON_NUM_THREADS", None)
+        else:
+            env["TOON_NUM_THREADS"] = value
+        code = "import toon_parser; print(toon_parser.get_num_threads())"
+        out = subprocess.run([sys.executable, "-c", code], env=env, capture_output=True, text=True, check=True)
+        return int(out.stdout)
+
+    def test_variable_sizes_the_pool(self):
+        self.assertEqual(self.num_threads("3"), 3)
+        self.assertEqual(self.num_threads(" 2 "), 2)
+
+    def test_unset_or_invalid_means_one_per_cpu(self):
+        default = self.num_threads(None)
+        self.assertGreaterEqual(default, 1)
+        for value in ("0", "", "many", "-2"):
+            with self.subTest(value=value):
+                self.assertEqual(self.num_threads(value), default)
+
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,100 @@
+"""LLM token accounting: ``count_tokens``, ``savings_report`` and ``field_stats``.
+
+Run with ``python -m unittest tests.test_tokens``.
+"""
+
+import json
+import unittest
+
+import toon_parser
+
+
+class CountTokensTest(unittest.TestCase):
+    def test_builtin_approximation(self):
+        self.assertEqual(toon_parser.count_tokens(""), 0)
+        self.assertEqual(toon_parser.count_tokens("hello world"), 2)
+        self.assertEqual(toon_parser.count_tokens(" ".join(["word"] * 100)), 100)
+        self.assertEqual(toon_parser.count_tokens("x" * 1000), 250)
+
+    def test_grows_with_the_text(self):
+        short = toon_parser.count_tokens("users[1]{id,name}:\n  1,Alice\n")
+        long = toon_parser.count_tokens("users[2]{id,name}:\n  1,Alice\n  2,Bob\n")
+        self.assertGreater(long, short)
+
+    def test_data_is_encoded_first(self):
+        self.assertEqual(toon_parser.count_tokens({"a": 1}), toon_parser.count_tokens("a: 1"))
+
+    def test_tokenizer_returning_tokens(self):
+        self.assertEqual(toon_parser.count_tokens("abc def ghi", tokenizer=str.split), 3)
+
+    def test_tokenizer_returning_a_count(self):
+        seen = []
+        self.assertEqual(toon_parser.count_tokens({"a": 1}, tokenizer=lambda s: seen.append(s) or 42), 42)
+        self.assertEqual(seen, ["a: 1"])
+
+
+USERS = {"users": [{"id": i, "name": "usér%d" % i} for i in range(20)]}
+
+
+def utf8_len(text):
+    return len(text.encode("utf-8"))
+
+
+class SavingsReportTest(unittest.TestCase):
+    def test_byte_sizes(self):
+        report = toon_parser.savings_report(USERS)
+        self.assertEqual(report["json_compact"]["bytes"], utf8_len(json.dumps(USERS, separators=(",", ":"), ensure_ascii=False)))
+        self.assertEqual(report["json_pretty"]["bytes"], utf8_len(json.dumps(USERS, indent=2, ensure_ascii=False)))
+        self.assertEqual(report["toon"]["bytes"], utf8_len(toon_parser.encode(USERS)))
+
+    def test_savings_are_fractions_of_the_baseline(self):
+        report = toon_parser.savings_report(USERS)
+        for baseline in ("json_compact", "json_pretty"):
+            saving = report["savings_vs_" + baseline.split("_")[1]]
+            for unit in ("bytes", "tokens"):
+                with self.subTest(baseline=baseline, unit=unit):
+                    expected = 1 - report["toon"][unit] / report[baseline][unit]
+                    self.assertAlmostEqual(saving[unit], expected)
+                    self.assertGreater(saving[unit], 0)
+
+    def test_tokens_use_the_tokenizer(self):
+        report = toon_parser.savings_report(USERS, tokenizer=utf8_len)
+        for entry in ("json_compact", "json_pretty", "toon"):
+            self.assertEqual(report[entry]["tokens"], report[entry]["bytes"])
+
+    def test_options_shape_the_toon_side(self):
+        report = toon_parser.savings_report(USERS, options=toon_parser.Options(delimiter="pipe"))
+        self.assertEqual(report["toon"]["bytes"], utf8_len(toon_parser.encode(USERS, delimiter="pipe")))
+
+    def test_toon_larger_than_json_is_negative(self):
+        report = toon_parser.savings_report({"a": [[1], [2]]})
+        self.assertLess(report["savings_vs_compact"]["bytes"], 0)
+
+
+class FieldStatsTest(unittest.TestCase):
+    DATA = {"users": [{"id": i, "bio": "x" * 50} for i in range(3)], "name": "n"}
+
+    def test_largest_first(self):
+        stats = toon_parser.field_stats(self.DATA)
+        self.assertEqual(
+            [entry["path"] for entry in stats],
+            ["$", "$.users", "$.users[]", "$.users[].bio", "$.name", "$.users[].id"],
+        )
+        sizes = [entry["bytes"] for entry in stats]
+        self.assertEqual(sizes, sorted(sizes, reverse=True))
+
+    def test_counts_and_roll_up(self):
+        stats = {entry["path"]: entry for entry in toon_parser.field_stats(self.DATA)}
+        self.assertEqual(stats["$.users[].bio"]["count"], 3)
+        self.assertGreaterEqual(stats["$.users[].bio"]["bytes"], 150)
+        self.assertGreaterEqual(stats["$.users"]["bytes"], stats["$.users[].bio"]["bytes"] + stats["$.users[].id"]["bytes"])
+        self.assertLessEqual(stats["$"]["bytes"], utf8_len(toon_parser.encode(self.DATA)))
+        for entry in stats.values():
+            self.assertGreater(entry["tokens"], 0)
+
+    def test_accepts_toon_text(self):
+        self.assertEqual(toon_parser.field_stats(toon_parser.encode(self.DATA)), toon_parser.field_stats(self.DATA))
+
+
+if __name__ == "__main__":
+    unittest.main()
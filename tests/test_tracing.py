@@ -0,0 +1,93 @@
+"""Timing spans forwarded to Python by ``enable_tracing``.
+
+The spans only exist in builds with the ``tracing`` cargo feature; other
+builds must refuse to enable tracing. A process can enable it once, so the
+feature tests share one callback.
+
+Run with ``python -m unittest tests.test_tracing``.
+"""
+
+import sys
+import unittest
+
+import toon_parser
+
+TRACING = "tracing" in toon_parser.version_info()["features"]
+
+
+@unittest.skipIf(TRACING, "built with the tracing feature")
+class WithoutTracingTest(unittest.TestCase):
+    def test_enable_is_refused(self):
+        with self.assertRaisesRegex(RuntimeError, "without the 'tracing' feature"):
+            toon_parser.enable_tracing(print)
+
+
+@unittest.skipUnless(TRACING, "built without the tracing feature")
+class TracingTest(unittest.TestCase):
+    spans = []
+    failing = False
+
+    @classmethod
+    def setUpClass(cls):
+        def callback(name, seconds, fields):
+            if cls.failing:
+                raise ValueError("callback failed")
+            cls.spans.append((name, seconds, fields))
+
+        toon_parser.enable_tracing(callback)
+
+    def setUp(self):
+        del self.spans[:]
+
+    def names_and_fields(self):
+        for _, seconds, _ in self.spans:
+            self.assertGreaterEqual(seconds, 0.0)
+        return [(name, fields) for name, _, fields in self.spans]
+
+    def test_decode(self):
+        toon_parser.decode("a: 1\n")
+        self.assertEqual(
+            self.names_and_fields(),
+            [("parse", {"bytes": 5}), ("convert", {"direction": "json_to_python"})],
+        )
+
+    def test_encode(self):
+        toon_parser.encode({"a": 1})
+        self.assertEqual(
+            self.names_and_fields(),
+            [("convert", {"direction": "python_to_json"}), ("encode", {"bytes": 4})],
+        )
+
+    def test_string_conversions(self):
+        toon_parser.json_to_toon('{"a":1}')
+        self.assertEqual(
+            self.names_and_fields(),
+            [("convert", {"direction": "json_to_value", "bytes": 7}), ("encode", {"bytes": 4})],
+        )
+        del self.spans[:]
+        toon_parser.toon_to_json("a: 1")
+        self.assertEqual(
+            self.names_and_fields(),
+            [("parse", {"bytes": 4}), ("convert", {"direction": "value_to_json"})],
+        )
+
+    def test_enable_only_once(self):
+        with self.assertRaisesRegex(RuntimeError, "already enabled"):
+            toon_parser.enable_tracing(print)
+
+    def test_callback_errors_do_not_fail_the_call(self):
+        raised = []
+        hook = sys.unraisablehook
+        sys.unraisablehook = raised.append
+        type(self).failing = True
+        try:
+            self.assertEqual(toon_parser.decode("a: 1\n"), {"a": 1})
+        finally:
+            type(self).failing = False
+            sys.unraisablehook = hook
+        self.assertEqual(len(raised), 2)
+        self.assertIsInstance(raised[0].exc_value, ValueError)
+
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,59 @@
+"""Fitting tables into a row or character budget (``truncate``).
+
+Run with ``python -m unittest tests.test_truncate``.
+"""
+
+import unittest
+
+import toon_parser
+
+DATA = {"rows": [{"id": i, "g": "a" if i < 8 else "b"} for i in range(10)], "k": 1}
+
+
+def ids(text):
+    return [row["id"] for row in toon_parser.decode(text)["rows"]]
+
+
+class TruncateTest(unittest.TestCase):
+    def test_head_with_annotation(self):
+        text = toon_parser.truncate(DATA, max_rows=3)
+        self.assertTrue(text.startswith("rows[3]{id,g}:\n  0,a\n  1,a\n  2,a\nk: 1\n"))
+        self.assertIn("# $.rows: kept 3 of 10 rows", text)
+        self.assertEqual(ids(text), [0, 1, 2])
+
+    def test_without_annotation(self):
+        text = toon_parser.truncate(DATA, max_rows=3, annotate=False)
+        self.assertNotIn("#", text)
+        self.assertEqual(toon_parser.decode(text)["k"], 1)
+
+    def test_accepts_toon_text(self):
+        self.assertEqual(toon_parser.truncate(toon_parser.encode(DATA), max_rows=2), toon_parser.truncate(DATA, max_rows=2))
+
+    def test_max_chars_fits_the_largest_cap(self):
+        data = {"rows": [{"id": i} for i in range(100)], "xs": list(range(50))}
+        text = toon_parser.truncate(data, max_chars=200)
+        self.assertLessEqual(len(text), 200)
+        kept = len(toon_parser.decode(text)["rows"])
+        self.assertGreater(len(toon_parser.truncate(data, max_rows=kept + 1)), 200)
+        self.assertEqual(len(toon_parser.decode(text)["xs"]), kept)
+
+    def test_random_is_seeded_and_ordered(self):
+        first = ids(toon_parser.truncate(DATA, max_rows=4, strategy="random", seed=1, annotate=False))
+        self.assertEqual(first, ids(toon_parser.truncate(DATA, max_rows=4, strategy="random", seed=1, annotate=False)))
+        self.assertEqual(first, sorted(first))
+        self.assertEqual(len(first), 4)
+
+    def test_stratified_spreads_rows(self):
+        self.assertEqual(ids(toon_parser.truncate(DATA, max_rows=4, strategy="stratified", annotate=False)), [0, 2, 5, 7])
+
+    def test_stratified_by_column_is_proportional(self):
+        text = toon_parser.truncate(DATA, max_rows=5, strategy="stratified", stratify_by="g", annotate=False)
+        self.assertEqual(ids(text), [0, 1, 2, 3, 8])
+
+    def test_unknown_strategy(self):
+        with self.assertRaisesRegex(ValueError, "Invalid strategy 'x'"):
+            toon_parser.truncate(DATA, strategy="x")
+
+
+if __name__ == "__main__":
+    unittest.main()
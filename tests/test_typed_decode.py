@@ -0,0 +1,203 @@
+"""Typed decode into dataclasses, TypedDicts and generic hints (``decode_as``).
+
+Run with ``python -m unittest tests.test_typed_decode``.
+"""
+
+import unittest
+from dataclasses import dataclass, field
+from typing import Literal, NotRequired, Optional, Required, TypedDict, Union
+
+import toon_parser
+from toon_parser import Schema, ToonSchemaError
+
+
+@dataclass
+class User:
+    id: int
+    name: str
+    tags: list = field(default_factory=list)
+
+
+class DecodeAsTest(unittest.TestCase):
+    def test_table_into_dataclasses(self):
+        doc = "users[2]{id,name}:\n  1,Ann\n  2,Bo\n"
+        self.assertEqual(
+            toon_parser.decode_as(doc, dict[str, list[User]]),
+            {"users": [User(1, "Ann"), User(2, "Bo")]},
+        )
+
+    def test_root_table(self):
+        self.assertEqual(toon_parser.decode_as("[1]{id,name}:\n  1,Ann\n", list[User]), [User(1, "Ann")])
+
+    def test_nested_dataclass_fields(self):
+        @dataclass
+        class Team:
+            lead: User
+            size: int
+
+        doc = "lead:\n  id: 1\n  name: Ann\n  tags[2]: a,b\nsize: 3\n"
+        self.assertEqual(toon_parser.decode_as(doc, Team), Team(User(1, "Ann", ["a", "b"]), 3))
+
+    def test_unknown_keys_are_ignored(self):
+        self.assertEqual(toon_parser.decode_as("id: 1\nname: Ann\nextra: x\n", User), User(1, "Ann"))
+
+    def test_wrong_type_names_field_and_line(self):
+        with self.assertRaises(ToonSchemaError) as ctx:
+            toon_parser.decode_as("[2]{id,name}:\n  1,Ann\n  x,Bo\n", list[User])
+        self.assertIn("$[1].id", str(ctx.exception))
+        self.assertIn("expected int", str(ctx.exception))
+        self.assertEqual(ctx.exception.instance_path, "/1/id")
+        self.assertEqual(ctx.exception.line, 3)
+
+    def test_missing_field(self):
+        with self.assertRaisesRegex(ToonSchemaError, "missing required key 'name'"):
+            toon_parser.decode_as("id: 1\n", User)
+
+    def test_builtin_hints(self):
+        self.assertEqual(toon_parser.decode_as("a: 1\nb: 2\n", dict[str, int]), {"a": 1, "b": 2})
+        self.assertEqual(toon_parser.decode_as("[2]: 1.5,2\n", list[float]), [1.5, 2.0])
+        with self.assertRaises(ToonSchemaError):
+            toon_parser.decode_as("a: x\n", dict[str, int])
+
+    def test_unsupported_type(self):
+        with self.assertRaises(TypeError):
+            toon_parser.decode_as("a: 1\n", set)
+
+
+class Point(TypedDict):
+    x: int
+    label: NotRequired[str]
+
+
+class Sparse(TypedDict, total=False):
+    x: int
+    label: Required[str]
+
+
+class TypedDictTest(unittest.TestCase):
+    def test_produces_plain_dicts(self):
+        value = toon_parser.decode_as("[2]{x,label}:\n  1,a\n  2,b\n", list[Point])
+        self.assertEqual(value, [{"x": 1, "label": "a"}, {"x": 2, "label": "b"}])
+        self.assertIs(type(value[0]), dict)
+
+    def test_not_required_key_may_be_absent(self):
+        self.assertEqual(toon_parser.decode_as("x: 1\n", Point), {"x": 1})
+
+    def test_required_key_must_be_present(self):
+        with self.assertRaisesRegex(ToonSchemaError, "missing required key 'x'"):
+            toon_parser.decode_as("label: a\n", Point)
+
+    def test_total_false_with_required(self):
+        self.assertEqual(toon_parser.decode_as("label: a\n", Sparse), {"label": "a"})
+        with self.assertRaisesRegex(ToonSchemaError, "missing required key 'label'"):
+            toon_parser.decode_as("x: 1\n", Sparse)
+
+    def test_values_are_checked(self):
+        with self.assertRaisesRegex(ToonSchemaError, r"\$\.x: expected int"):
+            toon_parser.decode_as("x: one\n", Point)
+
+
+class UnionTest(unittest.TestCase):
+    def test_optional_accepts_null(self):
+        self.assertEqual(toon_parser.decode_as("a: null\nb: 1\n", dict[str, Optional[int]]), {"a": None, "b": 1})
+        self.assertEqual(toon_parser.decode_as("a: null\n", dict[str, int | None]), {"a": None})
+
+    def test_union_tries_variants_in_order(self):
+        self.assertEqual(toon_parser.decode_as("a: 1\nb: x\n", dict[str, Union[int, str]]), {"a": 1, "b": "x"})
+
+    def test_union_error_lists_each_variant(self):
+        with self.assertRaises(ToonSchemaError) as ctx:
+            toon_parser.decode_as("a: 1.5\n", dict[str, Union[int, str]])
+        message = str(ctx.exception)
+        self.assertIn("no variant of Union[int, str] matched", message)
+        self.assertIn("int: expected int", message)
+        self.assertIn("str: expected str", message)
+
+    def test_literal(self):
+        colors = dict[str, Literal["red", "blue"]]
+        self.assertEqual(toon_parser.decode_as("a: red\n", colors), {"a": "red"})
+        with self.assertRaisesRegex(ToonSchemaError, 'expected Literal\\["red", "blue"\\], got "green"'):
+            toon_parser.decode_as("a: green\n", colors)
+
+    def test_optional_dataclass_field(self):
+        @dataclass
+        class Row:
+            id: int
+            note: Optional[str] = None
+
+        self.assertEqual(
+            toon_parser.decode_as("[2]{id,note}:\n  1,null\n  2,hi\n", list[Row]),
+            [Row(1, None), Row(2, "hi")],
+        )
+
+
+class SchemaTest(unittest.TestCase):
+    def test_decode_matches_decode_as(self):
+        users = Schema(list[User])
+        doc = "[2]{id,name}:\n  1,Ann\n  2,Bo\n"
+        self.assertEqual(users.decode(doc), toon_parser.decode_as(doc, list[User]))
+        self.assertEqual(toon_parser.decode_as(doc, users), [User(1, "Ann"), User(2, "Bo")])
+
+    def test_reuse_across_documents(self):
+        users = Schema(list[User])
+        for n in range(1, 4):
+            doc = "[1]{id,name}:\n  %d,U%d\n" % (n, n)
+            self.assertEqual(users.decode(doc), [User(n, "U%d" % n)])
+
+    def test_keeps_the_type_hint(self):
+        users = Schema(list[User])
+        self.assertEqual(users.tp, list[User])
+        self.assertIn("Schema(list[", repr(users))
+
+    def test_unsupported_type_fails_at_construction(self):
+        with self.assertRaises(TypeError):
+            Schema(set)
+
+    def test_errors_match_decode_as(self):
+        with self.assertRaisesRegex(ToonSchemaError, r"\$\[0\]\.id: expected int"):
+            Schema(list[User]).decode("[1]{id,name}:\n  x,Ann\n")
+
+
+@dataclass
+class Reading:
+    id: int
+    score: float
+    name: str
+    ok: bool
+
+
+class CoerceTest(unittest.TestCase):
+    def test_cells_convert_to_declared_types(self):
+        doc = '[2]{id,score,name,ok}:\n  "1","2.5",3,"true"\n  2,3,x,false\n'
+        self.assertEqual(
+            toon_parser.decode_as(doc, list[Reading], coerce=True),
+            [Reading(1, 2.5, "3", True), Reading(2, 3.0, "x", False)],
+        )
+
+    def test_without_coerce_strings_are_rejected(self):
+        with self.assertRaisesRegex(ToonSchemaError, r"\$\[0\]\.id: expected int, got str"):
+            toon_parser.decode_as('[1]{id,score,name,ok}:\n  "1",2,a,true\n', list[Reading])
+
+    def test_failures_are_reported_together_by_column(self):
+        doc = "[3]{id,score,name,ok}:\n  x,1,a,true\n  y,z,b,true\n  1,w,c,maybe\n"
+        with self.assertRaises(ToonSchemaError) as ctx:
+            toon_parser.decode_as(doc, list[Reading], coerce=True)
+        err = ctx.exception
+        self.assertIn("5 value(s) could not be coerced", str(err))
+        self.assertIn("column 'id' (int): 2 failure(s), first at line 2", str(err))
+        self.assertEqual(len(err.errors), 5)
+        self.assertEqual(
+            err.errors[0],
+            {"path": "$[0].id", "column": "id", "expected": "int", "value": "x", "line": 2},
+        )
+        self.assertEqual([e["column"] for e in err.errors], ["id", "id", "score", "score", "ok"])
+
+    def test_schema_decode_coerces(self):
+        self.assertEqual(
+            Schema(list[Reading]).decode('[1]{id,score,name,ok}:\n  "7",1,a,false\n', coerce=True),
+            [Reading(7, 1.0, "a", False)],
+        )
+
+
+if __name__ == "__main__":
+    unittest.main()
@@ -0,0 +1,80 @@
+"""Typed table headers such as ``users[2]{id:int,name:str}:``.
+
+Run with ``python -m unittest tests.test_typed_headers``.
+"""
+
+import unittest
+
+import toon_parser
+from toon_parser import ToonSyntaxError
+
+USERS = {
+    "users": [
+        {"id": 1, "name": "Ann", "score": 1.5, "ok": True, "note": None, "mixed": 1},
+        {"id": 2, "name": "7", "score": 2, "ok": False, "note": None, "mixed": "x"},
+    ]
+}
+
+
+class EncodeTest(unittest.TestCase):
+    def test_annotates_columns_of_one_type(self):
+        text = toon_parser.encode(USERS, typed_headers=True)
+        self.assertEqual(text.splitlines()[0], "users[2]{id:int,name:str,score:float,ok:bool,note,mixed}:")
+
+    def test_off_by_default(self):
+        self.assertEqual(toon_parser.encode(USERS).splitlines()[0], "users[2]{id,name,score,ok,note,mixed}:")
+
+    def test_round_trip(self):
+        value = toon_parser.decode(toon_parser.encode(USERS, typed_headers=True))
+        self.assertEqual(value, USERS)
+        # The int 2 in a float column comes back as a float
+        self.assertIsInstance(value["users"][1]["score"], float)
+
+    def test_other_delimiters_and_non_tables(self):
+        self.assertEqual(toon_parser.encode({"rows": [{"a": 1}, {"a": 2}]}, typed_headers=True, delimiter="pipe"), "rows[2|]{a:int}:\n  1\n  2")
+        self.assertEqual(toon_parser.encode({"xs": [1, 2], "o": {"a": 1}}, typed_headers=True), "xs[2]: 1,2\no:\n  a: 1")
+
+
+class DecodeTest(unittest.TestCase):
+    def test_cells_are_coerced(self):
+        text = 'rows[2]{id:int,name:str,score:float,ok:bool}:\n  "42",7,3,"true"\n  3.0,Bo,2.5,false\n'
+        self.assertEqual(
+            toon_parser.decode(text),
+            {"rows": [{"id": 42, "name": "7", "score": 3.0, "ok": True}, {"id": 3, "name": "Bo", "score": 2.5, "ok": False}]},
+        )
+
+    def test_null_and_unannotated_columns(self):
+        self.assertEqual(toon_parser.decode("rows[2]{a:int,b}:\n  null,2.0\n  1,x\n"), {"rows": [{"a": None, "b": 2.0}, {"a": 1, "b": "x"}]})
+
+    def test_strings_are_kept_as_written(self):
+        self.assertEqual(toon_parser.decode('rows[2]{p:str}:\n  C:\\dir\n  a"b\n'), {"rows": [{"p": "C:\\dir"}, {"p": 'a"b'}]})
+
+    def test_invalid_cells(self):
+        cases = [
+            ("rows[1]{id:int}:\n  abc\n", "cell abc in column id is not a valid int"),
+            ("rows[1]{id:int}:\n  2.5\n", "cell 2.5 in column id is not a valid int"),
+            ("rows[1]{x:float}:\n  abc\n", "cell abc in column x is not a valid float"),
+            ("rows[1]{ok:bool}:\n  yes\n", "cell yes in column ok is not a valid bool"),
+        ]
+        for text, message in cases:
+            with self.subTest(text=text):
+                with self.assertRaisesRegex(ToonSyntaxError, "^Line 2: " + message):
+                    toon_parser.decode(text)
+
+    def test_names_that_only_look_annotated(self):
+        self.assertEqual(toon_parser.decode('rows[1]{"a:int"}:\n  x\n'), {"rows": [{"a:int": "x"}]})
+        self.assertEqual(toon_parser.decode("rows[1]{a:date}:\n  x\n"), {"rows": [{"a:date": "x"}]})
+
+    def test_empty_table(self):
+        self.assertEqual(toon_parser.decode("rows[0]{a:int}:\n"), {"rows": []})
+
+    def test_other_entry_points(self):
+        text = "rows[2|]{a:int|b:str}:\n  1|2\n  3|x\n"
+        expected = {"rows": [{"a": 1, "b": "2"}, {"a": 3, "b": "x"}]}
+        self.assertEqual(toon_parser.loads(text), expected)
+        self.assertEqual(toon_parser.decode_bytes(text.encode()), expected)
+        self.assertEqual(toon_parser.decode_columns(text, "rows"), {"a": [1, 3], "b": ["2", "x"]})
+
+
+if __name__ == "__main__":
+    unittest.main()
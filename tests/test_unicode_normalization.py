@@ -0,0 +1,90 @@
+"""Unicode normalization of decoded strings with ``Options(normalize_unicode=...)``.
+
+Run with ``python -m unittest tests.test_unicode_normalization``.
+"""
+
+import io
+import unittest
+
+import toon_parser
+from toon_parser import Options
+
+COMPOSED = "caf\u00e9"
+DECOMPOSED = "cafe\u0301"
+WIDE_A = "\uff21"  # fullwidth A
+
+DOC = '"{d}": {d}\ntags[2]: {w},b\n'.format(d=DECOMPOSED, w=WIDE_A)
+
+
+class NormalizeValuesTest(unittest.TestCase):
+    def test_forms(self):
+        cases = [
+            ("nfc", COMPOSED, WIDE_A),
+            ("nfd", DECOMPOSED, WIDE_A),
+            ("nfkc", COMPOSED, "A"),
+            ("nfkd", DECOMPOSED, "A"),
+        ]
+        for form, word, letter in cases:
+            with self.subTest(form=form):
+                value = toon_parser.decode(DOC, options=Options(normalize_unicode=form))
+                self.assertEqual(value, {DECOMPOSED: word, "tags": [letter, "b"]})
+
+    def test_off_by_default(self):
+        self.assertEqual(toon_parser.decode(DOC), {DECOMPOSED: DECOMPOSED, "tags": [WIDE_A, "b"]})
+
+    def test_documents_from_different_tools_compare_equal(self):
+        opts = Options(normalize_unicode="nfc")
+        a = toon_parser.decode("name: {}\n".format(COMPOSED), options=opts)
+        b = toon_parser.decode("name: {}\n".format(DECOMPOSED), options=opts)
+        self.assertEqual(a, b)
+        self.assertEqual(toon_parser.content_hash(a), toon_parser.content_hash(b))
+
+    def test_every_options_entry_point(self):
+        opts = Options(normalize_unicode="nfkc")
+        expected = {DECOMPOSED: COMPOSED, "tags": ["A", "b"]}
+        self.assertEqual(toon_parser.loads(DOC, options=opts), expected)
+        self.assertEqual(toon_parser.load(io.StringIO(DOC), options=opts), expected)
+        self.assertEqual(toon_parser.decode_bytes(DOC.encode(), options=opts), expected)
+        self.assertEqual(toon_parser.decode_with_options(DOC, opts), expected)
+
+
+class NormalizeKeysTest(unittest.TestCase):
+    def test_keys(self):
+        opts = Options(normalize_unicode="nfc", normalize_keys=True)
+        self.assertEqual(toon_parser.decode(DOC, options=opts), {COMPOSED: COMPOSED, "tags": [WIDE_A, "b"]})
+
+    def test_keys_need_a_form(self):
+        self.assertEqual(toon_parser.decode(DOC, options=Options(normalize_keys=True)), toon_parser.decode(DOC))
+
+    def test_keys_that_normalize_alike_keep_the_last_value(self):
+        doc = '"{}": 1\n"{}": 2\n'.format(COMPOSED, DECOMPOSED)
+        opts = Options(normalize_unicode="nfc", normalize_keys=True)
+        self.assertEqual(toon_parser.decode(doc, options=opts), {COMPOSED: 2})
+
+
+class OptionTest(unittest.TestCase):
+    def test_attributes(self):
+        opts = Options()
+        self.assertEqual((opts.normalize_unicode, opts.normalize_keys), (None, False))
+        opts.normalize_unicode = "NFKC"
+        opts.normalize_keys = True
+        self.assertEqual((opts.normalize_unicode, opts.normalize_keys), ("nfkc", True))
+        opts.normalize_unicode = None
+        self.assertIsNone(opts.normalize_unicode)
+
+    def test_builders_repr_and_equality(self):
+        opts = Options().with_normalize_unicode("nfc").with_normalize_keys(True)
+        self.assertEqual(opts, Options(normalize_unicode="nfc", normalize_keys=True))
+        self.assertEqual(hash(opts), hash(Options(normalize_unicode="nfc", normalize_keys=True)))
+        self.assertNotEqual(opts, Options(normalize_unicode="nfd", normalize_keys=True))
+        self.assertIn("normalize_unicode='nfc', normalize_keys=True", repr(opts))
+
+    def test_invalid_form(self):
+        with self.assertRaisesRegex(ValueError, "Invalid normalize_unicode 'nfx'"):
+            Options(normalize_unicode="nfx")
+        with self.assertRaisesRegex(ValueError, "Invalid normalize_unicode"):
+            Options().with_normalize_unicode("utf8")
+
+
+if __name__ == "__main__":
+    unittest.main()
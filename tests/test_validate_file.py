@@ -0,0 +1,100 @@
+"""Streaming pre-flight checks with ``validate_file``.
+
+Run with ``python -m unittest tests.test_validate_file``.
+"""
+
+import os
+import pathlib
+import tempfile
+import unittest
+
+import toon_parser
+from toon_parser import Options
+
+STRICT = Options(strict=True)
+
+
+def error(line, column, message):
+    return {"line": line, "column": column, "severity": "error", "message": message}
+
+
+class ValidateFileTest(unittest.TestCase):
+    def setUp(self):
+        self.dir = tempfile.TemporaryDirectory()
+        self.path = os.path.join(self.dir.name, "data.toon")
+
+    def tearDown(self):
+        self.dir.cleanup()
+
+    def validate(self, data, **kwargs):
+        with open(self.path, "wb") as f:
+            f.write(data)
+        return toon_parser.validate_file(self.path, **kwargs)
+
+    def test_clean_file(self):
+        self.assertEqual(self.validate(b"users[2]{id,name}:\n  1,Ann\n  2,Bo\n"), [])
+        self.assertEqual(toon_parser.validate_file(pathlib.Path(self.path)), [])
+
+    def test_counts_and_rows(self):
+        self.assertEqual(
+            self.validate(b"users[3]{id,name}:\n  1,Ann\n  2\n"),
+            [error(1, 6, "header declares 3 rows but 2 found"), error(3, 3, "row has 1 values but the table declares 2 fields")],
+        )
+
+    def test_encoding_and_strings(self):
+        self.assertEqual(
+            self.validate(b'a: \xff\nb: "x\n'),
+            [error(1, 4, "invalid UTF-8"), error(2, 4, "unterminated string")],
+        )
+
+    def test_indentation(self):
+        self.assertEqual(
+            self.validate(b"a: 1\n   b: 2\n"),
+            [
+                {"line": 2, "column": 1, "severity": "warning", "message": "indentation of 3 spaces is not a multiple of 2"},
+                error(2, 1, "unexpected indentation of 3 spaces under a line at 0"),
+            ],
+        )
+        self.assertEqual(self.validate(b"a:\n   b: 2\n", options=STRICT), [error(2, 1, "indentation of 3 spaces is not a multiple of 2")])
+
+    def test_line_endings(self):
+        for ending in (b"\r\n", b"\r"):
+            with self.subTest(ending=ending):
+                data = ending.join([b"a: 1", b"b:", b"  c: 2", b""])
+                self.assertEqual(self.validate(data), [])
+                self.assertEqual(
+                    self.validate(data, options=STRICT),
+                    [error(1, 5, "carriage return line ending"), error(2, 3, "carriage return line ending"), error(3, 7, "carriage return line ending")],
+                )
+
+    def test_lone_carriage_returns_separate_lines(self):
+        self.assertEqual(
+            self.validate(b"xs[2]: 1\rys[1]: 1,2\r"),
+            [error(1, 3, "header declares 2 values but 1 found"), error(2, 3, "header declares 1 values but 2 found")],
+        )
+
+    def test_strict_empty_cells(self):
+        self.assertEqual(self.validate(b"r[1]{a,b}:\n  1,\n"), [])
+        self.assertEqual(self.validate(b"r[1]{a,b}:\n  1,\n", options=STRICT), [error(2, 3, "empty table cell")])
+
+    def test_limits(self):
+        self.assertEqual(
+            self.validate(b"xs[200]: 1\n", options=Options(max_array_length=10)),
+            [error(1, 1, "array declares 200 items, more than max_array_length=10"), error(1, 3, "header declares 200 values but 1 found")],
+        )
+        self.assertEqual(
+            self.validate(b"a: 1", options=Options(max_input_bytes=3)),
+            [{"line": None, "column": None, "severity": "error", "message": "input is 4 bytes, more than max_input_bytes=3"}],
+        )
+
+    def test_max_diagnostics(self):
+        self.assertEqual(len(self.validate(b"xs[3]: 1,2\n" * 5, max_diagnostics=2)), 2)
+        self.assertEqual(len(self.validate(b"xs[3]: 1,2\n" * 5)), 5)
+
+    def test_missing_file(self):
+        with self.assertRaises(FileNotFoundError):
+            toon_parser.validate_file(os.path.join(self.dir.name, "missing.toon"))
+
+
+if __name__ == "__main__":
+    unittest.main()
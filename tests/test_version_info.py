@@ -0,0 +1,49 @@
+"""Build metadata from ``version_info``.
+
+Run with ``python -m unittest tests.test_version_info``.
+"""
+
+import re
+import unittest
+
+import toon_parser
+
+VERSION = re.compile(r"^\d+\.\d+\.\d+")
+
+
+class VersionInfoTest(unittest.TestCase):
+    def setUp(self):
+        self.info = toon_parser.version_info()
+
+    def test_keys(self):
+        self.assertEqual(set(self.info), {"version", "toon", "pyo3", "features", "profile"})
+        self.assertEqual(set(self.info["toon"]), {"version", "source", "features"})
+        self.assertEqual(set(self.info["pyo3"]), {"version"})
+
+    def test_version_matches_the_module(self):
+        self.assertEqual(self.info["version"], toon_parser.__version__)
+        self.assertRegex(self.info["version"], VERSION)
+
+    def test_dependency_versions(self):
+        # "unknown" only when the build could not read Cargo.lock
+        for name in ("toon", "pyo3"):
+            with self.subTest(name=name):
+                version = self.info[name]["version"]
+                self.assertTrue(version == "unknown" or VERSION.match(version), version)
+        self.assertIsInstance(self.info["toon"]["source"], str)
+
+    def test_features(self):
+        self.assertIn("python", self.info["features"])
+        self.assertLessEqual(set(self.info["features"]), {"python", "wasm", "capi", "tracing"})
+        self.assertIn("json", self.info["toon"]["features"])
+
+    def test_profile(self):
+        self.assertIn(self.info["profile"], {"debug", "release"})
+
+    def test_fresh_dict_each_call(self):
+        self.info["features"].append("changed")
+        self.assertNotIn("changed", toon_parser.version_info()["features"])
+
+
+if __name__ == "__main__":
+    unittest.main()
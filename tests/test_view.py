@@ -0,0 +1,76 @@
+"""Notebook display with ``ToonView``.
+
+Run with ``python -m unittest tests.test_view``.
+"""
+
+import unittest
+
+import toon_parser
+from toon_parser import ToonSyntaxError, ToonView
+
+TABLE = "users[2]{id,name}:\n  1,Ann\n  2,<b>\n"
+
+
+def body(view):
+    """The HTML after the stylesheet."""
+    return view._repr_html_().split("</style>", 1)[1]
+
+
+class ReprTest(unittest.TestCase):
+    def test_summaries(self):
+        cases = [
+            (TABLE, "<ToonView dict with 1 keys>"),
+            (b"a: 1\nb: 2", "<ToonView dict with 2 keys>"),
+            ([1, 2], "<ToonView list with 2 items>"),
+            (5, "<ToonView int>"),
+            (None, "<ToonView null>"),
+        ]
+        for data, text in cases:
+            with self.subTest(data=data):
+                self.assertEqual(repr(ToonView(data)), text)
+
+    def test_errors(self):
+        with self.assertRaisesRegex(ToonSyntaxError, "^Line 1: "):
+            ToonView("xs[3]: 1")
+        with self.assertRaisesRegex(ValueError, "Cannot convert type 'object'"):
+            ToonView(object())
+
+
+class HtmlTest(unittest.TestCase):
+    def test_tables(self):
+        html = body(ToonView(TABLE))
+        self.assertIn(
+            "<table><thead><tr><th>id</th><th>name</th></tr></thead><tbody>"
+            '<tr><td><span class="n">1</span></td><td>Ann</td></tr>'
+            '<tr><td><span class="n">2</span></td><td>&lt;b&gt;</td></tr></tbody></table>',
+            html,
+        )
+
+    def test_data_and_text_render_alike(self):
+        self.assertEqual(ToonView(TABLE)._repr_html_(), ToonView(toon_parser.decode(TABLE))._repr_html_())
+
+    def test_long_tables_are_cut(self):
+        html = body(ToonView([{"a": i} for i in range(102)]))
+        self.assertEqual(html.count("<tr>"), 101)
+        self.assertIn('<span class="n">... 2 more rows</span>', html)
+
+    def test_trees_open_two_levels(self):
+        self.assertEqual(
+            body(ToonView({"a": {"b": {"c": 1}}})),
+            '<div class="toon-view"><ul><li><details open><summary><span class="k">a</span> <span class="n">{1 keys}</span></summary>'
+            '<ul><li><details><summary><span class="k">b</span> <span class="n">{1 keys}</span></summary>'
+            '<ul><li><span class="k">c</span>: <span class="n">1</span></li></ul></details></li></ul></details></li></ul></div>',
+        )
+
+    def test_rows_with_nested_values_are_trees(self):
+        html = body(ToonView([{"a": [1]}, {"a": [2]}]))
+        self.assertNotIn("<table>", html)
+        self.assertIn('<span class="k">[0]</span>', html)
+
+    def test_escaping(self):
+        html = body(ToonView({"<k>": "a & 'b' \"c\""}))
+        self.assertIn('<span class="k">&lt;k&gt;</span>: a &amp; &#39;b&#39; &quot;c&quot;', html)
+
+
+if __name__ == "__main__":
+    unittest.main()